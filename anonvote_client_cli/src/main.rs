@@ -1,5 +1,11 @@
-use anonvote_proto::proto::anonvote::{anon_vote_client::AnonVoteClient, ValidateIdReq, RegisterReq, VoteReq, ValidateVoteReq};
-use zkp_protocol::SecretKey;
+use anonvote_proto::proto::anonvote::{anon_vote_client::AnonVoteClient, ValidateIdReq, RegisterReq, CastVoteReq, GetVoteOptionsReq, GetElectionsReq, GetAuthorityKeyReq, GetParametersReq, GetInclusionProofReq, BallotReceipt, Ciphertext, BitProof, SumProof};
+use zkp_protocol::{zkp_util, SecretKey, Proof};
+use zkp_protocol::params::KeyParams;
+use zkp_protocol::group::GroupParams;
+use zkp_protocol::board::Hash;
+use zkp_protocol::merkle;
+use zkp_protocol::blind_signature::{self, SignerPublicKey};
+use zkp_protocol::elgamal::EncryptionKey;
 
 use std::io;
 use std::process;
@@ -12,10 +18,23 @@ fn input_string(prompt: &str) -> String {
     buf.trim().to_string() // Trim any trailing newlines or whitespace
 }
 
+fn hash(bytes : &[u8]) -> Option<Hash> {
+    Hash::try_from(bytes).ok()
+}
+
+// Checks that the receipt is for our ballot and that the root is signed with the board key, in the group of the election
+fn verify_receipt(receipt : &BallotReceipt, group : &GroupParams, election_id : &str, ballot_hash : &Hash) -> Option<Hash> {
+    let root = hash(&receipt.root)?;
+    let signature = Proof::from_bytes_be(&receipt.ka, &receipt.kb, &receipt.solution);
+    let public_key = BigUint::from_bytes_be(&receipt.public_key);
+    let valid = receipt.ballot_hash == ballot_hash && merkle::verify_root(group, &public_key, election_id, receipt.tree_size, &root, &signature);
+    valid.then_some(root)
+}
+
 fn handle_response<T>(res: Result<T, tonic::Status>) -> T {
     match res {
         Ok(response) => {
-            return response;
+            response
         }
         Err(e) => {
             eprintln!("{} : {}", e.code() , e.message());
@@ -30,44 +49,147 @@ async fn main() {
         .await
         .expect("Could not connect to the server");
 
+    let res = client.get_elections(GetElectionsReq {}).await;
+    let elections = handle_response(res).into_inner().elections;
+    let election_id = match elections.len() {
+        0 => {
+            eprintln!("There are no elections on the server");
+            process::exit(1);
+        }
+        1 => elections[0].id.clone(),
+        _ => {
+            for election in &elections {
+                println!("{} - {} ({})", election.id, election.title, election.phase().as_str_name());
+            }
+            input_string("Choose an election")
+        }
+    };
+    let Some(election) = elections.iter().find(|election| election.id == election_id) else {
+        eprintln!("Election {} doesn't exist", election_id);
+        process::exit(1);
+    };
+
+    // Key, ballots and receipts are in the group of the election. A built-in group has to come with its own numbers.
+    let res = client.get_parameters(GetParametersReq { election_id : election_id.clone() }).await;
+    let res = handle_response(res).into_inner();
+    let [p, q, alpha] = [&res.p, &res.q, &res.alpha].map(|bytes| BigUint::from_bytes_be(bytes));
+    let group = GroupParams::from_numbers(&res.group, &p, &q, &alpha).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    let key_params = KeyParams::from_bytes_be(&group, &res.alpha, &res.beta);
+    if !key_params.valid() {
+        eprintln!("The server sent invalid parameters for the election");
+        process::exit(1);
+    }
+
+    let encryption_key = EncryptionKey::from_bytes_be(&group, &election.encryption_key);
+    if !encryption_key.valid() {
+        eprintln!("Election {} has an invalid encryption key", election_id);
+        process::exit(1);
+    }
+
+    let secret_key = SecretKey::generate(&group);
+    let public_key = secret_key.public_key(&key_params);
+
+    // The authority signs the credential blinded, so it can't link the ID with the key.
+    // The exponent comes from the election id, a different key for some voters would tell them apart.
+    let res = client.get_authority_key(GetAuthorityKeyReq { election_id : election_id.clone() }).await;
+    let res = handle_response(res).into_inner();
+    let authority_key = SignerPublicKey::from_bytes_be(&res.n, &res.e);
+    if *authority_key.e() != blind_signature::election_exponent(&election_id) {
+        eprintln!("The server sent an authority key of another election");
+        process::exit(1);
+    }
+    let (blinded_credential, blinding_factor) = authority_key.blind(&blind_signature::credential(&election_id, &public_key));
+
     let id = input_string("Please provide ID number");
-    let res = client.validate_id(ValidateIdReq { id }).await;
+    let res = client.validate_id(ValidateIdReq {
+        id,
+        election_id : election_id.clone(),
+        blinded_credential : blinded_credential.to_bytes_be()
+    }).await;
     let res = handle_response(res).into_inner();
 
-    let secret_key = SecretKey::generate();
-    let public_key = secret_key.generate_public_key();
+    let signature = authority_key.unblind(&BigUint::from_bytes_be(&res.blind_signature), &blinding_factor).unwrap_or_else(|| {
+        eprintln!("Could not unblind the credential signature");
+        process::exit(1);
+    });
 
     let res = client.register(RegisterReq {
-        registration_key : res.registration_key,
+        election_id : election_id.clone(),
+        registration_key : String::new(),
         a : public_key.a().to_bytes_be(),
         b : public_key.b().to_bytes_be(),
-        alpha : public_key.alpha().to_bytes_be(),
-        beta : public_key.beta().to_bytes_be()
+        credential_signature : signature.to_bytes_be()
     }).await;
     let _res = handle_response(res).into_inner();
 
+    let res = client.get_vote_options(GetVoteOptionsReq { election_id : election_id.clone() }).await;
+    let res = handle_response(res).into_inner();
+    for (index, option) in res.option_details.iter().enumerate() {
+        if option.description.is_empty() {
+            println!("{} - {}", index, option.name);
+        } else {
+            println!("{} - {} ({})", index, option.name, option.description);
+        }
+    }
+
     let vote = input_string("Enter a vote").parse().unwrap();
 
-    let (k, ka, kb) = public_key.generate_challenge_request();
-    let res = client.vote(VoteReq {
-        vote,
+    // Server only gets the encrypted ballot with proofs that it is valid, the proof of the key is bound to it
+    let validity_context = zkp_util::validity_context(&election_id);
+    let (ballot, ballot_proof) = encryption_key.encrypt_vote_with_proof(vote, res.option_details.len(), &validity_context);
+    let proof = secret_key.prove(&public_key, &zkp_util::ballot_context(&election_id, &ballot));
+    let ballot_hash = merkle::ballot_hash(&election_id, &ballot);
+    let res = client.cast_vote(CastVoteReq {
+        election_id : election_id.clone(),
         a : public_key.a().to_bytes_be(),
         b : public_key.b().to_bytes_be(),
-        alpha : public_key.alpha().to_bytes_be(),
-        beta : public_key.beta().to_bytes_be(),
-        ka : ka.to_bytes_be(),
-        kb : kb.to_bytes_be()
+        ka : proof.ka().to_bytes_be(),
+        kb : proof.kb().to_bytes_be(),
+        solution : proof.solution().to_bytes_be(),
+        ballot : ballot.iter().map(|ciphertext| Ciphertext { c1 : ciphertext.c1().to_bytes_be(), c2 : ciphertext.c2().to_bytes_be() }).collect(),
+        option_proofs : ballot_proof.options().iter().map(|proof| BitProof {
+            a0 : proof.a0().to_bytes_be(),
+            b0 : proof.b0().to_bytes_be(),
+            a1 : proof.a1().to_bytes_be(),
+            b1 : proof.b1().to_bytes_be(),
+            e0 : proof.e0().to_bytes_be(),
+            e1 : proof.e1().to_bytes_be(),
+            z0 : proof.z0().to_bytes_be(),
+            z1 : proof.z1().to_bytes_be(),
+        }).collect(),
+        sum_proof : Some(SumProof {
+            a : ballot_proof.sum().a().to_bytes_be(),
+            b : ballot_proof.sum().b().to_bytes_be(),
+            z : ballot_proof.sum().z().to_bytes_be(),
+        })
     }).await;
     let res = handle_response(res).into_inner();
+    let receipt = res.receipt.unwrap_or_default();
+    if verify_receipt(&receipt, &group, &election_id, &ballot_hash).is_none() {
+        eprintln!("The server sent an invalid receipt for the ballot");
+        process::exit(1);
+    }
 
-    let solution = secret_key.solve(&k, &BigUint::from_bytes_be(&res.challenge));
+    println!("You've successfully voted!");
 
-    let res = client.validate_vote(ValidateVoteReq{
-        auth_session_id : res.auth_session_id,
-        vote,
-        solution : solution.to_bytes_be()
+    // Ask for the proof the way it would be done later, the ballot has to be in the tree under the signed root
+    let res = client.get_inclusion_proof(GetInclusionProofReq {
+        election_id : election_id.clone(),
+        ballot_hash : ballot_hash.to_vec()
     }).await;
-    let _res = handle_response(res).into_inner();
-
-    println!("You've successfully voted!");
+    let res = handle_response(res).into_inner();
+    let proof_receipt = res.receipt.unwrap_or_default();
+    let path : Option<Vec<Hash>> = res.path.iter().map(|sibling| hash(sibling)).collect();
+    let included = match (verify_receipt(&proof_receipt, &group, &election_id, &ballot_hash), path) {
+        (Some(root), Some(path)) => proof_receipt.leaf_index == receipt.leaf_index && merkle::verify_inclusion(&ballot_hash, proof_receipt.leaf_index, proof_receipt.tree_size, &path, &root),
+        _ => false,
+    };
+    if !included {
+        eprintln!("Could not verify that the ballot was counted");
+        process::exit(1);
+    }
+    println!("Receipt: ballot {} at position {} of {}", hex::encode(ballot_hash), receipt.leaf_index, proof_receipt.tree_size);
 }
@@ -1,4 +1,4 @@
-import init, {PublicKeyWasm, SecretKeyWasm} from "./anonvote_wasm/pkg/anonvote_wasm.js";
+import init, {PublicKeyWasm, SecretKeyWasm, encrypt_ballot, register_group} from "./anonvote_wasm/pkg/anonvote_wasm.js";
 
 let wasmInitialized = false; 
 
@@ -10,19 +10,83 @@ export function convert_to_uint8_array(obj) {
     return new Uint8Array(Object.values(obj));
 }
 
-export function generate_key_pair() {
+// Group of the election from GetParameters, a custom group has to be known before keys or ballots use it.
+// False if the numbers are not a group or not the numbers of the built-in group with that name.
+export function use_group(params) {
+    if (!wasmInitialized) {
+        return false;
+    }
+
+    try {
+        register_group(params.group, convert_to_uint8_array(params.p.data), convert_to_uint8_array(params.q.data), convert_to_uint8_array(params.alpha.data)); // Bytes come as a serialized Buffer
+        return true;
+    } catch (error) {
+        console.log(error);
+        return false;
+    }
+}
+
+// Key in the group and with the alpha and beta of the election from GetParameters, undefined if they are not valid
+export function generate_key_pair(group, alpha, beta) {
     if (!wasmInitialized) {
         return undefined;
     }
 
-    let secret_key = SecretKeyWasm.generate();
-    let public_key = secret_key.generate_public_key(secret_key);
+    let secret_key;
+    try {
+        secret_key = SecretKeyWasm.generate(group);
+    } catch (error) {
+        console.log(error); // Group is unknown
+        return undefined;
+    }
+    let public_key = secret_key.generate_public_key(convert_to_uint8_array(alpha), convert_to_uint8_array(beta));
+    if (!public_key) {
+        return undefined;
+    }
     return {
         secret_key : secret_key,
         public_key : public_key
     };
 }
 
+// Ballot and its proofs of validity as CastVote expects them, together with the wasm ballot needed for the proof of the key.
+// Ballots are in the group of the election, the same group the key of the voter was made in.
+export function encrypt_vote(encryption_key, vote, option_count, election_id, group) {
+    if (!wasmInitialized) {
+        return undefined;
+    }
+
+    let ballot;
+    try {
+        ballot = encrypt_ballot(convert_to_uint8_array(encryption_key), vote, option_count, election_id, group);
+    } catch (error) {
+        console.log(error); // Group is unknown
+        return undefined;
+    }
+    if (ballot === undefined) {
+        return undefined;
+    }
+    const ciphertexts = [];
+    const option_proofs = [];
+    for (let i = 0; i < ballot.option_count(); i++) {
+        ciphertexts.push({ c1 : ballot.c1(i), c2 : ballot.c2(i) });
+        const proof = ballot.option_proof(i);
+        option_proofs.push({
+            a0 : proof.a0(), b0 : proof.b0(),
+            a1 : proof.a1(), b1 : proof.b1(),
+            e0 : proof.e0(), e1 : proof.e1(),
+            z0 : proof.z0(), z1 : proof.z1()
+        });
+    }
+    const sum_proof = ballot.sum_proof();
+    return {
+        ballot : ballot,
+        ciphertexts : ciphertexts,
+        option_proofs : option_proofs,
+        sum_proof : { a : sum_proof.a(), b : sum_proof.b(), z : sum_proof.z() }
+    };
+}
+
 export function key_pair_to_json(key_pair) {
     if (!wasmInitialized) {
         return undefined;
@@ -33,7 +97,8 @@ export function key_pair_to_json(key_pair) {
         a : key_pair.public_key.a(),
         b : key_pair.public_key.b(),
         alpha : key_pair.public_key.alpha(),
-        beta : key_pair.public_key.beta()
+        beta : key_pair.public_key.beta(),
+        group : key_pair.public_key.group()
     };
     return JSON.stringify(jsObject);
 }
@@ -46,7 +111,7 @@ export function json_to_key_pair(jsObject) {
     if(!jsObject)
         return undefined;
 
-    const {a,b,alpha,beta,secret} = jsObject;
+    const {a,b,alpha,beta,secret,group} = jsObject;
 
     // Ensure all byte arrays are Uint8Arrays
     const aBytes = a ? convert_to_uint8_array(a) : null;
@@ -55,12 +120,19 @@ export function json_to_key_pair(jsObject) {
     const betaBytes = beta ? convert_to_uint8_array(beta) : null;
     const secretBytes = secret ? convert_to_uint8_array(secret) : null;
 
-    let public_key = PublicKeyWasm.new(
-        aBytes, 
-        bBytes, 
-        alphaBytes, 
-        betaBytes);
-    let private_key = SecretKeyWasm.new(secretBytes);
+    let public_key, private_key;
+    try {
+        public_key = PublicKeyWasm.new(
+            aBytes, 
+            bBytes, 
+            alphaBytes, 
+            betaBytes,
+            group || ""); // Keys saved before groups were added are in the default group
+        private_key = SecretKeyWasm.new(secretBytes, group || "");
+    } catch (error) {
+        console.log(error); // Group is unknown
+        return undefined;
+    }
 
     return {
         public_key : public_key,
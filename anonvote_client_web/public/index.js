@@ -1,7 +1,10 @@
-import { generate_key_pair, json_to_key_pair, key_pair_to_json, convert_to_uint8_array } from "./anonvote_wasm.js";
+import { generate_key_pair, json_to_key_pair, key_pair_to_json, encrypt_vote, use_group } from "./anonvote_wasm.js";
 
 const keyFileName = "userKey.anonvote";
 
+let electionId = "";
+let encryptionKeys = {}; // Election id -> key that ballots are encrypted with
+
 window.addEventListener('load', setup);
 
 async function setup() {
@@ -26,9 +29,36 @@ async function setup() {
     const submitVoteButton = document.getElementById("submitVoteButton");
     submitVoteButton.onclick = () => submitVote();
 
+    const electionSelect = document.getElementById("electionSelect");
+    electionSelect.onchange = () => selectElection(electionSelect.value);
+
+    await fetch('/elections', {
+        method: 'GET'
+      })
+      .then(response => response.json())
+      .then(data => {
+        data.elections.forEach(election => {
+            const option = document.createElement("option");
+            option.value = election.id;
+            option.textContent = election.title;
+            electionSelect.appendChild(option);
+            encryptionKeys[election.id] = election.encryptionKey.data; // Bytes come as a serialized Buffer
+        });
+      })
+      .catch(error => console.log(error));
+
+    if (electionSelect.options.length > 0) {
+        selectElection(electionSelect.options[0].value);
+    }
+}
+
+function selectElection(id) {
+    electionId = id;
+
     const voteOptionsDiv = document.getElementById("voteOptions");
+    voteOptionsDiv.innerHTML = "";
 
-    fetch('/vote_options', {
+    fetch('/vote_options?election_id=' + encodeURIComponent(electionId), {
         method: 'GET'
       })
       .then(response => {
@@ -100,7 +130,7 @@ async function showResults() {
 
     let voteOptions = null;
 
-    await fetch('/vote_options', {
+    await fetch('/vote_options?election_id=' + encodeURIComponent(electionId), {
         method: 'GET'
       })
       .then(response => {
@@ -126,7 +156,7 @@ async function showResults() {
 
     let voteResults = null;
 
-    await fetch('/vote_results', {
+    await fetch('/vote_results?election_id=' + encodeURIComponent(electionId), {
         method: 'GET'
       })
       .then(response => {
@@ -278,7 +308,7 @@ function validateID() {
     } else {
         api_call(
             '/validate_id', 
-            JSON.stringify({ id: idNumber }),
+            JSON.stringify({ id: idNumber, election_id: electionId }),
             data => {
                 const registrationKey = data.registrationKey;
                 message.innerHTML = 'ID validated successfully! Registration key: ' + registrationKey;
@@ -292,7 +322,7 @@ function validateID() {
     }
 }
 
-function registerUser() {
+async function registerUser() {
     const registrationCode = document.getElementById('registrationCode').value;
     const message = document.getElementById('registerMessage');
 
@@ -300,7 +330,11 @@ function registerUser() {
         message.innerHTML = 'Invalid registration code.';
         message.style.color = 'red';
     } else {
-        const generated_key = generate_key_pair();
+        // Key is made with the parameters of the election, only a and b are sent
+        const params = await fetch('/parameters?election_id=' + encodeURIComponent(electionId))
+            .then(response => response.ok ? response.json() : undefined)
+            .catch(_ => undefined);
+        const generated_key = params && use_group(params) && generate_key_pair(params.group, params.alpha.data, params.beta.data); // Bytes come as a serialized Buffer
         if(!generated_key) {
             message.innerHTML = 'Internal error, please try again later';
             message.style.color = 'red';
@@ -308,11 +342,10 @@ function registerUser() {
         }
 
         const registerReq = {
+            election_id : electionId,
             registrationKey : registrationCode,
             a : generated_key.public_key.a(),
-            b : generated_key.public_key.b(),
-            alpha : generated_key.public_key.alpha(),
-            beta : generated_key.public_key.beta()
+            b : generated_key.public_key.b()
         };
 
         api_call(
@@ -352,6 +385,14 @@ async function submitVote() {
 
     voteOptionInt = voteOptionInt - 1; // Votes are starting at 0
 
+    // Key file only names its group, a custom group of the election has to be known before the key is read
+    const params = await fetch('/parameters?election_id=' + encodeURIComponent(electionId))
+        .then(response => response.ok ? response.json() : undefined)
+        .catch(_ => undefined);
+    if (params) {
+        use_group(params);
+    }
+
     const jsonKeyPair = await readJSONFile(secretKeyFile);
     const keyPair = json_to_key_pair(jsonKeyPair);
     
@@ -361,53 +402,37 @@ async function submitVote() {
         return;
     }
 
-    const challengeReq = keyPair.public_key.generate_challenge_request();
+    // Only the encrypted ballot is sent, the proof is bound to it
+    const optionCount = document.querySelectorAll('input[name="vote"]').length;
+    const encryptedVote = encrypt_vote(encryptionKeys[electionId], voteOptionInt, optionCount, electionId, keyPair.public_key.group());
+    if(!encryptedVote) {
+        message.innerHTML = 'Election has an invalid encryption key.';
+        message.style.color = 'red';
+        return;
+    }
+    const proof = keyPair.private_key.prove(keyPair.public_key, electionId, encryptedVote.ballot);
 
     let voteReq = {
-        vote : voteOptionInt,
+        election_id : electionId,
+        ballot : encryptedVote.ciphertexts,
+        option_proofs : encryptedVote.option_proofs,
+        sum_proof : encryptedVote.sum_proof,
         a : keyPair.public_key.a(),
         b : keyPair.public_key.b(),
-        alpha : keyPair.public_key.alpha(),
-        beta : keyPair.public_key.beta(),
-        ka : challengeReq.ka(),
-        kb : challengeReq.kb()
+        ka : proof.ka(),
+        kb : proof.kb(),
+        solution : proof.solution()
     };
 
     api_call(
-        '/vote', 
+        '/cast_vote', 
         JSON.stringify(voteReq), 
-        (data) => {
-            message.innerHTML = 'Authentication...';
-            message.style.color = 'blue';
-            validateVote(voteOptionInt, keyPair, challengeReq, data.challenge.data, data.authSessionId);
-        },
-        (error) => {
-            message.innerHTML = error;
-            message.style.color = 'red';
-        });
-}
-
-function validateVote(vote, keyPair, challengeReq, challenge, session_id) {
-    const message = document.getElementById('voteMessage');
-
-    let solution = keyPair.private_key.solve(challengeReq.k(), convert_to_uint8_array(challenge));
-
-    let validationReq = {
-        auth_session_id : session_id,
-        vote : vote,
-        solution : solution
-    };
-
-    api_call(
-        '/validate_vote',
-        JSON.stringify(validationReq),
         _ => {
             message.innerHTML = 'Voting finished!';
             message.style.color = 'green';
         },
-        error => {
+        (error) => {
             message.innerHTML = error;
             message.style.color = 'red';
-        }
-    );
+        });
 }
@@ -25,8 +25,8 @@ app.get('/', (req, res) => {
   res.sendFile(path.join(__dirname, '..', 'public', 'index.html'));
 });
 
-app.get('/vote_options', (_, res) => {
-  client.GetVoteOptions({}, (error, response) => {
+app.get('/elections', (_, res) => {
+  client.GetElections({}, (error, response) => {
     if (error) {
       return res.status(500).send(error);
     }
@@ -34,8 +34,38 @@ app.get('/vote_options', (_, res) => {
   });
 });
 
-app.get('/vote_results', (_, res) => {
-  client.GetResults({}, (error, response) => {
+app.get('/vote_options', (req, res) => {
+  const electionId = req.query.election_id || '';
+  client.GetVoteOptions({ electionId }, (error, response) => {
+    if (error) {
+      return res.status(500).send(error);
+    }
+    res.json(response);
+  });
+});
+
+app.get('/vote_results', (req, res) => {
+  const electionId = req.query.election_id || '';
+  client.GetResults({ electionId }, (error, response) => {
+    if (error) {
+      return res.status(500).send(error);
+    }
+    res.json(response);
+  });
+});
+
+app.get('/parameters', (req, res) => {
+  const electionId = req.query.election_id || '';
+  client.GetParameters({ electionId }, (error, response) => {
+    if (error) {
+      return res.status(500).send(error);
+    }
+    res.json(response);
+  });
+});
+
+app.get('/authority_key', (_, res) => {
+  client.GetAuthorityKey({}, (error, response) => {
     if (error) {
       return res.status(500).send(error);
     }
@@ -45,7 +75,9 @@ app.get('/vote_results', (_, res) => {
 
 app.post('/validate_id', (req, res) => {
     const id = req.body.id || '';
-    client.ValidateID({ id }, (error, response) => {
+    const electionId = req.body.election_id || '';
+    const blindedCredential = req.body.blinded_credential ? convertToUint8Array(req.body.blinded_credential) : null;
+    client.ValidateID({ id, electionId, blindedCredential }, (error, response) => {
       if (error) {
         return res.status(500).send(error);
       }
@@ -54,20 +86,19 @@ app.post('/validate_id', (req, res) => {
   });
 
 app.post('/register', (req, res) => {
-  const { registrationKey, a, b, alpha, beta } = req.body;
+  const { registrationKey, a, b, election_id, credential_signature } = req.body;
 
   // Ensure all byte arrays are Uint8Arrays
   const aBytes = a ? convertToUint8Array(a) : null;
   const bBytes = b ? convertToUint8Array(b) : null;
-  const alphaBytes = alpha ? convertToUint8Array(alpha) : null;
-  const betaBytes = beta ? convertToUint8Array(beta) : null;
+  const credentialSignatureBytes = credential_signature ? convertToUint8Array(credential_signature) : null;
 
   const message = {
+    electionId : election_id,
     registrationKey,
     a: aBytes,
     b: bBytes,
-    alpha: alphaBytes,
-    beta: betaBytes,
+    credentialSignature: credentialSignatureBytes,
   };
 
   client.Register(message, (error, response) => {
@@ -78,46 +109,41 @@ app.post('/register', (req, res) => {
   });
 });
 
-app.post('/vote', (req, res) => {
-  const { vote, a, b, alpha, beta, ka, kb } = req.body;
+app.post('/cast_vote', (req, res) => {
+  const { ballot, option_proofs, sum_proof, a, b, ka, kb, solution, election_id } = req.body;
 
   // Ensure all byte arrays are Uint8Arrays
   const aBytes = a ? convertToUint8Array(a) : null;
   const bBytes = b ? convertToUint8Array(b) : null;
-  const alphaBytes = alpha ? convertToUint8Array(alpha) : null;
-  const betaBytes = beta ? convertToUint8Array(beta) : null;
   const kaBytes = ka ? convertToUint8Array(ka) : null;
-  const kbBytes = kb ? convertToUint8Array(kb) : null; 
+  const kbBytes = kb ? convertToUint8Array(kb) : null;
+  const solutionBytes = solution ? convertToUint8Array(solution) : null;
+  const ballotCiphertexts = (ballot || []).map(ciphertext => ({
+    c1 : convertToUint8Array(ciphertext.c1),
+    c2 : convertToUint8Array(ciphertext.c2)
+  }));
+  const optionProofs = (option_proofs || []).map(proof => Object.fromEntries(
+    Object.entries(proof).map(([name, value]) => [name, convertToUint8Array(value)])
+  ));
+  const sumProof = sum_proof ? {
+    a : convertToUint8Array(sum_proof.a),
+    b : convertToUint8Array(sum_proof.b),
+    z : convertToUint8Array(sum_proof.z)
+  } : null;
 
   const message = {
-    vote : vote,
+    electionId : election_id,
+    ballot : ballotCiphertexts,
+    optionProofs : optionProofs,
+    sumProof : sumProof,
     a: aBytes,
     b: bBytes,
-    alpha: alphaBytes,
-    beta: betaBytes,
     ka : kaBytes,
-    kb : kbBytes
-  };
-
-  client.Vote(message, (error, response) => {
-    if (error) {
-      return res.status(500).send(error);
-    }
-    res.json(response);
-  });
-});
-
-app.post('/validate_vote', (req, res) => {
-  const {auth_session_id, vote, solution} = req.body;
-  const solutionBytes = solution ? convertToUint8Array(solution) : null;
-
-  const message = {
-    authSessionId : String(auth_session_id),
-    vote : vote,
+    kb : kbBytes,
     solution : solutionBytes
   };
 
-  client.ValidateVote(message, (error, response) => {
+  client.CastVote(message, (error, response) => {
     if (error) {
       return res.status(500).send(error);
     }
@@ -1,15 +1,24 @@
+/// With blinded_credential set the server returns a blind signature of the credential instead of a registration key,
+/// see GetAuthorityKey and RegisterReq.credential_signature
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ValidateIdReq {
     #[prost(string, tag = "1")]
     pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub election_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "3")]
+    pub blinded_credential: ::prost::alloc::vec::Vec<u8>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ValidateIdRes {
     #[prost(string, tag = "1")]
     pub registration_key: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub blind_signature: ::prost::alloc::vec::Vec<u8>,
 }
+/// Voter key is a = alpha^x and b = beta^x with the parameters from GetParameters
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RegisterReq {
@@ -19,31 +28,36 @@ pub struct RegisterReq {
     pub a: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
     pub b: ::prost::alloc::vec::Vec<u8>,
-    #[prost(bytes = "vec", tag = "4")]
-    pub alpha: ::prost::alloc::vec::Vec<u8>,
-    #[prost(bytes = "vec", tag = "5")]
-    pub beta: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "6")]
+    pub election_id: ::prost::alloc::string::String,
+    /// Unblinded signature of the credential, used instead of the registration key
+    #[prost(bytes = "vec", tag = "7")]
+    pub credential_signature: ::prost::alloc::vec::Vec<u8>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RegisterRes {}
+/// Interactive flow, the key is proven with the challenge of the server instead of a proof bound to the ballot.
+/// The ballot is encrypted by the voter and proven valid the same way as in CastVoteReq.
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VoteReq {
-    #[prost(uint32, tag = "1")]
-    pub vote: u32,
     #[prost(bytes = "vec", tag = "2")]
     pub a: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
     pub b: ::prost::alloc::vec::Vec<u8>,
-    #[prost(bytes = "vec", tag = "4")]
-    pub alpha: ::prost::alloc::vec::Vec<u8>,
-    #[prost(bytes = "vec", tag = "5")]
-    pub beta: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "6")]
     pub ka: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "7")]
     pub kb: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "8")]
+    pub election_id: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "9")]
+    pub ballot: ::prost::alloc::vec::Vec<Ciphertext>,
+    #[prost(message, repeated, tag = "10")]
+    pub option_proofs: ::prost::alloc::vec::Vec<BitProof>,
+    #[prost(message, optional, tag = "11")]
+    pub sum_proof: ::core::option::Option<SumProof>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -58,109 +72,744 @@ pub struct VoteRes {
 pub struct ValidateVoteReq {
     #[prost(string, tag = "1")]
     pub auth_session_id: ::prost::alloc::string::String,
-    #[prost(uint32, tag = "2")]
-    pub vote: u32,
     #[prost(bytes = "vec", tag = "3")]
     pub solution: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "4")]
+    pub election_id: ::prost::alloc::string::String,
+}
+/// Receipt of an accepted ballot: its hash (merkle::ballot_hash), position in the Merkle tree of accepted ballots
+/// and the root of the tree at that time, signed with the bulletin board key
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BallotReceipt {
+    #[prost(bytes = "vec", tag = "1")]
+    pub ballot_hash: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub leaf_index: u64,
+    #[prost(uint64, tag = "3")]
+    pub tree_size: u64,
+    #[prost(bytes = "vec", tag = "4")]
+    pub root: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub public_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub ka: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "7")]
+    pub kb: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "8")]
+    pub solution: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateVoteRes {
+    #[prost(message, optional, tag = "1")]
+    pub receipt: ::core::option::Option<BallotReceipt>,
+}
+/// Exponential ElGamal ciphertext (alpha^r, alpha^m * h^r)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Ciphertext {
+    #[prost(bytes = "vec", tag = "1")]
+    pub c1: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub c2: ::prost::alloc::vec::Vec<u8>,
+}
+/// Proof that a ciphertext encrypts 0 or 1, case j has commitments (aj, bj), challenge ej and response zj
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BitProof {
+    #[prost(bytes = "vec", tag = "1")]
+    pub a0: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub b0: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub a1: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub b1: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub e0: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub e1: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "7")]
+    pub z0: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "8")]
+    pub z1: ::prost::alloc::vec::Vec<u8>,
+}
+/// Proof that the product of all ciphertexts of a ballot encrypts 1
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SumProof {
+    #[prost(bytes = "vec", tag = "1")]
+    pub a: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub b: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub z: ::prost::alloc::vec::Vec<u8>,
+}
+/// Vote with a non-interactive proof, the challenge is the hash of the public key, ka, kb and the ballot context
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CastVoteReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "3")]
+    pub a: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub b: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "7")]
+    pub ka: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "8")]
+    pub kb: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "9")]
+    pub solution: ::prost::alloc::vec::Vec<u8>,
+    /// One ciphertext per option encrypted with the election key, 1 for the chosen option and 0 for others
+    #[prost(message, repeated, tag = "10")]
+    pub ballot: ::prost::alloc::vec::Vec<Ciphertext>,
+    /// One per option, in the same order as the ballot
+    #[prost(message, repeated, tag = "11")]
+    pub option_proofs: ::prost::alloc::vec::Vec<BitProof>,
+    /// Exactly one option is chosen
+    #[prost(message, optional, tag = "12")]
+    pub sum_proof: ::core::option::Option<SumProof>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CastVoteRes {
+    #[prost(message, optional, tag = "1")]
+    pub receipt: ::core::option::Option<BallotReceipt>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ValidateVoteRes {}
+pub struct GetVoteOptionsReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetVoteOptionsReq {}
+pub struct VoteOption {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub description: ::prost::alloc::string::String,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetVoteOptionsRes {
+    /// Candidate names, kept for simple clients
     #[prost(string, repeated, tag = "1")]
     pub options: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, repeated, tag = "2")]
+    pub option_details: ::prost::alloc::vec::Vec<VoteOption>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetResultsReq {}
+pub struct GetResultsReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetResultsRes {
     #[prost(uint32, repeated, tag = "1")]
     pub votes: ::prost::alloc::vec::Vec<u32>,
 }
-/// Generated client implementations.
-pub mod anon_vote_client {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    use tonic::codegen::http::Uri;
-    #[derive(Debug, Clone)]
-    pub struct AnonVoteClient<T> {
-        inner: tonic::client::Grpc<T>,
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Election {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub title: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "3")]
+    pub options: ::prost::alloc::vec::Vec<VoteOption>,
+    /// Unix timestamp in seconds, registration closes when voting opens
+    #[prost(uint64, tag = "4")]
+    pub opens_at: u64,
+    /// Unix timestamp in seconds
+    #[prost(uint64, tag = "5")]
+    pub closes_at: u64,
+    /// Unix timestamp in seconds
+    #[prost(uint64, tag = "6")]
+    pub registration_opens_at: u64,
+    #[prost(enumeration = "ElectionPhase", tag = "7")]
+    pub phase: i32,
+    /// ElGamal key that ballots are encrypted with
+    #[prost(bytes = "vec", tag = "8")]
+    pub encryption_key: ::prost::alloc::vec::Vec<u8>,
+    /// Trustees needed to decrypt the tally, 0 if the server holds the key
+    #[prost(uint32, tag = "9")]
+    pub trustee_threshold: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetParametersReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+}
+/// Group and voter key parameters of an election, beta is derived from the election id
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetParametersRes {
+    /// Name of the group
+    #[prost(string, tag = "1")]
+    pub group: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub p: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub q: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub alpha: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub beta: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetAuthorityKeyReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+}
+/// RSA public key used for blind signatures of voter credentials of the election.
+/// Every election has its own exponent (see blind_signature::election_exponent), voters should check it.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetAuthorityKeyRes {
+    #[prost(bytes = "vec", tag = "1")]
+    pub n: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub e: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetElectionsReq {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetElectionsRes {
+    #[prost(message, repeated, tag = "1")]
+    pub elections: ::prost::alloc::vec::Vec<Election>,
+}
+/// Encrypted sum of all ballots for every option, what the trustees decrypt
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetEncryptedTallyReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetEncryptedTallyRes {
+    #[prost(message, repeated, tag = "1")]
+    pub tally: ::prost::alloc::vec::Vec<Ciphertext>,
+    #[prost(uint32, tag = "2")]
+    pub threshold: u32,
+    /// Key of trustee i is at i - 1
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub verification_keys: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// c1^x_i of one option's sum with a Chaum-Pedersen proof that x_i is behind the trustee's verification key
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PartialDecryption {
+    #[prost(bytes = "vec", tag = "1")]
+    pub d: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub ka: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub kb: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub solution: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitPartialDecryptionReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    /// Index of the trustee, starting from 1
+    #[prost(uint32, tag = "2")]
+    pub trustee: u32,
+    /// One for every option
+    #[prost(message, repeated, tag = "3")]
+    pub decryptions: ::prost::alloc::vec::Vec<PartialDecryption>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitPartialDecryptionRes {
+    /// Partial decryptions still needed before the results are computed
+    #[prost(uint32, tag = "1")]
+    pub remaining: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EncryptedBallot {
+    #[prost(message, repeated, tag = "1")]
+    pub options: ::prost::alloc::vec::Vec<Ciphertext>,
+    /// Validity proofs of a cast ballot, mixed ballots don't have them
+    #[prost(message, repeated, tag = "2")]
+    pub option_proofs: ::prost::alloc::vec::Vec<BitProof>,
+    #[prost(message, optional, tag = "3")]
+    pub sum_proof: ::core::option::Option<SumProof>,
+}
+/// Terelius-Wikstrom proof that the mixed ballots are a re-encrypted permutation of the cast ballots
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShuffleProof {
+    /// Commitment to the permutation, one per ballot
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub commitments: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// Commitment chain, one per ballot
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub chain: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// t1, t2, t3
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub t: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// One per option
+    #[prost(message, repeated, tag = "4")]
+    pub t4: ::prost::alloc::vec::Vec<Ciphertext>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub t_hat: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// s1, s2, s3
+    #[prost(bytes = "vec", repeated, tag = "6")]
+    pub s: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "7")]
+    pub s4: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "8")]
+    pub s_hat: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "9")]
+    pub s_prime: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// Cast ballots and their shuffle, the ballots are mixed the first time they are needed after closing
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetMixedBallotsReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetMixedBallotsRes {
+    #[prost(message, repeated, tag = "1")]
+    pub ballots: ::prost::alloc::vec::Vec<EncryptedBallot>,
+    /// The tally is the sum of these
+    #[prost(message, repeated, tag = "2")]
+    pub mixed_ballots: ::prost::alloc::vec::Vec<EncryptedBallot>,
+    #[prost(message, optional, tag = "3")]
+    pub proof: ::core::option::Option<ShuffleProof>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub encryption_key: ::prost::alloc::vec::Vec<u8>,
+}
+/// Hash-chained log of what the server accepted, see zkp_protocol::board for how entries are hashed
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBulletinBoardReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    /// Observers that already replicated the board only fetch the new entries
+    #[prost(uint64, tag = "2")]
+    pub from_index: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BoardSchedule {
+    #[prost(uint64, tag = "1")]
+    pub registration_opens_at: u64,
+    #[prost(uint64, tag = "2")]
+    pub opens_at: u64,
+    #[prost(uint64, tag = "3")]
+    pub closes_at: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BoardRegistration {
+    #[prost(bytes = "vec", tag = "1")]
+    pub a: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub b: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub alpha: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub beta: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BoardTally {
+    #[prost(uint32, repeated, tag = "1")]
+    pub votes: ::prost::alloc::vec::Vec<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BoardEntry {
+    #[prost(uint64, tag = "1")]
+    pub index: u64,
+    /// Hash of this entry chained to the previous one
+    #[prost(bytes = "vec", tag = "2")]
+    pub hash: ::prost::alloc::vec::Vec<u8>,
+    #[prost(oneof = "board_entry::Record", tags = "3, 4, 5, 6")]
+    pub record: ::core::option::Option<board_entry::Record>,
+}
+/// Nested message and enum types in `BoardEntry`.
+pub mod board_entry {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Record {
+        /// Election created or rescheduled
+        #[prost(message, tag = "3")]
+        Schedule(super::BoardSchedule),
+        #[prost(message, tag = "4")]
+        Registration(super::BoardRegistration),
+        #[prost(message, tag = "5")]
+        Ballot(super::EncryptedBallot),
+        #[prost(message, tag = "6")]
+        Tally(super::BoardTally),
     }
-    impl AnonVoteClient<tonic::transport::Channel> {
-        /// Attempt to create a new client by connecting to a given endpoint.
-        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
-        where
-            D: TryInto<tonic::transport::Endpoint>,
-            D::Error: Into<StdError>,
-        {
-            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
-            Ok(Self::new(conn))
-        }
+}
+/// Size and head of the whole board signed with the board key (Schnorr signature)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BoardCheckpoint {
+    #[prost(uint64, tag = "1")]
+    pub size: u64,
+    #[prost(bytes = "vec", tag = "2")]
+    pub head: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub public_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub ka: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub kb: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub solution: ::prost::alloc::vec::Vec<u8>,
+}
+/// Entries in order, the last message is the checkpoint
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BulletinBoardItem {
+    #[prost(oneof = "bulletin_board_item::Item", tags = "1, 2")]
+    pub item: ::core::option::Option<bulletin_board_item::Item>,
+}
+/// Nested message and enum types in `BulletinBoardItem`.
+pub mod bulletin_board_item {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Item {
+        #[prost(message, tag = "1")]
+        Entry(super::BoardEntry),
+        #[prost(message, tag = "2")]
+        Checkpoint(super::BoardCheckpoint),
     }
-    impl<T> AnonVoteClient<T>
-    where
-        T: tonic::client::GrpcService<tonic::body::BoxBody>,
-        T::Error: Into<StdError>,
-        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
-        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
-    {
-        pub fn new(inner: T) -> Self {
-            let inner = tonic::client::Grpc::new(inner);
-            Self { inner }
-        }
-        pub fn with_origin(inner: T, origin: Uri) -> Self {
-            let inner = tonic::client::Grpc::with_origin(inner, origin);
-            Self { inner }
-        }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> AnonVoteClient<InterceptedService<T, F>>
-        where
-            F: tonic::service::Interceptor,
-            T::ResponseBody: Default,
-            T: tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-                Response = http::Response<
-                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
-                >,
-            >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-            >>::Error: Into<StdError> + Send + Sync,
-        {
-            AnonVoteClient::new(InterceptedService::new(inner, interceptor))
-        }
-        /// Compress requests with the given encoding.
-        ///
-        /// This requires the server to support it otherwise it might respond with an
-        /// error.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.send_compressed(encoding);
-            self
-        }
-        /// Enable decompressing responses.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.accept_compressed(encoding);
-            self
-        }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_decoding_message_size(limit);
-            self
-        }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetInclusionProofReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub ballot_hash: ::prost::alloc::vec::Vec<u8>,
+}
+/// Receipt against the current root, with the sibling hashes from the leaf up to the root
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetInclusionProofRes {
+    #[prost(message, optional, tag = "1")]
+    pub receipt: ::core::option::Option<BallotReceipt>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub path: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateElectionReq {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub title: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "3")]
+    pub options: ::prost::alloc::vec::Vec<VoteOption>,
+    #[prost(uint64, tag = "4")]
+    pub registration_opens_at: u64,
+    #[prost(uint64, tag = "5")]
+    pub opens_at: u64,
+    #[prost(uint64, tag = "6")]
+    pub closes_at: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateElectionRes {
+    #[prost(message, optional, tag = "1")]
+    pub election: ::core::option::Option<Election>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetOptionsReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub options: ::prost::alloc::vec::Vec<VoteOption>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetOptionsRes {
+    #[prost(message, optional, tag = "1")]
+    pub election: ::core::option::Option<Election>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetPhaseReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    /// REGISTRATION_OPEN, VOTING_OPEN or CLOSED
+    #[prost(enumeration = "ElectionPhase", tag = "2")]
+    pub phase: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetPhaseRes {
+    #[prost(message, optional, tag = "1")]
+    pub election: ::core::option::Option<Election>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevokeRegistrationCodeReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub registration_key: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevokeRegistrationCodeRes {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetElectionStatsReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetElectionStatsRes {
+    #[prost(uint64, tag = "1")]
+    pub registered_ids: u64,
+    #[prost(uint64, tag = "2")]
+    pub active_registration_codes: u64,
+    #[prost(uint64, tag = "3")]
+    pub registered_users: u64,
+    #[prost(uint64, tag = "4")]
+    pub pending_votes: u64,
+    #[prost(uint64, tag = "5")]
+    pub pending_challenges: u64,
+    #[prost(uint64, tag = "6")]
+    pub votes: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportTallyReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OptionTally {
+    #[prost(message, optional, tag = "1")]
+    pub option: ::core::option::Option<VoteOption>,
+    #[prost(uint32, tag = "2")]
+    pub votes: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportTallyRes {
+    #[prost(message, optional, tag = "1")]
+    pub election: ::core::option::Option<Election>,
+    #[prost(message, repeated, tag = "2")]
+    pub results: ::prost::alloc::vec::Vec<OptionTally>,
+    #[prost(uint32, tag = "3")]
+    pub total_votes: u32,
+}
+/// Public result of the trustee key ceremony, the tally can only be decrypted by threshold trustees
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetTrusteesReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub threshold: u32,
+    #[prost(bytes = "vec", tag = "3")]
+    pub encryption_key: ::prost::alloc::vec::Vec<u8>,
+    /// Key of trustee i is at i - 1
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub verification_keys: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetTrusteesRes {
+    #[prost(message, optional, tag = "1")]
+    pub election: ::core::option::Option<Election>,
+}
+/// Election record of a tallied election for archiving, see "Election records" in the README. Tallies the election if it is closed and not tallied yet.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportElectionReq {
+    #[prost(string, tag = "1")]
+    pub election_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "RecordFormat", tag = "2")]
+    pub format: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportElectionRes {
+    #[prost(bytes = "vec", tag = "1")]
+    pub record: ::prost::alloc::vec::Vec<u8>,
+}
+/// Record in either format, only imported if it verifies and no election with the same id exists
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportElectionReq {
+    #[prost(bytes = "vec", tag = "1")]
+    pub record: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportElectionRes {
+    #[prost(message, optional, tag = "1")]
+    pub election: ::core::option::Option<Election>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ElectionPhase {
+    Draft = 0,
+    RegistrationOpen = 1,
+    VotingOpen = 2,
+    Closed = 3,
+    Tallied = 4,
+}
+impl ElectionPhase {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ElectionPhase::Draft => "DRAFT",
+            ElectionPhase::RegistrationOpen => "REGISTRATION_OPEN",
+            ElectionPhase::VotingOpen => "VOTING_OPEN",
+            ElectionPhase::Closed => "CLOSED",
+            ElectionPhase::Tallied => "TALLIED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DRAFT" => Some(Self::Draft),
+            "REGISTRATION_OPEN" => Some(Self::RegistrationOpen),
+            "VOTING_OPEN" => Some(Self::VotingOpen),
+            "CLOSED" => Some(Self::Closed),
+            "TALLIED" => Some(Self::Tallied),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RecordFormat {
+    Json = 0,
+    /// MessagePack after the "AVREC" magic bytes
+    Binary = 1,
+}
+impl RecordFormat {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            RecordFormat::Json => "JSON",
+            RecordFormat::Binary => "BINARY",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "JSON" => Some(Self::Json),
+            "BINARY" => Some(Self::Binary),
+            _ => None,
+        }
+    }
+}
+/// Generated client implementations.
+pub mod anon_vote_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    #[derive(Debug, Clone)]
+    pub struct AnonVoteClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl AnonVoteClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> AnonVoteClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> AnonVoteClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            AnonVoteClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
         /// Limits the maximum size of an encoded message.
         ///
         /// Default: `usize::MAX`
@@ -257,6 +906,28 @@ pub mod anon_vote_client {
                 .insert(GrpcMethod::new("anonvote.AnonVote", "ValidateVote"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn cast_vote(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CastVoteReq>,
+        ) -> std::result::Result<tonic::Response<super::CastVoteRes>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/CastVote",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "CastVote"));
+            self.inner.unary(req, path, codec).await
+        }
         pub async fn get_vote_options(
             &mut self,
             request: impl tonic::IntoRequest<super::GetVoteOptionsReq>,
@@ -282,67 +953,1480 @@ pub mod anon_vote_client {
                 .insert(GrpcMethod::new("anonvote.AnonVote", "GetVoteOptions"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_results(
-            &mut self,
-            request: impl tonic::IntoRequest<super::GetResultsReq>,
-        ) -> std::result::Result<tonic::Response<super::GetResultsRes>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/anonvote.AnonVote/GetResults",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("anonvote.AnonVote", "GetResults"));
-            self.inner.unary(req, path, codec).await
+        pub async fn get_results(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetResultsReq>,
+        ) -> std::result::Result<tonic::Response<super::GetResultsRes>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/GetResults",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "GetResults"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_elections(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetElectionsReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetElectionsRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/GetElections",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "GetElections"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_authority_key(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetAuthorityKeyReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetAuthorityKeyRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/GetAuthorityKey",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "GetAuthorityKey"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_parameters(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetParametersReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetParametersRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/GetParameters",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "GetParameters"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_encrypted_tally(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetEncryptedTallyReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetEncryptedTallyRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/GetEncryptedTally",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "GetEncryptedTally"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn submit_partial_decryption(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubmitPartialDecryptionReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::SubmitPartialDecryptionRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/SubmitPartialDecryption",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "SubmitPartialDecryption"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_mixed_ballots(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetMixedBallotsReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetMixedBallotsRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/GetMixedBallots",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "GetMixedBallots"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_bulletin_board(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetBulletinBoardReq>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::BulletinBoardItem>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/GetBulletinBoard",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "GetBulletinBoard"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn get_inclusion_proof(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetInclusionProofReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetInclusionProofRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVote/GetInclusionProof",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVote", "GetInclusionProof"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated client implementations.
+pub mod anon_vote_admin_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// Every admin request needs "authorization: Bearer <admin token>" metadata
+    #[derive(Debug, Clone)]
+    pub struct AnonVoteAdminClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl AnonVoteAdminClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> AnonVoteAdminClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> AnonVoteAdminClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            AnonVoteAdminClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        pub async fn create_election(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateElectionReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateElectionRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/CreateElection",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVoteAdmin", "CreateElection"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn set_options(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetOptionsReq>,
+        ) -> std::result::Result<tonic::Response<super::SetOptionsRes>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/SetOptions",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVoteAdmin", "SetOptions"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn set_phase(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetPhaseReq>,
+        ) -> std::result::Result<tonic::Response<super::SetPhaseRes>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/SetPhase",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVoteAdmin", "SetPhase"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn set_trustees(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetTrusteesReq>,
+        ) -> std::result::Result<tonic::Response<super::SetTrusteesRes>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/SetTrustees",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVoteAdmin", "SetTrustees"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn revoke_registration_code(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevokeRegistrationCodeReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::RevokeRegistrationCodeRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/RevokeRegistrationCode",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("anonvote.AnonVoteAdmin", "RevokeRegistrationCode"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_election_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetElectionStatsReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetElectionStatsRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/GetElectionStats",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVoteAdmin", "GetElectionStats"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn export_tally(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExportTallyReq>,
+        ) -> std::result::Result<tonic::Response<super::ExportTallyRes>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/ExportTally",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVoteAdmin", "ExportTally"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn export_election(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExportElectionReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExportElectionRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/ExportElection",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVoteAdmin", "ExportElection"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn import_election(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImportElectionReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportElectionRes>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/anonvote.AnonVoteAdmin/ImportElection",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("anonvote.AnonVoteAdmin", "ImportElection"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod anon_vote_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with AnonVoteServer.
+    #[async_trait]
+    pub trait AnonVote: Send + Sync + 'static {
+        async fn validate_id(
+            &self,
+            request: tonic::Request<super::ValidateIdReq>,
+        ) -> std::result::Result<tonic::Response<super::ValidateIdRes>, tonic::Status>;
+        async fn register(
+            &self,
+            request: tonic::Request<super::RegisterReq>,
+        ) -> std::result::Result<tonic::Response<super::RegisterRes>, tonic::Status>;
+        async fn vote(
+            &self,
+            request: tonic::Request<super::VoteReq>,
+        ) -> std::result::Result<tonic::Response<super::VoteRes>, tonic::Status>;
+        async fn validate_vote(
+            &self,
+            request: tonic::Request<super::ValidateVoteReq>,
+        ) -> std::result::Result<tonic::Response<super::ValidateVoteRes>, tonic::Status>;
+        async fn cast_vote(
+            &self,
+            request: tonic::Request<super::CastVoteReq>,
+        ) -> std::result::Result<tonic::Response<super::CastVoteRes>, tonic::Status>;
+        async fn get_vote_options(
+            &self,
+            request: tonic::Request<super::GetVoteOptionsReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetVoteOptionsRes>,
+            tonic::Status,
+        >;
+        async fn get_results(
+            &self,
+            request: tonic::Request<super::GetResultsReq>,
+        ) -> std::result::Result<tonic::Response<super::GetResultsRes>, tonic::Status>;
+        async fn get_elections(
+            &self,
+            request: tonic::Request<super::GetElectionsReq>,
+        ) -> std::result::Result<tonic::Response<super::GetElectionsRes>, tonic::Status>;
+        async fn get_authority_key(
+            &self,
+            request: tonic::Request<super::GetAuthorityKeyReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetAuthorityKeyRes>,
+            tonic::Status,
+        >;
+        async fn get_parameters(
+            &self,
+            request: tonic::Request<super::GetParametersReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetParametersRes>,
+            tonic::Status,
+        >;
+        async fn get_encrypted_tally(
+            &self,
+            request: tonic::Request<super::GetEncryptedTallyReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetEncryptedTallyRes>,
+            tonic::Status,
+        >;
+        async fn submit_partial_decryption(
+            &self,
+            request: tonic::Request<super::SubmitPartialDecryptionReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::SubmitPartialDecryptionRes>,
+            tonic::Status,
+        >;
+        async fn get_mixed_ballots(
+            &self,
+            request: tonic::Request<super::GetMixedBallotsReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetMixedBallotsRes>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the GetBulletinBoard method.
+        type GetBulletinBoardStream: futures_core::Stream<
+                Item = std::result::Result<super::BulletinBoardItem, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn get_bulletin_board(
+            &self,
+            request: tonic::Request<super::GetBulletinBoardReq>,
+        ) -> std::result::Result<
+            tonic::Response<Self::GetBulletinBoardStream>,
+            tonic::Status,
+        >;
+        async fn get_inclusion_proof(
+            &self,
+            request: tonic::Request<super::GetInclusionProofReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetInclusionProofRes>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct AnonVoteServer<T: AnonVote> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: AnonVote> AnonVoteServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for AnonVoteServer<T>
+    where
+        T: AnonVote,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/anonvote.AnonVote/ValidateID" => {
+                    #[allow(non_camel_case_types)]
+                    struct ValidateIDSvc<T: AnonVote>(pub Arc<T>);
+                    impl<T: AnonVote> tonic::server::UnaryService<super::ValidateIdReq>
+                    for ValidateIDSvc<T> {
+                        type Response = super::ValidateIdRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ValidateIdReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).validate_id(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ValidateIDSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/Register" => {
+                    #[allow(non_camel_case_types)]
+                    struct RegisterSvc<T: AnonVote>(pub Arc<T>);
+                    impl<T: AnonVote> tonic::server::UnaryService<super::RegisterReq>
+                    for RegisterSvc<T> {
+                        type Response = super::RegisterRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RegisterReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).register(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RegisterSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/Vote" => {
+                    #[allow(non_camel_case_types)]
+                    struct VoteSvc<T: AnonVote>(pub Arc<T>);
+                    impl<T: AnonVote> tonic::server::UnaryService<super::VoteReq>
+                    for VoteSvc<T> {
+                        type Response = super::VoteRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::VoteReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).vote(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = VoteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/ValidateVote" => {
+                    #[allow(non_camel_case_types)]
+                    struct ValidateVoteSvc<T: AnonVote>(pub Arc<T>);
+                    impl<T: AnonVote> tonic::server::UnaryService<super::ValidateVoteReq>
+                    for ValidateVoteSvc<T> {
+                        type Response = super::ValidateVoteRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ValidateVoteReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).validate_vote(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ValidateVoteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/CastVote" => {
+                    #[allow(non_camel_case_types)]
+                    struct CastVoteSvc<T: AnonVote>(pub Arc<T>);
+                    impl<T: AnonVote> tonic::server::UnaryService<super::CastVoteReq>
+                    for CastVoteSvc<T> {
+                        type Response = super::CastVoteRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CastVoteReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).cast_vote(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CastVoteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetVoteOptions" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetVoteOptionsSvc<T: AnonVote>(pub Arc<T>);
+                    impl<
+                        T: AnonVote,
+                    > tonic::server::UnaryService<super::GetVoteOptionsReq>
+                    for GetVoteOptionsSvc<T> {
+                        type Response = super::GetVoteOptionsRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetVoteOptionsReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_vote_options(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetVoteOptionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetResults" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetResultsSvc<T: AnonVote>(pub Arc<T>);
+                    impl<T: AnonVote> tonic::server::UnaryService<super::GetResultsReq>
+                    for GetResultsSvc<T> {
+                        type Response = super::GetResultsRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetResultsReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).get_results(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetResultsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetElections" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetElectionsSvc<T: AnonVote>(pub Arc<T>);
+                    impl<T: AnonVote> tonic::server::UnaryService<super::GetElectionsReq>
+                    for GetElectionsSvc<T> {
+                        type Response = super::GetElectionsRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetElectionsReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_elections(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetElectionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetAuthorityKey" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetAuthorityKeySvc<T: AnonVote>(pub Arc<T>);
+                    impl<
+                        T: AnonVote,
+                    > tonic::server::UnaryService<super::GetAuthorityKeyReq>
+                    for GetAuthorityKeySvc<T> {
+                        type Response = super::GetAuthorityKeyRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetAuthorityKeyReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_authority_key(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetAuthorityKeySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetParameters" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetParametersSvc<T: AnonVote>(pub Arc<T>);
+                    impl<
+                        T: AnonVote,
+                    > tonic::server::UnaryService<super::GetParametersReq>
+                    for GetParametersSvc<T> {
+                        type Response = super::GetParametersRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetParametersReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_parameters(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetParametersSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetEncryptedTally" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetEncryptedTallySvc<T: AnonVote>(pub Arc<T>);
+                    impl<
+                        T: AnonVote,
+                    > tonic::server::UnaryService<super::GetEncryptedTallyReq>
+                    for GetEncryptedTallySvc<T> {
+                        type Response = super::GetEncryptedTallyRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetEncryptedTallyReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_encrypted_tally(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetEncryptedTallySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/SubmitPartialDecryption" => {
+                    #[allow(non_camel_case_types)]
+                    struct SubmitPartialDecryptionSvc<T: AnonVote>(pub Arc<T>);
+                    impl<
+                        T: AnonVote,
+                    > tonic::server::UnaryService<super::SubmitPartialDecryptionReq>
+                    for SubmitPartialDecryptionSvc<T> {
+                        type Response = super::SubmitPartialDecryptionRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SubmitPartialDecryptionReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).submit_partial_decryption(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SubmitPartialDecryptionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetMixedBallots" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetMixedBallotsSvc<T: AnonVote>(pub Arc<T>);
+                    impl<
+                        T: AnonVote,
+                    > tonic::server::UnaryService<super::GetMixedBallotsReq>
+                    for GetMixedBallotsSvc<T> {
+                        type Response = super::GetMixedBallotsRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetMixedBallotsReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_mixed_ballots(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetMixedBallotsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetBulletinBoard" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetBulletinBoardSvc<T: AnonVote>(pub Arc<T>);
+                    impl<
+                        T: AnonVote,
+                    > tonic::server::ServerStreamingService<super::GetBulletinBoardReq>
+                    for GetBulletinBoardSvc<T> {
+                        type Response = super::BulletinBoardItem;
+                        type ResponseStream = T::GetBulletinBoardStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetBulletinBoardReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_bulletin_board(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetBulletinBoardSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVote/GetInclusionProof" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetInclusionProofSvc<T: AnonVote>(pub Arc<T>);
+                    impl<
+                        T: AnonVote,
+                    > tonic::server::UnaryService<super::GetInclusionProofReq>
+                    for GetInclusionProofSvc<T> {
+                        type Response = super::GetInclusionProofRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetInclusionProofReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_inclusion_proof(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetInclusionProofSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        Ok(
+                            http::Response::builder()
+                                .status(200)
+                                .header("grpc-status", "12")
+                                .header("content-type", "application/grpc")
+                                .body(empty_body())
+                                .unwrap(),
+                        )
+                    })
+                }
+            }
+        }
+    }
+    impl<T: AnonVote> Clone for AnonVoteServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    impl<T: AnonVote> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(Arc::clone(&self.0))
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
         }
     }
+    impl<T: AnonVote> tonic::server::NamedService for AnonVoteServer<T> {
+        const NAME: &'static str = "anonvote.AnonVote";
+    }
 }
 /// Generated server implementations.
-pub mod anon_vote_server {
+pub mod anon_vote_admin_server {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with AnonVoteServer.
+    /// Generated trait containing gRPC methods that should be implemented for use with AnonVoteAdminServer.
     #[async_trait]
-    pub trait AnonVote: Send + Sync + 'static {
-        async fn validate_id(
+    pub trait AnonVoteAdmin: Send + Sync + 'static {
+        async fn create_election(
             &self,
-            request: tonic::Request<super::ValidateIdReq>,
-        ) -> std::result::Result<tonic::Response<super::ValidateIdRes>, tonic::Status>;
-        async fn register(
+            request: tonic::Request<super::CreateElectionReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateElectionRes>,
+            tonic::Status,
+        >;
+        async fn set_options(
             &self,
-            request: tonic::Request<super::RegisterReq>,
-        ) -> std::result::Result<tonic::Response<super::RegisterRes>, tonic::Status>;
-        async fn vote(
+            request: tonic::Request<super::SetOptionsReq>,
+        ) -> std::result::Result<tonic::Response<super::SetOptionsRes>, tonic::Status>;
+        async fn set_phase(
             &self,
-            request: tonic::Request<super::VoteReq>,
-        ) -> std::result::Result<tonic::Response<super::VoteRes>, tonic::Status>;
-        async fn validate_vote(
+            request: tonic::Request<super::SetPhaseReq>,
+        ) -> std::result::Result<tonic::Response<super::SetPhaseRes>, tonic::Status>;
+        async fn set_trustees(
             &self,
-            request: tonic::Request<super::ValidateVoteReq>,
-        ) -> std::result::Result<tonic::Response<super::ValidateVoteRes>, tonic::Status>;
-        async fn get_vote_options(
+            request: tonic::Request<super::SetTrusteesReq>,
+        ) -> std::result::Result<tonic::Response<super::SetTrusteesRes>, tonic::Status>;
+        async fn revoke_registration_code(
             &self,
-            request: tonic::Request<super::GetVoteOptionsReq>,
+            request: tonic::Request<super::RevokeRegistrationCodeReq>,
         ) -> std::result::Result<
-            tonic::Response<super::GetVoteOptionsRes>,
+            tonic::Response<super::RevokeRegistrationCodeRes>,
             tonic::Status,
         >;
-        async fn get_results(
+        async fn get_election_stats(
             &self,
-            request: tonic::Request<super::GetResultsReq>,
-        ) -> std::result::Result<tonic::Response<super::GetResultsRes>, tonic::Status>;
+            request: tonic::Request<super::GetElectionStatsReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetElectionStatsRes>,
+            tonic::Status,
+        >;
+        async fn export_tally(
+            &self,
+            request: tonic::Request<super::ExportTallyReq>,
+        ) -> std::result::Result<tonic::Response<super::ExportTallyRes>, tonic::Status>;
+        async fn export_election(
+            &self,
+            request: tonic::Request<super::ExportElectionReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExportElectionRes>,
+            tonic::Status,
+        >;
+        async fn import_election(
+            &self,
+            request: tonic::Request<super::ImportElectionReq>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportElectionRes>,
+            tonic::Status,
+        >;
     }
+    /// Every admin request needs "authorization: Bearer <admin token>" metadata
     #[derive(Debug)]
-    pub struct AnonVoteServer<T: AnonVote> {
+    pub struct AnonVoteAdminServer<T: AnonVoteAdmin> {
         inner: _Inner<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
@@ -350,7 +2434,7 @@ pub mod anon_vote_server {
         max_encoding_message_size: Option<usize>,
     }
     struct _Inner<T>(Arc<T>);
-    impl<T: AnonVote> AnonVoteServer<T> {
+    impl<T: AnonVoteAdmin> AnonVoteAdminServer<T> {
         pub fn new(inner: T) -> Self {
             Self::from_arc(Arc::new(inner))
         }
@@ -402,9 +2486,9 @@ pub mod anon_vote_server {
             self
         }
     }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for AnonVoteServer<T>
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for AnonVoteAdminServer<T>
     where
-        T: AnonVote,
+        T: AnonVoteAdmin,
         B: Body + Send + 'static,
         B::Error: Into<StdError> + Send + 'static,
     {
@@ -420,22 +2504,26 @@ pub mod anon_vote_server {
         fn call(&mut self, req: http::Request<B>) -> Self::Future {
             let inner = self.inner.clone();
             match req.uri().path() {
-                "/anonvote.AnonVote/ValidateID" => {
+                "/anonvote.AnonVoteAdmin/CreateElection" => {
                     #[allow(non_camel_case_types)]
-                    struct ValidateIDSvc<T: AnonVote>(pub Arc<T>);
-                    impl<T: AnonVote> tonic::server::UnaryService<super::ValidateIdReq>
-                    for ValidateIDSvc<T> {
-                        type Response = super::ValidateIdRes;
+                    struct CreateElectionSvc<T: AnonVoteAdmin>(pub Arc<T>);
+                    impl<
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::CreateElectionReq>
+                    for CreateElectionSvc<T> {
+                        type Response = super::CreateElectionRes;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ValidateIdReq>,
+                            request: tonic::Request<super::CreateElectionReq>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move { (*inner).validate_id(request).await };
+                            let fut = async move {
+                                (*inner).create_election(request).await
+                            };
                             Box::pin(fut)
                         }
                     }
@@ -446,7 +2534,7 @@ pub mod anon_vote_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ValidateIDSvc(inner);
+                        let method = CreateElectionSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -462,22 +2550,24 @@ pub mod anon_vote_server {
                     };
                     Box::pin(fut)
                 }
-                "/anonvote.AnonVote/Register" => {
+                "/anonvote.AnonVoteAdmin/SetOptions" => {
                     #[allow(non_camel_case_types)]
-                    struct RegisterSvc<T: AnonVote>(pub Arc<T>);
-                    impl<T: AnonVote> tonic::server::UnaryService<super::RegisterReq>
-                    for RegisterSvc<T> {
-                        type Response = super::RegisterRes;
+                    struct SetOptionsSvc<T: AnonVoteAdmin>(pub Arc<T>);
+                    impl<
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::SetOptionsReq>
+                    for SetOptionsSvc<T> {
+                        type Response = super::SetOptionsRes;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::RegisterReq>,
+                            request: tonic::Request<super::SetOptionsReq>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move { (*inner).register(request).await };
+                            let fut = async move { (*inner).set_options(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -488,7 +2578,7 @@ pub mod anon_vote_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = RegisterSvc(inner);
+                        let method = SetOptionsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -504,22 +2594,24 @@ pub mod anon_vote_server {
                     };
                     Box::pin(fut)
                 }
-                "/anonvote.AnonVote/Vote" => {
+                "/anonvote.AnonVoteAdmin/SetPhase" => {
                     #[allow(non_camel_case_types)]
-                    struct VoteSvc<T: AnonVote>(pub Arc<T>);
-                    impl<T: AnonVote> tonic::server::UnaryService<super::VoteReq>
-                    for VoteSvc<T> {
-                        type Response = super::VoteRes;
+                    struct SetPhaseSvc<T: AnonVoteAdmin>(pub Arc<T>);
+                    impl<
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::SetPhaseReq>
+                    for SetPhaseSvc<T> {
+                        type Response = super::SetPhaseRes;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::VoteReq>,
+                            request: tonic::Request<super::SetPhaseReq>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move { (*inner).vote(request).await };
+                            let fut = async move { (*inner).set_phase(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -530,7 +2622,7 @@ pub mod anon_vote_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = VoteSvc(inner);
+                        let method = SetPhaseSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -546,23 +2638,25 @@ pub mod anon_vote_server {
                     };
                     Box::pin(fut)
                 }
-                "/anonvote.AnonVote/ValidateVote" => {
+                "/anonvote.AnonVoteAdmin/SetTrustees" => {
                     #[allow(non_camel_case_types)]
-                    struct ValidateVoteSvc<T: AnonVote>(pub Arc<T>);
-                    impl<T: AnonVote> tonic::server::UnaryService<super::ValidateVoteReq>
-                    for ValidateVoteSvc<T> {
-                        type Response = super::ValidateVoteRes;
+                    struct SetTrusteesSvc<T: AnonVoteAdmin>(pub Arc<T>);
+                    impl<
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::SetTrusteesReq>
+                    for SetTrusteesSvc<T> {
+                        type Response = super::SetTrusteesRes;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ValidateVoteReq>,
+                            request: tonic::Request<super::SetTrusteesReq>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                (*inner).validate_vote(request).await
+                                (*inner).set_trustees(request).await
                             };
                             Box::pin(fut)
                         }
@@ -574,7 +2668,7 @@ pub mod anon_vote_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ValidateVoteSvc(inner);
+                        let method = SetTrusteesSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -590,25 +2684,25 @@ pub mod anon_vote_server {
                     };
                     Box::pin(fut)
                 }
-                "/anonvote.AnonVote/GetVoteOptions" => {
+                "/anonvote.AnonVoteAdmin/RevokeRegistrationCode" => {
                     #[allow(non_camel_case_types)]
-                    struct GetVoteOptionsSvc<T: AnonVote>(pub Arc<T>);
+                    struct RevokeRegistrationCodeSvc<T: AnonVoteAdmin>(pub Arc<T>);
                     impl<
-                        T: AnonVote,
-                    > tonic::server::UnaryService<super::GetVoteOptionsReq>
-                    for GetVoteOptionsSvc<T> {
-                        type Response = super::GetVoteOptionsRes;
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::RevokeRegistrationCodeReq>
+                    for RevokeRegistrationCodeSvc<T> {
+                        type Response = super::RevokeRegistrationCodeRes;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetVoteOptionsReq>,
+                            request: tonic::Request<super::RevokeRegistrationCodeReq>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                (*inner).get_vote_options(request).await
+                                (*inner).revoke_registration_code(request).await
                             };
                             Box::pin(fut)
                         }
@@ -620,7 +2714,7 @@ pub mod anon_vote_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = GetVoteOptionsSvc(inner);
+                        let method = RevokeRegistrationCodeSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -636,22 +2730,26 @@ pub mod anon_vote_server {
                     };
                     Box::pin(fut)
                 }
-                "/anonvote.AnonVote/GetResults" => {
+                "/anonvote.AnonVoteAdmin/GetElectionStats" => {
                     #[allow(non_camel_case_types)]
-                    struct GetResultsSvc<T: AnonVote>(pub Arc<T>);
-                    impl<T: AnonVote> tonic::server::UnaryService<super::GetResultsReq>
-                    for GetResultsSvc<T> {
-                        type Response = super::GetResultsRes;
+                    struct GetElectionStatsSvc<T: AnonVoteAdmin>(pub Arc<T>);
+                    impl<
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::GetElectionStatsReq>
+                    for GetElectionStatsSvc<T> {
+                        type Response = super::GetElectionStatsRes;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetResultsReq>,
+                            request: tonic::Request<super::GetElectionStatsReq>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move { (*inner).get_results(request).await };
+                            let fut = async move {
+                                (*inner).get_election_stats(request).await
+                            };
                             Box::pin(fut)
                         }
                     }
@@ -662,7 +2760,145 @@ pub mod anon_vote_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = GetResultsSvc(inner);
+                        let method = GetElectionStatsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVoteAdmin/ExportTally" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExportTallySvc<T: AnonVoteAdmin>(pub Arc<T>);
+                    impl<
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::ExportTallyReq>
+                    for ExportTallySvc<T> {
+                        type Response = super::ExportTallyRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExportTallyReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).export_tally(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ExportTallySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVoteAdmin/ExportElection" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExportElectionSvc<T: AnonVoteAdmin>(pub Arc<T>);
+                    impl<
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::ExportElectionReq>
+                    for ExportElectionSvc<T> {
+                        type Response = super::ExportElectionRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExportElectionReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).export_election(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ExportElectionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/anonvote.AnonVoteAdmin/ImportElection" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportElectionSvc<T: AnonVoteAdmin>(pub Arc<T>);
+                    impl<
+                        T: AnonVoteAdmin,
+                    > tonic::server::UnaryService<super::ImportElectionReq>
+                    for ImportElectionSvc<T> {
+                        type Response = super::ImportElectionRes;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ImportElectionReq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).import_election(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ImportElectionSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -693,7 +2929,7 @@ pub mod anon_vote_server {
             }
         }
     }
-    impl<T: AnonVote> Clone for AnonVoteServer<T> {
+    impl<T: AnonVoteAdmin> Clone for AnonVoteAdminServer<T> {
         fn clone(&self) -> Self {
             let inner = self.inner.clone();
             Self {
@@ -705,7 +2941,7 @@ pub mod anon_vote_server {
             }
         }
     }
-    impl<T: AnonVote> Clone for _Inner<T> {
+    impl<T: AnonVoteAdmin> Clone for _Inner<T> {
         fn clone(&self) -> Self {
             Self(Arc::clone(&self.0))
         }
@@ -715,7 +2951,7 @@ pub mod anon_vote_server {
             write!(f, "{:?}", self.0)
         }
     }
-    impl<T: AnonVote> tonic::server::NamedService for AnonVoteServer<T> {
-        const NAME: &'static str = "anonvote.AnonVote";
+    impl<T: AnonVoteAdmin> tonic::server::NamedService for AnonVoteAdminServer<T> {
+        const NAME: &'static str = "anonvote.AnonVoteAdmin";
     }
 }
@@ -0,0 +1,515 @@
+use crate::db::VoteStore;
+use crate::model::election::{self, Election, ElectionPhase};
+use crate::model::candidate::Candidate;
+use crate::model::group::StoredGroup;
+use crate::model::trustees::TrusteeKey;
+use crate::server_impl::{storage_error, vote_option_msg, election_msg, tally_election};
+use crate::{candidates, elections, records};
+
+use anonvote_proto::proto::anonvote::anon_vote_admin_server::AnonVoteAdmin;
+use anonvote_proto::proto::anonvote::{CreateElectionReq, CreateElectionRes, SetOptionsReq, SetOptionsRes, SetPhaseReq, SetPhaseRes, SetTrusteesReq, SetTrusteesRes, RevokeRegistrationCodeReq, RevokeRegistrationCodeRes, GetElectionStatsReq, GetElectionStatsRes, ExportTallyReq, ExportTallyRes, OptionTally, VoteOption, ExportElectionReq, ExportElectionRes, ImportElectionReq, ImportElectionRes, RecordFormat};
+use anonvote_proto::proto::anonvote::ElectionPhase as ElectionPhaseMsg;
+use anonvote_verifier::record::ElectionRecord;
+use anonvote_verifier::verify;
+
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+use zkp_protocol::board::BoardKey;
+use zkp_protocol::elgamal::{DecryptionKey, EncryptionKey};
+
+use tonic::{Request, Response, Status, Code };
+use tonic::service::Interceptor;
+
+// Checks the "authorization: Bearer <token>" metadata of every admin request
+#[derive(Clone)]
+pub struct AdminAuth {
+    token : String,
+}
+
+impl AdminAuth {
+    pub fn new(token : String) -> AdminAuth {
+        AdminAuth {
+            token
+        }
+    }
+
+    // Compares every byte so the time it takes doesn't tell how much of the token was right
+    fn token_valid(&self, token : &str) -> bool {
+        let (expected, token) = (self.token.as_bytes(), token.as_bytes());
+        expected.len() == token.len() && expected.iter().zip(token).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+impl Interceptor for AdminAuth {
+    fn call(&mut self, req : Request<()>) -> Result<Request<()>, Status> {
+        let token = req.metadata().get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match token {
+            Some(token) if self.token_valid(token) => Ok(req),
+            _ => Err(Status::new(Code::Unauthenticated, "Invalid admin token!")),
+        }
+    }
+}
+
+fn candidate(option : VoteOption) -> Candidate {
+    Candidate {
+        id : option.id,
+        name : option.name,
+        description : option.description,
+    }
+}
+
+pub struct AnonVoteAdminImpl<S : VoteStore> {
+    db : Arc<S>,
+    decryption_key : DecryptionKey, // Exporting the tally decrypts it
+    board_key : BoardKey, // Signs the bulletin board of exported records
+}
+
+impl<S : VoteStore> AnonVoteAdminImpl<S> {
+    pub fn new(db : Arc<S>, decryption_key : DecryptionKey, board_key : BoardKey) -> AnonVoteAdminImpl<S> {
+        AnonVoteAdminImpl {
+            db,
+            decryption_key,
+            board_key
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn get_election(&self, election_id : &str) -> Result<Election, Status> {
+        self.db.get_election(election_id).ok_or(Status::new(Code::NotFound, "Election not found!"))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn validate_options(options : Vec<VoteOption>) -> Result<Vec<Candidate>, Status> {
+        let options : Vec<Candidate> = options.into_iter().map(candidate).collect();
+        candidates::validate_candidates(&options).map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?;
+        Ok(options)
+    }
+}
+
+#[tonic::async_trait]
+impl<S : VoteStore> AnonVoteAdmin for AnonVoteAdminImpl<S> {
+    async fn create_election(&self, req : Request<CreateElectionReq>) -> Result<Response<CreateElectionRes>, Status> {
+        let req = req.into_inner();
+        let election = Election {
+            id : req.id,
+            title : req.title,
+            options : Self::validate_options(req.options)?,
+            registration_opens_at : req.registration_opens_at,
+            opens_at : req.opens_at,
+            closes_at : req.closes_at,
+            group : StoredGroup(self.decryption_key.group().clone()), // Elections are in the group of the server keys
+            tally : None,
+            trustees : None,
+            partial_tallies : Vec::new(),
+        };
+        elections::validate_elections(std::slice::from_ref(&election)).map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?;
+
+        let created = self.db.create_election(election.clone()).map_err(storage_error)?;
+        if !created {
+            return Err(Status::new(Code::AlreadyExists, "Election with this id already exists!"));
+        }
+        Ok(Response::new(CreateElectionRes {
+            election : Some(election_msg(&election, self.decryption_key.encryption_key()))
+        }))
+    }
+
+    async fn set_options(&self, req : Request<SetOptionsReq>) -> Result<Response<SetOptionsRes>, Status> {
+        let req = req.into_inner();
+        self.get_election(&req.election_id)?;
+
+        // Votes are indices of the options, so options can't change once voting starts. The phase is checked with the change.
+        let options = Self::validate_options(req.options)?;
+        let changed = self.db.set_options(&req.election_id, options, election::now()).map_err(storage_error)?;
+        if !changed {
+            return Err(Status::new(Code::FailedPrecondition, "Options can't be changed once voting is open!"));
+        }
+        Ok(Response::new(SetOptionsRes {
+            election : Some(election_msg(&self.get_election(&req.election_id)?, self.decryption_key.encryption_key()))
+        }))
+    }
+
+    async fn set_phase(&self, req : Request<SetPhaseReq>) -> Result<Response<SetPhaseRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+
+        let phase = match ElectionPhaseMsg::from_i32(req.phase) {
+            Some(ElectionPhaseMsg::RegistrationOpen) => ElectionPhase::RegistrationOpen,
+            Some(ElectionPhaseMsg::VotingOpen) => ElectionPhase::VotingOpen,
+            Some(ElectionPhaseMsg::Closed) => ElectionPhase::Closed,
+            _ => return Err(Status::new(Code::InvalidArgument, "Only registration, voting or closed phase can be set!")),
+        };
+
+        let schedule = election.schedule_for_phase(phase, election::now());
+        let (registration_opens_at, opens_at, closes_at) = schedule.ok_or(Status::new(Code::FailedPrecondition, "Election can only move to a later phase!"))?;
+        let changed = self.db.set_schedule(&req.election_id, registration_opens_at, opens_at, closes_at).map_err(storage_error)?;
+        if !changed {
+            return Err(Status::new(Code::FailedPrecondition, "Election can only move to a later phase!"));
+        }
+        Ok(Response::new(SetPhaseRes {
+            election : Some(election_msg(&self.get_election(&req.election_id)?, self.decryption_key.encryption_key()))
+        }))
+    }
+
+    async fn set_trustees(&self, req : Request<SetTrusteesReq>) -> Result<Response<SetTrusteesRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+
+        // Ballots are encrypted with the trustee key, so it can't change once voting starts. The phase is checked with the change.
+        let trustees = TrusteeKey {
+            threshold : req.threshold,
+            encryption_key : EncryptionKey::from_bytes_be(&election.group, &req.encryption_key),
+            verification_keys : req.verification_keys.iter().map(|key| BigUint::from_bytes_be(key)).collect(),
+        };
+        if !trustees.valid() {
+            return Err(Status::new(Code::InvalidArgument, "Invalid trustee key!"));
+        }
+        let changed = self.db.set_trustees(&req.election_id, trustees, election::now()).map_err(storage_error)?;
+        if !changed {
+            return Err(Status::new(Code::FailedPrecondition, "Trustees can't be changed once voting is open!"));
+        }
+        Ok(Response::new(SetTrusteesRes {
+            election : Some(election_msg(&self.get_election(&req.election_id)?, self.decryption_key.encryption_key()))
+        }))
+    }
+
+    async fn revoke_registration_code(&self, req : Request<RevokeRegistrationCodeReq>) -> Result<Response<RevokeRegistrationCodeRes>, Status> {
+        let req = req.into_inner();
+        self.get_election(&req.election_id)?;
+
+        let revoked = self.db.use_registration_code(&req.election_id, &req.registration_key).map_err(storage_error)?;
+        if !revoked {
+            return Err(Status::new(Code::NotFound, "Registration key is not active!"));
+        }
+        Ok(Response::new(RevokeRegistrationCodeRes {}))
+    }
+
+    async fn get_election_stats(&self, req : Request<GetElectionStatsReq>) -> Result<Response<GetElectionStatsRes>, Status> {
+        let req = req.into_inner();
+        let stats = self.db.get_stats(&req.election_id).ok_or(Status::new(Code::NotFound, "Election not found!"))?;
+        Ok(Response::new(GetElectionStatsRes {
+            registered_ids : stats.registered_ids as u64,
+            active_registration_codes : stats.active_registration_codes as u64,
+            registered_users : stats.registered_users as u64,
+            pending_votes : stats.pending_votes as u64,
+            pending_challenges : stats.challenges as u64,
+            votes : stats.votes as u64,
+        }))
+    }
+
+    async fn export_tally(&self, req : Request<ExportTallyReq>) -> Result<Response<ExportTallyRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+        let votes = tally_election(self.db.as_ref(), election, &self.decryption_key)?;
+
+        // Re-read the election so the exported phase is Tallied
+        let election = self.get_election(&req.election_id)?;
+        let results = election.options.iter().zip(&votes).map(|(option, votes)| OptionTally {
+            option : Some(vote_option_msg(option)),
+            votes : *votes,
+        }).collect();
+        Ok(Response::new(ExportTallyRes {
+            election : Some(election_msg(&election, self.decryption_key.encryption_key())),
+            results,
+            total_votes : votes.iter().sum(),
+        }))
+    }
+
+    async fn export_election(&self, req : Request<ExportElectionReq>) -> Result<Response<ExportElectionRes>, Status> {
+        let req = req.into_inner();
+        let format = RecordFormat::from_i32(req.format).ok_or(Status::new(Code::InvalidArgument, "Unknown record format!"))?;
+        let election = self.get_election(&req.election_id)?;
+        tally_election(self.db.as_ref(), election, &self.decryption_key)?;
+
+        let archive = self.db.export_election(&req.election_id).ok_or(Status::new(Code::NotFound, "Election not found!"))?;
+        let record = records::election_record(&archive, &self.decryption_key, &self.board_key);
+        let record = record.ok_or(Status::new(Code::Internal, "Could not export the election!"))?;
+        Ok(Response::new(ExportElectionRes {
+            record : match format {
+                RecordFormat::Json => record.to_json().into_bytes(),
+                RecordFormat::Binary => record.to_binary(),
+            }
+        }))
+    }
+
+    async fn import_election(&self, req : Request<ImportElectionReq>) -> Result<Response<ImportElectionRes>, Status> {
+        let req = req.into_inner();
+        let record = ElectionRecord::read(&req.record).map_err(|e| Status::new(Code::InvalidArgument, format!("Could not read the record: {}", e)))?;
+
+        // Only elections anyone could verify are imported
+        let report = verify::verify(&record);
+        if !report.valid {
+            let failed : Vec<&str> = report.checks.iter().filter(|check| !check.passed).map(|check| check.name).collect();
+            return Err(Status::new(Code::InvalidArgument, format!("Record doesn't verify, failed checks: {}", failed.join(", "))));
+        }
+        let archive = records::election_archive(&record).map_err(|e| Status::new(Code::InvalidArgument, e))?;
+        let election = archive.election.clone();
+        // Exported again with the board key of this server, which signs in its group
+        if *election.group != *self.decryption_key.group() {
+            return Err(Status::new(Code::InvalidArgument, format!("Record is in group {}, the server uses {}", election.group.name(), self.decryption_key.group().name())));
+        }
+        elections::validate_elections(std::slice::from_ref(&election)).map_err(|e| Status::new(Code::InvalidArgument, e.to_string()))?;
+
+        let imported = self.db.import_election(archive).map_err(storage_error)?;
+        if !imported {
+            return Err(Status::new(Code::AlreadyExists, "Election with this id already exists!"));
+        }
+        Ok(Response::new(ImportElectionRes {
+            election : Some(election_msg(&election, self.decryption_key.encryption_key()))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::server_impl::election_phase_msg;
+    use crate::model::pending_vote::PendingVote;
+    use crate::model::ballot::EncryptedBallot;
+    use crate::model::user_data::{UserData, VoterId};
+    use zkp_protocol::{zkp_util, SecretKey};
+    use anonvote_verifier::record::EntryContent;
+    use zkp_protocol::params::KeyParams;
+    use zkp_protocol::group::GroupParams;
+
+    const TEST_ELECTION : &str = "test";
+
+    fn option(id : &str, name : &str) -> VoteOption {
+        VoteOption {
+            id : id.to_string(),
+            name : name.to_string(),
+            description : String::new(),
+        }
+    }
+
+    fn create_election_req(id : &str) -> CreateElectionReq {
+        CreateElectionReq {
+            id : id.to_string(),
+            title : format!("Election {}", id),
+            options : vec![option("1", "Alice"), option("2", "Bob")],
+            registration_opens_at : u64::MAX - 2,
+            opens_at : u64::MAX - 1,
+            closes_at : u64::MAX,
+        }
+    }
+
+    async fn create_admin() -> (Arc<MemoryDB>, AnonVoteAdminImpl<MemoryDB>) {
+        let db = Arc::new(MemoryDB::default());
+        let admin_impl = AnonVoteAdminImpl::new(db.clone(), DecryptionKey::generate(&GroupParams::default()), BoardKey::generate(&GroupParams::default()));
+        let res = admin_impl.create_election(Request::new(create_election_req(TEST_ELECTION))).await;
+        assert!(res.is_ok(), "Did throw error while creating election. Error: {:?}", res);
+        (db, admin_impl)
+    }
+
+    async fn set_phase(admin_impl : &AnonVoteAdminImpl<MemoryDB>, phase : ElectionPhaseMsg) -> Result<Response<SetPhaseRes>, Status> {
+        admin_impl.set_phase(Request::new(SetPhaseReq {
+            election_id : TEST_ELECTION.to_string(),
+            phase : phase as i32,
+        })).await
+    }
+
+    #[test]
+    fn test_auth() {
+        let mut auth = AdminAuth::new("secret".to_string());
+
+        let mut req = Request::new(());
+        req.metadata_mut().insert("authorization", "Bearer secret".parse().unwrap());
+        assert!(auth.call(req).is_ok(), "Valid token was rejected");
+
+        for header in [None, Some("Bearer secre"), Some("Bearer secret2"), Some("secret"), Some("Basic secret")] {
+            let mut req = Request::new(());
+            if let Some(header) = header {
+                req.metadata_mut().insert("authorization", header.parse().unwrap());
+            }
+            let res = auth.call(req);
+            assert_eq!(res.unwrap_err().code(), Code::Unauthenticated, "Accepted invalid authorization: {:?}", header);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_election() {
+        let (db, admin_impl) = create_admin().await;
+        assert_eq!(db.get_election(TEST_ELECTION).unwrap().current_phase(), ElectionPhase::Draft);
+
+        let res = admin_impl.create_election(Request::new(create_election_req(TEST_ELECTION))).await;
+        assert_eq!(res.unwrap_err().code(), Code::AlreadyExists, "Created the same election twice");
+
+        let mut req = create_election_req("other");
+        req.options.push(option("1", "Carol"));
+        let res = admin_impl.create_election(Request::new(req)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Created election with duplicate option ids");
+
+        let mut req = create_election_req("other");
+        req.closes_at = req.opens_at;
+        let res = admin_impl.create_election(Request::new(req)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Created election with invalid schedule");
+
+        let res = admin_impl.create_election(Request::new(create_election_req(""))).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Created election without id");
+        assert!(db.get_election("other").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_set_options_and_phase() {
+        let (db, admin_impl) = create_admin().await;
+
+        let req = SetOptionsReq {
+            election_id : TEST_ELECTION.to_string(),
+            options : vec![option("1", "Alice"), option("2", "Bob"), option("3", "Carol")],
+        };
+        let res = admin_impl.set_options(Request::new(req.clone())).await;
+        assert_eq!(res.unwrap().into_inner().election.unwrap().options.len(), 3);
+
+        let res = set_phase(&admin_impl, ElectionPhaseMsg::Draft).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Moved election back to draft");
+
+        for phase in [ElectionPhaseMsg::RegistrationOpen, ElectionPhaseMsg::VotingOpen] {
+            let res = set_phase(&admin_impl, phase).await;
+            assert_eq!(res.unwrap().into_inner().election.unwrap().phase, phase as i32);
+        }
+
+        let res = set_phase(&admin_impl, ElectionPhaseMsg::RegistrationOpen).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Moved election to an earlier phase");
+
+        let res = admin_impl.set_options(Request::new(req)).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Changed options while voting is open");
+
+        let res = admin_impl.export_tally(Request::new(ExportTallyReq { election_id : TEST_ELECTION.to_string() })).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Exported tally while voting is open");
+
+        for (voter_id, vote) in [(1, 2), (2, 2), (3, 0)] {
+            let ballot = EncryptedBallot::encrypt(admin_impl.decryption_key.encryption_key(), vote, 3);
+            assert!(db.add_vote(TEST_ELECTION, VoterId([voter_id; 32]), ballot).unwrap());
+        }
+
+        let res = set_phase(&admin_impl, ElectionPhaseMsg::Closed).await;
+        assert_eq!(res.unwrap().into_inner().election.unwrap().phase, ElectionPhaseMsg::Closed as i32);
+
+        let res = admin_impl.export_tally(Request::new(ExportTallyReq { election_id : TEST_ELECTION.to_string() })).await;
+        let res = res.unwrap().into_inner();
+        assert_eq!(res.election.unwrap().phase, election_phase_msg(ElectionPhase::Tallied) as i32);
+        assert_eq!(res.total_votes, 3);
+        let results : Vec<(String, u32)> = res.results.into_iter().map(|r| (r.option.unwrap().name, r.votes)).collect();
+        assert_eq!(results, vec![("Alice".to_string(), 1), ("Bob".to_string(), 0), ("Carol".to_string(), 2)]);
+        assert_eq!(db.get_election(TEST_ELECTION).unwrap().tally, Some(vec![1, 0, 2]));
+    }
+
+    #[tokio::test]
+    async fn test_set_trustees() {
+        let (db, admin_impl) = create_admin().await;
+        let (trustees, _) = crate::model::trustees::test::test_trustees(&GroupParams::default());
+        let req = SetTrusteesReq {
+            election_id : TEST_ELECTION.to_string(),
+            threshold : trustees.threshold,
+            encryption_key : trustees.encryption_key.h().to_bytes_be(),
+            verification_keys : trustees.verification_keys.iter().map(|key| key.to_bytes_be()).collect(),
+        };
+
+        let mut invalid = req.clone();
+        invalid.threshold = 4;
+        let res = admin_impl.set_trustees(Request::new(invalid)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Set threshold bigger than trustee count");
+
+        let res = admin_impl.set_trustees(Request::new(req.clone())).await;
+        let election = res.unwrap().into_inner().election.unwrap();
+        assert_eq!(election.encryption_key, req.encryption_key);
+        assert_eq!(election.trustee_threshold, 2);
+        assert_eq!(db.get_election(TEST_ELECTION).unwrap().trustees, Some(trustees));
+
+        set_phase(&admin_impl, ElectionPhaseMsg::VotingOpen).await.unwrap();
+        let res = admin_impl.set_trustees(Request::new(req)).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Changed trustees while voting is open");
+    }
+
+    #[tokio::test]
+    async fn test_export_import() {
+        let (db, admin_impl) = create_admin().await;
+        let export_req = |format : RecordFormat| Request::new(ExportElectionReq { election_id : TEST_ELECTION.to_string(), format : format as i32 });
+        set_phase(&admin_impl, ElectionPhaseMsg::VotingOpen).await.unwrap();
+        for vote in [1, 0, 1] {
+            let user = UserData { key : SecretKey::generate(&GroupParams::default()).public_key(&KeyParams::for_election(&GroupParams::default(), TEST_ELECTION)) };
+            assert!(db.try_register_user(TEST_ELECTION, user.clone()).unwrap());
+            let (options, proof) = admin_impl.decryption_key.encryption_key().encrypt_vote_with_proof(vote, 2, &zkp_util::validity_context(TEST_ELECTION));
+            assert!(db.add_vote(TEST_ELECTION, user.id(), EncryptedBallot { options, proof : Some(proof) }).unwrap());
+        }
+        let res = admin_impl.export_election(export_req(RecordFormat::Json)).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Exported election while voting is open");
+
+        set_phase(&admin_impl, ElectionPhaseMsg::Closed).await.unwrap();
+        let json = admin_impl.export_election(export_req(RecordFormat::Json)).await.unwrap().into_inner().record;
+        let binary = admin_impl.export_election(export_req(RecordFormat::Binary)).await.unwrap().into_inner().record;
+        assert!(binary.len() < json.len());
+        for record in [&json, &binary] {
+            let record = ElectionRecord::read(record).unwrap();
+            assert!(verify::verify(&record).valid, "Exported record doesn't verify");
+            assert_eq!(record.tally, vec![1, 2]);
+            let proofs = record.board.entries.iter().filter(|entry| matches!(entry.content, EntryContent::Ballot { proof : Some(_), .. })).count();
+            assert_eq!(proofs, 3, "Ballot proofs are missing from the record");
+        }
+        let res = admin_impl.export_election(Request::new(ExportElectionReq { election_id : TEST_ELECTION.to_string(), format : 5 })).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument);
+
+        // Another server keeps the key of the election and can export it again
+        let other_db = Arc::new(MemoryDB::default());
+        let other_impl = AnonVoteAdminImpl::new(other_db.clone(), DecryptionKey::generate(&GroupParams::default()), BoardKey::generate(&GroupParams::default()));
+        let res = other_impl.import_election(Request::new(ImportElectionReq { record : binary.clone() })).await;
+        let election = res.unwrap().into_inner().election.unwrap();
+        assert_eq!(election.phase, ElectionPhaseMsg::Tallied as i32);
+        assert_eq!(election.encryption_key, admin_impl.decryption_key.encryption_key().h().to_bytes_be());
+        assert_eq!(other_db.get_election(TEST_ELECTION).unwrap().tally, Some(vec![1, 2]));
+        assert_eq!(other_db.get_stats(TEST_ELECTION).unwrap().registered_users, 3);
+        assert_eq!(other_db.get_board(TEST_ELECTION, 0).unwrap().head, db.get_board(TEST_ELECTION, 0).unwrap().head, "Board changed after import");
+        let record = other_impl.export_election(export_req(RecordFormat::Json)).await.unwrap().into_inner().record;
+        assert!(verify::verify(&ElectionRecord::read(&record).unwrap()).valid, "Record of an imported election doesn't verify");
+
+        let res = other_impl.import_election(Request::new(ImportElectionReq { record : json.clone() })).await;
+        assert_eq!(res.unwrap_err().code(), Code::AlreadyExists, "Imported the same election twice");
+        let mut tampered = ElectionRecord::read(&json).unwrap();
+        tampered.election_id = "other".to_string();
+        tampered.tally = vec![2, 1];
+        let res = other_impl.import_election(Request::new(ImportElectionReq { record : tampered.to_binary() })).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Imported a record that doesn't verify");
+        let res = other_impl.import_election(Request::new(ImportElectionReq { record : b"garbage".to_vec() })).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument);
+        assert!(other_db.get_election("other").is_none());
+
+        // Server of another group can't take over the election
+        let ristretto = GroupParams::ristretto255();
+        let other_impl = AnonVoteAdminImpl::new(Arc::new(MemoryDB::default()), DecryptionKey::generate(&ristretto), BoardKey::generate(&ristretto));
+        let res = other_impl.import_election(Request::new(ImportElectionReq { record : binary })).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Imported a record of another group");
+    }
+
+    #[tokio::test]
+    async fn test_registration_codes_and_stats() {
+        let (db, admin_impl) = create_admin().await;
+        assert!(db.add_registered_id(TEST_ELECTION, "12345".to_string()).unwrap());
+        assert!(db.add_registration_code(TEST_ELECTION, "code1".to_string()).unwrap());
+        assert!(db.add_registration_code(TEST_ELECTION, "code2".to_string()).unwrap());
+        assert!(db.add_pending_vote(TEST_ELECTION, VoterId([1; 32]), PendingVote { ballot : EncryptedBallot { options : Vec::new(), proof : None }, expires_at : u64::MAX }).unwrap());
+
+        let revoke_req = |code : &str| Request::new(RevokeRegistrationCodeReq {
+            election_id : TEST_ELECTION.to_string(),
+            registration_key : code.to_string(),
+        });
+        assert!(admin_impl.revoke_registration_code(revoke_req("code1")).await.is_ok());
+        let res = admin_impl.revoke_registration_code(revoke_req("code1")).await;
+        assert_eq!(res.unwrap_err().code(), Code::NotFound, "Revoked the same code twice");
+
+        let res = admin_impl.get_election_stats(Request::new(GetElectionStatsReq { election_id : TEST_ELECTION.to_string() })).await;
+        assert_eq!(res.unwrap().into_inner(), GetElectionStatsRes {
+            registered_ids : 1,
+            active_registration_codes : 1,
+            registered_users : 0,
+            pending_votes : 1,
+            pending_challenges : 0,
+            votes : 0,
+        });
+
+        let res = admin_impl.get_election_stats(Request::new(GetElectionStatsReq { election_id : "missing".to_string() })).await;
+        assert_eq!(res.unwrap_err().code(), Code::NotFound);
+    }
+}
@@ -0,0 +1,236 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use zkp_protocol::blind_signature::SignerSecretKey;
+use zkp_protocol::elgamal::DecryptionKey;
+use zkp_protocol::group::GroupParams;
+use zkp_protocol::board::BoardKey;
+
+use crate::identity::{IdHasher, ID_KEY_LEN};
+use crate::model::group::StoredGroup;
+
+pub const AUTHORITY_KEY_BITS : u64 = 2048;
+
+// Key used to blindly sign voter credentials. Signatures are checked when the voter registers,
+// which can be after a restart, so the key is generated once and kept in a file.
+#[derive(Serialize, Deserialize)]
+struct AuthorityKeyFile {
+    n : String,
+    e : String,
+    d : String,
+}
+
+// Key that decrypts the tally, in the group of the server. Ballots are encrypted with its public part, so it has to survive restarts too.
+#[derive(Serialize, Deserialize)]
+struct DecryptionKeyFile {
+    x : String,
+    #[serde(default)]
+    group : StoredGroup,
+}
+
+// Key that signs the bulletin board checkpoints, observers pin its public part
+#[derive(Serialize, Deserialize)]
+struct BoardKeyFile {
+    x : String,
+    #[serde(default)]
+    group : StoredGroup,
+}
+
+// Key of the voter id hashes, registered ids and hashed rosters only match with the same key
+#[derive(Serialize, Deserialize)]
+struct IdKeyFile {
+    key : String,
+}
+
+fn hex_bytes(hex_str : &str) -> io::Result<Vec<u8>> {
+    hex::decode(hex_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn from_hex(hex_str : &str) -> io::Result<BigUint> {
+    Ok(BigUint::from_bytes_be(&hex_bytes(hex_str)?))
+}
+
+// A key of another group would make every stored ballot or signature unreadable, the server doesn't start with it
+fn check_group(stored : &GroupParams, group : &GroupParams) -> io::Result<()> {
+    if stored != group {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Key is in group {}, the server uses {}", stored.name(), group.name())));
+    }
+    Ok(())
+}
+
+// Same as the ballot file, the key is written to a temporary file that is renamed into place, so a crash never leaves half a key.
+// Only the owner can read it.
+fn write_key_file(path : &Path, key_file : &impl Serialize) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let content = serde_json::to_vec(key_file).map_err(io::Error::from)?;
+
+    // Left over from a crash, it could have been created with other permissions
+    match fs::remove_file(&tmp_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(&content)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    File::open(dir)?.sync_all()
+}
+
+pub fn load_or_generate_signer_key(path : impl AsRef<Path>, bits : u64) -> io::Result<SignerSecretKey> {
+    let path = path.as_ref();
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            let key : AuthorityKeyFile = serde_json::from_str(&content).map_err(io::Error::from)?;
+            Ok(SignerSecretKey::new(from_hex(&key.n)?, from_hex(&key.e)?, from_hex(&key.d)?))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let key = SignerSecretKey::generate(bits);
+            let key_file = AuthorityKeyFile {
+                n : hex::encode(key.public_key().n().to_bytes_be()),
+                e : hex::encode(key.public_key().e().to_bytes_be()),
+                d : hex::encode(key.d().to_bytes_be()),
+            };
+            write_key_file(path, &key_file)?;
+            Ok(key)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn load_or_generate_decryption_key(path : impl AsRef<Path>, group : &GroupParams) -> io::Result<DecryptionKey> {
+    let path = path.as_ref();
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            let key : DecryptionKeyFile = serde_json::from_str(&content).map_err(io::Error::from)?;
+            check_group(&key.group, group)?;
+            let key = DecryptionKey::from_bytes_be(group, &hex_bytes(&key.x)?);
+            if !key.encryption_key().valid() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid decryption key"));
+            }
+            Ok(key)
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let key = DecryptionKey::generate(group);
+            let key_file = DecryptionKeyFile {
+                x : hex::encode(key.secret().to_bytes_be()),
+                group : StoredGroup(group.clone()),
+            };
+            write_key_file(path, &key_file)?;
+            Ok(key)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn load_or_generate_board_key(path : impl AsRef<Path>, group : &GroupParams) -> io::Result<BoardKey> {
+    let path = path.as_ref();
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            let key : BoardKeyFile = serde_json::from_str(&content).map_err(io::Error::from)?;
+            check_group(&key.group, group)?;
+            let key = BoardKey::from_bytes_be(group, &hex_bytes(&key.x)?);
+            if !group.contains(key.public_key()) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid board key"));
+            }
+            Ok(key)
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let key = BoardKey::generate(group);
+            let key_file = BoardKeyFile {
+                x : hex::encode(key.secret().to_bytes_be()),
+                group : StoredGroup(group.clone()),
+            };
+            write_key_file(path, &key_file)?;
+            Ok(key)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn load_or_generate_id_key(path : impl AsRef<Path>) -> io::Result<IdHasher> {
+    let path = path.as_ref();
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            let key : IdKeyFile = serde_json::from_str(&content).map_err(io::Error::from)?;
+            let key = hex::decode(&key.key).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if key.len() != ID_KEY_LEN {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid id key length"));
+            }
+            Ok(IdHasher::new(&key))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let hasher = IdHasher::generate();
+            let key_file = IdKeyFile {
+                key : hex::encode(hasher.key()),
+            };
+            write_key_file(path, &key_file)?;
+            Ok(hasher)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load_or_generate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("authority_key.json");
+
+        let key = load_or_generate_signer_key(&path, 512).unwrap();
+        let loaded = load_or_generate_signer_key(&path, 512).unwrap();
+        assert_eq!(key.public_key().n(), loaded.public_key().n(), "Key was generated again instead of loaded");
+        assert_eq!(key.d(), loaded.d());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600, "Key file can be read by others");
+        }
+        assert!(!path.with_extension("tmp").exists(), "Temporary key file was left behind");
+
+        fs::write(&path, "{}").unwrap();
+        assert!(load_or_generate_signer_key(&path, 512).is_err(), "Did not throw error while loading invalid key file");
+
+        let group = GroupParams::ristretto255();
+        let path = dir.path().join("decryption_key.json");
+        let key = load_or_generate_decryption_key(&path, &group).unwrap();
+        let loaded = load_or_generate_decryption_key(&path, &group).unwrap();
+        assert_eq!(key.secret(), loaded.secret(), "Key was generated again instead of loaded");
+        assert_eq!(loaded.group(), &group);
+        assert!(load_or_generate_decryption_key(&path, &GroupParams::default()).is_err(), "Loaded the key in another group");
+
+        fs::write(&path, "{\"x\":\"\"}").unwrap();
+        assert!(load_or_generate_decryption_key(&path, &GroupParams::default()).is_err(), "Did not throw error while loading trivial key");
+
+        let path = dir.path().join("board_key.json");
+        let key = load_or_generate_board_key(&path, &group).unwrap();
+        let loaded = load_or_generate_board_key(&path, &group).unwrap();
+        assert_eq!(key.public_key(), loaded.public_key(), "Key was generated again instead of loaded");
+        assert!(load_or_generate_board_key(&path, &GroupParams::default()).is_err(), "Loaded the key in another group");
+
+        // Key files from before the group was stored are in the default group
+        fs::write(&path, format!("{{\"x\":\"{}\"}}", hex::encode(key.secret().to_bytes_be()))).unwrap();
+        assert!(load_or_generate_board_key(&path, &GroupParams::default()).is_ok());
+        fs::write(&path, "{\"x\":\"\"}").unwrap();
+        assert!(load_or_generate_board_key(&path, &GroupParams::default()).is_err(), "Did not throw error while loading trivial key");
+
+        let path = dir.path().join("id_key.json");
+        let hasher = load_or_generate_id_key(&path).unwrap();
+        let loaded = load_or_generate_id_key(&path).unwrap();
+        assert_eq!(hasher.hash("12345"), loaded.hash("12345"), "Key was generated again instead of loaded");
+
+        fs::write(&path, "{\"key\":\"\"}").unwrap();
+        assert!(load_or_generate_id_key(&path).is_err(), "Did not throw error while loading empty key");
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::model::candidate::Candidate;
+
+// Candidates can be provided in 3 formats, picked by the file extension:
+// - .txt  : one candidate name per line, ids are assigned by position (1, 2, 3...)
+// - .toml : [[candidates]] tables with id, name and optional description
+// - .json : { "candidates" : [ { "id" : ..., "name" : ..., "description" : ... } ] }
+
+#[derive(Debug)]
+pub enum CandidatesError {
+    Io(io::Error),
+    Parse(String),
+    Empty,
+    MissingField(usize),
+    DuplicateId(String),
+    DuplicateName(String),
+}
+
+impl fmt::Display for CandidatesError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CandidatesError::Io(e) => write!(f, "Could not read candidates file: {}", e),
+            CandidatesError::Parse(e) => write!(f, "Could not parse candidates file: {}", e),
+            CandidatesError::Empty => write!(f, "Candidate list is empty"),
+            CandidatesError::MissingField(index) => write!(f, "Candidate #{} has empty id or name", index + 1),
+            CandidatesError::DuplicateId(id) => write!(f, "Duplicate candidate id: {}", id),
+            CandidatesError::DuplicateName(name) => write!(f, "Duplicate candidate name: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for CandidatesError {}
+
+#[derive(Deserialize)]
+struct CandidatesFile {
+    candidates : Vec<Candidate>,
+}
+
+pub fn load_candidates(path : impl AsRef<Path>) -> Result<Vec<Candidate>, CandidatesError> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(CandidatesError::Io)?;
+    let candidates = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => parse_toml(&content)?,
+        Some("json") => parse_json(&content)?,
+        _ => parse_plain_text(&content),
+    };
+    validate_candidates(&candidates)?;
+    Ok(candidates)
+}
+
+pub fn parse_plain_text(content : &str) -> Vec<Candidate> {
+    content.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(index, name)| Candidate {
+            id : (index + 1).to_string(),
+            name : name.to_string(),
+            description : String::new(),
+        })
+        .collect()
+}
+
+pub fn parse_toml(content : &str) -> Result<Vec<Candidate>, CandidatesError> {
+    let file : CandidatesFile = toml::from_str(content).map_err(|e| CandidatesError::Parse(e.to_string()))?;
+    Ok(file.candidates)
+}
+
+pub fn parse_json(content : &str) -> Result<Vec<Candidate>, CandidatesError> {
+    let file : CandidatesFile = serde_json::from_str(content).map_err(|e| CandidatesError::Parse(e.to_string()))?;
+    Ok(file.candidates)
+}
+
+pub fn validate_candidates(candidates : &[Candidate]) -> Result<(), CandidatesError> {
+    if candidates.is_empty() {
+        return Err(CandidatesError::Empty);
+    }
+
+    let mut ids = HashSet::new();
+    let mut names = HashSet::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        if candidate.id.trim().is_empty() || candidate.name.trim().is_empty() {
+            return Err(CandidatesError::MissingField(index));
+        }
+        if !ids.insert(candidate.id.as_str()) {
+            return Err(CandidatesError::DuplicateId(candidate.id.clone()));
+        }
+        if !names.insert(candidate.name.as_str()) {
+            return Err(CandidatesError::DuplicateName(candidate.name.clone()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_formats() {
+        let plain = parse_plain_text("Alice\n\n  Bob  \nCarol\n");
+        assert_eq!(plain.len(), 3, "Empty lines should be skipped");
+        assert_eq!(plain[1], Candidate { id : "2".to_string(), name : "Bob".to_string(), description : String::new() });
+
+        let toml = parse_toml(r#"
+            [[candidates]]
+            id = "alice"
+            name = "Alice"
+            description = "The first one"
+
+            [[candidates]]
+            id = "bob"
+            name = "Bob"
+        "#).unwrap();
+        assert_eq!(toml.len(), 2);
+        assert_eq!(toml[0].description, "The first one");
+        assert_eq!(toml[1].description, "", "Description should be optional");
+
+        let json = parse_json(r#"{ "candidates" : [ { "id" : "alice", "name" : "Alice", "description" : "The first one" } ] }"#).unwrap();
+        assert_eq!(json, toml[..1]);
+
+        assert!(parse_toml("[[candidates]]\nname = \"Alice\"").is_err(), "Did not throw error while missing id");
+    }
+
+    #[test]
+    fn test_validation() {
+        let candidate = |id : &str, name : &str| Candidate { id : id.to_string(), name : name.to_string(), description : String::new() };
+
+        assert!(validate_candidates(&[candidate("1", "Alice"), candidate("2", "Bob")]).is_ok());
+        assert!(matches!(validate_candidates(&[]), Err(CandidatesError::Empty)));
+        assert!(matches!(validate_candidates(&[candidate("1", "Alice"), candidate("1", "Bob")]), Err(CandidatesError::DuplicateId(_))));
+        assert!(matches!(validate_candidates(&[candidate("1", "Alice"), candidate("2", "Alice")]), Err(CandidatesError::DuplicateName(_))));
+        assert!(matches!(validate_candidates(&[candidate("1", " ")]), Err(CandidatesError::MissingField(0))));
+    }
+
+    #[test]
+    fn test_load_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let txt_path = dir.path().join("candidates.txt");
+        fs::write(&txt_path, "Alice\nBob\n").unwrap();
+        assert_eq!(load_candidates(&txt_path).unwrap().len(), 2);
+
+        let json_path = dir.path().join("candidates.json");
+        fs::write(&json_path, r#"{ "candidates" : [ { "id" : "a", "name" : "Alice" }, { "id" : "a", "name" : "Bob" } ] }"#).unwrap();
+        assert!(load_candidates(&json_path).is_err(), "Did not throw error while loading duplicate ids");
+
+        assert!(load_candidates(dir.path().join("missing.txt")).is_err(), "Did not throw error while loading missing file");
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::ballot::EncryptedBallot;
+use crate::model::mix::BallotMix;
+use crate::model::user_data::VoterId;
+
+// Ballots of one election. The voted set is sorted and the ballots are shuffled,
+// so nothing in the file tells which user cast which ballot. Ballots are encrypted with the election key.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ElectionBallots {
+    pub voted : Vec<VoterId>,
+    pub ballots : Vec<EncryptedBallot>,
+    #[serde(default)]
+    pub mix : Option<BallotMix>, // Set once the election closes
+}
+
+pub type BallotSnapshot = BTreeMap<String, ElectionBallots>;
+
+// Ballots are kept out of the write-ahead log, since the order of records in an append-only log
+// would link every ballot with the user that voted right before it. Appending to the ballot file would do the same.
+// Instead every election has its own file in the ballot directory (hex of the election id), which is rewritten
+// as a whole on every vote. The new file replaces the old one atomically, so the voted set and the ballots always match.
+// A vote costs a write of all ballots of its election, an election with n voters writes O(n^2) bytes in total.
+// Someone who watches every version of the file can still compare them, this only protects the data at rest.
+pub struct BallotDir {
+    path : PathBuf,
+}
+
+impl BallotDir {
+    pub fn open(path : &Path) -> io::Result<BallotDir> {
+        fs::create_dir_all(path)?;
+        Ok(BallotDir { path : path.to_path_buf() })
+    }
+
+    // Ballots of every election that has a file
+    pub fn read(&self) -> io::Result<BallotSnapshot> {
+        let mut snapshot = BallotSnapshot::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            // Temporary files are left over from a write that didn't finish, the old file is still there
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let election_id = path.file_stem()
+                .and_then(|stem| hex::decode(stem.to_string_lossy().as_bytes()).ok())
+                .and_then(|id| String::from_utf8(id).ok())
+                .ok_or(io::Error::new(io::ErrorKind::InvalidData, format!("Unknown file in the ballot directory : {}", path.display())))?;
+            let content = fs::read_to_string(&path)?;
+            let ballots = serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Corrupted ballot file : {}", e)))?;
+            snapshot.insert(election_id, ballots);
+        }
+        Ok(snapshot)
+    }
+
+    // Replaces the ballots of the election. After a failed write the file still has the old ballots.
+    pub fn write(&self, election_id : &str, ballots : &ElectionBallots) -> io::Result<()> {
+        let path = self.path.join(hex::encode(election_id)).with_extension("json");
+        let tmp_path = path.with_extension("tmp");
+        let content = serde_json::to_vec(ballots).map_err(io::Error::from)?;
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&content)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+
+        // Rename is only durable once the directory is synced
+        File::open(&self.path)?.sync_all()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_bigint::BigUint;
+    use zkp_protocol::elgamal::Ciphertext;
+
+    fn test_ballot(value : u32) -> EncryptedBallot {
+        EncryptedBallot { options : vec![Ciphertext::new(BigUint::from(value), BigUint::from(value + 1))], proof : None }
+    }
+
+    #[test]
+    fn test_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.ballots");
+
+        let ballot_dir = BallotDir::open(&path).unwrap();
+        assert!(ballot_dir.read().unwrap().is_empty());
+
+        let mut ballots = ElectionBallots { voted : vec![VoterId([1; 32]), VoterId([2; 32])], ballots : vec![test_ballot(0), test_ballot(2)], mix : None };
+        ballot_dir.write("test", &ballots).unwrap();
+        ballot_dir.write("other/election", &ElectionBallots::default()).unwrap();
+        ballots.voted.push(VoterId([3; 32]));
+        ballots.ballots.push(test_ballot(1));
+        ballot_dir.write("test", &ballots).unwrap();
+
+        let recovered = BallotDir::open(&path).unwrap().read().unwrap();
+        assert_eq!(recovered.len(), 2);
+        assert_eq!(recovered["test"], ballots);
+        assert_eq!(recovered["other/election"], ElectionBallots::default(), "Ballots of the other election were lost");
+        assert!(!path.join(hex::encode("test")).with_extension("tmp").exists(), "Temporary file was left behind");
+
+        // Only the file of the election that changed is written
+        let other_path = path.join(hex::encode("other/election")).with_extension("json");
+        let modified = fs::metadata(&other_path).unwrap().modified().unwrap();
+        ballot_dir.write("test", &ballots).unwrap();
+        assert_eq!(fs::metadata(&other_path).unwrap().modified().unwrap(), modified, "Ballots of the other election were rewritten");
+
+        // Write that stopped before the rename
+        fs::write(path.join(hex::encode("test")).with_extension("tmp"), "{\"voted\":").unwrap();
+        assert_eq!(ballot_dir.read().unwrap()["test"], ballots);
+
+        fs::write(path.join(hex::encode("test")).with_extension("json"), "{\"voted\":").unwrap();
+        assert!(ballot_dir.read().is_err(), "Did not throw error while reading corrupted ballot file");
+    }
+}
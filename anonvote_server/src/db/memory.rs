@@ -0,0 +1,449 @@
+use std::collections::{HashSet, HashMap};
+use std::io;
+use std::sync::{Mutex, RwLock};
+use std::vec::Vec;
+
+use rand::Rng;
+use zkp_protocol::board::{BoardRecord, Hash};
+use zkp_protocol::merkle;
+
+use crate::db::{ElectionArchive, ElectionStats, VoteStore};
+use crate::db::wal::WalRecord;
+use crate::db::ballots::ElectionBallots;
+use crate::model::user_data::{UserData, VoterId};
+use crate::model::challenge_data::ChallengeData;
+use crate::model::pending_vote::PendingVote;
+use crate::model::election::{Election, ElectionPhase};
+use crate::model::candidate::Candidate;
+use crate::model::ballot::EncryptedBallot;
+use crate::model::mix::BallotMix;
+use crate::model::board::{BulletinBoard, BoardView};
+use crate::model::ballot_tree::{BallotTree, InclusionProof};
+use crate::model::trustees::{PartialTally, TrusteeKey};
+
+// Everything that belongs to a single election
+struct ElectionData {
+    election : Election,
+    active_registration_codes : HashSet<String>,
+    registered_users : HashMap<VoterId, UserData>,
+    registered_ids : HashSet<String>,
+    pending_votes : HashMap<VoterId, PendingVote>,
+    voted : HashSet<VoterId>,
+    ballots : Vec<EncryptedBallot>, // Kept apart from the voted set and shuffled, so a ballot can't be linked to the user
+    mix : Option<BallotMix>,
+    challenge_map : HashMap<String, ChallengeData>,
+    board : BulletinBoard,
+    ballot_tree : BallotTree, // Ballots in the order of the board, for the receipts
+}
+
+impl ElectionData {
+    fn new(election : Election) -> ElectionData {
+        let mut data = ElectionData::empty(election);
+        let record = ElectionData::schedule_record(&data.election);
+        data.post(record);
+        data
+    }
+
+    // Without the schedule on the board, for imported elections that bring their own board
+    fn empty(election : Election) -> ElectionData {
+        ElectionData {
+            election,
+            active_registration_codes : HashSet::new(),
+            registered_users : HashMap::new(),
+            registered_ids : HashSet::new(),
+            pending_votes : HashMap::new(),
+            voted : HashSet::new(),
+            ballots : Vec::new(),
+            mix : None,
+            challenge_map : HashMap::new(),
+            board : BulletinBoard::default(),
+            ballot_tree : BallotTree::default(),
+        }
+    }
+
+    fn schedule_record(election : &Election) -> BoardRecord {
+        BoardRecord::Schedule {
+            registration_opens_at : election.registration_opens_at,
+            opens_at : election.opens_at,
+            closes_at : election.closes_at,
+        }
+    }
+
+    // The voted set is sorted so it doesn't tell the order of voting
+    fn ballots(&self) -> ElectionBallots {
+        let mut voted : Vec<VoterId> = self.voted.iter().cloned().collect();
+        voted.sort_unstable();
+        ElectionBallots { voted, ballots : self.ballots.clone(), mix : self.mix.clone() }
+    }
+
+    fn post(&mut self, record : BoardRecord) {
+        if let BoardRecord::Ballot { ballot, .. } = &record {
+            self.ballot_tree.append(&self.election.id, ballot);
+        }
+        self.board.append(&self.election.id, record);
+    }
+}
+
+// Where a change is written before it is applied, so the state in memory is never ahead of the disk.
+// Records are only built when they are needed, MemoryDB on its own doesn't write anything (see AnonVoteDB).
+pub trait ChangeLog : Send + Sync + 'static {
+    fn append(&self, record : impl FnOnce() -> WalRecord) -> io::Result<()>;
+    // Ballots and who voted are kept apart from the log (see ballots.rs), these are the ballots of the election after the change
+    fn write_ballots(&self, election_id : &str, ballots : impl FnOnce() -> ElectionBallots) -> io::Result<()>;
+}
+
+impl ChangeLog for () {
+    fn append(&self, _record : impl FnOnce() -> WalRecord) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_ballots(&self, _election_id : &str, _ballots : impl FnOnce() -> ElectionBallots) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Keeps everything in memory, all data is lost once the server stops unless a log is given.
+pub struct MemoryDB<L : ChangeLog = ()> {
+    elections : RwLock<HashMap<String, Mutex<ElectionData>>>,
+    log : L,
+}
+
+impl Default for MemoryDB {
+    fn default() -> MemoryDB {
+        MemoryDB {
+            elections : RwLock::new(HashMap::new()),
+            log : (),
+        }
+    }
+}
+
+impl MemoryDB {
+    // Same state, every change from now on is logged first
+    pub fn with_log<L : ChangeLog>(self, log : L) -> MemoryDB<L> {
+        MemoryDB {
+            elections : self.elections,
+            log,
+        }
+    }
+}
+
+impl<L : ChangeLog> MemoryDB<L> {
+    // Runs the function on the data of the given election, returns None if the election doesn't exist
+    fn with_election<T>(&self, election_id : &str, f : impl FnOnce(&mut ElectionData) -> T) -> Option<T> {
+        let elections = self.elections.read().unwrap();
+        let election_data = &mut elections.get(election_id)?.lock().unwrap();
+        Some(f(election_data))
+    }
+
+    // Changes the election if `check` allows it. The check, the log and the change all happen under the lock
+    // of the election, the change is only applied once it is logged and nothing changes if logging fails.
+    fn change<T>(&self, election_id : &str, value : T, check : impl FnOnce(&ElectionData, &T) -> bool, record : impl FnOnce(&T) -> WalRecord, apply : impl FnOnce(&mut ElectionData, T)) -> io::Result<bool> {
+        self.with_election(election_id, |data| {
+            if !check(data, &value) {
+                return Ok(false);
+            }
+            self.log.append(|| record(&value))?;
+            apply(data, value);
+            Ok(true)
+        }).unwrap_or(Ok(false))
+    }
+
+    // Replaces the ballots of the election with the ones from the ballot file, returns None if the election doesn't exist.
+    // Also returns the ballots that are not on the bulletin board, the ballot file is written before the log so
+    // the server may have stopped in between. Ballots on the board that are missing from the file can't be explained.
+    pub fn restore_ballots(&self, election_id : &str, ballots : ElectionBallots) -> Option<io::Result<Vec<EncryptedBallot>>> {
+        self.with_election(election_id, |data| {
+            let mut posted : HashMap<Hash, usize> = HashMap::new();
+            for record in data.board.records() {
+                if let BoardRecord::Ballot { ballot, .. } = record {
+                    *posted.entry(merkle::ballot_hash(election_id, &ballot)).or_default() += 1;
+                }
+            }
+            let mut unposted = Vec::new();
+            for ballot in &ballots.ballots {
+                match posted.get_mut(&merkle::ballot_hash(election_id, &ballot.options)) {
+                    Some(count) if *count > 0 => *count -= 1,
+                    _ => unposted.push(ballot.clone()),
+                }
+            }
+            if posted.values().any(|count| *count > 0) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Ballots on the bulletin board of {} are missing from the ballot file", election_id)));
+            }
+
+            data.voted = ballots.voted.into_iter().collect();
+            data.ballots = ballots.ballots;
+            data.mix = ballots.mix;
+            Ok(unposted)
+        })
+    }
+
+    // Only adds the entry to the bulletin board, used to rebuild the board of ballots that are restored from their own file
+    pub fn post_to_board(&self, election_id : &str, record : BoardRecord) -> bool {
+        self.with_election(election_id, |data| data.post(record)).is_some()
+    }
+}
+
+impl<L : ChangeLog> VoteStore for MemoryDB<L> {
+    fn create_election(&self, election : Election) -> io::Result<bool> {
+        let elections = &mut self.elections.write().unwrap();
+        if elections.contains_key(&election.id) {
+            return Ok(false);
+        }
+        self.log.append(|| WalRecord::CreateElection { election : election.clone() })?;
+        elections.insert(election.id.clone(), Mutex::new(ElectionData::new(election)));
+        Ok(true)
+    }
+
+    fn get_election(&self, election_id : &str) -> Option<Election> {
+        self.with_election(election_id, |data| data.election.clone())
+    }
+
+    fn get_elections(&self) -> Vec<Election> {
+        let elections = self.elections.read().unwrap();
+        let mut elections : Vec<Election> = elections.values().map(|data| data.lock().unwrap().election.clone()).collect();
+        elections.sort_by(|a, b| a.id.cmp(&b.id));
+        elections
+    }
+
+    fn set_schedule(&self, election_id : &str, registration_opens_at : u64, opens_at : u64, closes_at : u64) -> io::Result<bool> {
+        self.change(election_id, (registration_opens_at, opens_at, closes_at),
+            |data, _| data.election.tally.is_none(),
+            |_| WalRecord::SetSchedule { election_id : election_id.to_string(), registration_opens_at, opens_at, closes_at },
+            |data, _| {
+                data.election.registration_opens_at = registration_opens_at;
+                data.election.opens_at = opens_at;
+                data.election.closes_at = closes_at;
+                let record = ElectionData::schedule_record(&data.election);
+                data.post(record);
+            })
+    }
+
+    fn set_tally(&self, election_id : &str, tally : Vec<u32>) -> io::Result<bool> {
+        self.change(election_id, tally,
+            |data, _| data.election.tally.is_none(),
+            |tally| WalRecord::SetTally { election_id : election_id.to_string(), tally : tally.clone() },
+            |data, tally| {
+                data.post(BoardRecord::Tally { votes : tally.clone() });
+                data.election.tally = Some(tally);
+            })
+    }
+
+    fn set_options(&self, election_id : &str, options : Vec<Candidate>, now : u64) -> io::Result<bool> {
+        self.change(election_id, options,
+            |data, _| data.election.phase(now) < ElectionPhase::VotingOpen,
+            |options| WalRecord::SetOptions { election_id : election_id.to_string(), options : options.clone(), at : now },
+            |data, options| data.election.options = options)
+    }
+
+    fn set_trustees(&self, election_id : &str, trustees : TrusteeKey, now : u64) -> io::Result<bool> {
+        self.change(election_id, trustees,
+            |data, _| data.election.phase(now) < ElectionPhase::VotingOpen,
+            |trustees| WalRecord::SetTrustees { election_id : election_id.to_string(), trustees : trustees.clone(), at : now },
+            |data, trustees| data.election.trustees = Some(trustees))
+    }
+
+    fn add_partial_tally(&self, election_id : &str, partial_tally : PartialTally) -> io::Result<bool> {
+        self.change(election_id, partial_tally,
+            |data, partial_tally| {
+                let election = &data.election;
+                election.tally.is_none() && election.partial_tallies.iter().all(|partial| partial.trustee != partial_tally.trustee)
+            },
+            |partial_tally| WalRecord::AddPartialTally { election_id : election_id.to_string(), partial_tally : partial_tally.clone() },
+            |data, partial_tally| data.election.partial_tallies.push(partial_tally))
+    }
+
+    fn get_stats(&self, election_id : &str) -> Option<ElectionStats> {
+        self.with_election(election_id, |data| ElectionStats {
+            registered_ids : data.registered_ids.len(),
+            active_registration_codes : data.active_registration_codes.len(),
+            registered_users : data.registered_users.len(),
+            pending_votes : data.pending_votes.len(),
+            challenges : data.challenge_map.len(),
+            votes : data.voted.len(),
+        })
+    }
+
+    fn add_registered_id(&self, election_id : &str, id : String) -> io::Result<bool> {
+        self.change(election_id, id,
+            |data, id| !data.registered_ids.contains(id),
+            |id| WalRecord::AddRegisteredId { election_id : election_id.to_string(), id : id.clone() },
+            |data, id| { data.registered_ids.insert(id); })
+    }
+
+    fn add_registration_code(&self, election_id : &str, code : String) -> io::Result<bool> {
+        self.change(election_id, code,
+            |data, code| !data.active_registration_codes.contains(code),
+            |code| WalRecord::AddRegistrationCode { election_id : election_id.to_string(), code : code.clone() },
+            |data, code| { data.active_registration_codes.insert(code); })
+    }
+
+    fn use_registration_code(&self, election_id : &str, code : &str) -> io::Result<bool> {
+        self.change(election_id, code,
+            |data, code| data.active_registration_codes.contains(*code),
+            |code| WalRecord::UseRegistrationCode { election_id : election_id.to_string(), code : code.to_string() },
+            |data, code| { data.active_registration_codes.remove(code); })
+    }
+
+    fn try_register_user(&self, election_id : &str, user : UserData) -> io::Result<bool> {
+        self.change(election_id, user,
+            |data, user| !data.registered_users.contains_key(&user.id()),
+            |user| WalRecord::register_user(election_id, user),
+            |data, user| {
+                data.post(BoardRecord::Registration { voter : user.key.clone() });
+                data.registered_users.insert(user.id(), user);
+            })
+    }
+
+    fn user_registered(&self, election_id : &str, voter_id : VoterId) -> bool {
+        self.with_election(election_id, |data| data.registered_users.contains_key(&voter_id)).unwrap_or(false)
+    }
+
+    fn get_user(&self, election_id : &str, voter_id : VoterId) -> Option<UserData> {
+        self.with_election(election_id, |data| data.registered_users.get(&voter_id).cloned()).flatten()
+    }
+
+    fn add_pending_vote(&self, election_id : &str, voter_id : VoterId, pending_vote : PendingVote) -> io::Result<bool> {
+        Ok(self.with_election(election_id, |data| {
+            if data.pending_votes.contains_key(&voter_id) {
+                return false;
+            }
+            data.pending_votes.insert(voter_id, pending_vote);
+            true
+        }).unwrap_or(false))
+    }
+
+    fn get_pending_vote(&self, election_id : &str, voter_id : VoterId) -> Option<PendingVote> {
+        self.with_election(election_id, |data| data.pending_votes.get(&voter_id).cloned()).flatten()
+    }
+
+    fn get_and_remove_pending_vote(&self, election_id : &str, voter_id : VoterId) -> io::Result<Option<PendingVote>> {
+        Ok(self.with_election(election_id, |data| data.pending_votes.remove(&voter_id)).flatten())
+    }
+
+    // Who voted is only written with the ballots, the log only gets the ballot as it appears on the bulletin board.
+    // The ballots go first: if the server stops before the log is written, the voter is still marked as voted
+    // and the ballot is put on the board on the next start (see restore_ballots).
+    // A ballot that is already on the board is refused, its published proof must not let someone else cast it again.
+    fn add_vote(&self, election_id : &str, voter_id : VoterId, ballot : EncryptedBallot) -> io::Result<bool> {
+        self.with_election(election_id, |data| {
+            if data.mix.is_some() || data.voted.contains(&voter_id) || data.ballot_tree.contains(&merkle::ballot_hash(election_id, &ballot.options)) {
+                return Ok(false);
+            }
+
+            // Inserting at a random position keeps the ballots uniformly shuffled
+            let position = rand::rng().random_range(0..=data.ballots.len());
+            let insert = |ballots : &mut Vec<EncryptedBallot>, ballot : EncryptedBallot| {
+                ballots.push(ballot);
+                let last = ballots.len() - 1;
+                ballots.swap(position, last);
+            };
+            self.log.write_ballots(election_id, || {
+                let mut ballots = data.ballots();
+                ballots.voted.push(voter_id);
+                ballots.voted.sort_unstable();
+                insert(&mut ballots.ballots, ballot.clone());
+                ballots
+            })?;
+            self.log.append(|| WalRecord::PostBallot { election_id : election_id.to_string(), ballot : ballot.clone() })?;
+
+            data.voted.insert(voter_id);
+            data.post(BoardRecord::Ballot { ballot : ballot.options.clone(), proof : ballot.proof.clone() });
+            insert(&mut data.ballots, ballot);
+            Ok(true)
+        }).unwrap_or(Ok(false))
+    }
+
+    fn user_voted(&self, election_id : &str, voter_id : VoterId) -> bool {
+        self.with_election(election_id, |data| data.voted.contains(&voter_id)).unwrap_or(false)
+    }
+
+    fn add_challenge(&self, election_id : &str, session_id : &str, challenge_data : ChallengeData) -> io::Result<bool> {
+        Ok(self.with_election(election_id, |data| {
+            if data.challenge_map.contains_key(session_id) {
+                return false;
+            }
+            data.challenge_map.insert(session_id.to_string(), challenge_data);
+            true
+        }).unwrap_or(false))
+    }
+
+    fn get_challenge(&self, election_id : &str, session_id : &str) -> Option<ChallengeData> {
+        self.with_election(election_id, |data| data.challenge_map.get(session_id).cloned()).flatten()
+    }
+
+    fn remove_challenge(&self, election_id : &str, session_id : &str) -> io::Result<bool> {
+        Ok(self.with_election(election_id, |data| data.challenge_map.remove(session_id).is_some()).unwrap_or(false))
+    }
+
+    fn remove_expired(&self, now : u64) -> io::Result<usize> {
+        let elections = self.elections.read().unwrap();
+        let mut removed = 0;
+        for data in elections.values() {
+            let data = &mut data.lock().unwrap();
+            let count = data.pending_votes.len() + data.challenge_map.len();
+            data.pending_votes.retain(|_, pending_vote| !pending_vote.expired(now));
+            data.challenge_map.retain(|_, challenge| !challenge.expired(now));
+            removed += count - data.pending_votes.len() - data.challenge_map.len();
+        }
+        Ok(removed)
+    }
+
+    fn get_ballots(&self, election_id : &str) -> Option<Vec<EncryptedBallot>> {
+        self.with_election(election_id, |data| data.ballots.clone())
+    }
+
+    // Stored with the ballots, so they always hold the cast ballots together with their mix
+    fn set_mix(&self, election_id : &str, mix : BallotMix) -> io::Result<bool> {
+        self.with_election(election_id, |data| {
+            // A vote that came in after the ballots were read is missing from the mix
+            if data.mix.is_some() || data.ballots.len() != mix.ballots.len() {
+                return Ok(false);
+            }
+            self.log.write_ballots(election_id, || ElectionBallots { mix : Some(mix.clone()), ..data.ballots() })?;
+            data.mix = Some(mix);
+            Ok(true)
+        }).unwrap_or(Ok(false))
+    }
+
+    fn get_mix(&self, election_id : &str) -> Option<BallotMix> {
+        self.with_election(election_id, |data| data.mix.clone()).flatten()
+    }
+
+    fn get_board(&self, election_id : &str, from : u64) -> Option<BoardView> {
+        self.with_election(election_id, |data| data.board.view(from))
+    }
+
+    fn get_inclusion_proof(&self, election_id : &str, ballot_hash : &Hash) -> Option<InclusionProof> {
+        self.with_election(election_id, |data| data.ballot_tree.proof(ballot_hash)).flatten()
+    }
+
+    fn export_election(&self, election_id : &str) -> Option<ElectionArchive> {
+        self.with_election(election_id, |data| ElectionArchive {
+            election : data.election.clone(),
+            board : data.board.records(),
+            ballots : data.ballots.clone(),
+            mix : data.mix.clone(),
+        })
+    }
+
+    // Registered users come from the board, who voted is not part of the archive
+    fn import_election(&self, archive : ElectionArchive) -> io::Result<bool> {
+        let elections = &mut self.elections.write().unwrap();
+        if elections.contains_key(&archive.election.id) {
+            return Ok(false);
+        }
+        self.log.append(|| WalRecord::import_election(&archive))?;
+        let mut data = ElectionData::empty(archive.election);
+        for record in archive.board {
+            if let BoardRecord::Registration { voter } = &record {
+                let user = UserData { key : voter.clone() };
+                data.registered_users.insert(user.id(), user);
+            }
+            data.post(record);
+        }
+        data.ballots = archive.ballots;
+        data.mix = archive.mix;
+        elections.insert(data.election.id.clone(), Mutex::new(data));
+        Ok(true)
+    }
+}
@@ -0,0 +1,100 @@
+mod memory;
+mod persistent;
+mod wal;
+mod ballots;
+
+use std::io;
+use std::vec::Vec;
+
+use zkp_protocol::board::{BoardRecord, Hash};
+
+use crate::model::user_data::{UserData, VoterId};
+use crate::model::challenge_data::ChallengeData;
+use crate::model::pending_vote::PendingVote;
+use crate::model::election::Election;
+use crate::model::candidate::Candidate;
+use crate::model::ballot::EncryptedBallot;
+use crate::model::mix::BallotMix;
+use crate::model::board::BoardView;
+use crate::model::ballot_tree::InclusionProof;
+use crate::model::trustees::{PartialTally, TrusteeKey};
+
+pub use memory::MemoryDB;
+pub use persistent::AnonVoteDB;
+
+// Number of entries of each kind stored for an election
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ElectionStats {
+    pub registered_ids : usize,
+    pub active_registration_codes : usize,
+    pub registered_users : usize,
+    pub pending_votes : usize,
+    pub challenges : usize,
+    pub votes : usize,
+}
+
+// Everything public about an election, what is exported to an election record and imported back.
+// Who voted is not part of it, the ballots can't be linked to the registered users.
+#[derive(Clone)]
+pub struct ElectionArchive {
+    pub election : Election,
+    pub board : Vec<BoardRecord>, // Records of the bulletin board in order, hashes are computed again on import
+    pub ballots : Vec<EncryptedBallot>, // In storage order, the input of the mix
+    pub mix : Option<BallotMix>,
+}
+
+// Storage operations needed by the server.
+// Methods that change the state return io::Result since backends may fail to persist the change,
+// the bool tells if the change was applied (same semantic as HashSet::insert/remove).
+// All data is partitioned per election, operations on an election that doesn't exist change nothing.
+pub trait VoteStore : Send + Sync + 'static {
+    fn create_election(&self, election : Election) -> io::Result<bool>;
+    fn get_election(&self, election_id : &str) -> Option<Election>;
+    fn get_elections(&self) -> Vec<Election>;
+    // These fail (return false) once the election is tallied, results are final at that point
+    fn set_schedule(&self, election_id : &str, registration_opens_at : u64, opens_at : u64, closes_at : u64) -> io::Result<bool>;
+    fn set_tally(&self, election_id : &str, tally : Vec<u32>) -> io::Result<bool>;
+    // Votes are indices of the options and ballots are encrypted with the trustee key, these also fail once voting is open at `now`
+    fn set_options(&self, election_id : &str, options : Vec<Candidate>, now : u64) -> io::Result<bool>;
+    fn set_trustees(&self, election_id : &str, trustees : TrusteeKey, now : u64) -> io::Result<bool>;
+    fn add_partial_tally(&self, election_id : &str, partial_tally : PartialTally) -> io::Result<bool>; // Also fails if the trustee already sent one
+    fn get_stats(&self, election_id : &str) -> Option<ElectionStats>;
+
+    fn add_registered_id(&self, election_id : &str, id : String) -> io::Result<bool>;
+    fn add_registration_code(&self, election_id : &str, code : String) -> io::Result<bool>;
+    fn use_registration_code(&self, election_id : &str, code : &str) -> io::Result<bool>; // Also used to revoke codes
+
+    fn try_register_user(&self, election_id : &str, user : UserData) -> io::Result<bool>;
+    fn user_registered(&self, election_id : &str, voter_id : VoterId) -> bool;
+    fn get_user(&self, election_id : &str, voter_id : VoterId) -> Option<UserData>;
+
+    fn add_pending_vote(&self, election_id : &str, voter_id : VoterId, pending_vote : PendingVote) -> io::Result<bool>;
+    fn get_pending_vote(&self, election_id : &str, voter_id : VoterId) -> Option<PendingVote>;
+    fn get_and_remove_pending_vote(&self, election_id : &str, voter_id : VoterId) -> io::Result<Option<PendingVote>>;
+
+    fn add_vote(&self, election_id : &str, voter_id : VoterId, ballot : EncryptedBallot) -> io::Result<bool>;
+    fn user_voted(&self, election_id : &str, voter_id : VoterId) -> bool;
+
+    fn add_challenge(&self, election_id : &str, session_id : &str, challenge_data : ChallengeData) -> io::Result<bool>;
+    fn get_challenge(&self, election_id : &str, session_id : &str) -> Option<ChallengeData>;
+    fn remove_challenge(&self, election_id : &str, session_id : &str) -> io::Result<bool>;
+
+    // Removes challenges and pending votes of all elections that expired at `now`, returns how many were removed
+    fn remove_expired(&self, now : u64) -> io::Result<usize>;
+
+    // Ballots in random order, the tally is computed from them without decrypting any single ballot
+    fn get_ballots(&self, election_id : &str) -> Option<Vec<EncryptedBallot>>;
+    // The mix is stored only once and only if it has as many ballots as were cast, no more votes are accepted after that
+    fn set_mix(&self, election_id : &str, mix : BallotMix) -> io::Result<bool>;
+    fn get_mix(&self, election_id : &str) -> Option<BallotMix>;
+
+    // Public bulletin board of the election from the given index on. Creating and rescheduling the election,
+    // registering a user, adding a vote and setting the tally also add an entry to the board.
+    fn get_board(&self, election_id : &str, from : u64) -> Option<BoardView>;
+    // Proof that the ballot with the given hash (zkp_protocol::merkle::ballot_hash) is in the tree of accepted ballots
+    fn get_inclusion_proof(&self, election_id : &str, ballot_hash : &Hash) -> Option<InclusionProof>;
+
+    fn export_election(&self, election_id : &str) -> Option<ElectionArchive>;
+    // Adds the election with its board, registered users and ballots, fails if an election with the same id exists
+    fn import_election(&self, archive : ElectionArchive) -> io::Result<bool>;
+}
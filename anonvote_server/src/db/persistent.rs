@@ -0,0 +1,507 @@
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::vec::Vec;
+
+use zkp_protocol::board::{BoardRecord, Hash};
+
+use crate::db::{ElectionArchive, ElectionStats, MemoryDB, VoteStore};
+use crate::db::memory::ChangeLog;
+use crate::db::wal::{Wal, WalRecord};
+use crate::db::ballots::{BallotDir, ElectionBallots};
+use crate::model::user_data::{UserData, VoterId};
+use crate::model::challenge_data::ChallengeData;
+use crate::model::pending_vote::PendingVote;
+use crate::model::election::Election;
+use crate::model::candidate::Candidate;
+use crate::model::ballot::EncryptedBallot;
+use crate::model::mix::BallotMix;
+use crate::model::board::BoardView;
+use crate::model::ballot_tree::InclusionProof;
+use crate::model::trustees::{PartialTally, TrusteeKey};
+
+// State is kept in memory, every change is written to the write-ahead log before it is applied
+// and on startup the state is rebuilt by replaying the log.
+// Ballots are stored in a directory next to the log (anonvote.wal -> anonvote.ballots), see ballots.rs.
+// Pending votes and challenges are only kept in memory, sessions started before a restart have to vote again.
+pub struct AnonVoteDB {
+    state : MemoryDB<Storage>,
+}
+
+// What the changes are written to before they are applied
+struct Storage {
+    wal : Mutex<Wal>,
+    ballots : BallotDir,
+}
+
+impl AnonVoteDB {
+    pub fn connect(path : impl AsRef<Path>) -> io::Result<AnonVoteDB> {
+        let path = path.as_ref();
+        let (mut wal, records) = Wal::open(path)?;
+        let state = MemoryDB::default();
+        for record in records {
+            AnonVoteDB::replay(&state, record)?;
+        }
+
+        let ballot_dir = BallotDir::open(&path.with_extension("ballots"))?;
+        for (election_id, ballots) in ballot_dir.read()? {
+            let unposted = state.restore_ballots(&election_id, ballots)
+                .ok_or(io::Error::new(io::ErrorKind::InvalidData, format!("Ballots for unknown election {}", election_id)))??;
+
+            // The server stopped between writing the ballots and the log, the voter is marked as voted so the ballot counts
+            for ballot in unposted {
+                wal.append(&WalRecord::PostBallot { election_id : election_id.clone(), ballot : ballot.clone() })?;
+                state.post_to_board(&election_id, BoardRecord::Ballot { ballot : ballot.options, proof : ballot.proof });
+            }
+        }
+
+        Ok(AnonVoteDB {
+            state : state.with_log(Storage {
+                wal : Mutex::new(wal),
+                ballots : ballot_dir,
+            }),
+        })
+    }
+
+    fn replay(state : &MemoryDB, record : WalRecord) -> io::Result<()> {
+        match record {
+            WalRecord::CreateElection { election } => {
+                state.create_election(election)?;
+            }
+            WalRecord::SetSchedule { election_id, registration_opens_at, opens_at, closes_at } => {
+                state.set_schedule(&election_id, registration_opens_at, opens_at, closes_at)?;
+            }
+            WalRecord::SetTally { election_id, tally } => {
+                state.set_tally(&election_id, tally)?;
+            }
+            WalRecord::SetOptions { election_id, options, at } => {
+                state.set_options(&election_id, options, at)?;
+            }
+            WalRecord::SetTrustees { election_id, trustees, at } => {
+                state.set_trustees(&election_id, trustees, at)?;
+            }
+            WalRecord::AddPartialTally { election_id, partial_tally } => {
+                state.add_partial_tally(&election_id, partial_tally)?;
+            }
+            WalRecord::AddRegisteredId { election_id, id } => {
+                state.add_registered_id(&election_id, id)?;
+            }
+            WalRecord::AddRegistrationCode { election_id, code } => {
+                state.add_registration_code(&election_id, code)?;
+            }
+            WalRecord::UseRegistrationCode { election_id, code } => {
+                state.use_registration_code(&election_id, &code)?;
+            }
+            WalRecord::RegisterUser { election_id, a, b, alpha, beta, group } => {
+                state.try_register_user(&election_id, WalRecord::user_data(&a, &b, &alpha, &beta, &group)?)?;
+            }
+            WalRecord::PostBallot { election_id, ballot } => {
+                state.post_to_board(&election_id, BoardRecord::Ballot { ballot : ballot.options, proof : ballot.proof });
+            }
+            WalRecord::ImportElection { election, board, ballots, mix } => {
+                let board = board.iter().map(|content| content.parse(&election.group)).collect();
+                state.import_election(ElectionArchive { election, board, ballots, mix : mix.map(|mix| *mix) })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Every change of the state is synced to disk before it is applied
+impl ChangeLog for Storage {
+    fn append(&self, record : impl FnOnce() -> WalRecord) -> io::Result<()> {
+        self.wal.lock().unwrap().append(&record())
+    }
+
+    fn write_ballots(&self, election_id : &str, ballots : impl FnOnce() -> ElectionBallots) -> io::Result<()> {
+        self.ballots.write(election_id, &ballots())
+    }
+}
+
+impl VoteStore for AnonVoteDB {
+    fn create_election(&self, election : Election) -> io::Result<bool> {
+        self.state.create_election(election)
+    }
+
+    fn get_election(&self, election_id : &str) -> Option<Election> {
+        self.state.get_election(election_id)
+    }
+
+    fn get_elections(&self) -> Vec<Election> {
+        self.state.get_elections()
+    }
+
+    fn set_schedule(&self, election_id : &str, registration_opens_at : u64, opens_at : u64, closes_at : u64) -> io::Result<bool> {
+        self.state.set_schedule(election_id, registration_opens_at, opens_at, closes_at)
+    }
+
+    fn set_tally(&self, election_id : &str, tally : Vec<u32>) -> io::Result<bool> {
+        self.state.set_tally(election_id, tally)
+    }
+
+    fn set_options(&self, election_id : &str, options : Vec<Candidate>, now : u64) -> io::Result<bool> {
+        self.state.set_options(election_id, options, now)
+    }
+
+    fn set_trustees(&self, election_id : &str, trustees : TrusteeKey, now : u64) -> io::Result<bool> {
+        self.state.set_trustees(election_id, trustees, now)
+    }
+
+    fn add_partial_tally(&self, election_id : &str, partial_tally : PartialTally) -> io::Result<bool> {
+        self.state.add_partial_tally(election_id, partial_tally)
+    }
+
+    fn get_stats(&self, election_id : &str) -> Option<ElectionStats> {
+        self.state.get_stats(election_id)
+    }
+
+    fn add_registered_id(&self, election_id : &str, id : String) -> io::Result<bool> {
+        self.state.add_registered_id(election_id, id)
+    }
+
+    fn add_registration_code(&self, election_id : &str, code : String) -> io::Result<bool> {
+        self.state.add_registration_code(election_id, code)
+    }
+
+    fn use_registration_code(&self, election_id : &str, code : &str) -> io::Result<bool> {
+        self.state.use_registration_code(election_id, code)
+    }
+
+    fn try_register_user(&self, election_id : &str, user : UserData) -> io::Result<bool> {
+        self.state.try_register_user(election_id, user)
+    }
+
+    fn user_registered(&self, election_id : &str, voter_id : VoterId) -> bool {
+        self.state.user_registered(election_id, voter_id)
+    }
+
+    fn get_user(&self, election_id : &str, voter_id : VoterId) -> Option<UserData> {
+        self.state.get_user(election_id, voter_id)
+    }
+
+    fn add_pending_vote(&self, election_id : &str, voter_id : VoterId, pending_vote : PendingVote) -> io::Result<bool> {
+        self.state.add_pending_vote(election_id, voter_id, pending_vote)
+    }
+
+    fn get_pending_vote(&self, election_id : &str, voter_id : VoterId) -> Option<PendingVote> {
+        self.state.get_pending_vote(election_id, voter_id)
+    }
+
+    fn get_and_remove_pending_vote(&self, election_id : &str, voter_id : VoterId) -> io::Result<Option<PendingVote>> {
+        self.state.get_and_remove_pending_vote(election_id, voter_id)
+    }
+
+    // The ballot is also logged without the voter, so the bulletin board keeps its order after a restart
+    fn add_vote(&self, election_id : &str, voter_id : VoterId, ballot : EncryptedBallot) -> io::Result<bool> {
+        self.state.add_vote(election_id, voter_id, ballot)
+    }
+
+    fn user_voted(&self, election_id : &str, voter_id : VoterId) -> bool {
+        self.state.user_voted(election_id, voter_id)
+    }
+
+    fn add_challenge(&self, election_id : &str, session_id : &str, challenge_data : ChallengeData) -> io::Result<bool> {
+        self.state.add_challenge(election_id, session_id, challenge_data)
+    }
+
+    fn get_challenge(&self, election_id : &str, session_id : &str) -> Option<ChallengeData> {
+        self.state.get_challenge(election_id, session_id)
+    }
+
+    fn remove_challenge(&self, election_id : &str, session_id : &str) -> io::Result<bool> {
+        self.state.remove_challenge(election_id, session_id)
+    }
+
+    fn remove_expired(&self, now : u64) -> io::Result<usize> {
+        self.state.remove_expired(now)
+    }
+
+    fn get_ballots(&self, election_id : &str) -> Option<Vec<EncryptedBallot>> {
+        self.state.get_ballots(election_id)
+    }
+
+    fn set_mix(&self, election_id : &str, mix : BallotMix) -> io::Result<bool> {
+        self.state.set_mix(election_id, mix)
+    }
+
+    fn get_mix(&self, election_id : &str) -> Option<BallotMix> {
+        self.state.get_mix(election_id)
+    }
+
+    fn get_board(&self, election_id : &str, from : u64) -> Option<BoardView> {
+        self.state.get_board(election_id, from)
+    }
+
+    fn get_inclusion_proof(&self, election_id : &str, ballot_hash : &Hash) -> Option<InclusionProof> {
+        self.state.get_inclusion_proof(election_id, ballot_hash)
+    }
+
+    fn export_election(&self, election_id : &str) -> Option<ElectionArchive> {
+        self.state.export_election(election_id)
+    }
+
+    // Ballots are in the log record, they are only written to the ballot file with a vote or mix of the election
+    fn import_election(&self, archive : ElectionArchive) -> io::Result<bool> {
+        self.state.import_election(archive)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_bigint::BigUint;
+    use zkp_protocol::{zkp_util, SecretKey};
+    use zkp_protocol::merkle;
+    use zkp_protocol::params::KeyParams;
+    use zkp_protocol::group::GroupParams;
+    use crate::model::group::StoredGroup;
+    use zkp_protocol::elgamal::DecryptionKey;
+    use crate::model::ballot::decrypt_tally;
+
+    fn test_election(id : &str) -> Election {
+        Election {
+            id : id.to_string(),
+            title : format!("Election {}", id),
+            options : crate::candidates::parse_plain_text("Alice\nBob\nCarol"),
+            registration_opens_at : 0,
+            opens_at : 0,
+            closes_at : u64::MAX,
+            tally : None,
+            trustees : None,
+            partial_tallies : Vec::new(),
+            group : StoredGroup::default(),
+        }
+    }
+
+    #[test]
+    fn test_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anonvote.wal");
+
+        let user = UserData { key : SecretKey::from_bytes_be(&GroupParams::default(), &123456789u32.to_be_bytes()).public_key(&KeyParams::for_election(&GroupParams::default(), "first")) };
+        let voter_id = user.id();
+        // Key of a custom group, the log has to keep its numbers
+        let default = GroupParams::default();
+        let custom = GroupParams::from_numbers("custom", default.p(), default.q(), default.alpha()).unwrap();
+        let other_user = UserData { key : SecretKey::generate(&custom).public_key(&KeyParams::for_election(&custom, "second")) };
+        let key = DecryptionKey::generate(&GroupParams::default());
+
+        {
+            let db = AnonVoteDB::connect(&path).unwrap();
+            assert!(db.create_election(test_election("first")).unwrap());
+            assert!(db.create_election(test_election("second")).unwrap());
+            assert!(!db.create_election(test_election("first")).unwrap(), "Created the same election twice");
+            assert!(db.add_registered_id("first", "12345".to_string()).unwrap());
+            assert!(db.add_registration_code("first", "code1".to_string()).unwrap());
+            assert!(db.add_registration_code("first", "code2".to_string()).unwrap());
+            assert!(db.use_registration_code("first", "code1").unwrap());
+            assert!(db.try_register_user("first", user.clone()).unwrap());
+            assert!(db.try_register_user("second", other_user.clone()).unwrap());
+            assert!(db.add_pending_vote("first", voter_id, PendingVote { ballot : EncryptedBallot::encrypt(key.encryption_key(), 2, 3), expires_at : u64::MAX }).unwrap());
+            assert!(db.add_challenge("first", "session", ChallengeData {
+                voter_id,
+                challenge : BigUint::from(7u32),
+                ka : BigUint::from(8u32),
+                kb : BigUint::from(9u32),
+                expires_at : u64::MAX,
+            }).unwrap());
+        }
+
+        {
+            let db = AnonVoteDB::connect(&path).unwrap();
+            assert_eq!(db.get_elections(), vec![test_election("first"), test_election("second")], "Elections were not recovered");
+            assert!(!db.add_registered_id("first", "12345".to_string()).unwrap(), "Registered ID was not recovered");
+            assert!(!db.use_registration_code("first", "code1").unwrap(), "Used registration code was recovered as active");
+            assert!(db.use_registration_code("first", "code2").unwrap(), "Active registration code was not recovered");
+            assert!(db.user_registered("first", voter_id), "Registered user was not recovered");
+            let recovered = db.get_user("second", other_user.id()).expect("Registered user was not recovered");
+            assert_eq!(recovered.key.group(), &custom, "Key was recovered in the wrong group");
+            assert_eq!(recovered.id(), other_user.id());
+
+            // Pending vote links the user with the vote, so sessions are never persisted
+            assert!(db.get_pending_vote("first", voter_id).is_none(), "Pending vote was recovered");
+            assert!(db.get_challenge("first", "session").is_none(), "Challenge was recovered");
+
+            assert!(db.add_vote("first", voter_id, EncryptedBallot::encrypt(key.encryption_key(), 2, 3)).unwrap());
+        }
+
+        let db = AnonVoteDB::connect(&path).unwrap();
+        assert!(db.user_voted("first", voter_id), "Vote was not recovered");
+        assert_eq!(decrypt_tally(&db.get_ballots("first").unwrap(), 3, &key), Some(vec![0, 0, 1]));
+
+        assert!(db.set_schedule("first", 0, 10, 20).unwrap());
+        assert!(db.set_tally("first", vec![0, 0, 1]).unwrap());
+        assert!(!db.set_tally("first", vec![1, 0, 0]).unwrap(), "Tally was changed after it was set");
+        assert!(!db.set_schedule("first", 0, 10, 30).unwrap(), "Schedule was changed after the election was tallied");
+        assert!(!db.set_options("first", crate::candidates::parse_plain_text("Dave"), 0).unwrap(), "Options were changed after the election was tallied");
+        assert!(db.set_schedule("second", 0, 100, 200).unwrap());
+        assert!(db.set_options("second", crate::candidates::parse_plain_text("Dave\nEve"), 50).unwrap());
+        assert!(!db.set_options("second", crate::candidates::parse_plain_text("Frank"), 100).unwrap(), "Options were changed once voting was open");
+
+        let (trustees, key_shares) = crate::model::trustees::test::test_trustees(&GroupParams::default());
+        let sum = trustees.encryption_key.encrypt(0);
+        let partial_tally = PartialTally { trustee : 2, decryptions : vec![key_shares[1].partial_decrypt(&sum, b"second").unwrap()] };
+        assert!(!db.set_trustees("second", trustees.clone(), 150).unwrap(), "Trustees were changed once voting was open");
+        assert!(db.set_trustees("second", trustees.clone(), 50).unwrap());
+        assert!(db.add_partial_tally("second", partial_tally.clone()).unwrap());
+        assert!(!db.add_partial_tally("second", partial_tally.clone()).unwrap(), "Same trustee decrypted the tally twice");
+        assert!(!db.set_trustees("first", trustees.clone(), 0).unwrap(), "Trustees were changed after the election was tallied");
+
+        let db = AnonVoteDB::connect(&path).unwrap();
+        let election = db.get_election("first").unwrap();
+        assert_eq!((election.opens_at, election.closes_at), (10, 20), "Schedule was not recovered");
+        assert_eq!(election.tally, Some(vec![0, 0, 1]), "Tally was not recovered");
+        let election = db.get_election("second").unwrap();
+        assert_eq!(election.options, crate::candidates::parse_plain_text("Dave\nEve"), "Options were not recovered");
+        assert_eq!(election.trustees, Some(trustees), "Trustees were not recovered");
+        assert_eq!(election.partial_tallies, vec![partial_tally], "Partial tally was not recovered");
+
+        // Other elections are not affected
+        assert!(!db.user_registered("second", voter_id));
+        assert!(!db.user_voted("second", voter_id));
+        assert!(db.add_registered_id("second", "12345".to_string()).unwrap(), "Same ID should be able to register in a different election");
+        assert_eq!(db.get_ballots("second"), Some(vec![]));
+        assert_eq!(db.get_ballots("missing"), None);
+        assert!(!db.add_registered_id("missing", "12345".to_string()).unwrap(), "Registered ID in election that doesn't exist");
+    }
+
+    // Log that can't write anything, like a full disk
+    struct FailingLog;
+
+    impl ChangeLog for FailingLog {
+        fn append(&self, _record : impl FnOnce() -> WalRecord) -> io::Result<()> {
+            Err(io::Error::other("Disk is full"))
+        }
+
+        fn write_ballots(&self, _election_id : &str, _ballots : impl FnOnce() -> ElectionBallots) -> io::Result<()> {
+            Err(io::Error::other("Disk is full"))
+        }
+    }
+
+    #[test]
+    fn test_failed_log() {
+        let db = MemoryDB::default();
+        assert!(db.create_election(test_election("first")).unwrap());
+        let db = db.with_log(FailingLog);
+
+        // Nothing is applied when the change can't be logged
+        assert!(db.create_election(test_election("second")).is_err());
+        assert!(db.add_registered_id("first", "12345".to_string()).is_err());
+        assert!(db.set_schedule("first", 0, 10, 20).is_err());
+        assert!(db.add_vote("first", VoterId([1; 32]), EncryptedBallot::encrypt(DecryptionKey::generate(&GroupParams::default()).encryption_key(), 0, 2)).is_err());
+        assert!(!db.user_voted("first", VoterId([1; 32])), "Vote was applied without being written");
+        assert_eq!(db.get_elections(), vec![test_election("first")], "Change was applied without being logged");
+        assert_eq!(db.get_stats("first").unwrap().registered_ids, 0, "Change was applied without being logged");
+        assert_eq!(db.get_board("first", 0).unwrap().size, 1);
+
+        // Changes that don't apply are not logged at all
+        assert!(!db.add_registered_id("missing", "12345".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_ballots() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anonvote.wal");
+        let key = DecryptionKey::generate(&GroupParams::default());
+        let ballot = |vote| EncryptedBallot::encrypt(key.encryption_key(), vote, 3);
+        let (head, root);
+        let mut ballot_hash = [0; 32];
+
+        {
+            let db = AnonVoteDB::connect(&path).unwrap();
+            assert!(db.create_election(test_election("first")).unwrap());
+            for (voter_id, vote) in [(30, 0), (10, 1), (20, 2), (40, 2)] {
+                let ballot = ballot(vote);
+                ballot_hash = merkle::ballot_hash("first", &ballot.options);
+                assert!(db.add_vote("first", VoterId([voter_id; 32]), ballot).unwrap());
+            }
+            assert!(!db.add_vote("first", VoterId([10; 32]), ballot(0)).unwrap(), "User voted twice");
+            head = db.get_board("first", 0).unwrap().head;
+            root = db.get_inclusion_proof("first", &ballot_hash).unwrap().root;
+        }
+
+        // Who voted is not in the log, the ballot files have no order that could link voters and ballots
+        let log = std::fs::read_to_string(&path).unwrap();
+        assert!(!log.contains("vote"), "Vote was written to the log");
+        let snapshot = BallotDir::open(&path.with_extension("ballots")).unwrap().read().unwrap();
+        assert_eq!(snapshot["first"].voted, [10, 20, 30, 40].map(|id| VoterId([id; 32])));
+        assert_eq!(decrypt_tally(&snapshot["first"].ballots, 3, &key), Some(vec![1, 1, 2]));
+
+        let db = AnonVoteDB::connect(&path).unwrap();
+        assert_eq!(decrypt_tally(&db.get_ballots("first").unwrap(), 3, &key), Some(vec![1, 1, 2]));
+        let board = db.get_board("first", 0).unwrap();
+        assert_eq!((board.size, board.head), (5, head), "Bulletin board was not recovered in the same order");
+        let proof = db.get_inclusion_proof("first", &ballot_hash).unwrap();
+        assert_eq!((proof.leaf_index, proof.tree_size, proof.root), (3, 4, root), "Ballot tree was not recovered in the same order");
+        assert!(db.user_voted("first", VoterId([40; 32])));
+        assert!(!db.user_voted("first", VoterId([50; 32])));
+
+        // Mix is kept with the ballots and closes the ballot box
+        let context = zkp_util::election_context("first");
+        let mix = BallotMix::shuffle(key.encryption_key(), &db.get_ballots("first").unwrap(), &context).unwrap();
+        assert!(db.set_mix("first", mix.clone()).unwrap());
+        assert!(!db.set_mix("first", mix.clone()).unwrap(), "Ballots were mixed twice");
+        assert!(!db.add_vote("first", VoterId([50; 32]), ballot(0)).unwrap(), "Voted after the ballots were mixed");
+        let db = AnonVoteDB::connect(&path).unwrap();
+        assert_eq!(db.get_mix("first"), Some(mix.clone()), "Mix was not recovered");
+        assert_eq!(db.get_mix("missing"), None);
+
+        // Imported election is in the log with its ballots, they are only in the ballot file after a vote or mix of that election
+        let mut archive = db.export_election("first").unwrap();
+        archive.election.id = "imported".to_string();
+        assert!(db.import_election(archive.clone()).unwrap());
+        assert!(!db.import_election(archive).unwrap(), "Imported the same election twice");
+        let db = AnonVoteDB::connect(&path).unwrap();
+        assert_eq!(db.get_mix("imported"), Some(mix), "Mix of the imported election was not recovered");
+        assert_eq!(db.get_ballots("imported"), db.get_ballots("first"));
+        assert_eq!(db.get_board("imported", 0).unwrap().size, 5, "Board of the imported election was not recovered");
+        let ballot = &db.get_ballots("imported").unwrap()[0];
+        assert!(db.get_inclusion_proof("imported", &merkle::ballot_hash("imported", &ballot.options)).is_some(), "Ballot tree of the imported election was not recovered");
+
+        // Ballots can't be restored without the log that created the election
+        std::fs::remove_file(&path).unwrap();
+        assert!(AnonVoteDB::connect(&path).is_err(), "Loaded ballots of an election that doesn't exist");
+    }
+
+    #[test]
+    fn test_unlogged_ballot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anonvote.wal");
+        let key = DecryptionKey::generate(&GroupParams::default());
+        let last = EncryptedBallot::encrypt(key.encryption_key(), 1, 2);
+        let last_hash = merkle::ballot_hash("first", &last.options);
+
+        {
+            let db = AnonVoteDB::connect(&path).unwrap();
+            assert!(db.create_election(test_election("first")).unwrap());
+            assert!(db.add_vote("first", VoterId([1; 32]), EncryptedBallot::encrypt(key.encryption_key(), 0, 2)).unwrap());
+            assert!(db.add_vote("first", VoterId([2; 32]), last).unwrap());
+        }
+
+        // Server stopped after the ballot file was written, before the ballot was logged
+        let log = std::fs::read_to_string(&path).unwrap();
+        let lines : Vec<&str> = log.lines().collect();
+        assert!(lines[lines.len() - 1].contains("post_ballot"));
+        std::fs::write(&path, lines[..lines.len() - 1].join("\n") + "\n").unwrap();
+
+        let db = AnonVoteDB::connect(&path).unwrap();
+        assert!(db.user_voted("first", VoterId([2; 32])));
+        assert_eq!(decrypt_tally(&db.get_ballots("first").unwrap(), 2, &key), Some(vec![1, 1]));
+        assert!(db.get_inclusion_proof("first", &last_hash).is_some(), "Unlogged ballot was not put on the bulletin board");
+        let head = db.get_board("first", 0).unwrap().head;
+        drop(db);
+
+        // It is logged as well, the next start finds the same board
+        let db = AnonVoteDB::connect(&path).unwrap();
+        assert_eq!(db.get_board("first", 0).unwrap().head, head, "Unlogged ballot was posted again");
+        drop(db);
+
+        // Ballot on the board that is missing from the ballot file can't be explained
+        let ballots_path = path.with_extension("ballots");
+        let ballot_dir = BallotDir::open(&ballots_path).unwrap();
+        let mut ballots = ballot_dir.read().unwrap().remove("first").unwrap();
+        ballots.ballots.retain(|ballot| merkle::ballot_hash("first", &ballot.options) != last_hash);
+        ballot_dir.write("first", &ballots).unwrap();
+        assert!(AnonVoteDB::connect(&path).is_err(), "Loaded ballot file without a ballot from the board");
+    }
+}
@@ -0,0 +1,215 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use zkp_protocol::PublicKey;
+use zkp_protocol::group::GroupParams;
+use anonvote_verifier::record::EntryContent;
+
+use crate::model::user_data::UserData;
+use crate::model::election::Election;
+use crate::model::group::StoredGroup;
+use crate::model::candidate::Candidate;
+use crate::model::trustees::{PartialTally, TrusteeKey};
+use crate::model::ballot::EncryptedBallot;
+use crate::model::mix::BallotMix;
+use crate::db::ElectionArchive;
+
+// Every change of the database state is described by one record.
+// Who voted, pending votes and challenges are not logged, they would link users with their votes (see ballots.rs).
+// Ballots are only logged as they appear on the public bulletin board, without the voter.
+// Records are written as one JSON object per line, so the log stays readable by hand.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WalRecord {
+    CreateElection { election : Election },
+    SetSchedule { election_id : String, registration_opens_at : u64, opens_at : u64, closes_at : u64 },
+    SetTally { election_id : String, tally : Vec<u32> },
+    // Time of the change, so the replay checks the phase the election was in. Older logs don't have it, they replay as a draft.
+    SetOptions { election_id : String, options : Vec<Candidate>, #[serde(default)] at : u64 },
+    SetTrustees { election_id : String, trustees : TrusteeKey, #[serde(default)] at : u64 },
+    AddPartialTally { election_id : String, partial_tally : PartialTally },
+    AddRegisteredId { election_id : String, id : String },
+    AddRegistrationCode { election_id : String, code : String },
+    UseRegistrationCode { election_id : String, code : String },
+    // Logs from before the key groups only have keys in the default group, later ones the name of a built-in group
+    RegisterUser { election_id : String, a : String, b : String, alpha : String, beta : String, #[serde(default)] group : StoredGroup },
+    PostBallot { election_id : String, ballot : EncryptedBallot },
+    // Ballots of an imported election are public already, they come with the record
+    ImportElection { election : Election, board : Vec<EntryContent>, ballots : Vec<EncryptedBallot>, mix : Option<Box<BallotMix>> },
+}
+
+fn to_hex(num : &BigUint) -> String {
+    hex::encode(num.to_bytes_be())
+}
+
+fn from_hex(hex_str : &str) -> io::Result<BigUint> {
+    let bytes = hex::decode(hex_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(BigUint::from_bytes_be(&bytes))
+}
+
+impl WalRecord {
+    pub fn register_user(election_id : &str, user : &UserData) -> WalRecord {
+        WalRecord::RegisterUser {
+            election_id : election_id.to_string(),
+            a : to_hex(user.key.a()),
+            b : to_hex(user.key.b()),
+            alpha : to_hex(user.key.alpha()),
+            beta : to_hex(user.key.beta()),
+            group : StoredGroup(user.key.group().clone()),
+        }
+    }
+
+    pub fn import_election(archive : &ElectionArchive) -> WalRecord {
+        WalRecord::ImportElection {
+            election : archive.election.clone(),
+            board : archive.board.iter().map(EntryContent::new).collect(),
+            ballots : archive.ballots.clone(),
+            mix : archive.mix.clone().map(Box::new),
+        }
+    }
+
+    pub fn user_data(a : &str, b : &str, alpha : &str, beta : &str, group : &GroupParams) -> io::Result<UserData> {
+        let key = PublicKey::new(group, from_hex(a)?, from_hex(b)?, from_hex(alpha)?, from_hex(beta)?);
+        Ok(UserData { key })
+    }
+}
+
+// Append-only write-ahead log.
+// A record is only considered committed once it is fully written and synced to disk, changes are applied
+// in memory only after that (see memory::ChangeLog). A failed append is cut off the end of the file again,
+// if even that fails the log refuses further writes until the server is restarted.
+pub struct Wal {
+    file : File,
+    len : u64,
+    failed : bool,
+}
+
+impl Wal {
+    // Opens (or creates) the log at the given path and returns all committed records.
+    // A partially written record at the end of the file (crash during append) is discarded.
+    pub fn open(path : &Path) -> io::Result<(Wal, Vec<WalRecord>)> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut records = Vec::new();
+        let mut valid_len : u64 = 0;
+        let mut reader = BufReader::new(&file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 {
+                break;
+            }
+
+            // Last line without the terminating newline is a torn write
+            if !line.ends_with('\n') {
+                break;
+            }
+
+            match serde_json::from_str::<WalRecord>(line.trim_end()) {
+                Ok(record) => records.push(record),
+                Err(e) => {
+                    // Corruption in the middle of the log can't be recovered automatically
+                    if reader.fill_buf()?.is_empty() {
+                        break;
+                    }
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Corrupted write-ahead log at byte {} : {}", valid_len, e)));
+                }
+            }
+            valid_len += read as u64;
+        }
+        drop(reader);
+
+        if file.metadata()?.len() != valid_len {
+            file.set_len(valid_len)?;
+            file.sync_all()?;
+        }
+        file.seek(SeekFrom::End(0))?;
+
+        Ok((Wal { file, len : valid_len, failed : false }, records))
+    }
+
+    pub fn append(&mut self, record : &WalRecord) -> io::Result<()> {
+        if self.failed {
+            return Err(io::Error::other("Write-ahead log is in failed state, restart is required"));
+        }
+        let mut line = serde_json::to_string(record).map_err(io::Error::from)?;
+        line.push('\n');
+        match self.file.write_all(line.as_bytes()).and_then(|_| self.file.sync_data()) {
+            Ok(()) => {
+                self.len += line.len() as u64;
+                Ok(())
+            }
+            Err(e) => {
+                // Part of the record may be in the file, the next record would follow it on the same line
+                self.failed = self.file.set_len(self.len).and_then(|_| self.file.sync_data()).is_err();
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_torn_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.wal");
+
+        {
+            let (mut wal, records) = Wal::open(&path).unwrap();
+            assert!(records.is_empty());
+            wal.append(&WalRecord::AddRegisteredId { election_id : "test".to_string(), id : "12345".to_string() }).unwrap();
+            wal.append(&WalRecord::AddRegistrationCode { election_id : "test".to_string(), code : "code".to_string() }).unwrap();
+        }
+
+        // Simulate crash in the middle of writing a record
+        let mut content = fs::read(&path).unwrap();
+        content.extend_from_slice(b"{\"op\":\"use_registr");
+        fs::write(&path, &content).unwrap();
+
+        {
+            let (mut wal, records) = Wal::open(&path).unwrap();
+            assert_eq!(records, vec![
+                WalRecord::AddRegisteredId { election_id : "test".to_string(), id : "12345".to_string() },
+                WalRecord::AddRegistrationCode { election_id : "test".to_string(), code : "code".to_string() },
+            ]);
+            wal.append(&WalRecord::UseRegistrationCode { election_id : "test".to_string(), code : "code".to_string() }).unwrap();
+        }
+
+        let (_, records) = Wal::open(&path).unwrap();
+        assert_eq!(records.len(), 3, "Record appended after recovery was not read back");
+        assert_eq!(records[2], WalRecord::UseRegistrationCode { election_id : "test".to_string(), code : "code".to_string() });
+    }
+
+    #[test]
+    fn test_corrupted_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.wal");
+        fs::write(&path, "{\"op\":\"add_registered_id\",\"election_id\":\"test\",\"id\":\"12345\"}\ngarbage\n{\"op\":\"add_registered_id\",\"election_id\":\"test\",\"id\":\"54321\"}\n").unwrap();
+        assert!(Wal::open(&path).is_err(), "Did not throw error while opening corrupted log");
+    }
+
+    #[test]
+    fn test_old_records() {
+        // Logs written before the time of the change was recorded
+        let record : WalRecord = serde_json::from_str("{\"op\":\"set_options\",\"election_id\":\"test\",\"options\":[]}").unwrap();
+        assert_eq!(record, WalRecord::SetOptions { election_id : "test".to_string(), options : Vec::new(), at : 0 });
+        let record : WalRecord = serde_json::from_str("{\"op\":\"register_user\",\"election_id\":\"test\",\"a\":\"02\",\"b\":\"03\",\"alpha\":\"04\",\"beta\":\"05\"}").unwrap();
+        let WalRecord::RegisterUser { group, .. } = record else { panic!("Wrong record") };
+        assert_eq!(group.0, GroupParams::default());
+        let record : WalRecord = serde_json::from_str("{\"op\":\"register_user\",\"election_id\":\"test\",\"a\":\"02\",\"b\":\"03\",\"alpha\":\"04\",\"beta\":\"05\",\"group\":\"ristretto255\"}").unwrap();
+        let WalRecord::RegisterUser { group, .. } = record else { panic!("Wrong record") };
+        assert_eq!(group.0, GroupParams::ristretto255());
+    }
+}
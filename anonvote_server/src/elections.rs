@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::candidates::{self, CandidatesError};
+use zkp_protocol::group::GroupParams;
+
+use crate::model::election::{self, Election};
+use crate::model::group::StoredGroup;
+
+// Elections file is a TOML file with [[elections]] tables:
+//
+// [[elections]]
+// id = "mayor"
+// title = "Mayor election"
+// candidates = "mayor_candidates.toml" # relative to the elections file
+// registration_opens_at = 1767139200 # optional, unix timestamp in seconds
+// opens_at = 1767225600 # unix timestamp in seconds, registration closes when voting opens
+// closes_at = 1767830400 # unix timestamp in seconds
+
+#[derive(Debug)]
+pub enum ElectionsError {
+    Io(io::Error),
+    Parse(String),
+    Empty,
+    MissingId(usize),
+    DuplicateId(String),
+    InvalidSchedule(String),
+    Candidates(String, CandidatesError),
+}
+
+impl fmt::Display for ElectionsError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElectionsError::Io(e) => write!(f, "Could not read elections file: {}", e),
+            ElectionsError::Parse(e) => write!(f, "Could not parse elections file: {}", e),
+            ElectionsError::Empty => write!(f, "Election list is empty"),
+            ElectionsError::MissingId(index) => write!(f, "Election #{} has empty id", index + 1),
+            ElectionsError::DuplicateId(id) => write!(f, "Duplicate election id: {}", id),
+            ElectionsError::InvalidSchedule(id) => write!(f, "Election {} has invalid schedule, phases must follow each other", id),
+            ElectionsError::Candidates(id, e) => write!(f, "Election {}: {}", id, e),
+        }
+    }
+}
+
+impl std::error::Error for ElectionsError {}
+
+#[derive(Deserialize)]
+struct ElectionsFile {
+    elections : Vec<ElectionEntry>,
+}
+
+#[derive(Deserialize)]
+struct ElectionEntry {
+    id : String,
+    title : String,
+    candidates : String,
+    registration_opens_at : Option<u64>,
+    opens_at : u64,
+    closes_at : u64,
+}
+
+// Elections are created in the group of the server
+pub fn load_elections(path : impl AsRef<Path>, group : &GroupParams) -> Result<Vec<Election>, ElectionsError> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(ElectionsError::Io)?;
+    let file : ElectionsFile = toml::from_str(&content).map_err(|e| ElectionsError::Parse(e.to_string()))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut elections = Vec::new();
+    for entry in file.elections {
+        let options = candidates::load_candidates(base_dir.join(&entry.candidates))
+            .map_err(|e| ElectionsError::Candidates(entry.id.clone(), e))?;
+        elections.push(Election {
+            id : entry.id,
+            title : entry.title,
+            options,
+            registration_opens_at : entry.registration_opens_at.unwrap_or(0),
+            opens_at : entry.opens_at,
+            closes_at : entry.closes_at,
+            group : StoredGroup(group.clone()),
+            tally : None,
+            trustees : None,
+            partial_tallies : Vec::new(),
+        });
+    }
+    validate_elections(&elections)?;
+    Ok(elections)
+}
+
+// Election used when the server is started with a single candidates file,
+// registration opens right away and voting follows right after it
+pub fn default_election(candidates_path : impl AsRef<Path>, registration_duration : u64, voting_duration : u64, group : &GroupParams) -> Result<Election, ElectionsError> {
+    let options = candidates::load_candidates(candidates_path).map_err(|e| ElectionsError::Candidates("default".to_string(), e))?;
+    let registration_opens_at = election::now();
+    let opens_at = registration_opens_at.saturating_add(registration_duration);
+    Ok(Election {
+        id : "default".to_string(),
+        title : "Default election".to_string(),
+        options,
+        registration_opens_at,
+        opens_at,
+        closes_at : opens_at.saturating_add(voting_duration),
+        group : StoredGroup(group.clone()),
+        tally : None,
+        trustees : None,
+        partial_tallies : Vec::new(),
+    })
+}
+
+pub fn validate_elections(elections : &[Election]) -> Result<(), ElectionsError> {
+    if elections.is_empty() {
+        return Err(ElectionsError::Empty);
+    }
+
+    let mut ids = HashSet::new();
+    for (index, election) in elections.iter().enumerate() {
+        if election.id.trim().is_empty() {
+            return Err(ElectionsError::MissingId(index));
+        }
+        if !ids.insert(election.id.as_str()) {
+            return Err(ElectionsError::DuplicateId(election.id.clone()));
+        }
+        if !Election::schedule_valid(election.registration_opens_at, election.opens_at, election.closes_at) {
+            return Err(ElectionsError::InvalidSchedule(election.id.clone()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load_elections() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("mayor.txt"), "Alice\nBob\n").unwrap();
+        fs::write(dir.path().join("referendum.txt"), "Yes\nNo\nAbstain\n").unwrap();
+
+        let path = dir.path().join("elections.toml");
+        fs::write(&path, r#"
+            [[elections]]
+            id = "mayor"
+            title = "Mayor election"
+            candidates = "mayor.txt"
+            opens_at = 100
+            closes_at = 200
+
+            [[elections]]
+            id = "referendum"
+            title = "Referendum"
+            candidates = "referendum.txt"
+            registration_opens_at = 50
+            opens_at = 100
+            closes_at = 150
+        "#).unwrap();
+
+        let elections = load_elections(&path, &GroupParams::ristretto255()).unwrap();
+        assert_eq!(elections.len(), 2);
+        assert!(elections.iter().all(|election| *election.group == GroupParams::ristretto255()), "Election is not in the group of the server");
+        assert_eq!(elections[0].options.len(), 2);
+        assert_eq!((elections[0].registration_opens_at, elections[0].opens_at, elections[0].closes_at), (0, 100, 200), "Registration should open right away by default");
+        assert_eq!(elections[1].options.len(), 3);
+        assert_eq!((elections[1].registration_opens_at, elections[1].opens_at, elections[1].closes_at), (50, 100, 150));
+
+        fs::write(&path, r#"
+            [[elections]]
+            id = "mayor"
+            title = "Mayor election"
+            candidates = "mayor.txt"
+            opens_at = 100
+            closes_at = 200
+
+            [[elections]]
+            id = "mayor"
+            title = "Mayor election again"
+            candidates = "mayor.txt"
+            opens_at = 100
+            closes_at = 200
+        "#).unwrap();
+        assert!(matches!(load_elections(&path, &GroupParams::default()), Err(ElectionsError::DuplicateId(_))));
+
+        fs::write(&path, r#"
+            [[elections]]
+            id = "mayor"
+            title = "Mayor election"
+            candidates = "missing.txt"
+            opens_at = 100
+            closes_at = 200
+        "#).unwrap();
+        assert!(matches!(load_elections(&path, &GroupParams::default()), Err(ElectionsError::Candidates(_, _))));
+
+        fs::write(&path, r#"
+            [[elections]]
+            id = "mayor"
+            title = "Mayor election"
+            candidates = "mayor.txt"
+            opens_at = 200
+            closes_at = 100
+        "#).unwrap();
+        assert!(matches!(load_elections(&path, &GroupParams::default()), Err(ElectionsError::InvalidSchedule(_))));
+    }
+}
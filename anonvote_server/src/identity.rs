@@ -0,0 +1,309 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::model::election;
+
+// Identity config is a TOML file that picks the provider:
+//
+// provider = "demo" # any 5 character id, the default without a config
+//
+// provider = "roster"
+// path = "voters.csv" # relative to the config file
+// hashed = false # true if the roster was hashed with --hash-roster, only the hashes are kept in memory either way
+//
+// provider = "token"
+// secret = "..." # HMAC key shared with the token issuer, can also be set with ANONVOTE_TOKEN_SECRET
+
+pub const TOKEN_SECRET_ENV : &str = "ANONVOTE_TOKEN_SECRET";
+pub const ID_KEY_LEN : usize = 32;
+
+// Decides who may register for an election. This is where a real deployment asks the authority that knows the voters.
+pub trait IdentityProvider : Send + Sync + 'static {
+    // Identity the voter is registered under, None if the id is not eligible for the election.
+    // Every id of the same voter must give the same identity, so a voter can't register twice.
+    fn validate(&self, election_id : &str, id : &str) -> Option<String>;
+}
+
+#[derive(Debug)]
+pub enum IdentityError {
+    Io(io::Error),
+    Parse(String),
+    EmptyRoster,
+    InvalidHash(usize),
+    MissingSecret,
+}
+
+impl fmt::Display for IdentityError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentityError::Io(e) => write!(f, "Could not read identity config: {}", e),
+            IdentityError::Parse(e) => write!(f, "Could not parse identity config: {}", e),
+            IdentityError::EmptyRoster => write!(f, "Roster has no ids"),
+            IdentityError::InvalidHash(line) => write!(f, "Roster line {} is not an ID hash", line),
+            IdentityError::MissingSecret => write!(f, "Token provider needs a secret, set it in the config or {}", TOKEN_SECRET_ENV),
+        }
+    }
+}
+
+impl std::error::Error for IdentityError {}
+
+#[derive(Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+enum IdentityConfig {
+    Demo,
+    Roster {
+        path : String,
+        #[serde(default)]
+        hashed : bool,
+    },
+    Token {
+        secret : Option<String>,
+    },
+}
+
+pub fn load_provider(path : impl AsRef<Path>, hasher : &IdHasher) -> Result<Box<dyn IdentityProvider>, IdentityError> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(IdentityError::Io)?;
+    let config : IdentityConfig = toml::from_str(&content).map_err(|e| IdentityError::Parse(e.to_string()))?;
+    match config {
+        IdentityConfig::Demo => Ok(Box::new(DemoRule)),
+        IdentityConfig::Roster { path : roster_path, hashed } => {
+            let base_dir = path.parent().unwrap_or(Path::new(""));
+            Ok(Box::new(Roster::load(base_dir.join(roster_path), hashed, hasher)?))
+        }
+        IdentityConfig::Token { secret } => {
+            let secret = secret.or(std::env::var(TOKEN_SECRET_ENV).ok()).ok_or(IdentityError::MissingSecret)?;
+            Ok(Box::new(TokenIssuer::new(secret.as_bytes())))
+        }
+    }
+}
+
+// IDs are personal data, the server only keeps them as HMAC-SHA256 with a secret key (id_key.json).
+// IDs are easy to enumerate, without the key a leaked hash can't be checked against guessed IDs.
+#[derive(Clone)]
+pub struct IdHasher {
+    key : Vec<u8>,
+}
+
+impl IdHasher {
+    pub fn new(key : &[u8]) -> IdHasher {
+        IdHasher {
+            key : key.to_vec()
+        }
+    }
+
+    pub fn generate() -> IdHasher {
+        IdHasher::new(&rand::random::<[u8; ID_KEY_LEN]>())
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    pub fn hash(&self, id : &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC takes keys of any size");
+        mac.update(b"anonvote-id");
+        mac.update(id.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+// Simulation rule the server always had: a valid id is exactly 5 characters long
+pub struct DemoRule;
+
+impl IdentityProvider for DemoRule {
+    fn validate(&self, _election_id : &str, id : &str) -> Option<String> {
+        (id.len() == 5).then(|| id.to_string())
+    }
+}
+
+// Static list of eligible ids for all elections, one per line in the first column of a CSV file.
+// A first line "id" is taken as a header. Plain ids are hashed while the roster is read, only the hashes are kept.
+// A roster hashed with --hash-roster only works with the id key it was hashed with.
+pub struct Roster {
+    hashes : HashSet<String>,
+    hasher : IdHasher,
+}
+
+impl Roster {
+    pub fn load(path : impl AsRef<Path>, hashed : bool, hasher : &IdHasher) -> Result<Roster, IdentityError> {
+        let content = fs::read_to_string(path).map_err(IdentityError::Io)?;
+        Roster::parse(&content, hashed, hasher)
+    }
+
+    pub fn parse(content : &str, hashed : bool, hasher : &IdHasher) -> Result<Roster, IdentityError> {
+        let mut hashes = HashSet::new();
+        for (index, line) in content.lines().enumerate() {
+            let id = line.split(',').next().unwrap_or("").trim();
+            if id.is_empty() || (index == 0 && id.eq_ignore_ascii_case("id")) {
+                continue;
+            }
+            if !hashed {
+                hashes.insert(hasher.hash(id));
+            } else if id.len() == 64 && hex::decode(id).is_ok() {
+                hashes.insert(id.to_ascii_lowercase());
+            } else {
+                return Err(IdentityError::InvalidHash(index + 1));
+            }
+        }
+        if hashes.is_empty() {
+            return Err(IdentityError::EmptyRoster);
+        }
+        Ok(Roster { hashes, hasher : hasher.clone() })
+    }
+
+    // Hashed roster that can replace the plain one, sorted so the order of the original file is lost
+    pub fn to_csv(&self) -> String {
+        let mut hashes : Vec<&String> = self.hashes.iter().collect();
+        hashes.sort_unstable();
+        let mut csv = "id\n".to_string();
+        for hash in hashes {
+            csv.push_str(hash);
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl IdentityProvider for Roster {
+    fn validate(&self, _election_id : &str, id : &str) -> Option<String> {
+        let id = id.trim();
+        self.hashes.contains(&self.hasher.hash(id)).then(|| id.to_string())
+    }
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Tokens handed out by an issuer that already identified the voter, for a single election and until they expire:
+// "<subject>.<expires_at>.<hex HMAC-SHA256>". The voter registers under the subject, so a second token doesn't help.
+pub struct TokenIssuer {
+    secret : Vec<u8>,
+}
+
+impl TokenIssuer {
+    pub fn new(secret : &[u8]) -> TokenIssuer {
+        TokenIssuer {
+            secret : secret.to_vec()
+        }
+    }
+
+    fn mac(&self, election_id : &str, subject : &str, expires_at : u64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC takes keys of any size");
+        mac.update(b"anonvote-token");
+        for part in [election_id, subject] {
+            mac.update(&(part.len() as u32).to_be_bytes());
+            mac.update(part.as_bytes());
+        }
+        mac.update(&expires_at.to_be_bytes());
+        mac
+    }
+
+    pub fn issue(&self, election_id : &str, subject : &str, expires_at : u64) -> String {
+        let tag = self.mac(election_id, subject, expires_at).finalize().into_bytes();
+        format!("{}.{}.{}", subject, expires_at, hex::encode(tag))
+    }
+}
+
+impl IdentityProvider for TokenIssuer {
+    fn validate(&self, election_id : &str, id : &str) -> Option<String> {
+        // Subject may contain dots, the other parts can't
+        let mut parts = id.rsplitn(3, '.');
+        let tag = hex::decode(parts.next()?).ok()?;
+        let expires_at : u64 = parts.next()?.parse().ok()?;
+        let subject = parts.next().filter(|subject| !subject.is_empty())?;
+        if expires_at <= election::now() {
+            return None;
+        }
+        // verify_slice compares in constant time
+        self.mac(election_id, subject, expires_at).verify_slice(&tag).ok()?;
+        Some(subject.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_demo() {
+        assert_eq!(DemoRule.validate("test", "12345"), Some("12345".to_string()));
+        assert_eq!(DemoRule.validate("test", "123456"), None);
+    }
+
+    #[test]
+    fn test_hash() {
+        let hasher = IdHasher::generate();
+        assert_eq!(hasher.hash("A-100"), hasher.hash("A-100"));
+        assert_ne!(hasher.hash("A-100"), hasher.hash("A-101"));
+        assert_ne!(hasher.hash("A-100"), IdHasher::generate().hash("A-100"), "Hash doesn't depend on the key");
+        assert_eq!(IdHasher::new(hasher.key()).hash("A-100"), hasher.hash("A-100"));
+    }
+
+    #[test]
+    fn test_roster() {
+        let hasher = IdHasher::generate();
+        let roster = Roster::parse("id,name\nA-100,Alice\n\nB-200, Bob\n", false, &hasher).unwrap();
+        assert_eq!(roster.validate("test", "A-100"), Some("A-100".to_string()));
+        assert_eq!(roster.validate("test", "B-200"), Some("B-200".to_string()));
+        assert_eq!(roster.validate("test", "id"), None, "Header was read as an id");
+        assert_eq!(roster.validate("test", "C-300"), None);
+        assert!(!roster.to_csv().contains("A-100"), "Hashed roster has a plain id");
+
+        let hashed = Roster::parse(&roster.to_csv().to_uppercase(), true, &hasher).unwrap();
+        assert_eq!(hashed.validate("test", "A-100"), Some("A-100".to_string()));
+        assert_eq!(hashed.validate("test", &hasher.hash("A-100")), None, "Hash was accepted as an id");
+        let other_key = Roster::parse(&roster.to_csv(), true, &IdHasher::generate()).unwrap();
+        assert_eq!(other_key.validate("test", "A-100"), None, "Roster was checked with a different key");
+
+        assert!(matches!(Roster::parse("id\n", false, &hasher), Err(IdentityError::EmptyRoster)));
+        assert!(matches!(Roster::parse("A-100\n", true, &hasher), Err(IdentityError::InvalidHash(1))));
+    }
+
+    #[test]
+    fn test_token() {
+        let issuer = TokenIssuer::new(b"secret");
+        let expires_at = election::now() + 60;
+        let token = issuer.issue("test", "alice.smith", expires_at);
+        assert_eq!(issuer.validate("test", &token), Some("alice.smith".to_string()));
+        assert_eq!(issuer.validate("other", &token), None, "Token was accepted for a different election");
+        assert_eq!(TokenIssuer::new(b"other").validate("test", &token), None, "Token was accepted with a different secret");
+
+        let expired = issuer.issue("test", "alice", election::now() - 1);
+        assert_eq!(issuer.validate("test", &expired), None, "Expired token was accepted");
+        let extended = token.replacen(&expires_at.to_string(), &(expires_at + 1).to_string(), 1);
+        assert_eq!(issuer.validate("test", &extended), None, "Token with changed expiry was accepted");
+        for invalid in ["", "alice", "alice.1.zz", &token[1..]] {
+            assert_eq!(issuer.validate("test", invalid), None, "Accepted invalid token {:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_load_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identity.toml");
+        fs::write(dir.path().join("voters.csv"), "A-100\n").unwrap();
+
+        let hasher = IdHasher::generate();
+        fs::write(&path, "provider = \"roster\"\npath = \"voters.csv\"\n").unwrap();
+        let provider = load_provider(&path, &hasher).unwrap();
+        assert!(provider.validate("test", "A-100").is_some());
+        assert!(provider.validate("test", "12345").is_none(), "Roster accepted an id of the demo rule");
+
+        fs::write(&path, "provider = \"token\"\nsecret = \"secret\"\n").unwrap();
+        let provider = load_provider(&path, &hasher).unwrap();
+        assert!(provider.validate("test", &TokenIssuer::new(b"secret").issue("test", "alice", u64::MAX)).is_some());
+
+        fs::write(&path, "provider = \"demo\"\n").unwrap();
+        assert!(load_provider(&path, &hasher).unwrap().validate("test", "12345").is_some());
+
+        fs::write(&path, "provider = \"ldap\"\n").unwrap();
+        assert!(matches!(load_provider(&path, &hasher), Err(IdentityError::Parse(_))));
+    }
+}
@@ -1,26 +1,188 @@
 mod server_impl;
+mod admin_impl;
+mod sweeper;
+mod authority;
 mod db;
+mod candidates;
+mod elections;
+mod identity;
+mod records;
 mod model {
     pub mod user_data;
     pub mod challenge_data;
+    pub mod pending_vote;
+    pub mod candidate;
+    pub mod election;
+    pub mod group;
+    pub mod ballot;
+    pub mod trustees;
+    pub mod mix;
+    pub mod board;
+    pub mod ballot_tree;
 }
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use anonvote_proto::proto::anonvote::anon_vote_server::AnonVoteServer;
+use anonvote_proto::proto::anonvote::anon_vote_admin_server::AnonVoteAdminServer;
+use rand::distr::{Alphanumeric, SampleString};
 use tonic::transport::Server;
+use zkp_protocol::blind_signature::SignerSecretKey;
+use zkp_protocol::elgamal::DecryptionKey;
+use zkp_protocol::board::BoardKey;
+use zkp_protocol::group::GroupParams;
 use server_impl::AnonVoteImpl;
-use db::AnonVoteDB;
+use admin_impl::{AdminAuth, AnonVoteAdminImpl};
+use db::{AnonVoteDB, MemoryDB, VoteStore};
+use identity::IdHasher;
+
+const DB_PATH : &str = "anonvote.wal";
+const CANDIDATES_PATH : &str = "candidates.txt";
+const AUTHORITY_KEY_PATH : &str = "authority_key.json";
+const DECRYPTION_KEY_PATH : &str = "decryption_key.json";
+const BOARD_KEY_PATH : &str = "board_key.json";
+const ID_KEY_PATH : &str = "id_key.json";
+const REGISTRATION_DURATION_MIN : u64 = 10;
+const VOTING_DURATION_MIN : u64 = 60;
+const ADMIN_TOKEN_ENV : &str = "ANONVOTE_ADMIN_TOKEN";
+const ADMIN_TOKEN_LEN : usize = 32;
+const SWEEP_INTERVAL : Duration = Duration::from_secs(30);
+const TOKEN_VALID_MIN : u64 = 24 * 60;
+
+fn arg_value(name : &str) -> Option<String> {
+    let mut args = std::env::args();
+    args.position(|arg| arg == name)?;
+    args.next()
+}
+
+// --issue-token <subject> --election <id> prints a token for the token identity provider and exits,
+// for whoever identifies the voters. The secret is read from ANONVOTE_TOKEN_SECRET.
+fn issue_token(subject : &str) {
+    let secret = std::env::var(identity::TOKEN_SECRET_ENV).unwrap_or_else(|_| {
+        eprintln!("{} is not set", identity::TOKEN_SECRET_ENV);
+        std::process::exit(1);
+    });
+    let election_id = arg_value("--election").unwrap_or("default".to_string());
+    let valid_minutes = arg_value("--token-minutes").and_then(|v| v.parse().ok()).unwrap_or(TOKEN_VALID_MIN);
+    let expires_at = model::election::now().saturating_add(valid_minutes * 60);
+    println!("{}", identity::TokenIssuer::new(secret.as_bytes()).issue(&election_id, subject, expires_at));
+}
+
+// --hash-roster <csv> prints the roster with the ids hashed with the id key of this server and exits.
+// The output can replace the plain roster (hashed = true), so the list of voters doesn't have to stay on the server.
+fn hash_roster(roster_path : &str) {
+    let id_hasher = authority::load_or_generate_id_key(ID_KEY_PATH).expect("Could not load the id key");
+    let roster = identity::Roster::load(roster_path, false, &id_hasher).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    print!("{}", roster.to_csv());
+}
 
 #[tokio::main]
 async fn main() {
+    if let Some(subject) = arg_value("--issue-token") {
+        issue_token(&subject);
+        return;
+    }
+    if let Some(roster_path) = arg_value("--hash-roster") {
+        hash_roster(&roster_path);
+        return;
+    }
+
+    // Group of the voter keys, ballots, proofs and board signatures of every election on this server,
+    // one of the built-in groups (e.g. ristretto255), a custom group from --group-file, the default group otherwise
+    let group = match (arg_value("--group"), arg_value("--group-file")) {
+        (Some(_), Some(_)) => Err("Pass either --group or --group-file".to_string()),
+        (Some(name), None) => GroupParams::by_name(&name).map_err(|e| e.to_string()),
+        (None, Some(path)) => model::group::load_group_file(&path).map_err(|e| format!("Could not load the group file: {}", e)),
+        (None, None) => Ok(GroupParams::default()),
+    };
+    let group = group.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    // Pass --in-memory to run without persisting anything (useful for demos and testing)
+    let in_memory = std::env::args().any(|arg| arg == "--in-memory");
+    if in_memory {
+        let authority_key = SignerSecretKey::generate(authority::AUTHORITY_KEY_BITS);
+        serve(MemoryDB::default(), authority_key, DecryptionKey::generate(&group), BoardKey::generate(&group), IdHasher::generate()).await;
+    } else {
+        let authority_key = authority::load_or_generate_signer_key(AUTHORITY_KEY_PATH, authority::AUTHORITY_KEY_BITS).expect("Could not load the authority key");
+        let decryption_key = authority::load_or_generate_decryption_key(DECRYPTION_KEY_PATH, &group).expect("Could not load the decryption key");
+        let board_key = authority::load_or_generate_board_key(BOARD_KEY_PATH, &group).expect("Could not load the board key");
+        let id_hasher = authority::load_or_generate_id_key(ID_KEY_PATH).expect("Could not load the id key");
+        serve(AnonVoteDB::connect(DB_PATH).expect("Could not open the database"), authority_key, decryption_key, board_key, id_hasher).await;
+    }
+}
+
+async fn serve<S : VoteStore>(db : S, authority_key : SignerSecretKey, decryption_key : DecryptionKey, board_key : BoardKey, id_hasher : IdHasher) {
     let addr = "127.0.0.1:50051".to_string();
-    let db = AnonVoteDB::connect();
-    let vote_option_count = db.get_vote_options_count() as u32;
-    let anonvote_impl = AnonVoteImpl::new(db, vote_option_count);
+    let group = decryption_key.group().clone();
+
+    // Stored elections are tallied with the keys of this server, so they must be in its group
+    if let Some(election) = db.get_elections().iter().find(|election| *election.group != group) {
+        eprintln!("Election {} is in group {}, the server uses {}", election.id, election.group.name(), group.name());
+        std::process::exit(1);
+    }
+
+    // Either a TOML file with multiple elections, or a single candidates file (.txt, .toml or .json) for one default election
+    let elections = match arg_value("--elections") {
+        Some(elections_path) => elections::load_elections(&elections_path, &group),
+        None => {
+            let candidates_path = arg_value("--candidates").unwrap_or(CANDIDATES_PATH.to_string());
+            let registration_duration = arg_value("--registration-minutes").and_then(|v| v.parse().ok()).unwrap_or(REGISTRATION_DURATION_MIN);
+            let voting_duration = arg_value("--voting-minutes").and_then(|v| v.parse().ok()).unwrap_or(VOTING_DURATION_MIN);
+            elections::default_election(&candidates_path, registration_duration * 60, voting_duration * 60, &group).map(|election| vec![election])
+        }
+    };
+    let elections = elections.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    for election in elections {
+        let (id, option_count) = (election.id.clone(), election.options.len());
+        let created = db.create_election(election).expect("Could not store the election");
+        if created {
+            println!("Created election {} with {} candidates", id, option_count);
+        } else {
+            println!("Election {} already exists, keeping the stored one", id);
+        }
+    }
+
+    // Admin token from --admin-token or the environment, otherwise a random one for this run
+    let admin_token = arg_value("--admin-token").or(std::env::var(ADMIN_TOKEN_ENV).ok()).unwrap_or_else(|| {
+        let token = Alphanumeric.sample_string(&mut rand::rng(), ADMIN_TOKEN_LEN);
+        println!("Admin token: {}", token);
+        token
+    });
+
+    // Who may register, the demo rule unless --identity points to a config
+    let identity = match arg_value("--identity") {
+        Some(identity_path) => identity::load_provider(&identity_path, &id_hasher),
+        None => Ok(Box::new(identity::DemoRule) as Box<dyn identity::IdentityProvider>),
+    };
+    let identity = identity.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    let session_ttl = arg_value("--session-ttl").and_then(|v| v.parse().ok()).unwrap_or(server_impl::SESSION_TTL_SEC);
+
+    let db = Arc::new(db);
+    tokio::spawn(sweeper::run(db.clone(), SWEEP_INTERVAL));
+
+    let anonvote_impl = AnonVoteImpl::new(db.clone(), session_ttl, authority_key, decryption_key.clone(), board_key.clone(), identity, id_hasher);
+    let admin_impl = AnonVoteAdminImpl::new(db, decryption_key, board_key);
 
     println!("Starting server...");
 
     let server = Server::builder()
         .add_service(AnonVoteServer::new(anonvote_impl))
+        .add_service(AnonVoteAdminServer::with_interceptor(admin_impl, AdminAuth::new(admin_token)))
         .serve(addr.parse().expect("Could not convert address"));
 
     println!("Server started on {}", addr);
@@ -0,0 +1,131 @@
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use zkp_protocol::ballot_proof::{BallotProof, BitProof, SumProof};
+use zkp_protocol::elgamal::{self, Ciphertext, DecryptionKey};
+use zkp_protocol::group::GroupParams;
+
+// Ballot encrypted with the election key, one ciphertext per option (see zkp_protocol::elgamal).
+// Ciphertexts are stored as pairs of hex strings, cast ballots also keep the proof that they are valid.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(into = "BallotData", try_from = "BallotData")]
+pub struct EncryptedBallot {
+    pub options : Vec<Ciphertext>,
+    pub proof : Option<BallotProof>,
+}
+
+// Ballots without a proof keep the old format of a list of ciphertexts
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BallotData {
+    Options(Vec<[String; 2]>),
+    Proved { options : Vec<[String; 2]>, proof : ProofData },
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProofData {
+    options : Vec<[String; 8]>,
+    sum : [String; 3],
+}
+
+fn to_hex(number : &BigUint) -> String {
+    hex::encode(number.to_bytes_be())
+}
+
+fn from_hex(number : &str) -> Result<BigUint, hex::FromHexError> {
+    Ok(BigUint::from_bytes_be(&hex::decode(number)?))
+}
+
+impl From<EncryptedBallot> for BallotData {
+    fn from(ballot : EncryptedBallot) -> Self {
+        let options = ballot.options.iter().map(|ciphertext| [to_hex(ciphertext.c1()), to_hex(ciphertext.c2())]).collect();
+        match ballot.proof {
+            None => BallotData::Options(options),
+            Some(proof) => BallotData::Proved {
+                options,
+                proof : ProofData {
+                    options : proof.options().iter().map(|option| {
+                        [option.a0(), option.b0(), option.a1(), option.b1(), option.e0(), option.e1(), option.z0(), option.z1()].map(to_hex)
+                    }).collect(),
+                    sum : [proof.sum().a(), proof.sum().b(), proof.sum().z()].map(to_hex),
+                },
+            },
+        }
+    }
+}
+
+impl TryFrom<BallotData> for EncryptedBallot {
+    type Error = hex::FromHexError;
+
+    fn try_from(data : BallotData) -> Result<Self, Self::Error> {
+        let (options, proof) = match data {
+            BallotData::Options(options) => (options, None),
+            BallotData::Proved { options, proof } => (options, Some(proof)),
+        };
+        let options = options.iter().map(|[c1, c2]| Ok(Ciphertext::new(from_hex(c1)?, from_hex(c2)?))).collect::<Result<_, Self::Error>>()?;
+        let proof = proof.map(|proof| {
+            let options = proof.options.iter().map(|[a0, b0, a1, b1, e0, e1, z0, z1]| {
+                Ok(BitProof::new(from_hex(a0)?, from_hex(b0)?, from_hex(a1)?, from_hex(b1)?, from_hex(e0)?, from_hex(e1)?, from_hex(z0)?, from_hex(z1)?))
+            }).collect::<Result<_, Self::Error>>()?;
+            let [a, b, z] = &proof.sum;
+            Ok::<_, Self::Error>(BallotProof::new(options, SumProof::new(from_hex(a)?, from_hex(b)?, from_hex(z)?)))
+        }).transpose()?;
+        Ok(EncryptedBallot { options, proof })
+    }
+}
+
+// Cast ballots are encrypted by the voters, the server only encrypts ballots in tests
+#[cfg(test)]
+impl EncryptedBallot {
+    pub fn encrypt(encryption_key : &elgamal::EncryptionKey, vote : u32, option_count : usize) -> EncryptedBallot {
+        EncryptedBallot {
+            options : encryption_key.encrypt_vote(vote, option_count),
+            proof : None,
+        }
+    }
+}
+
+// Ciphertexts of each option are added together, so only the sum of all ballots is ever decrypted.
+// Returns None if some ballot is missing an option or is not in the group.
+pub fn encrypted_tally(group : &GroupParams, ballots : &[EncryptedBallot], option_count : usize) -> Option<Vec<Ciphertext>> {
+    (0..option_count).map(|option| {
+        let ciphertexts = ballots.iter().map(|ballot| ballot.options.get(option)).collect::<Option<Vec<_>>>()?;
+        elgamal::sum(group, ciphertexts)
+    }).collect()
+}
+
+// Returns None if some sum can't be decrypted, which means that some ballot doesn't encrypt a single vote.
+pub fn decrypt_tally(ballots : &[EncryptedBallot], option_count : usize, decryption_key : &DecryptionKey) -> Option<Vec<u32>> {
+    let max = u32::try_from(ballots.len()).ok()?;
+    let tally = encrypted_tally(decryption_key.group(), ballots, option_count)?;
+    tally.iter().map(|sum| decryption_key.decrypt(sum, max)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decrypt_tally() {
+        let key = DecryptionKey::generate(&GroupParams::default());
+        let ballots : Vec<EncryptedBallot> = [1, 0, 1].iter().map(|vote| EncryptedBallot::encrypt(key.encryption_key(), *vote, 2)).collect();
+        assert_eq!(decrypt_tally(&ballots, 2, &key), Some(vec![1, 2]));
+        assert_eq!(decrypt_tally(&[], 2, &key), Some(vec![0, 0]));
+
+        let json = serde_json::to_string(&ballots[0]).unwrap();
+        assert_eq!(serde_json::from_str::<EncryptedBallot>(&json).unwrap(), ballots[0], "Ballot changed after serialization");
+        assert!(json.starts_with("[["), "Ballot without a proof changed format");
+
+        let (options, proof) = key.encryption_key().encrypt_vote_with_proof(1, 2, b"context");
+        let proved = EncryptedBallot { options, proof : Some(proof) };
+        let json = serde_json::to_string(&proved).unwrap();
+        assert_eq!(serde_json::from_str::<EncryptedBallot>(&json).unwrap(), proved, "Ballot proof changed after serialization");
+
+        // Ballot with more than one vote for an option
+        let mut invalid = ballots.clone();
+        invalid.push(EncryptedBallot { options : vec![key.encryption_key().encrypt(0), key.encryption_key().encrypt(4)], proof : None });
+        assert_eq!(decrypt_tally(&invalid, 2, &key), None);
+
+        let short = EncryptedBallot { options : vec![key.encryption_key().encrypt(1)], proof : None };
+        assert_eq!(decrypt_tally(&[short], 2, &key), None, "Decrypted tally with a ballot that is missing an option");
+    }
+}
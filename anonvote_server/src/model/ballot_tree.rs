@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use zkp_protocol::board::Hash;
+use zkp_protocol::elgamal::Ciphertext;
+use zkp_protocol::merkle;
+
+// Merkle tree of the accepted ballots of one election (see zkp_protocol::merkle), leaves in the order of the bulletin board
+#[derive(Clone, Default)]
+pub struct BallotTree {
+    ballot_hashes : Vec<Hash>,
+    indices : HashMap<Hash, u64>,
+}
+
+// Path from the ballot to the root of the whole tree at the time it was read
+pub struct InclusionProof {
+    pub leaf_index : u64,
+    pub tree_size : u64,
+    pub root : Hash,
+    pub path : Vec<Hash>,
+}
+
+impl BallotTree {
+    pub fn append(&mut self, election_id : &str, ballot : &[Ciphertext]) {
+        let ballot_hash = merkle::ballot_hash(election_id, ballot);
+        self.indices.insert(ballot_hash, self.ballot_hashes.len() as u64);
+        self.ballot_hashes.push(ballot_hash);
+    }
+
+    pub fn contains(&self, ballot_hash : &Hash) -> bool {
+        self.indices.contains_key(ballot_hash)
+    }
+
+    // Returns None if the ballot is not in the tree
+    pub fn proof(&self, ballot_hash : &Hash) -> Option<InclusionProof> {
+        let leaf_index = *self.indices.get(ballot_hash)?;
+        Some(InclusionProof {
+            leaf_index,
+            tree_size : self.ballot_hashes.len() as u64,
+            root : merkle::root(&self.ballot_hashes),
+            path : merkle::inclusion_proof(&self.ballot_hashes, leaf_index as usize)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use zkp_protocol::elgamal::DecryptionKey;
+    use zkp_protocol::group::GroupParams;
+
+    #[test]
+    fn test_proof() {
+        let key = DecryptionKey::generate(&GroupParams::default());
+        let ballots : Vec<Vec<Ciphertext>> = [0, 1, 1].iter().map(|vote| key.encryption_key().encrypt_vote(*vote, 2)).collect();
+        let mut ballot_tree = BallotTree::default();
+        for ballot in &ballots {
+            ballot_tree.append("test", ballot);
+        }
+
+        let ballot_hash = merkle::ballot_hash("test", &ballots[1]);
+        let proof = ballot_tree.proof(&ballot_hash).unwrap();
+        assert_eq!((proof.leaf_index, proof.tree_size), (1, 3));
+        assert!(merkle::verify_inclusion(&ballot_hash, proof.leaf_index, proof.tree_size, &proof.path, &proof.root));
+        assert!(ballot_tree.proof(&merkle::ballot_hash("other", &ballots[1])).is_none(), "Found a ballot of a different election");
+        assert!(ballot_tree.contains(&ballot_hash) && !ballot_tree.contains(&merkle::ballot_hash("other", &ballots[1])));
+    }
+}
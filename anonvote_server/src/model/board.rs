@@ -0,0 +1,69 @@
+use zkp_protocol::board::{self, BoardRecord, Hash, EMPTY_HASH};
+
+// Hash chain of one election (see zkp_protocol::board), the index of an entry is its position
+#[derive(Clone, Default)]
+pub struct BulletinBoard {
+    entries : Vec<BoardEntry>,
+}
+
+#[derive(Clone)]
+pub struct BoardEntry {
+    pub record : BoardRecord,
+    pub hash : Hash,
+}
+
+// Entries from some index on, together with the size and head of the whole board at the time they were read
+pub struct BoardView {
+    pub first_index : u64,
+    pub entries : Vec<BoardEntry>,
+    pub size : u64,
+    pub head : Hash,
+}
+
+impl BulletinBoard {
+    pub fn append(&mut self, election_id : &str, record : BoardRecord) {
+        let hash = board::entry_hash(election_id, self.entries.len() as u64, &self.head(), &record);
+        self.entries.push(BoardEntry { record, hash });
+    }
+
+    pub fn head(&self) -> Hash {
+        self.entries.last().map(|entry| entry.hash).unwrap_or(EMPTY_HASH)
+    }
+
+    pub fn records(&self) -> Vec<BoardRecord> {
+        self.entries.iter().map(|entry| entry.record.clone()).collect()
+    }
+
+    pub fn view(&self, from : u64) -> BoardView {
+        let first_index = from.min(self.entries.len() as u64);
+        BoardView {
+            first_index,
+            entries : self.entries[first_index as usize..].to_vec(),
+            size : self.entries.len() as u64,
+            head : self.head(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_view() {
+        let mut bulletin_board = BulletinBoard::default();
+        assert_eq!(bulletin_board.view(0).head, EMPTY_HASH);
+        for votes in [vec![1], vec![2], vec![3]] {
+            bulletin_board.append("test", BoardRecord::Tally { votes });
+        }
+
+        let view = bulletin_board.view(1);
+        assert_eq!((view.first_index, view.entries.len(), view.size), (1, 2, 3));
+        let entries : Vec<(BoardRecord, Hash)> = view.entries.iter().map(|entry| (entry.record.clone(), entry.hash)).collect();
+        let previous = bulletin_board.view(0).entries[0].hash;
+        assert_eq!(board::verify_entries("test", 1, &previous, &entries), Some(view.head));
+
+        let view = bulletin_board.view(10);
+        assert_eq!((view.first_index, view.entries.len(), view.size), (3, 0, 3), "Reading past the end should return no entries");
+    }
+}
@@ -1,9 +1,18 @@
 use num_bigint::BigUint;
 
+use crate::model::user_data::VoterId;
+
 #[derive(Clone)]
 pub struct ChallengeData {
-    pub user_hash : u64,
+    pub voter_id : VoterId,
     pub challenge : BigUint,
     pub ka : BigUint,
     pub kb : BigUint,
-}
\ No newline at end of file
+    pub expires_at : u64, // Unix timestamp in seconds
+}
+
+impl ChallengeData {
+    pub fn expired(&self, now : u64) -> bool {
+        now >= self.expires_at
+    }
+}
@@ -0,0 +1,132 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::candidate::Candidate;
+use crate::model::group::StoredGroup;
+use crate::model::trustees::{PartialTally, TrusteeKey};
+
+// Draft -> RegistrationOpen -> VotingOpen -> Closed -> Tallied
+// All transitions except the last one happen on their own once the scheduled time is reached,
+// Tallied is reached once the final results are computed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ElectionPhase {
+    Draft,
+    RegistrationOpen,
+    VotingOpen,
+    Closed,
+    Tallied,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Election {
+    pub id : String,
+    pub title : String,
+    pub options : Vec<Candidate>,
+    pub registration_opens_at : u64, // Unix timestamp in seconds
+    pub opens_at : u64, // Unix timestamp in seconds, registration closes when voting opens
+    pub closes_at : u64, // Unix timestamp in seconds
+    #[serde(default)]
+    pub group : StoredGroup, // Voter keys, ballots and proofs of the election are in this group
+    #[serde(default)]
+    pub tally : Option<Vec<u32>>, // Final results, only set once the election is closed
+    #[serde(default)]
+    pub trustees : Option<TrusteeKey>, // Without trustees the tally is decrypted with the server key
+    #[serde(default)]
+    pub partial_tallies : Vec<PartialTally>, // Verified decryptions of the tally by the trustees
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl Election {
+    pub fn phase(&self, now : u64) -> ElectionPhase {
+        if self.tally.is_some() {
+            ElectionPhase::Tallied
+        } else if now < self.registration_opens_at {
+            ElectionPhase::Draft
+        } else if now < self.opens_at {
+            ElectionPhase::RegistrationOpen
+        } else if now < self.closes_at {
+            ElectionPhase::VotingOpen
+        } else {
+            ElectionPhase::Closed
+        }
+    }
+
+    pub fn current_phase(&self) -> ElectionPhase {
+        self.phase(now())
+    }
+
+    pub fn schedule_valid(registration_opens_at : u64, opens_at : u64, closes_at : u64) -> bool {
+        registration_opens_at <= opens_at && opens_at < closes_at
+    }
+
+    // Schedule that puts the election in the given phase at `now`, later phases keep their time if they can.
+    // Returns None if the election can't move to that phase, elections only move forward and
+    // Tallied is only reached by computing the results.
+    pub fn schedule_for_phase(&self, phase : ElectionPhase, now : u64) -> Option<(u64, u64, u64)> {
+        if phase <= self.phase(now) {
+            return None;
+        }
+        match phase {
+            ElectionPhase::RegistrationOpen => {
+                let opens_at = self.opens_at.max(now.saturating_add(1));
+                Some((now, opens_at, self.closes_at.max(opens_at.saturating_add(1))))
+            }
+            ElectionPhase::VotingOpen => Some((self.registration_opens_at.min(now), now, self.closes_at.max(now.saturating_add(1)))),
+            ElectionPhase::Closed => {
+                let opens_at = self.opens_at.min(now.saturating_sub(1));
+                Some((self.registration_opens_at.min(opens_at), opens_at, now))
+            }
+            ElectionPhase::Draft | ElectionPhase::Tallied => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_phases() {
+        let mut election = Election {
+            id : "test".to_string(),
+            title : "Test".to_string(),
+            options : vec![],
+            registration_opens_at : 100,
+            opens_at : 200,
+            closes_at : 300,
+            group : StoredGroup::default(),
+            tally : None,
+            trustees : None,
+            partial_tallies : Vec::new(),
+        };
+
+        assert_eq!(election.phase(0), ElectionPhase::Draft);
+        assert_eq!(election.phase(100), ElectionPhase::RegistrationOpen);
+        assert_eq!(election.phase(199), ElectionPhase::RegistrationOpen);
+        assert_eq!(election.phase(200), ElectionPhase::VotingOpen);
+        assert_eq!(election.phase(300), ElectionPhase::Closed);
+        assert_eq!(election.phase(u64::MAX), ElectionPhase::Closed);
+
+        for (now, phase) in [(0, ElectionPhase::RegistrationOpen), (0, ElectionPhase::VotingOpen), (150, ElectionPhase::VotingOpen), (150, ElectionPhase::Closed), (250, ElectionPhase::Closed)] {
+            let (registration_opens_at, opens_at, closes_at) = election.schedule_for_phase(phase, now).unwrap();
+            assert!(Election::schedule_valid(registration_opens_at, opens_at, closes_at), "Invalid schedule for {:?} at {}", phase, now);
+            let moved = Election { registration_opens_at, opens_at, closes_at, ..election.clone() };
+            assert_eq!(moved.phase(now), phase);
+        }
+        assert_eq!(election.schedule_for_phase(ElectionPhase::RegistrationOpen, 0), Some((0, 200, 300)), "Later phases should keep their schedule");
+        assert_eq!(election.schedule_for_phase(ElectionPhase::RegistrationOpen, 150), None, "Election can't move to the phase it is in");
+        assert_eq!(election.schedule_for_phase(ElectionPhase::VotingOpen, 350), None, "Election can't move backwards");
+        assert_eq!(election.schedule_for_phase(ElectionPhase::Tallied, 350), None);
+
+        election.tally = Some(vec![]);
+        assert_eq!(election.phase(0), ElectionPhase::Tallied, "Tallied election should stay tallied");
+
+        assert!(Election::schedule_valid(100, 100, 101));
+        assert!(!Election::schedule_valid(100, 99, 200), "Voting can't open before registration");
+        assert!(!Election::schedule_valid(100, 200, 200), "Voting can't close when it opens");
+    }
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use zkp_protocol::group::{GroupError, GroupParams};
+
+// Group of an election, a key file or a registered key, stored with its numbers so a custom group is checked again when it is read.
+// Files from before the group was stored are in the default group. Older logs only have the name of a built-in group.
+// The --group-file of the server has the same format.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(into = "GroupHex", try_from = "GroupValue")]
+pub struct StoredGroup(pub GroupParams);
+
+#[derive(Serialize, Deserialize)]
+struct GroupHex {
+    name : String,
+    p : String,
+    q : String,
+    alpha : String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GroupValue {
+    Numbers(GroupHex),
+    Name(String),
+}
+
+impl Deref for StoredGroup {
+    type Target = GroupParams;
+
+    fn deref(&self) -> &GroupParams {
+        &self.0
+    }
+}
+
+impl From<StoredGroup> for GroupHex {
+    fn from(group : StoredGroup) -> Self {
+        GroupHex {
+            name : group.name().to_string(),
+            p : hex::encode(group.p().to_bytes_be()),
+            q : hex::encode(group.q().to_bytes_be()),
+            alpha : hex::encode(group.alpha().to_bytes_be()),
+        }
+    }
+}
+
+impl TryFrom<GroupValue> for StoredGroup {
+    type Error = GroupError;
+
+    fn try_from(group : GroupValue) -> Result<Self, Self::Error> {
+        let group = match group {
+            GroupValue::Numbers(group) => {
+                let number = |value : &str, name| hex::decode(value).map(|bytes| BigUint::from_bytes_be(&bytes)).map_err(|_| GroupError::InvalidHex(name));
+                GroupParams::from_numbers(&group.name, &number(&group.p, "p")?, &number(&group.q, "q")?, &number(&group.alpha, "alpha")?)?
+            }
+            GroupValue::Name(name) => GroupParams::by_name(&name)?,
+        };
+        Ok(StoredGroup(group))
+    }
+}
+
+// Custom group of the server (--group-file), a JSON object with the name and the numbers in hex
+pub fn load_group_file(path : impl AsRef<Path>) -> io::Result<GroupParams> {
+    let content = fs::read_to_string(path)?;
+    let group : StoredGroup = serde_json::from_str(&content).map_err(io::Error::from)?;
+    Ok(group.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialization() {
+        for group in [GroupParams::default(), GroupParams::ristretto255()] {
+            let json = serde_json::to_string(&StoredGroup(group.clone())).unwrap();
+            assert_eq!(serde_json::from_str::<StoredGroup>(&json).unwrap().0, group, "Group changed after serialization");
+        }
+
+        let json = serde_json::to_string(&StoredGroup(GroupParams::ristretto255())).unwrap().replace("ristretto255", "ffdhe2048");
+        assert!(serde_json::from_str::<StoredGroup>(&json).is_err(), "Read a built-in group with the numbers of another one");
+
+        // Custom group, checked when it is read
+        let json = "{\"name\":\"custom\",\"p\":\"17\",\"q\":\"0b\",\"alpha\":\"04\"}";
+        let group = serde_json::from_str::<StoredGroup>(json).unwrap();
+        assert_eq!((group.name(), group.p()), ("custom", &BigUint::from(23u32)));
+        assert_eq!(serde_json::to_string(&group).unwrap(), json);
+        assert!(serde_json::from_str::<StoredGroup>(&json.replace("04", "05")).is_err(), "Read a custom group with an invalid generator");
+
+        assert_eq!(serde_json::from_str::<StoredGroup>("\"ristretto255\"").unwrap().0, GroupParams::ristretto255());
+        assert!(serde_json::from_str::<StoredGroup>("\"custom\"").is_err(), "Read a custom group without its numbers");
+    }
+
+    #[test]
+    fn test_group_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("group.json");
+        assert!(load_group_file(&path).is_err(), "Loaded a missing group file");
+
+        fs::write(&path, "{\"name\":\"custom\",\"p\":\"17\",\"q\":\"0b\",\"alpha\":\"04\"}").unwrap();
+        assert_eq!(load_group_file(&path).unwrap().q(), &BigUint::from(11u32));
+        fs::write(&path, "{\"name\":\"custom\",\"p\":\"19\",\"q\":\"0b\",\"alpha\":\"04\"}").unwrap();
+        assert!(load_group_file(&path).is_err(), "Loaded a group with p not prime");
+    }
+}
@@ -0,0 +1,113 @@
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use zkp_protocol::elgamal::{Ciphertext, EncryptionKey};
+use zkp_protocol::mixnet::{self, ShuffleProof};
+
+use crate::model::ballot::EncryptedBallot;
+
+// Ballots re-encrypted and shuffled by the mix-net once the election closes (see zkp_protocol::mixnet),
+// with the proof that they hold the same votes as the cast ballots. Numbers are stored as hex strings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(into = "BallotMixHex", try_from = "BallotMixHex")]
+pub struct BallotMix {
+    pub ballots : Vec<EncryptedBallot>,
+    pub proof : ShuffleProof,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BallotMixHex {
+    ballots : Vec<EncryptedBallot>,
+    commitments : Vec<String>,
+    chain : Vec<String>,
+    t : Vec<String>,
+    t4 : Vec<[String; 2]>,
+    t_hat : Vec<String>,
+    s : Vec<String>,
+    s4 : Vec<String>,
+    s_hat : Vec<String>,
+    s_prime : Vec<String>,
+}
+
+fn to_hex(values : &[BigUint]) -> Vec<String> {
+    values.iter().map(|value| hex::encode(value.to_bytes_be())).collect()
+}
+
+fn from_hex(values : &[String]) -> Result<Vec<BigUint>, String> {
+    values.iter().map(|value| hex::decode(value).map(|bytes| BigUint::from_bytes_be(&bytes)).map_err(|e| e.to_string())).collect()
+}
+
+fn triple(values : &[String]) -> Result<[BigUint; 3], String> {
+    <[BigUint; 3]>::try_from(from_hex(values)?).map_err(|_| "Expected 3 values".to_string())
+}
+
+impl From<BallotMix> for BallotMixHex {
+    fn from(mix : BallotMix) -> Self {
+        let proof = &mix.proof;
+        BallotMixHex {
+            commitments : to_hex(proof.commitments()),
+            chain : to_hex(proof.chain()),
+            t : to_hex(proof.t()),
+            t4 : proof.t4().iter().map(|t4| [hex::encode(t4.c1().to_bytes_be()), hex::encode(t4.c2().to_bytes_be())]).collect(),
+            t_hat : to_hex(proof.t_hat()),
+            s : to_hex(proof.s()),
+            s4 : to_hex(proof.s4()),
+            s_hat : to_hex(proof.s_hat()),
+            s_prime : to_hex(proof.s_prime()),
+            ballots : mix.ballots,
+        }
+    }
+}
+
+impl TryFrom<BallotMixHex> for BallotMix {
+    type Error = String;
+
+    fn try_from(mix : BallotMixHex) -> Result<Self, Self::Error> {
+        let t4 = mix.t4.iter().map(|pair| {
+            let [c1, c2] = <[BigUint; 2]>::try_from(from_hex(pair)?).map_err(|_| "Expected 2 values".to_string())?;
+            Ok(Ciphertext::new(c1, c2))
+        }).collect::<Result<_, Self::Error>>()?;
+        let proof = ShuffleProof::new(from_hex(&mix.commitments)?, from_hex(&mix.chain)?, triple(&mix.t)?, t4, from_hex(&mix.t_hat)?,
+            triple(&mix.s)?, from_hex(&mix.s4)?, from_hex(&mix.s_hat)?, from_hex(&mix.s_prime)?);
+        Ok(BallotMix { ballots : mix.ballots, proof })
+    }
+}
+
+fn ciphertexts(ballots : &[EncryptedBallot]) -> Vec<Vec<Ciphertext>> {
+    ballots.iter().map(|ballot| ballot.options.clone()).collect()
+}
+
+impl BallotMix {
+    // Returns None if the ballots don't all have the same number of options
+    pub fn shuffle(key : &EncryptionKey, ballots : &[EncryptedBallot], context : &[u8]) -> Option<BallotMix> {
+        let (mixed, proof) = mixnet::shuffle(key, &ciphertexts(ballots), context)?;
+        let ballots = mixed.into_iter().map(|options| EncryptedBallot { options, proof : None }).collect();
+        Some(BallotMix { ballots, proof })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use zkp_protocol::zkp_util;
+    use zkp_protocol::elgamal::DecryptionKey;
+    use zkp_protocol::group::GroupParams;
+    use crate::model::ballot;
+
+    #[test]
+    fn test_mix() {
+        let key = DecryptionKey::generate(&GroupParams::default());
+        let context = zkp_util::election_context("test");
+        let ballots : Vec<EncryptedBallot> = [1, 0, 1].iter().map(|vote| EncryptedBallot::encrypt(key.encryption_key(), *vote, 2)).collect();
+
+        let mix = BallotMix::shuffle(key.encryption_key(), &ballots, &context).unwrap();
+        let verify = |ballots : &[EncryptedBallot]| mixnet::verify_shuffle(key.encryption_key(), &ciphertexts(ballots), &ciphertexts(&mix.ballots), &mix.proof, &context);
+        assert!(verify(&ballots));
+        assert_ne!(mix.ballots, ballots, "Ballots were not re-encrypted");
+        assert_eq!(ballot::decrypt_tally(&mix.ballots, 2, &key), Some(vec![1, 2]));
+
+        let json = serde_json::to_string(&mix).unwrap();
+        assert_eq!(serde_json::from_str::<BallotMix>(&json).unwrap(), mix, "Mix changed after serialization");
+
+        assert!(!verify(&ballots[1..]), "Verified with a missing ballot");
+    }
+}
@@ -0,0 +1,14 @@
+use crate::model::ballot::EncryptedBallot;
+
+// Ballot waiting for the user to solve the challenge, expires together with the challenge
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingVote {
+    pub ballot : EncryptedBallot,
+    pub expires_at : u64, // Unix timestamp in seconds
+}
+
+impl PendingVote {
+    pub fn expired(&self, now : u64) -> bool {
+        now >= self.expires_at
+    }
+}
@@ -0,0 +1,184 @@
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use zkp_protocol::Proof;
+use zkp_protocol::elgamal::{Ciphertext, EncryptionKey};
+use zkp_protocol::group::GroupParams;
+use zkp_protocol::threshold::{self, PartialDecryption};
+
+use crate::model::group::StoredGroup;
+
+// Public result of the trustee key ceremony (see zkp_protocol::threshold), in the group of the election.
+// Numbers are stored as hex strings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(into = "TrusteeKeyHex", try_from = "TrusteeKeyHex")]
+pub struct TrusteeKey {
+    pub threshold : u32,
+    pub encryption_key : EncryptionKey,
+    pub verification_keys : Vec<BigUint>, // Key of trustee i is at i - 1
+}
+
+#[derive(Serialize, Deserialize)]
+struct TrusteeKeyHex {
+    #[serde(default)]
+    group : StoredGroup,
+    threshold : u32,
+    encryption_key : String,
+    verification_keys : Vec<String>,
+}
+
+// Decryptions of every option's sum by a single trustee, only verified ones are stored
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(into = "PartialTallyHex", try_from = "PartialTallyHex")]
+pub struct PartialTally {
+    pub trustee : u32,
+    pub decryptions : Vec<PartialDecryption>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PartialTallyHex {
+    trustee : u32,
+    decryptions : Vec<[String; 4]>, // d, ka, kb, solution
+}
+
+fn to_hex(num : &BigUint) -> String {
+    hex::encode(num.to_bytes_be())
+}
+
+fn from_hex(hex_str : &str) -> Result<BigUint, hex::FromHexError> {
+    Ok(BigUint::from_bytes_be(&hex::decode(hex_str)?))
+}
+
+impl From<TrusteeKey> for TrusteeKeyHex {
+    fn from(key : TrusteeKey) -> Self {
+        TrusteeKeyHex {
+            group : StoredGroup(key.encryption_key.group().clone()),
+            threshold : key.threshold,
+            encryption_key : to_hex(key.encryption_key.h()),
+            verification_keys : key.verification_keys.iter().map(to_hex).collect(),
+        }
+    }
+}
+
+impl TryFrom<TrusteeKeyHex> for TrusteeKey {
+    type Error = hex::FromHexError;
+
+    fn try_from(key : TrusteeKeyHex) -> Result<Self, Self::Error> {
+        Ok(TrusteeKey {
+            threshold : key.threshold,
+            encryption_key : EncryptionKey::new(&key.group, from_hex(&key.encryption_key)?),
+            verification_keys : key.verification_keys.iter().map(|key| from_hex(key)).collect::<Result<_, Self::Error>>()?,
+        })
+    }
+}
+
+impl From<PartialTally> for PartialTallyHex {
+    fn from(tally : PartialTally) -> Self {
+        PartialTallyHex {
+            trustee : tally.trustee,
+            decryptions : tally.decryptions.iter().map(|partial| {
+                let proof = partial.proof();
+                [to_hex(partial.d()), to_hex(proof.ka()), to_hex(proof.kb()), to_hex(proof.solution())]
+            }).collect(),
+        }
+    }
+}
+
+impl TryFrom<PartialTallyHex> for PartialTally {
+    type Error = hex::FromHexError;
+
+    fn try_from(tally : PartialTallyHex) -> Result<Self, Self::Error> {
+        let decryptions = tally.decryptions.iter().map(|[d, ka, kb, solution]| {
+            let proof = Proof::new(from_hex(ka)?, from_hex(kb)?, from_hex(solution)?);
+            Ok(PartialDecryption::new(tally.trustee, from_hex(d)?, proof))
+        }).collect::<Result<_, Self::Error>>()?;
+        Ok(PartialTally { trustee : tally.trustee, decryptions })
+    }
+}
+
+impl TrusteeKey {
+    pub fn valid(&self) -> bool {
+        let trustee_count = u32::try_from(self.verification_keys.len()).unwrap_or(u32::MAX);
+        let group = self.encryption_key.group();
+        threshold::valid_parameters(group, self.threshold, trustee_count) &&
+            self.encryption_key.valid() &&
+            self.verification_keys.iter().all(|key| group.contains(key))
+    }
+
+    pub fn verification_key(&self, trustee : u32) -> Option<&BigUint> {
+        self.verification_keys.get(trustee.checked_sub(1)? as usize)
+    }
+}
+
+impl PartialTally {
+    // Every option's sum must be decrypted by the trustee whose index is claimed
+    pub fn verify(&self, key : &TrusteeKey, tally : &[Ciphertext], context : &[u8]) -> bool {
+        let Some(verification_key) = key.verification_key(self.trustee) else {
+            return false;
+        };
+        self.decryptions.len() == tally.len() && self.decryptions.iter().zip(tally).all(|(partial, ciphertext)| {
+            partial.index() == self.trustee && partial.verify(key.encryption_key.group(), verification_key, ciphertext, context)
+        })
+    }
+}
+
+// Combines verified partial tallies, at least threshold of them are needed.
+// Returns None if some sum can't be decrypted.
+pub fn combine_tally(group : &GroupParams, tally : &[Ciphertext], partials : &[PartialTally], max : u32) -> Option<Vec<u32>> {
+    tally.iter().enumerate().map(|(option, ciphertext)| {
+        let decryptions = partials.iter().map(|partial| partial.decryptions.get(option).cloned()).collect::<Option<Vec<_>>>()?;
+        threshold::combine(group, ciphertext, &decryptions, max)
+    }).collect()
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use zkp_protocol::zkp_util;
+    use zkp_protocol::threshold::{Dealing, KeyShare};
+
+    // Key of a 2 of 3 ceremony and the key shares of the trustees
+    pub fn test_trustees(group : &GroupParams) -> (TrusteeKey, Vec<KeyShare>) {
+        let dealings : Vec<Dealing> = (0..3).map(|_| Dealing::generate(group, 2, 3).unwrap()).collect();
+        let commitments : Vec<Vec<BigUint>> = dealings.iter().map(|dealing| dealing.commitments().to_vec()).collect();
+        let key_shares = (1..=3).map(|index| {
+            let shares : Vec<BigUint> = dealings.iter().map(|dealing| dealing.shares()[index as usize - 1].clone()).collect();
+            KeyShare::combine(group, index, &shares)
+        }).collect();
+        let key = TrusteeKey {
+            threshold : 2,
+            encryption_key : threshold::election_key(group, &commitments).unwrap(),
+            verification_keys : (1..=3).map(|index| threshold::verification_key(group, &commitments, index).unwrap()).collect(),
+        };
+        (key, key_shares)
+    }
+
+    #[test]
+    fn test_combine_tally() {
+        let (key, key_shares) = test_trustees(&GroupParams::ristretto255());
+        assert!(key.valid());
+        let context = zkp_util::election_context("test");
+
+        let tally = vec![key.encryption_key.encrypt(2), key.encryption_key.encrypt(0)];
+        let partials : Vec<PartialTally> = key_shares.iter().map(|key_share| PartialTally {
+            trustee : key_share.index(),
+            decryptions : tally.iter().map(|ciphertext| key_share.partial_decrypt(ciphertext, &context).unwrap()).collect(),
+        }).collect();
+        assert!(partials.iter().all(|partial| partial.verify(&key, &tally, &context)));
+        assert_eq!(combine_tally(key.encryption_key.group(), &tally, &partials[1..], 2), Some(vec![2, 0]));
+
+        let json = serde_json::to_string(&partials[0]).unwrap();
+        assert_eq!(serde_json::from_str::<PartialTally>(&json).unwrap(), partials[0], "Partial tally changed after serialization");
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(serde_json::from_str::<TrusteeKey>(&json).unwrap(), key, "Trustee key changed after serialization");
+
+        let claimed = PartialTally { trustee : 3, decryptions : partials[0].decryptions.clone() };
+        assert!(!claimed.verify(&key, &tally, &context), "Verified decryptions of a different trustee");
+        let unknown = PartialTally { trustee : 4, decryptions : partials[0].decryptions.clone() };
+        assert!(!unknown.verify(&key, &tally, &context), "Verified decryptions of an unknown trustee");
+        assert!(!partials[0].verify(&key, &tally[..1], &context), "Verified with a missing option");
+
+        let mut invalid = key.clone();
+        invalid.threshold = 4;
+        assert!(!invalid.valid(), "Threshold bigger than trustee count");
+    }
+}
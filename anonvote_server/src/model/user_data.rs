@@ -1,39 +1,71 @@
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
+use std::fmt;
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
 use zkp_protocol::PublicKey;
 
-#[derive(Hash, Clone)]
+// Voter identifier, SHA-256 of the canonical encoding of the voter's key.
+// Users, pending votes and votes are keyed by it and the voted sets are persisted, so it has to stay the same across builds.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VoterId(pub [u8; 32]);
+
+impl fmt::Debug for VoterId {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VoterId({})", hex::encode(self.0))
+    }
+}
+
+// Hex in the ballot file, like the other numbers that are written to disk
+impl Serialize for VoterId {
+    fn serialize<S : Serializer>(&self, serializer : S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for VoterId {
+    fn deserialize<D : Deserializer<'de>>(deserializer : D) -> Result<VoterId, D::Error> {
+        let hex_str = String::deserialize(deserializer)?;
+        let mut id = [0u8; 32];
+        hex::decode_to_slice(&hex_str, &mut id).map_err(serde::de::Error::custom)?;
+        Ok(VoterId(id))
+    }
+}
+
+#[derive(Clone)]
 pub struct UserData {
     pub key : PublicKey,
 }
 
 impl UserData {
-    pub fn get_hash(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.hash(&mut hasher);
-        hasher.finish()
+    pub fn id(&self) -> VoterId {
+        let mut hasher = Sha256::new();
+        hasher.update(b"anonvote-voter");
+        hasher.update(self.key.encode());
+        VoterId(hasher.finalize().into())
     }
 }
 
 #[cfg(test)]
 mod test {
     use num_bigint::BigUint;
+    use zkp_protocol::group::GroupParams;
 
     use super::*;
 
     #[test]
-    fn test_hash() {
-
-        let data1 = PublicKey::new(BigUint::from(1u32), BigUint::from(2u32), BigUint::from(3u32),BigUint::from(4u32)); 
-        let data2 = PublicKey::new(BigUint::from(1u32), BigUint::from(2u32), BigUint::from(3u32),BigUint::from(4u32)); 
+    fn test_id() {
+        let key = |a : u32| PublicKey::new(&GroupParams::default(), BigUint::from(a), BigUint::from(2u32), BigUint::from(3u32), BigUint::from(4u32));
 
-        let data1 = UserData{ key: data1};
-        let data2 = UserData{ key: data2};
+        let data1 = UserData { key : key(1) };
+        let data2 = UserData { key : key(1) };
+        assert_eq!(data1.id(), data2.id(), "Ids should be the same for equal UserData instances.");
+        assert_ne!(data1.id(), UserData { key : key(5) }.id());
 
-        let hash1 = data1.get_hash();
-        let hash2 = data2.get_hash();
+        // Pinned, the id must not change between builds since voted sets are persisted
+        assert_eq!(hex::encode(data1.id().0), "d97ab0055df3998fa42e0b5a0dd5bc41a0b1d6910979d8d23a80f6ed7252c343");
 
-        assert_eq!(hash1, hash2, "Hashes should be the same for equal UserData instances.");
+        let json = serde_json::to_string(&data1.id()).unwrap();
+        assert_eq!(serde_json::from_str::<VoterId>(&json).unwrap(), data1.id());
+        assert!(serde_json::from_str::<VoterId>("\"1234\"").is_err(), "Short id was accepted");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,141 @@
+use anonvote_verifier::record::{self, BulletinBoardRecord, CheckpointRecord, DecryptionRecord, ElectionRecord, EntryRecord, GroupRecord,
+    HexBytes, MixRecord, OptionRecord, ShuffleProofRecord, TrusteesRecord, RECORD_VERSION};
+use zkp_protocol::zkp_util;
+use zkp_protocol::board::{BoardKey, BoardRecord};
+use zkp_protocol::elgamal::{DecryptionKey, EncryptionKey};
+use zkp_protocol::threshold::KeyShare;
+
+use crate::db::ElectionArchive;
+use crate::model::ballot::{self, EncryptedBallot};
+use crate::model::board::BulletinBoard;
+use crate::model::candidate::Candidate;
+use crate::model::election::Election;
+use crate::model::group::StoredGroup;
+use crate::model::mix::BallotMix;
+use crate::model::trustees::{PartialTally, TrusteeKey};
+
+// Conversion between the stored elections and the election records of anonvote_verifier (see "Election records" in the README)
+
+fn ballots_record(ballots : &[EncryptedBallot]) -> Vec<Vec<[HexBytes; 2]>> {
+    ballots.iter().map(|ballot| record::ciphertexts_to_record(&ballot.options)).collect()
+}
+
+fn ballots_from_record(ballots : &[Vec<[HexBytes; 2]>]) -> Vec<EncryptedBallot> {
+    ballots.iter().map(|ballot| EncryptedBallot { options : record::ciphertexts_from_record(ballot), proof : None }).collect()
+}
+
+// Record of a tallied election, the board is hashed again and its checkpoint signed with the board key.
+// Without trustees the server decrypts the tally again with a proof, as trustee 1 of 1 with the server key.
+// Returns None if the election is not tallied yet.
+pub fn election_record(archive : &ElectionArchive, decryption_key : &DecryptionKey, board_key : &BoardKey) -> Option<ElectionRecord> {
+    let election = &archive.election;
+    let tally = election.tally.clone()?;
+    let mix = archive.mix.as_ref()?;
+    let context = zkp_util::election_context(&election.id);
+
+    let mut board = BulletinBoard::default();
+    for record in &archive.board {
+        board.append(&election.id, record.clone());
+    }
+    let view = board.view(0);
+    let signature = board_key.sign_checkpoint(&election.id, view.size, &view.head);
+
+    let (encryption_key, trustees, decryptions) = match &election.trustees {
+        Some(trustees) => {
+            let trustees_record = TrusteesRecord {
+                threshold : trustees.threshold,
+                verification_keys : trustees.verification_keys.iter().map(HexBytes::from).collect(),
+            };
+            let decryptions = election.partial_tallies.iter().map(|partial| DecryptionRecord::new(partial.trustee, &partial.decryptions)).collect();
+            (trustees.encryption_key.h(), Some(trustees_record), decryptions)
+        }
+        None => {
+            let key_share = KeyShare::new(decryption_key.group(), 1, decryption_key.secret().clone());
+            let sums = ballot::encrypted_tally(decryption_key.group(), &mix.ballots, election.options.len())?;
+            let partials = sums.iter().map(|sum| key_share.partial_decrypt(sum, &context)).collect::<Option<Vec<_>>>()?;
+            (decryption_key.encryption_key().h(), None, vec![DecryptionRecord::new(1, &partials)])
+        }
+    };
+
+    Some(ElectionRecord {
+        version : RECORD_VERSION,
+        election_id : election.id.clone(),
+        title : election.title.clone(),
+        group : GroupRecord::new(&election.group),
+        options : election.options.iter().map(|option| OptionRecord {
+            id : option.id.clone(),
+            name : option.name.clone(),
+            description : option.description.clone(),
+        }).collect(),
+        encryption_key : encryption_key.into(),
+        trustees,
+        board : BulletinBoardRecord {
+            public_key : board_key.public_key().into(),
+            entries : view.entries.iter().map(|entry| EntryRecord::new(&entry.record, &entry.hash)).collect(),
+            checkpoint : CheckpointRecord::new(view.size, &view.head, &signature),
+        },
+        mix : MixRecord {
+            ballots : ballots_record(&archive.ballots),
+            mixed_ballots : ballots_record(&mix.ballots),
+            proof : ShuffleProofRecord::new(&mix.proof),
+        },
+        decryptions,
+        tally,
+    })
+}
+
+// Election as it is stored from a record, the record should be verified first.
+// The key of an election without trustees is kept as a 1 of 1 trustee key, it is not the key of this server.
+pub fn election_archive(record : &ElectionRecord) -> Result<ElectionArchive, String> {
+    let group = record.group.parse()?;
+    let board : Vec<BoardRecord> = record.board.entries.iter().map(|entry| entry.content.parse(&group)).collect();
+    let schedule = board.iter().rev().find_map(|record| match record {
+        BoardRecord::Schedule { registration_opens_at, opens_at, closes_at } => Some((*registration_opens_at, *opens_at, *closes_at)),
+        _ => None,
+    });
+    let (registration_opens_at, opens_at, closes_at) = schedule.ok_or("Board has no schedule")?;
+
+    let encryption_key = EncryptionKey::new(&group, record.encryption_key.number());
+    let trustees = match &record.trustees {
+        Some(trustees) => TrusteeKey {
+            threshold : trustees.threshold,
+            encryption_key,
+            verification_keys : trustees.verification_keys.iter().map(HexBytes::number).collect(),
+        },
+        None => TrusteeKey {
+            threshold : 1,
+            verification_keys : vec![encryption_key.h().clone()],
+            encryption_key,
+        },
+    };
+
+    let election = Election {
+        id : record.election_id.clone(),
+        title : record.title.clone(),
+        options : record.options.iter().map(|option| Candidate {
+            id : option.id.clone(),
+            name : option.name.clone(),
+            description : option.description.clone(),
+        }).collect(),
+        registration_opens_at,
+        opens_at,
+        closes_at,
+        group : StoredGroup(group),
+        tally : Some(record.tally.clone()),
+        trustees : Some(trustees),
+        partial_tallies : record.decryptions.iter().map(|decryption| PartialTally {
+            trustee : decryption.trustee,
+            decryptions : decryption.parse(),
+        }).collect(),
+    };
+    let mix = BallotMix {
+        ballots : ballots_from_record(&record.mix.mixed_ballots),
+        proof : record.mix.proof.parse()?,
+    };
+    Ok(ElectionArchive {
+        election,
+        board,
+        ballots : ballots_from_record(&record.mix.ballots),
+        mix : Some(mix),
+    })
+}
@@ -1,114 +1,423 @@
-use crate::db::AnonVoteDB;
-use crate::model::user_data::UserData;
+use crate::db::VoteStore;
+use crate::model::user_data::{UserData, VoterId};
 use crate::model::challenge_data::ChallengeData;
+use crate::model::pending_vote::PendingVote;
+use crate::model::election::{self, Election, ElectionPhase};
+use crate::model::candidate::Candidate;
+use crate::model::ballot::{self, EncryptedBallot};
+use crate::model::trustees::{self, PartialTally};
+use crate::model::mix::BallotMix;
+use crate::model::board::BoardEntry;
+use crate::identity::{IdHasher, IdentityProvider};
 
 use anonvote_proto::proto::anonvote::anon_vote_server::AnonVote;
-use anonvote_proto::proto::anonvote::{ValidateIdReq, ValidateIdRes, RegisterReq, RegisterRes, VoteReq, VoteRes, ValidateVoteReq, ValidateVoteRes, GetVoteOptionsReq, GetVoteOptionsRes, GetResultsReq, GetResultsRes};
+use anonvote_proto::proto::anonvote::{ValidateIdReq, ValidateIdRes, RegisterReq, RegisterRes, VoteReq, VoteRes, ValidateVoteReq, ValidateVoteRes, CastVoteReq, CastVoteRes, GetVoteOptionsReq, GetVoteOptionsRes, GetResultsReq, GetResultsRes, VoteOption, GetElectionsReq, GetElectionsRes, GetAuthorityKeyReq, GetAuthorityKeyRes, GetParametersReq, GetParametersRes, GetEncryptedTallyReq, GetEncryptedTallyRes, SubmitPartialDecryptionReq, SubmitPartialDecryptionRes, GetMixedBallotsReq, GetMixedBallotsRes, GetBulletinBoardReq, BulletinBoardItem, GetInclusionProofReq, GetInclusionProofRes, BallotReceipt};
+use anonvote_proto::proto::anonvote::{Election as ElectionMsg, ElectionPhase as ElectionPhaseMsg, Ciphertext as CiphertextMsg, BitProof as BitProofMsg, SumProof as SumProofMsg, EncryptedBallot as EncryptedBallotMsg, ShuffleProof as ShuffleProofMsg};
+use anonvote_proto::proto::anonvote::{BoardEntry as BoardEntryMsg, BoardSchedule, BoardRegistration, BoardTally, BoardCheckpoint, board_entry, bulletin_board_item};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use num_bigint::BigUint;
 use tonic::{Request, Response, Status, Code };
 use rand::distr::{Alphanumeric, SampleString};
-use zkp_protocol::{zkp_constants, zkp_util, PublicKey};
+use zkp_protocol::{zkp_util, Proof};
+use zkp_protocol::params::KeyParams;
+use zkp_protocol::blind_signature::{self, SignerSecretKey};
+use zkp_protocol::elgamal::{Ciphertext, DecryptionKey, EncryptionKey};
+use zkp_protocol::ballot_proof::{BallotProof, BitProof, SumProof};
+use zkp_protocol::threshold::PartialDecryption;
+use zkp_protocol::board::{BoardKey, BoardRecord, Hash};
+use zkp_protocol::merkle;
 
 const REGISTRATION_KEY_LEN : usize = 16;
 const AUTH_KEY_LEN : usize = 16;
+pub const SESSION_TTL_SEC : u64 = 300; // Time to solve the challenge after voting
+
+// The client only gets a generic message, the operator sees the cause (a failed log write needs a restart)
+pub fn storage_error(err : std::io::Error) -> Status {
+    eprintln!("Storage error: {}", err);
+    Status::new(Code::Internal, "Internal storage error, please try again!")
+}
+
+pub fn vote_option_msg(candidate : &Candidate) -> VoteOption {
+    VoteOption {
+        id : candidate.id.clone(),
+        name : candidate.name.clone(),
+        description : candidate.description.clone(),
+    }
+}
+
+// Ballots are encrypted with the key of the trustees if the election has them, otherwise with the server key
+pub fn election_key<'a>(election : &'a Election, server_key : &'a EncryptionKey) -> &'a EncryptionKey {
+    election.trustees.as_ref().map(|trustees| &trustees.encryption_key).unwrap_or(server_key)
+}
+
+pub fn election_msg(election : &Election, server_key : &EncryptionKey) -> ElectionMsg {
+    ElectionMsg {
+        id : election.id.clone(),
+        title : election.title.clone(),
+        options : election.options.iter().map(vote_option_msg).collect(),
+        opens_at : election.opens_at,
+        closes_at : election.closes_at,
+        registration_opens_at : election.registration_opens_at,
+        phase : election_phase_msg(election.current_phase()) as i32,
+        encryption_key : election_key(election, server_key).h().to_bytes_be(),
+        trustee_threshold : election.trustees.as_ref().map(|trustees| trustees.threshold).unwrap_or(0),
+    }
+}
+
+fn ciphertext_msg(ciphertext : &Ciphertext) -> CiphertextMsg {
+    CiphertextMsg {
+        c1 : ciphertext.c1().to_bytes_be(),
+        c2 : ciphertext.c2().to_bytes_be(),
+    }
+}
+
+fn ballot_msg(ballot : &EncryptedBallot) -> EncryptedBallotMsg {
+    EncryptedBallotMsg {
+        options : ballot.options.iter().map(ciphertext_msg).collect(),
+        option_proofs : ballot.proof.as_ref().map(bit_proof_msgs).unwrap_or_default(),
+        sum_proof : ballot.proof.as_ref().map(sum_proof_msg),
+    }
+}
+
+fn shuffle_proof_msg(mix : &BallotMix) -> ShuffleProofMsg {
+    let bytes = |values : &[BigUint]| values.iter().map(|value| value.to_bytes_be()).collect();
+    let proof = &mix.proof;
+    ShuffleProofMsg {
+        commitments : bytes(proof.commitments()),
+        chain : bytes(proof.chain()),
+        t : bytes(proof.t()),
+        t4 : proof.t4().iter().map(ciphertext_msg).collect(),
+        t_hat : bytes(proof.t_hat()),
+        s : bytes(proof.s()),
+        s4 : bytes(proof.s4()),
+        s_hat : bytes(proof.s_hat()),
+        s_prime : bytes(proof.s_prime()),
+    }
+}
+
+fn board_entry_msg(index : u64, entry : &BoardEntry) -> BoardEntryMsg {
+    let record = match &entry.record {
+        BoardRecord::Schedule { registration_opens_at, opens_at, closes_at } => board_entry::Record::Schedule(BoardSchedule {
+            registration_opens_at : *registration_opens_at,
+            opens_at : *opens_at,
+            closes_at : *closes_at,
+        }),
+        BoardRecord::Registration { voter } => board_entry::Record::Registration(BoardRegistration {
+            a : voter.a().to_bytes_be(),
+            b : voter.b().to_bytes_be(),
+            alpha : voter.alpha().to_bytes_be(),
+            beta : voter.beta().to_bytes_be(),
+        }),
+        BoardRecord::Ballot { ballot, proof } => board_entry::Record::Ballot(EncryptedBallotMsg {
+            options : ballot.iter().map(ciphertext_msg).collect(),
+            option_proofs : proof.as_ref().map(bit_proof_msgs).unwrap_or_default(),
+            sum_proof : proof.as_ref().map(sum_proof_msg),
+        }),
+        BoardRecord::Tally { votes } => board_entry::Record::Tally(BoardTally {
+            votes : votes.clone(),
+        }),
+    };
+    BoardEntryMsg {
+        index,
+        hash : entry.hash.to_vec(),
+        record : Some(record),
+    }
+}
+
+fn bit_proof_msgs(ballot_proof : &BallotProof) -> Vec<BitProofMsg> {
+    ballot_proof.options().iter().map(|proof| BitProofMsg {
+        a0 : proof.a0().to_bytes_be(),
+        b0 : proof.b0().to_bytes_be(),
+        a1 : proof.a1().to_bytes_be(),
+        b1 : proof.b1().to_bytes_be(),
+        e0 : proof.e0().to_bytes_be(),
+        e1 : proof.e1().to_bytes_be(),
+        z0 : proof.z0().to_bytes_be(),
+        z1 : proof.z1().to_bytes_be(),
+    }).collect()
+}
+
+fn sum_proof_msg(ballot_proof : &BallotProof) -> SumProofMsg {
+    SumProofMsg {
+        a : ballot_proof.sum().a().to_bytes_be(),
+        b : ballot_proof.sum().b().to_bytes_be(),
+        z : ballot_proof.sum().z().to_bytes_be(),
+    }
+}
+
+fn bit_proof(proof : &BitProofMsg) -> BitProof {
+    let [a0, b0, a1, b1, e0, e1, z0, z1] = [&proof.a0, &proof.b0, &proof.a1, &proof.b1, &proof.e0, &proof.e1, &proof.z0, &proof.z1].map(|bytes| BigUint::from_bytes_be(bytes));
+    BitProof::new(a0, b0, a1, b1, e0, e1, z0, z1)
+}
+
+fn sum_proof(proof : &SumProofMsg) -> SumProof {
+    SumProof::new(BigUint::from_bytes_be(&proof.a), BigUint::from_bytes_be(&proof.b), BigUint::from_bytes_be(&proof.z))
+}
+
+pub fn election_phase_msg(phase : ElectionPhase) -> ElectionPhaseMsg {
+    match phase {
+        ElectionPhase::Draft => ElectionPhaseMsg::Draft,
+        ElectionPhase::RegistrationOpen => ElectionPhaseMsg::RegistrationOpen,
+        ElectionPhase::VotingOpen => ElectionPhaseMsg::VotingOpen,
+        ElectionPhase::Closed => ElectionPhaseMsg::Closed,
+        ElectionPhase::Tallied => ElectionPhaseMsg::Tallied,
+    }
+}
+
+// Ballots are re-encrypted and shuffled by the mix-net the first time they are needed after closing,
+// the mix is stored so everyone verifies and decrypts the same one.
+#[allow(clippy::result_large_err)]
+pub fn mix_ballots<S : VoteStore>(db : &S, election : &Election, server_key : &EncryptionKey) -> Result<BallotMix, Status> {
+    if election.current_phase() < ElectionPhase::Closed {
+        return Err(Status::new(Code::FailedPrecondition, "Ballots are mixed once the election is closed!"));
+    }
+    let context = zkp_util::election_context(&election.id);
+    loop {
+        if let Some(mix) = db.get_mix(&election.id) {
+            return Ok(mix);
+        }
+        let ballots = db.get_ballots(&election.id).ok_or(Status::new(Code::NotFound, "Election not found!"))?;
+        let mix = BallotMix::shuffle(election_key(election, server_key), &ballots, &context);
+        let mix = mix.ok_or(Status::new(Code::Internal, "Could not mix the ballots!"))?;
+        // Not stored if someone else mixed them first or a late vote came in, try again
+        if db.set_mix(&election.id, mix.clone()).map_err(storage_error)? {
+            return Ok(mix);
+        }
+    }
+}
+
+// Final results of the election, the tally is computed and stored the first time they are requested after closing.
+// Results are withheld while the election is running, so partial results don't influence voters.
+// Only the sum of all mixed ballots is decrypted, never a single ballot.
+// Elections with trustees are tallied once enough of them sent their partial decryptions.
+#[allow(clippy::result_large_err)]
+pub fn tally_election<S : VoteStore>(db : &S, election : Election, decryption_key : &DecryptionKey) -> Result<Vec<u32>, Status> {
+    match election.current_phase() {
+        ElectionPhase::Tallied => Ok(election.tally.unwrap_or_default()),
+        ElectionPhase::Closed => {
+            let ballots = mix_ballots(db, &election, decryption_key.encryption_key())?.ballots;
+            let votes = match &election.trustees {
+                Some(trustees) => {
+                    if (election.partial_tallies.len() as u32) < trustees.threshold {
+                        return Err(Status::new(Code::FailedPrecondition, "Waiting for the trustees to decrypt the tally!"));
+                    }
+                    let max = u32::try_from(ballots.len()).unwrap_or(u32::MAX);
+                    let group = trustees.encryption_key.group();
+                    let tally = ballot::encrypted_tally(group, &ballots, election.options.len());
+                    tally.and_then(|tally| trustees::combine_tally(group, &tally, &election.partial_tallies, max))
+                }
+                None => ballot::decrypt_tally(&ballots, election.options.len(), decryption_key),
+            };
+            let votes = votes.ok_or(Status::new(Code::Internal, "Could not decrypt the tally!"))?;
+            let tallied = db.set_tally(&election.id, votes.clone()).map_err(storage_error)?;
+            if tallied {
+                Ok(votes)
+            } else {
+                // Someone else tallied the election in the meantime, use the stored tally
+                let election = db.get_election(&election.id).ok_or(Status::new(Code::NotFound, "Election not found!"))?;
+                Ok(election.tally.unwrap_or_default())
+            }
+        }
+        _ => Err(Status::new(Code::FailedPrecondition, "Results are not available until the election is closed!")),
+    }
+}
 
-pub struct AnonVoteImpl {
-    db : AnonVoteDB, 
-    vote_option_count : u32 // Valid votes are {1, 2, ... , vote_option_count}
+pub struct AnonVoteImpl<S : VoteStore> {
+    db : Arc<S>, // Shared with the admin service
+    session_ttl : u64,
+    authority_key : SignerSecretKey,
+    decryption_key : DecryptionKey,
+    board_key : BoardKey,
+    identity : Box<dyn IdentityProvider>, // Decides who is eligible to register
+    id_hasher : IdHasher, // Registered identities are only stored as hashes
+    key_params : Mutex<HashMap<String, KeyParams>>, // Beta of every election is derived once
 }
 
-impl AnonVoteImpl {
-    pub fn new(db : AnonVoteDB, vote_option_count : u32) -> AnonVoteImpl {
+impl<S : VoteStore> AnonVoteImpl<S> {
+    pub fn new(db : Arc<S>, session_ttl : u64, authority_key : SignerSecretKey, decryption_key : DecryptionKey, board_key : BoardKey, identity : Box<dyn IdentityProvider>, id_hasher : IdHasher) -> AnonVoteImpl<S> {
         AnonVoteImpl {
             db,
-            vote_option_count
+            session_ttl,
+            authority_key,
+            decryption_key,
+            board_key,
+            identity,
+            id_hasher,
+            key_params : Mutex::new(HashMap::new())
         }
     }
 }
 
-impl AnonVoteImpl {
+impl<S : VoteStore> AnonVoteImpl<S> {
     fn generate_random_string(len : usize) -> String {
         let rnd = &mut rand::rng();
         Alphanumeric.sample_string(rnd, len)
     }
-    
-    fn validate_id(_id : &String) -> bool {
-        // This is where the we will call goverment apis to validate identity of the person
-        // Since this is simulation we will assume rule:
-        // 1. Valid id is exactly 5 chars in length
-        _id.len() == 5
+
+    // Voter keys are in the group of the election
+    fn key_params(&self, election : &Election) -> KeyParams {
+        let mut key_params = self.key_params.lock().unwrap();
+        key_params.entry(election.id.clone()).or_insert_with(|| KeyParams::for_election(&election.group, &election.id)).clone()
     }
 
-    fn validate_user_data(user : &UserData) -> bool {
+    // Voters only send a and b, alpha and beta are the parameters of the election
+    fn user_data(&self, election : &Election, a : &[u8], b : &[u8]) -> UserData {
+        let key = self.key_params(election).public_key(BigUint::from_bytes_be(a), BigUint::from_bytes_be(b));
+        UserData { key }
+    }
 
-        // All numbers must be non-zero values
-        if *user.key.a() == BigUint::ZERO || *user.key.b() == BigUint::ZERO || 
-        *user.key.alpha() == BigUint::ZERO || *user.key.beta() == BigUint::ZERO {
-            return false;
-        }
+    fn validate_user_data(&self, election : &Election, user : &UserData) -> bool {
+        // Same rules the election verifier checks for every registered key
+        self.key_params(election).valid_key(&user.key)
+    }
 
-        // All numbers cannot be trivial (one)
-        if *user.key.a() == *zkp_constants::one() || *user.key.b() == *zkp_constants::one() || 
-        *user.key.alpha() == *zkp_constants::one() || *user.key.beta() == *zkp_constants::one() {
-            return false;
-        }
+    #[allow(clippy::result_large_err)] // Status is what tonic handlers return anyway
+    fn get_election(&self, election_id : &str) -> Result<Election, Status> {
+        self.db.get_election(election_id).ok_or(Status::new(Code::NotFound, "Election not found!"))
+    }
 
-        // Alpha and beta must be different
-        if *user.key.alpha() == *user.key.beta() {
-            return false;
+    #[allow(clippy::result_large_err)]
+    fn require_phase(election : &Election, phase : ElectionPhase) -> Result<(), Status> {
+        if election.current_phase() == phase {
+            return Ok(());
         }
+        let message = match phase {
+            ElectionPhase::RegistrationOpen => "Registration is not open for this election!",
+            ElectionPhase::VotingOpen => "Voting is not open for this election!",
+            ElectionPhase::Closed => "Election is not waiting for the tally!",
+            _ => "Election is not in the right phase for this request!",
+        };
+        Err(Status::new(Code::FailedPrecondition, message))
+    }
+
+    // Receipt against the current root of the ballot tree, with the path from the ballot to the root
+    #[allow(clippy::result_large_err)]
+    fn receipt(&self, election_id : &str, ballot_hash : &Hash) -> Result<(BallotReceipt, Vec<Hash>), Status> {
+        let proof = self.db.get_inclusion_proof(election_id, ballot_hash).ok_or(Status::new(Code::NotFound, "Ballot not found!"))?;
+        let signature = merkle::sign_root(&self.board_key, election_id, proof.tree_size, &proof.root);
+        let receipt = BallotReceipt {
+            ballot_hash : ballot_hash.to_vec(),
+            leaf_index : proof.leaf_index,
+            tree_size : proof.tree_size,
+            root : proof.root.to_vec(),
+            public_key : self.board_key.public_key().to_bytes_be(),
+            ka : signature.ka().to_bytes_be(),
+            kb : signature.kb().to_bytes_be(),
+            solution : signature.solution().to_bytes_be(),
+        };
+        Ok((receipt, proof.path))
+    }
 
-        // All numbers must be less than p
-        if *user.key.a() >= *zkp_constants::p() || *user.key.b() >= *zkp_constants::p() || 
-        *user.key.alpha() >= *zkp_constants::p() || *user.key.beta() >= *zkp_constants::p() {
-            return false;
+    // Valid votes are {0, 1, ... , option_count - 1}
+    // Ballot needs a ciphertext in the group for every option
+    #[allow(clippy::result_large_err)]
+    fn parse_ballot(&self, election : &Election, ballot : &[CiphertextMsg]) -> Result<EncryptedBallot, Status> {
+        let group = election_key(election, self.decryption_key.encryption_key()).group();
+        let options : Vec<Ciphertext> = ballot.iter().map(|ciphertext| Ciphertext::from_bytes_be(&ciphertext.c1, &ciphertext.c2)).collect();
+        if options.len() != election.options.len() || !options.iter().all(|ciphertext| ciphertext.valid(group)) {
+            return Err(Status::new(Code::InvalidArgument, "Invalid ballot!"));
         }
+        Ok(EncryptedBallot { options, proof : None })
+    }
 
-        // Alpha and beta must be generators of order of q
-        // If x is generator of order q => x^q mod p = 1
-        if *zkp_constants::one() != user.key.alpha().modpow(&zkp_constants::q(), &zkp_constants::p()) {
-            return false;
+    // Server can't check the range of an encrypted vote, so the ballot comes with proofs that
+    // every option encrypts 0 or 1 and that exactly one option is chosen. The proofs are kept with the ballot
+    // and published on the board, so a ballot that was already cast is refused.
+    #[allow(clippy::result_large_err)]
+    fn verify_ballot(&self, election : &Election, ballot : &mut EncryptedBallot, option_proofs : &[BitProofMsg], sum_proof : Option<&SumProofMsg>) -> Result<(), Status> {
+        let option_proofs = option_proofs.iter().map(bit_proof).collect();
+        let sum_proof = sum_proof.map(self::sum_proof).ok_or(Status::new(Code::InvalidArgument, "Missing ballot proof!"))?;
+        let proof = BallotProof::new(option_proofs, sum_proof);
+        let context = zkp_util::validity_context(&election.id);
+        if !election_key(election, self.decryption_key.encryption_key()).verify_ballot(&ballot.options, &proof, &context) {
+            return Err(Status::new(Code::InvalidArgument, "Invalid ballot proof!"));
         }
-        if *zkp_constants::one() != user.key.beta().modpow(&zkp_constants::q(), &zkp_constants::p()) {
-            return false;
+        if self.db.get_inclusion_proof(&election.id, &merkle::ballot_hash(&election.id, &ballot.options)).is_some() {
+            return Err(Status::new(Code::InvalidArgument, "Ballot was already cast!"));
         }
-        
-        return true;
+        ballot.proof = Some(proof);
+        Ok(())
     }
 
-    fn vote_valid(&self, vote : &u32) -> bool {
-        *vote < self.vote_option_count
+    // Sum of all mixed ballots for every option
+    #[allow(clippy::result_large_err)]
+    fn encrypted_tally(&self, election : &Election) -> Result<Vec<Ciphertext>, Status> {
+        let ballots = mix_ballots(self.db.as_ref(), election, self.decryption_key.encryption_key())?.ballots;
+        let group = election_key(election, self.decryption_key.encryption_key()).group();
+        ballot::encrypted_tally(group, &ballots, election.options.len()).ok_or(Status::new(Code::Internal, "Could not compute the tally!"))
+    }
+
+    // Checks that the user is allowed to vote, returns the user hash
+    #[allow(clippy::result_large_err)]
+    fn check_voter(&self, election : &Election, user_data : &UserData) -> Result<VoterId, Status> {
+        let voter_id = user_data.id();
+
+        // Check if user data is valid
+        if !self.validate_user_data(election, user_data) {
+            return Err(Status::new(Code::InvalidArgument, "Invalid user data!"));
+        }
+        
+        // Check if user is registered
+        if !self.db.user_registered(&election.id, voter_id) {
+            return Err(Status::new(Code::InvalidArgument, "User not registered!"));
+        }
+
+        // Check if user has already voted
+        if self.db.user_voted(&election.id, voter_id) {
+            return Err(Status::new(Code::AlreadyExists, "User already voted!"));
+        }
+        Ok(voter_id)
     }
 }
 
 #[tonic::async_trait]
-impl AnonVote for AnonVoteImpl {
+impl<S : VoteStore> AnonVote for AnonVoteImpl<S> {
     async fn validate_id(&self, req : Request<ValidateIdReq>) -> Result<Response<ValidateIdRes>, Status> {
         let req = req.into_inner();
-
-        // Check id validity
-        let valid_id = AnonVoteImpl::validate_id(&req.id);
-        if !valid_id {
-            return Err(Status::new(Code::InvalidArgument, "User identification failed!"));
+        let election = self.get_election(&req.election_id)?;
+        Self::require_phase(&election, ElectionPhase::RegistrationOpen)?;
+
+        // Check id validity, the voter is registered under the identity the provider returns
+        let identity = self.identity.validate(&req.election_id, &req.id);
+        let identity = identity.ok_or(Status::new(Code::InvalidArgument, "User identification failed!"))?;
+
+        // Signed with the key of this election, the credential can't register in any other
+        let authority_key = self.authority_key.for_election(&req.election_id);
+        let blinded_credential = BigUint::from_bytes_be(&req.blinded_credential);
+        if blinded_credential >= *authority_key.public_key().n() {
+            return Err(Status::new(Code::InvalidArgument, "Invalid blinded credential!"));
         }
 
-        // Add ID to the registered list, while also checking if the id is already registered
-        let added = self.db.add_registered_id(req.id);
+        // Add ID to the registered list, while also checking if the id is already registered.
+        // Only the hash is stored, the plain identity is dropped with the request.
+        let added = self.db.add_registered_id(&req.election_id, self.id_hasher.hash(&identity)).map_err(storage_error)?;
         if !added {
             return Err(Status::new(Code::AlreadyExists, "This ID already generated code!"));
         }
 
+        // Blind signature of the voter's credential, the server never sees which key it signed.
+        // Voters should register some time after this, so the timing doesn't link the ID with the key either.
+        if !req.blinded_credential.is_empty() {
+            let blind_signature = authority_key.sign_blinded(&blinded_credential);
+            return Ok(Response::new(ValidateIdRes {
+                registration_key : String::new(),
+                blind_signature : blind_signature.to_bytes_be()
+            }));
+        }
+
         // Generate registration key
         let mut registration_key : String;
         loop { // We are looping just in case that generated registration_key already exists
-            registration_key = AnonVoteImpl::generate_random_string(REGISTRATION_KEY_LEN);
-            let added = self.db.add_registration_code(registration_key.clone());
+            registration_key = Self::generate_random_string(REGISTRATION_KEY_LEN);
+            let added = self.db.add_registration_code(&req.election_id, registration_key.clone()).map_err(storage_error)?;
             if added {
                 break;
             }
         }
-        Ok(Response::new(ValidateIdRes{registration_key}))
+        Ok(Response::new(ValidateIdRes{registration_key, blind_signature : Vec::new()}))
     }
 
     async fn register(&self, req : Request<RegisterReq>) -> Result<Response<RegisterRes>, Status> {
@@ -116,21 +425,37 @@ impl AnonVote for AnonVoteImpl {
         // Since we are removing and re-adding the code in some cases
 
         let req = req.into_inner();
-        let public_key = PublicKey::from_bytes_be(&req.a, &req.b, &req.alpha, &req.beta);
-        let user_data = UserData { key : public_key };
-        let user_data_valid = AnonVoteImpl::validate_user_data(&user_data);
+        let election = self.get_election(&req.election_id)?;
+        Self::require_phase(&election, ElectionPhase::RegistrationOpen)?;
+
+        let user_data = self.user_data(&election, &req.a, &req.b);
+        let user_data_valid = self.validate_user_data(&election, &user_data);
         if !user_data_valid {
             return Err(Status::new(Code::InvalidArgument, "Invalid user data!"));
         }
 
-        let valid_key = self.db.use_registration_code(&req.registration_key);
+        if !req.credential_signature.is_empty() {
+            // Signature is bound to this key and election, the same signature can only register the same key again
+            let signature = BigUint::from_bytes_be(&req.credential_signature);
+            let credential = blind_signature::credential(&req.election_id, &user_data.key);
+            if !self.authority_key.public_key().for_election(&req.election_id).verify(&credential, &signature) {
+                return Err(Status::new(Code::InvalidArgument, "Invalid credential signature!"));
+            }
+            let succ = self.db.try_register_user(&req.election_id, user_data).map_err(storage_error)?;
+            if !succ {
+                return Err(Status::new(Code::AlreadyExists, "User with this public key already exists!"));
+            }
+            return Ok(Response::new(RegisterRes{}));
+        }
+
+        let valid_key = self.db.use_registration_code(&req.election_id, &req.registration_key).map_err(storage_error)?;
         if !valid_key {
             return Err(Status::new(Code::InvalidArgument, "Invalid registration key!"));
         }
 
-        let succ = self.db.try_register_user(user_data);
+        let succ = self.db.try_register_user(&req.election_id, user_data).map_err(storage_error)?;
         if !succ {
-            self.db.add_registration_code(req.registration_key); // Add back registartion code since it was not used
+            self.db.add_registration_code(&req.election_id, req.registration_key).map_err(storage_error)?; // Add back registartion code since it was not used
             return Err(Status::new(Code::AlreadyExists, "User with this public key already exists! Please try again."));
         }
         Ok(Response::new(RegisterRes{}))
@@ -138,51 +463,42 @@ impl AnonVote for AnonVoteImpl {
 
     async fn vote(&self, req : Request<VoteReq>) -> Result<Response<VoteRes>, Status> {
         let req= req.into_inner();
-
-        // Get user hash
-        let public_key = PublicKey::from_bytes_be(&req.a, &req.b, &req.alpha, &req.beta);
-        let user_data = UserData { key : public_key };
-        let user_hash = user_data.get_hash();
-
-        // Check if user data is valid
-        if !AnonVoteImpl::validate_user_data(&user_data) {
-            return Err(Status::new(Code::InvalidArgument, "Invalid user data!"));
-        }
-
-        // Check if vote is valid
-        if !self.vote_valid(&req.vote) {
-            return Err(Status::new(Code::InvalidArgument, "Invalid vote!"));
-        }
-        
-        // Check if user is registered
-        if !self.db.user_registered(user_hash) {
-            return Err(Status::new(Code::InvalidArgument, "User not registered!"));
-        }
-
-        // Check if user has already voted
-        if self.db.user_voted(user_hash) {
-            return Err(Status::new(Code::AlreadyExists, "User already voted!"));
+        let election = self.get_election(&req.election_id)?;
+        Self::require_phase(&election, ElectionPhase::VotingOpen)?;
+
+        let user_data = self.user_data(&election, &req.a, &req.b);
+        let mut ballot = self.parse_ballot(&election, &req.ballot)?;
+        let voter_id = self.check_voter(&election, &user_data)?;
+        self.verify_ballot(&election, &mut ballot, &req.option_proofs, req.sum_proof.as_ref())?;
+
+        // Pending vote of an expired session doesn't block the user, even if the sweeper didn't remove it yet
+        let now = election::now();
+        let expired = self.db.get_pending_vote(&req.election_id, voter_id).is_some_and(|pending_vote| pending_vote.expired(now));
+        if expired {
+            self.db.get_and_remove_pending_vote(&req.election_id, voter_id).map_err(storage_error)?;
         }
 
         // Try to ddd vote to pending votes
-        let vote_added = self.db.add_pending_vote(user_hash, req.vote);
+        let expires_at = now.saturating_add(self.session_ttl);
+        let vote_added = self.db.add_pending_vote(&req.election_id, voter_id, PendingVote { ballot, expires_at }).map_err(storage_error)?;
         if !vote_added {
             return Err(Status::new(Code::AlreadyExists, "This user aleady has pending vote!"));
         }
 
         // Generate challenge
-        let c = zkp_util::generate_challenge();
+        let c = self.key_params(&election).group().random_exponent();
         let c_bytes = c.to_bytes_be();
         let challenge = ChallengeData {
-            user_hash,
+            voter_id,
             ka : BigUint::from_bytes_be(&req.ka),
             kb : BigUint::from_bytes_be(&req.kb),
-            challenge : c
+            challenge : c,
+            expires_at
         };
 
         // Generate session_id
-        let session_id = AnonVoteImpl::generate_random_string(AUTH_KEY_LEN);
-        let added = self.db.add_challenge(&session_id.clone(), challenge);
+        let session_id = Self::generate_random_string(AUTH_KEY_LEN);
+        let added = self.db.add_challenge(&req.election_id, &session_id.clone(), challenge).map_err(storage_error)?;
 
         // Edge case - if there is already same session id in the db give internal error to try again
         // TODO: Handle case where we generated same session id 
@@ -198,19 +514,21 @@ impl AnonVote for AnonVoteImpl {
 
     async fn validate_vote(&self, req : Request<ValidateVoteReq>) -> Result<Response<ValidateVoteRes>, Status> {
         let req = req.into_inner();
-        let challenge_data = self.db.get_challenge(&req.auth_session_id);
+        let election = self.get_election(&req.election_id)?;
+        Self::require_phase(&election, ElectionPhase::VotingOpen)?;
+
+        let challenge_data = self.db.get_challenge(&req.election_id, &req.auth_session_id);
         let challenge_data = challenge_data.ok_or(Status::new(Code::InvalidArgument, "Invalid session id!"))?;
-        
-        let pending_vote = self.db.get_pending_vote(challenge_data.user_hash);
-        let pending_vote = pending_vote.ok_or(Status::new(Code::InvalidArgument, "The pending vote linked with this session no longer exists!"))?; 
-        
-        // TODO: Check if the vote in request is even needed, maybe we want to hide the initial vote from the validation part
-        if pending_vote != req.vote {
-            return Err(Status::new(Code::InvalidArgument, "The pending vote does not match the vote provided!"));
+        if challenge_data.expired(election::now()) {
+            return Err(Status::new(Code::FailedPrecondition, "Session expired, please vote again!"));
+        }
+
+        if self.db.get_pending_vote(&req.election_id, challenge_data.voter_id).is_none() {
+            return Err(Status::new(Code::InvalidArgument, "The pending vote linked with this session no longer exists!"));
         }
 
         // TODO: Delete the challenge from db if user_data doesn't exist
-        let user_data = self.db.get_user(challenge_data.user_hash);
+        let user_data = self.db.get_user(&req.election_id, challenge_data.voter_id);
         let user_data = user_data.ok_or(Status::new(Code::InvalidArgument, "The user linked with this session no longer exists."))?;
         let solution = BigUint::from_bytes_be(&req.solution);
 
@@ -219,137 +537,451 @@ impl AnonVote for AnonVoteImpl {
             return Err(Status::new(Code::InvalidArgument, "The solution provided is not verified!"));
         }
 
-        let removed = self.db.remove_challenge(&req.auth_session_id);
+        let removed = self.db.remove_challenge(&req.election_id, &req.auth_session_id).map_err(storage_error)?;
         if !removed {
             // We did find the challenge at the beggining, someone else also tried to verify the challenge
             // at the same time. We will mark this as internal error for now.
             return Err(Status::new(Code::Internal, "Internal error E0001!"));
         }
 
-        let pending_vote = self.db.get_and_remove_pending_vote(challenge_data.user_hash);
+        let pending_vote = self.db.get_and_remove_pending_vote(&req.election_id, challenge_data.voter_id).map_err(storage_error)?;
 
         // Similar error as E0001
         let pending_vote = pending_vote.ok_or(Status::new(Code::Internal, "Internal error E0002!"))?;
 
-        let added = self.db.add_vote(challenge_data.user_hash, pending_vote);
+        // Ballot was encrypted by the voter and its proofs checked with the vote
+        let ballot = pending_vote.ballot;
+        let ballot_hash = merkle::ballot_hash(&req.election_id, &ballot.options);
+        let added = self.db.add_vote(&req.election_id, challenge_data.voter_id, ballot).map_err(storage_error)?;
         if !added {
             // Similar error as E0001
             return Err(Status::new(Code::Internal, "Internal error E0003!"));
         }
 
-        Ok(Response::new(ValidateVoteRes { }))
+        let (receipt, _) = self.receipt(&req.election_id, &ballot_hash)?;
+        Ok(Response::new(ValidateVoteRes {
+            receipt : Some(receipt)
+        }))
     }
 
-    async fn get_vote_options(&self, _req : Request<GetVoteOptionsReq>) -> Result<Response<GetVoteOptionsRes>, Status> {
-        let options = self.db.get_vote_options();
+    async fn cast_vote(&self, req : Request<CastVoteReq>) -> Result<Response<CastVoteRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+        Self::require_phase(&election, ElectionPhase::VotingOpen)?;
+
+        let user_data = self.user_data(&election, &req.a, &req.b);
+        let mut ballot = self.parse_ballot(&election, &req.ballot)?;
+        let voter_id = self.check_voter(&election, &user_data)?;
+
+        // Proof is bound to the election and the ballot, so it can't be used for anything else
+        let proof = Proof::from_bytes_be(&req.ka, &req.kb, &req.solution);
+        let context = zkp_util::ballot_context(&req.election_id, &ballot.options);
+        if !user_data.key.verify_proof(&proof, &context) {
+            return Err(Status::new(Code::InvalidArgument, "The proof provided is not verified!"));
+        }
+        self.verify_ballot(&election, &mut ballot, &req.option_proofs, req.sum_proof.as_ref())?;
+
+        let ballot_hash = merkle::ballot_hash(&req.election_id, &ballot.options);
+        let added = self.db.add_vote(&req.election_id, voter_id, ballot).map_err(storage_error)?;
+        if !added {
+            // Someone voted with the same key in the meantime
+            return Err(Status::new(Code::AlreadyExists, "User already voted!"));
+        }
+
+        let (receipt, _) = self.receipt(&req.election_id, &ballot_hash)?;
+        Ok(Response::new(CastVoteRes {
+            receipt : Some(receipt)
+        }))
+    }
+
+    async fn get_vote_options(&self, req : Request<GetVoteOptionsReq>) -> Result<Response<GetVoteOptionsRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+        let options = election.options.iter().map(|candidate| candidate.name.clone()).collect();
+        let option_details = election.options.iter().map(vote_option_msg).collect();
         Ok(Response::new(GetVoteOptionsRes {
-            options
+            options,
+            option_details
         }))
     }
 
-    async fn get_results(&self, _req : Request<GetResultsReq>) -> Result<Response<GetResultsRes>, Status> {
-        let votes= self.db.get_vote_results();
+    async fn get_results(&self, req : Request<GetResultsReq>) -> Result<Response<GetResultsRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+        let votes = tally_election(self.db.as_ref(), election, &self.decryption_key)?;
+
         Ok(Response::new(GetResultsRes {
             votes
         }))
     }
+
+    async fn get_authority_key(&self, req : Request<GetAuthorityKeyReq>) -> Result<Response<GetAuthorityKeyRes>, Status> {
+        let req = req.into_inner();
+        self.get_election(&req.election_id)?;
+        let public_key = self.authority_key.public_key().for_election(&req.election_id);
+        Ok(Response::new(GetAuthorityKeyRes {
+            n : public_key.n().to_bytes_be(),
+            e : public_key.e().to_bytes_be()
+        }))
+    }
+
+    async fn get_parameters(&self, req : Request<GetParametersReq>) -> Result<Response<GetParametersRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+        let key_params = self.key_params(&election);
+        Ok(Response::new(GetParametersRes {
+            group : key_params.group().name().to_string(),
+            p : key_params.group().p().to_bytes_be(),
+            q : key_params.group().q().to_bytes_be(),
+            alpha : key_params.alpha().to_bytes_be(),
+            beta : key_params.beta().to_bytes_be()
+        }))
+    }
+
+    async fn get_encrypted_tally(&self, req : Request<GetEncryptedTallyReq>) -> Result<Response<GetEncryptedTallyRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+        let trustees = election.trustees.as_ref().ok_or(Status::new(Code::FailedPrecondition, "Election has no trustees!"))?;
+        if election.current_phase() < ElectionPhase::Closed {
+            return Err(Status::new(Code::FailedPrecondition, "Results are not available until the election is closed!"));
+        }
+
+        let tally = self.encrypted_tally(&election)?;
+        Ok(Response::new(GetEncryptedTallyRes {
+            tally : tally.iter().map(ciphertext_msg).collect(),
+            threshold : trustees.threshold,
+            verification_keys : trustees.verification_keys.iter().map(|key| key.to_bytes_be()).collect(),
+        }))
+    }
+
+    async fn submit_partial_decryption(&self, req : Request<SubmitPartialDecryptionReq>) -> Result<Response<SubmitPartialDecryptionRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+        let trustees = election.trustees.as_ref().ok_or(Status::new(Code::FailedPrecondition, "Election has no trustees!"))?;
+        Self::require_phase(&election, ElectionPhase::Closed)?;
+
+        // Decryptions are bound to the election and checked against the verification key of the trustee
+        let decryptions = req.decryptions.iter().map(|partial| {
+            let proof = Proof::from_bytes_be(&partial.ka, &partial.kb, &partial.solution);
+            PartialDecryption::new(req.trustee, BigUint::from_bytes_be(&partial.d), proof)
+        }).collect();
+        let partial_tally = PartialTally { trustee : req.trustee, decryptions };
+        let tally = self.encrypted_tally(&election)?;
+        if !partial_tally.verify(trustees, &tally, &zkp_util::election_context(&election.id)) {
+            return Err(Status::new(Code::InvalidArgument, "Invalid partial decryption!"));
+        }
+
+        let added = self.db.add_partial_tally(&req.election_id, partial_tally).map_err(storage_error)?;
+        if !added {
+            return Err(Status::new(Code::AlreadyExists, "Trustee already decrypted the tally!"));
+        }
+
+        // Results are computed as soon as enough trustees decrypted the tally
+        let election = self.get_election(&req.election_id)?;
+        let remaining = trustees.threshold.saturating_sub(election.partial_tallies.len() as u32);
+        if remaining == 0 {
+            tally_election(self.db.as_ref(), election, &self.decryption_key)?;
+        }
+        Ok(Response::new(SubmitPartialDecryptionRes {
+            remaining
+        }))
+    }
+
+    async fn get_mixed_ballots(&self, req : Request<GetMixedBallotsReq>) -> Result<Response<GetMixedBallotsRes>, Status> {
+        let req = req.into_inner();
+        let election = self.get_election(&req.election_id)?;
+        let mix = mix_ballots(self.db.as_ref(), &election, self.decryption_key.encryption_key())?;
+        let ballots = self.db.get_ballots(&election.id).ok_or(Status::new(Code::NotFound, "Election not found!"))?;
+
+        Ok(Response::new(GetMixedBallotsRes {
+            ballots : ballots.iter().map(ballot_msg).collect(),
+            mixed_ballots : mix.ballots.iter().map(ballot_msg).collect(),
+            proof : Some(shuffle_proof_msg(&mix)),
+            encryption_key : election_key(&election, self.decryption_key.encryption_key()).h().to_bytes_be(),
+        }))
+    }
+
+    type GetBulletinBoardStream = tokio_stream::Iter<std::vec::IntoIter<Result<BulletinBoardItem, Status>>>;
+
+    // Sends the entries from the requested index on and a checkpoint signed over the whole board
+    async fn get_bulletin_board(&self, req : Request<GetBulletinBoardReq>) -> Result<Response<Self::GetBulletinBoardStream>, Status> {
+        let req = req.into_inner();
+        let view = self.db.get_board(&req.election_id, req.from_index).ok_or(Status::new(Code::NotFound, "Election not found!"))?;
+        let signature = self.board_key.sign_checkpoint(&req.election_id, view.size, &view.head);
+
+        let entries = (view.first_index..).zip(&view.entries).map(|(index, entry)| bulletin_board_item::Item::Entry(board_entry_msg(index, entry)));
+        let checkpoint = bulletin_board_item::Item::Checkpoint(BoardCheckpoint {
+            size : view.size,
+            head : view.head.to_vec(),
+            public_key : self.board_key.public_key().to_bytes_be(),
+            ka : signature.ka().to_bytes_be(),
+            kb : signature.kb().to_bytes_be(),
+            solution : signature.solution().to_bytes_be(),
+        });
+        let items : Vec<Result<BulletinBoardItem, Status>> = entries.chain([checkpoint]).map(|item| BulletinBoardItem { item : Some(item) }).map(Ok).collect();
+        Ok(Response::new(tokio_stream::iter(items)))
+    }
+
+    // Proof against the current root, so a voter can check later that the ballot from their receipt is still there
+    async fn get_inclusion_proof(&self, req : Request<GetInclusionProofReq>) -> Result<Response<GetInclusionProofRes>, Status> {
+        let req = req.into_inner();
+        let ballot_hash = Hash::try_from(req.ballot_hash.as_slice()).map_err(|_| Status::new(Code::InvalidArgument, "Invalid ballot hash!"))?;
+        self.get_election(&req.election_id)?;
+
+        let (receipt, path) = self.receipt(&req.election_id, &ballot_hash)?;
+        Ok(Response::new(GetInclusionProofRes {
+            receipt : Some(receipt),
+            path : path.iter().map(|hash| hash.to_vec()).collect(),
+        }))
+    }
+
+    async fn get_elections(&self, _req : Request<GetElectionsReq>) -> Result<Response<GetElectionsRes>, Status> {
+        let elections = self.db.get_elections().iter().map(|election| election_msg(election, self.decryption_key.encryption_key())).collect();
+        Ok(Response::new(GetElectionsRes {
+            elections
+        }))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use zkp_protocol::SecretKey;
+    use crate::db::{AnonVoteDB, MemoryDB};
+    use crate::identity::{DemoRule, IdHasher, TokenIssuer};
+    use crate::model::group::StoredGroup;
+    use zkp_protocol::{group, PublicKey, SecretKey};
+    use zkp_protocol::group::GroupParams;
+    use zkp_protocol::blind_signature::SignerPublicKey;
+    use zkp_protocol::threshold::KeyShare;
+    use zkp_protocol::mixnet;
+    use zkp_protocol::scalar::SecretScalar;
+    use zkp_protocol::board::{self, EMPTY_HASH};
+    use tokio_stream::StreamExt;
+    use anonvote_proto::proto::anonvote::PartialDecryption as PartialDecryptionMsg;
     use num_bigint::BigUint;
+    use std::path::Path;
+    use std::sync::OnceLock;
+    use tempfile::TempDir;
+
+    // Every test is run against every storage backend
+    trait TestBackend : VoteStore + Sized {
+        fn open(dir : &Path) -> Self;
+    }
 
-    #[tokio::test]
-    async fn test_validate_id() {
-        let db = AnonVoteDB::connect();
-        let server_impl = AnonVoteImpl::new(db, 3);
+    impl TestBackend for MemoryDB {
+        fn open(_dir : &Path) -> Self {
+            MemoryDB::default()
+        }
+    }
+
+    impl TestBackend for AnonVoteDB {
+        fn open(dir : &Path) -> Self {
+            AnonVoteDB::connect(dir.join("anonvote.wal")).unwrap()
+        }
+    }
+
+    macro_rules! backend_tests {
+        ($($test:ident),* $(,)?) => {
+            mod memory_db {
+                $(
+                    #[tokio::test]
+                    async fn $test() {
+                        super::$test::<crate::db::MemoryDB>().await;
+                    }
+                )*
+            }
+
+            mod anonvote_db {
+                $(
+                    #[tokio::test]
+                    async fn $test() {
+                        super::$test::<crate::db::AnonVoteDB>().await;
+                    }
+                )*
+            }
+        };
+    }
+
+    backend_tests!(test_validate_id, test_register, test_vote, test_verify, test_vote_options, test_elections, test_lifecycle, test_session_expiry, test_cast_vote, test_blind_registration, test_trustee_tally, test_mixed_ballots, test_bulletin_board, test_ballot_receipts, test_validate_token, test_parameters, test_key_group);
+
+    const TEST_ELECTION : &str = "test";
+
+    fn test_election(id : &str, candidates : Vec<Candidate>) -> Election {
+        Election {
+            id : id.to_string(),
+            title : format!("Election {}", id),
+            options : candidates,
+            registration_opens_at : 0,
+            opens_at : u64::MAX - 1,
+            closes_at : u64::MAX,
+            tally : None,
+            trustees : None,
+            partial_tallies : Vec::new(),
+            group : StoredGroup::default(),
+        }
+    }
+
+    // Moves the election to the given phase by rescheduling it
+    fn set_phase<S : VoteStore>(server_impl : &AnonVoteImpl<S>, election_id : &str, phase : ElectionPhase) {
+        let (registration_opens_at, opens_at, closes_at) = match phase {
+            ElectionPhase::Draft => (u64::MAX - 2, u64::MAX - 1, u64::MAX),
+            ElectionPhase::RegistrationOpen => (0, u64::MAX - 1, u64::MAX),
+            ElectionPhase::VotingOpen => (0, 0, u64::MAX),
+            ElectionPhase::Closed => (0, 0, 1),
+            ElectionPhase::Tallied => panic!("Election can only be tallied by getting the results"),
+        };
+        assert!(server_impl.db.set_schedule(election_id, registration_opens_at, opens_at, closes_at).unwrap());
+    }
+
+    // Generating a key for every test would be slow, all tests share one small key
+    fn test_authority_key() -> SignerSecretKey {
+        static KEY : OnceLock<SignerSecretKey> = OnceLock::new();
+        KEY.get_or_init(|| SignerSecretKey::generate(512)).clone()
+    }
+
+    // TempDir has to outlive the server since persistent backends keep their files there
+    fn create_server<S : TestBackend>() -> (TempDir, AnonVoteImpl<S>) {
+        create_server_in(&GroupParams::default())
+    }
+
+    // Server whose keys and test election are in the given group
+    fn create_server_in<S : TestBackend>(group : &GroupParams) -> (TempDir, AnonVoteImpl<S>) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = S::open(dir.path());
+        let candidates = crate::candidates::parse_plain_text("Alice\nBob\nCarol");
+        db.create_election(Election { group : StoredGroup(group.clone()), ..test_election(TEST_ELECTION, candidates) }).unwrap();
+        let server_impl = AnonVoteImpl::new(Arc::new(db), SESSION_TTL_SEC, test_authority_key(), DecryptionKey::generate(group), BoardKey::generate(group), Box::new(DemoRule), IdHasher::generate());
+        (dir, server_impl)
+    }
+
+    async fn test_validate_id<S : TestBackend>() {
+        let (dir, server_impl) = create_server::<S>();
 
         let validate_req = Request::new(ValidateIdReq {
-            id : String::from("123456")
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("123456"),
+            blinded_credential : vec!()
         });
         let validate_res = server_impl.validate_id(validate_req).await;
         assert!(validate_res.is_err(), "Did not throw error while passing invalid ID");
 
         let validate_req = Request::new(ValidateIdReq {
-            id : String::from("12345")
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("12345"),
+            blinded_credential : vec!()
         });
         let validate_res = server_impl.validate_id(validate_req).await;
         assert!(validate_res.is_ok(), "Did throw error while passing valid ID");
 
         let validate_req = Request::new(ValidateIdReq {
-            id : String::from("12345")
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("12345"),
+            blinded_credential : vec!()
         });
         let validate_res = server_impl.validate_id(validate_req).await;
         assert!(validate_res.is_err(), "Did not throw error while passing same ID twice");
+
+        // Only the hash of the id is stored, in memory and on disk
+        let added = server_impl.db.add_registered_id(TEST_ELECTION, server_impl.id_hasher.hash("12345")).unwrap();
+        assert!(!added, "Registered id was not stored as a hash");
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                continue;
+            }
+            let content = std::fs::read(path).unwrap();
+            assert!(!content.windows(7).any(|w| w == b"\"12345\""), "Plain id was written to disk");
+        }
+    }
+
+    async fn test_validate_token<S : TestBackend>() {
+        let (_dir, mut server_impl) = create_server::<S>();
+        let issuer = TokenIssuer::new(b"secret");
+        server_impl.identity = Box::new(TokenIssuer::new(b"secret"));
+        let validate_req = |id : String| Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id,
+            blinded_credential : vec!()
+        });
+
+        let res = server_impl.validate_id(validate_req("12345".to_string())).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted an id that is not a token");
+        let res = server_impl.validate_id(validate_req(issuer.issue(TEST_ELECTION, "alice", u64::MAX))).await;
+        assert!(res.is_ok(), "Did throw error while passing valid token. Error: {:?}", res);
+
+        // Voter is registered under the subject, not the token
+        let res = server_impl.validate_id(validate_req(issuer.issue(TEST_ELECTION, "alice", u64::MAX - 1))).await;
+        assert_eq!(res.unwrap_err().code(), Code::AlreadyExists, "Same voter registered twice with a second token");
+        assert_eq!(server_impl.db.get_stats(TEST_ELECTION).unwrap().registered_ids, 1);
     }
 
-    #[tokio::test]
-    async fn test_register() {
-        let db = AnonVoteDB::connect();
-        let server_impl = AnonVoteImpl::new(db, 3);
+    async fn test_register<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
 
         let validate_req = Request::new(ValidateIdReq {
-            id : String::from("12345")
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("12345"),
+            blinded_credential : vec!()
         });
         let validate_res = server_impl.validate_id(validate_req).await;
         assert!(validate_res.is_ok(), "Did throw error while passing valid ID");
 
         let registration_key = validate_res.unwrap().into_inner().registration_key;
 
-        let secret_key = SecretKey::new(BigUint::from(123456789u32));
-        let public_key = secret_key.generate_public_key();
+        let secret_key = SecretKey::from_bytes_be(&GroupParams::default(), &123456789u32.to_be_bytes());
+        let public_key = secret_key.public_key(&KeyParams::for_election(&GroupParams::default(), TEST_ELECTION));
 
-        let secret_key2 = SecretKey::new(BigUint::from(742524531u32));
-        let public_key2 = secret_key2.generate_public_key();
+        let secret_key2 = SecretKey::from_bytes_be(&GroupParams::default(), &742524531u32.to_be_bytes());
+        let public_key2 = secret_key2.public_key(&KeyParams::for_election(&GroupParams::default(), TEST_ELECTION));
 
         let register_req = Request::new(RegisterReq {
+            election_id : TEST_ELECTION.to_string(),
             registration_key : registration_key.clone(),
             a : public_key.a().to_bytes_be(),
-            b : public_key.b().to_bytes_be(),
-            alpha : public_key.alpha().to_bytes_be(),
-            beta : vec!()
+            b : vec!(),
+            credential_signature : vec!()
         });
 
         let register_res = server_impl.register(register_req).await;
         assert!(register_res.is_err(), "Did not throw error while passing invalid public key");
 
         let register_req = Request::new(RegisterReq {
+            election_id : TEST_ELECTION.to_string(),
             registration_key : String::from("asdsafsafad"),
             a : public_key.a().to_bytes_be(),
             b : public_key.b().to_bytes_be(),
-            alpha : public_key.alpha().to_bytes_be(),
-            beta : public_key.beta().to_bytes_be(),
+            credential_signature : vec!()
         });
 
         let register_res = server_impl.register(register_req).await;
         assert!(register_res.is_err(), "Did not throw error while passing invalid registration key");
 
         let register_req = Request::new(RegisterReq {
+            election_id : TEST_ELECTION.to_string(),
             registration_key : registration_key.clone(),
             a : public_key.a().to_bytes_be(),
             b : public_key.b().to_bytes_be(),
-            alpha : public_key.alpha().to_bytes_be(),
-            beta : public_key.beta().to_bytes_be(),
+            credential_signature : vec!()
         });
 
         let register_res = server_impl.register(register_req).await;
         assert!(register_res.is_ok(), "Did throw error while passing valid registration");
 
         let register_req = Request::new(RegisterReq {
+            election_id : TEST_ELECTION.to_string(),
             registration_key : registration_key.clone(),
             a : public_key2.a().to_bytes_be(),
             b : public_key2.b().to_bytes_be(),
-            alpha : public_key2.alpha().to_bytes_be(),
-            beta : public_key2.beta().to_bytes_be(),
+            credential_signature : vec!()
         });
 
         let register_res = server_impl.register(register_req).await;
         assert!(register_res.is_err(), "Did not throw error while trying to register twice");
 
         let validate_req = Request::new(ValidateIdReq {
-            id : String::from("64532")
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("64532"),
+            blinded_credential : vec!()
         });
         let validate_res = server_impl.validate_id(validate_req).await;
         assert!(validate_res.is_ok(), "Did throw error while passing valid ID");
@@ -357,28 +989,180 @@ mod test {
         let registration_key = validate_res.unwrap().into_inner().registration_key;
 
         let register_req = Request::new(RegisterReq {
+            election_id : TEST_ELECTION.to_string(),
             registration_key : registration_key.clone(),
             a : public_key.a().to_bytes_be(),
             b : public_key.b().to_bytes_be(),
-            alpha : public_key.alpha().to_bytes_be(),
-            beta : public_key.beta().to_bytes_be(),
+            credential_signature : vec!()
         });
 
         let register_res = server_impl.register(register_req).await;
         assert!(register_res.is_err(), "Did not throw error while trying to register with same public key twice");
     }
 
+    async fn test_blind_registration<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+
+        let res = server_impl.get_authority_key(Request::new(GetAuthorityKeyReq { election_id : "missing".to_string() })).await;
+        assert_eq!(res.unwrap_err().code(), Code::NotFound, "Got authority key of an election that doesn't exist");
+        let res = server_impl.get_authority_key(Request::new(GetAuthorityKeyReq { election_id : TEST_ELECTION.to_string() })).await.unwrap().into_inner();
+        let authority_key = SignerPublicKey::from_bytes_be(&res.n, &res.e);
+        assert_eq!(authority_key.e(), &blind_signature::election_exponent(TEST_ELECTION), "Authority key is not the key of the election");
+
+        let public_key = SecretKey::from_bytes_be(&GroupParams::default(), &123456789u32.to_be_bytes()).public_key(&KeyParams::for_election(&GroupParams::default(), TEST_ELECTION));
+        let public_key2 = SecretKey::from_bytes_be(&GroupParams::default(), &742524531u32.to_be_bytes()).public_key(&KeyParams::for_election(&GroupParams::default(), TEST_ELECTION));
+        let credential = blind_signature::credential(TEST_ELECTION, &public_key);
+        let (blinded, r) = authority_key.blind(&credential);
+
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("12345"),
+            blinded_credential : authority_key.n().to_bytes_be()
+        });
+        let validate_res = server_impl.validate_id(validate_req).await;
+        assert_eq!(validate_res.unwrap_err().code(), Code::InvalidArgument, "Did not throw error while passing blinded credential out of range");
+
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("12345"),
+            blinded_credential : blinded.to_bytes_be()
+        });
+        let validate_res = server_impl.validate_id(validate_req).await.unwrap().into_inner();
+        assert!(validate_res.registration_key.is_empty(), "Registration key was generated for blind registration");
+        let signature = authority_key.unblind(&BigUint::from_bytes_be(&validate_res.blind_signature), &r).unwrap();
+        assert!(authority_key.verify(&credential, &signature), "Unblinded signature is not valid");
+
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("12345"),
+            blinded_credential : blinded.to_bytes_be()
+        });
+        let validate_res = server_impl.validate_id(validate_req).await;
+        assert_eq!(validate_res.unwrap_err().code(), Code::AlreadyExists, "Did not throw error while signing for same ID twice");
+
+        let register_req = |key : &PublicKey| Request::new(RegisterReq {
+            election_id : TEST_ELECTION.to_string(),
+            registration_key : String::new(),
+            a : key.a().to_bytes_be(),
+            b : key.b().to_bytes_be(),
+            credential_signature : signature.to_bytes_be()
+        });
+
+        let register_res = server_impl.register(register_req(&public_key2)).await;
+        assert_eq!(register_res.unwrap_err().code(), Code::InvalidArgument, "Did not throw error while passing signature of another key");
+
+        let register_res = server_impl.register(register_req(&public_key)).await;
+        assert!(register_res.is_ok(), "Did throw error while passing valid credential signature");
+
+        let register_res = server_impl.register(register_req(&public_key)).await;
+        assert_eq!(register_res.unwrap_err().code(), Code::AlreadyExists, "Did not throw error while reusing credential signature");
+
+        // Credential of another election blinded by a voter of this one
+        let other_election = "other";
+        server_impl.db.create_election(test_election(other_election, crate::candidates::parse_plain_text("Alice\nBob"))).unwrap();
+        let other_key = SecretKey::from_bytes_be(&GroupParams::default(), &98765u32.to_be_bytes()).public_key(&KeyParams::for_election(&GroupParams::default(), other_election));
+        let credential = blind_signature::credential(other_election, &other_key);
+        let (blinded, r) = authority_key.blind(&credential);
+        let validate_res = server_impl.validate_id(Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("23456"),
+            blinded_credential : blinded.to_bytes_be()
+        })).await.unwrap().into_inner();
+        let signature = authority_key.unblind(&BigUint::from_bytes_be(&validate_res.blind_signature), &r).unwrap();
+        let register_res = server_impl.register(Request::new(RegisterReq {
+            election_id : other_election.to_string(),
+            registration_key : String::new(),
+            a : other_key.a().to_bytes_be(),
+            b : other_key.b().to_bytes_be(),
+            credential_signature : signature.to_bytes_be()
+        })).await;
+        assert_eq!(register_res.unwrap_err().code(), Code::InvalidArgument, "Registered with a credential signed for another election");
+    }
+
+    async fn test_parameters<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+
+        let res = server_impl.get_parameters(Request::new(GetParametersReq { election_id : TEST_ELECTION.to_string() })).await.unwrap().into_inner();
+        let key_params = KeyParams::from_bytes_be(&GroupParams::default(), &res.alpha, &res.beta);
+        assert!(key_params.valid(), "Published parameters are not valid");
+        assert_eq!(key_params, KeyParams::for_election(&GroupParams::default(), TEST_ELECTION));
+        assert_eq!((res.group.as_str(), BigUint::from_bytes_be(&res.p)), (group::DEFAULT_GROUP, group::default_group().p().clone()));
+
+        let res = server_impl.get_parameters(Request::new(GetParametersReq { election_id : "missing".to_string() })).await;
+        assert_eq!(res.unwrap_err().code(), Code::NotFound);
+
+        // a and b must be in the subgroup, not only below p
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id : String::from("12345"),
+            blinded_credential : vec!()
+        });
+        let registration_key = server_impl.validate_id(validate_req).await.unwrap().into_inner().registration_key;
+        let secret_key = SecretKey::from_bytes_be(&GroupParams::default(), &123456789u32.to_be_bytes());
+        let public_key = secret_key.public_key(&key_params);
+        let register_req = |b : &BigUint| Request::new(RegisterReq {
+            election_id : TEST_ELECTION.to_string(),
+            registration_key : registration_key.clone(),
+            a : public_key.a().to_bytes_be(),
+            b : b.to_bytes_be(),
+            credential_signature : vec!()
+        });
+        let register_res = server_impl.register(register_req(&(group::default_group().p() - 1u32))).await;
+        assert_eq!(register_res.unwrap_err().code(), Code::InvalidArgument, "Registered a key outside of the subgroup");
+        let register_res = server_impl.register(register_req(public_key.b())).await;
+        assert!(register_res.is_ok(), "Did throw error while passing valid registration");
+    }
+
+    async fn test_key_group<S : TestBackend>() {
+        let (_dir, server_impl) = create_server_in::<S>(&GroupParams::ristretto255());
+
+        let res = server_impl.get_parameters(Request::new(GetParametersReq { election_id : TEST_ELECTION.to_string() })).await.unwrap().into_inner();
+        let [p, q, alpha] = [&res.p, &res.q, &res.alpha].map(|bytes| BigUint::from_bytes_be(bytes));
+        let group = GroupParams::from_numbers(&res.group, &p, &q, &alpha).unwrap();
+        assert_eq!(group, GroupParams::ristretto255(), "Published group is not the group of the election");
+        let key_params = KeyParams::from_bytes_be(&group, &res.alpha, &res.beta);
+        assert!(key_params.valid(), "Published parameters are not valid");
+
+        let secret_key = SecretKey::generate(&group);
+        let public_key = secret_key.public_key(&key_params);
+        let user = TestUserData(String::from("12345"), secret_key, public_key);
+        register_user(&server_impl, &user).await;
+
+        // Key of the default group is not a key of this server
+        let other = generate_user("54321", 5315314).await;
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id : other.0.clone(),
+            blinded_credential : vec!()
+        });
+        let registration_key = server_impl.validate_id(validate_req).await.unwrap().into_inner().registration_key;
+        let register_res = server_impl.register(Request::new(RegisterReq {
+            election_id : TEST_ELECTION.to_string(),
+            registration_key,
+            a : other.2.a().to_bytes_be(),
+            b : other.2.b().to_bytes_be(),
+            credential_signature : vec!()
+        })).await;
+        assert_eq!(register_res.unwrap_err().code(), Code::InvalidArgument, "Registered a key of another group");
+
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+        let (auth_session_id, k, c) = vote(&server_impl, &user, 1).await;
+        assert!(validate_vote(&server_impl, &user, auth_session_id, k, c).await.is_ok(), "Did throw error while voting with a key on the curve");
+    }
+
     struct TestUserData(String, SecretKey, PublicKey);
 
     async fn generate_user(id : &str, secret_key : u32) -> TestUserData {
-        let sk = SecretKey::new(BigUint::from(secret_key));
-        let pk = sk.generate_public_key();
+        let sk = SecretKey::from_bytes_be(&GroupParams::default(), &secret_key.to_be_bytes());
+        let pk = sk.public_key(&KeyParams::for_election(&GroupParams::default(), TEST_ELECTION));
         TestUserData(String::from(id), sk, pk)
     }
 
-    async fn register_user(server_impl : &AnonVoteImpl, user: &TestUserData) {
+    async fn register_user<S : VoteStore>(server_impl : &AnonVoteImpl<S>, user: &TestUserData) {
         let validate_req = Request::new(ValidateIdReq {
-            id : user.0.clone()
+            election_id : TEST_ELECTION.to_string(),
+            id : user.0.clone(),
+            blinded_credential : vec!()
         });
         let validate_res = server_impl.validate_id(validate_req).await;
         assert!(validate_res.is_ok(), "Did throw error while passing valid ID");
@@ -386,115 +1170,81 @@ mod test {
         let registration_key = validate_res.unwrap().into_inner().registration_key;
 
         let register_req = Request::new(RegisterReq {
-            registration_key : registration_key,
+            election_id : TEST_ELECTION.to_string(),
+            registration_key,
             a : user.2.a().to_bytes_be(),
             b : user.2.b().to_bytes_be(),
-            alpha : user.2.alpha().to_bytes_be(),
-            beta : user.2.beta().to_bytes_be(),
+            credential_signature : vec!()
         });
 
         let register_res = server_impl.register(register_req).await;
         assert!(register_res.is_ok(), "Did throw error while passing valid registration");
     }
 
-    #[tokio::test]
-    async fn test_vote() {
-        let db = AnonVoteDB::connect();
-        let server_impl = AnonVoteImpl::new(db, 3);
+    async fn test_vote<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
 
         let user1 = generate_user("12345", 123456789u32).await;
         register_user(&server_impl, &user1).await;
+        let user2 = generate_user("54321", 4135164u32).await;
+        register_user(&server_impl, &user2).await;
 
-        let (_, ka,kb) = user1.2.generate_challenge_request();
-        let vote_req = Request::new(VoteReq {
-            vote : 1,
-            alpha : user1.2.alpha().to_bytes_be(),
-            beta : user1.2.beta().to_bytes_be(),
-            a : user1.2.a().to_bytes_be(),
-            b : user1.2.b().to_bytes_be(),
-            ka : ka.to_bytes_be(),
-            kb : kb.to_bytes_be()
-        });
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
 
-        let vote_res = server_impl.vote(vote_req).await;
+        let (ballot, ballot_proof) = encrypt_ballot(&server_impl, 1);
+        let vote_res = server_impl.vote(vote_req(TEST_ELECTION, &user1.2, &ballot, Some(&ballot_proof))).await;
         assert!(vote_res.is_ok(), "Did throw error while voting correctly. Error: {:?}",vote_res);
 
-        let (_, ka,kb) = user1.2.generate_challenge_request();
-        let vote_req = Request::new(VoteReq {
-            vote : 0,
-            alpha : user1.2.alpha().to_bytes_be(),
-            beta : user1.2.beta().to_bytes_be(),
-            a : user1.2.a().to_bytes_be(),
-            b : user1.2.b().to_bytes_be(),
-            ka : ka.to_bytes_be(),
-            kb : kb.to_bytes_be()
-        });
-
-        let vote_res = server_impl.vote(vote_req).await;
+        let (ballot, ballot_proof) = encrypt_ballot(&server_impl, 0);
+        let vote_res = server_impl.vote(vote_req(TEST_ELECTION, &user1.2, &ballot, Some(&ballot_proof))).await;
         assert!(vote_res.is_err(), "Did not throw error while voting again");
 
-        let user2 = generate_user("54321", 4135164u32).await;
-        let other_secret_key = SecretKey::new(BigUint::from(5315314u32));
-        let other_public_key = other_secret_key.generate_public_key();
-
-        register_user(&server_impl, &user2).await;
-
-        let (_, ka,kb) = user2.2.generate_challenge_request();
-        let vote_req = Request::new(VoteReq {
-            vote : 2,
-            alpha : vec!(),
-            beta : user2.2.beta().to_bytes_be(),
-            a : user2.2.a().to_bytes_be(),
-            b : user2.2.b().to_bytes_be(),
-            ka : ka.to_bytes_be(),
-            kb : kb.to_bytes_be()
-        });
+        let other_secret_key = SecretKey::from_bytes_be(&GroupParams::default(), &5315314u32.to_be_bytes());
+        let other_public_key = other_secret_key.public_key(&KeyParams::for_election(&GroupParams::default(), TEST_ELECTION));
 
-        let vote_res = server_impl.vote(vote_req).await;
+        let (ballot, ballot_proof) = encrypt_ballot(&server_impl, 2);
+        let mut req = vote_req(TEST_ELECTION, &user2.2, &ballot, Some(&ballot_proof));
+        req.get_mut().b = vec!();
+        let vote_res = server_impl.vote(req).await;
         assert!(vote_res.is_err(), "Did not throw error while passing invalid user data");
 
-        let (_, ka,kb) = user2.2.generate_challenge_request();
-        let vote_req = Request::new(VoteReq {
-            vote : 10,
-            alpha : user2.2.alpha().to_bytes_be(),
-            beta : user2.2.beta().to_bytes_be(),
-            a : user2.2.a().to_bytes_be(),
-            b : user2.2.b().to_bytes_be(),
-            ka : ka.to_bytes_be(),
-            kb : kb.to_bytes_be()
-        });
+        // Server only gets the ballot, it has to come with the proofs that it holds exactly one vote
+        let vote_res = server_impl.vote(vote_req(TEST_ELECTION, &user2.2, &ballot, None)).await;
+        assert_eq!(vote_res.unwrap_err().code(), Code::InvalidArgument, "Did not throw error while passing ballot without proof");
 
-        let vote_res = server_impl.vote(vote_req).await;
-        assert!(vote_res.is_err(), "Did not throw error while passing invalid vote");
-
-        let (_, ka,kb) = other_public_key.generate_challenge_request();
-        let vote_req = Request::new(VoteReq {
-            vote : 2,
-            alpha : other_public_key.alpha().to_bytes_be(),
-            beta : other_public_key.beta().to_bytes_be(),
-            a : other_public_key.a().to_bytes_be(),
-            b : other_public_key.b().to_bytes_be(),
-            ka : ka.to_bytes_be(),
-            kb : kb.to_bytes_be()
-        });
+        let election = server_impl.db.get_election(TEST_ELECTION).unwrap();
+        let (other_ballot, other_proof) = election_key(&election, server_impl.decryption_key.encryption_key()).encrypt_vote_with_proof(2, 3, &zkp_util::validity_context("other"));
+        let vote_res = server_impl.vote(vote_req(TEST_ELECTION, &user2.2, &other_ballot, Some(&other_proof))).await;
+        assert_eq!(vote_res.unwrap_err().code(), Code::InvalidArgument, "Did not throw error while passing ballot proof of another election");
 
-        let vote_res = server_impl.vote(vote_req).await;
+        let vote_res = server_impl.vote(vote_req(TEST_ELECTION, &user2.2, &ballot[..2], Some(&ballot_proof))).await;
+        assert_eq!(vote_res.unwrap_err().code(), Code::InvalidArgument, "Did not throw error while passing ballot with missing options");
+
+        let vote_res = server_impl.vote(vote_req(TEST_ELECTION, &other_public_key, &other_ballot, Some(&other_proof))).await;
         assert!(vote_res.is_err(), "Did not throw error while passing unregistered user");
     }
 
-    async fn vote(server_impl : &AnonVoteImpl, user : &TestUserData, vote : u32) -> (String, BigUint, BigUint) {
-        register_user(&server_impl, user).await;
-
-        let (k, ka,kb) = user.2.generate_challenge_request();
-        let vote_req = Request::new(VoteReq {
-            vote,
-            alpha : user.2.alpha().to_bytes_be(),
-            beta : user.2.beta().to_bytes_be(),
-            a : user.2.a().to_bytes_be(),
-            b : user.2.b().to_bytes_be(),
+    // Vote request of the interactive flow, a new challenge request is made every time
+    fn vote_req(election_id : &str, voter : &PublicKey, ballot : &[Ciphertext], ballot_proof : Option<&BallotProof>) -> Request<VoteReq> {
+        let (_, ka, kb) = voter.generate_challenge_request();
+        Request::new(VoteReq {
+            election_id : election_id.to_string(),
+            a : voter.a().to_bytes_be(),
+            b : voter.b().to_bytes_be(),
             ka : ka.to_bytes_be(),
-            kb : kb.to_bytes_be()
-        });
+            kb : kb.to_bytes_be(),
+            ballot : ballot.iter().map(ciphertext_msg).collect(),
+            option_proofs : ballot_proof.map(bit_proof_msgs).unwrap_or_default(),
+            sum_proof : ballot_proof.map(sum_proof_msg),
+        })
+    }
+
+    async fn vote<S : VoteStore>(server_impl : &AnonVoteImpl<S>, user : &TestUserData, vote : u32) -> (String, SecretScalar, BigUint) {
+        let (ballot, ballot_proof) = encrypt_ballot(server_impl, vote);
+        let mut vote_req = vote_req(TEST_ELECTION, &user.2, &ballot, Some(&ballot_proof));
+        let (k, ka, kb) = user.2.generate_challenge_request();
+        vote_req.get_mut().ka = ka.to_bytes_be();
+        vote_req.get_mut().kb = kb.to_bytes_be();
 
         let vote_res = server_impl.vote(vote_req).await;
         assert!(vote_res.is_ok(), "Did throw error while voting correctly. Error: {:?}",vote_res);
@@ -503,65 +1253,553 @@ mod test {
         (vote_res.auth_session_id, k, BigUint::from_bytes_be(&vote_res.challenge))
     }
 
-    #[tokio::test]
-    async fn test_verify() {
-        let db = AnonVoteDB::connect();
-        let server_impl = AnonVoteImpl::new(db, 3);
+    async fn test_verify<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
 
         let user = generate_user("12345", 12341u32).await;
+        register_user(&server_impl, &user).await;
+        let user2 = generate_user("13345", 423211u32).await;
+        register_user(&server_impl, &user2).await;
+
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+
         let (auth_session_id, k, c) = vote(&server_impl, &user, 1).await;
 
         let solution = user.1.solve(&k, &c);
 
         let validate_req = Request::new(ValidateVoteReq {
+            election_id : TEST_ELECTION.to_string(),
             auth_session_id : auth_session_id.clone(),
             solution : solution.to_bytes_be(),
-            vote : 1
         });
 
         let validate_res = server_impl.validate_vote(validate_req).await;
         assert!(validate_res.is_ok(), "Did throw error while voting correctly. Error: {:?}",validate_res);
 
+        // Stored ballot is the one the voter encrypted
+        let receipt = validate_res.unwrap().into_inner().receipt.unwrap();
+        let ballots = server_impl.db.get_ballots(TEST_ELECTION).unwrap();
+        verify_receipt(&server_impl, &receipt, &ballots[0].options);
+        assert_eq!((receipt.leaf_index, receipt.tree_size), (0, 1));
+
         let validate_req = Request::new(ValidateVoteReq {
+            election_id : TEST_ELECTION.to_string(),
             auth_session_id : auth_session_id.clone(),
             solution : solution.to_bytes_be(),
-            vote : 1
         });
 
         let validate_res = server_impl.validate_vote(validate_req).await;
         assert!(validate_res.is_err(), "Did not throw error while trying to validate correctly twice");
 
-        let user = generate_user("13345", 423211u32).await;
+        let user = user2;
         let (auth_session_id, k, c) = vote(&server_impl, &user, 1).await;
 
         let solution = user.1.solve(&k, &c) - BigUint::from(1u32);
 
         let validate_req = Request::new(ValidateVoteReq {
+            election_id : TEST_ELECTION.to_string(),
             auth_session_id : auth_session_id.clone(),
             solution : solution.to_bytes_be(),
-            vote : 1
         });
 
         let validate_res = server_impl.validate_vote(validate_req).await;
         assert!(validate_res.is_err(), "Did not throw error while not giving correct solution.");
 
         let validate_req = Request::new(ValidateVoteReq {
-            auth_session_id : auth_session_id.clone(),
+            election_id : TEST_ELECTION.to_string(),
+            auth_session_id : "dsadasadas".to_string(),
             solution : solution.to_bytes_be(),
-            vote : 2
         });
 
         let validate_res = server_impl.validate_vote(validate_req).await;
-        assert!(validate_res.is_err(), "Did not throw error while not giving valid vote.");
+        assert!(validate_res.is_err(), "Did not throw error while not giving valid auth session id.");
+        
+    }
+
+    async fn test_vote_options<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+        let candidates = crate::candidates::parse_toml(r#"
+            [[candidates]]
+            id = "alice"
+            name = "Alice"
+            description = "The first one"
+
+            [[candidates]]
+            id = "bob"
+            name = "Bob"
+        "#).unwrap();
+        server_impl.db.create_election(test_election("rich", candidates)).unwrap();
+
+        let res = server_impl.get_vote_options(Request::new(GetVoteOptionsReq { election_id : "rich".to_string() })).await;
+        assert!(res.is_ok(), "Did throw error while getting vote options");
+
+        let res = res.unwrap().into_inner();
+        assert_eq!(res.options, vec!["Alice".to_string(), "Bob".to_string()]);
+        assert_eq!(res.option_details.len(), 2);
+        assert_eq!(res.option_details[0].id, "alice");
+        assert_eq!(res.option_details[0].description, "The first one");
+
+        let res = server_impl.get_vote_options(Request::new(GetVoteOptionsReq { election_id : "missing".to_string() })).await;
+        assert!(res.is_err(), "Did not throw error while passing unknown election");
+    }
+
+    async fn test_elections<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+        let candidates = crate::candidates::parse_plain_text("Yes\nNo");
+        server_impl.db.create_election(test_election("other", candidates)).unwrap();
+
+        let res = server_impl.get_elections(Request::new(GetElectionsReq {})).await.unwrap().into_inner();
+        let ids : Vec<&str> = res.elections.iter().map(|election| election.id.as_str()).collect();
+        assert_eq!(ids, vec!["other", TEST_ELECTION]);
+        assert_eq!(res.elections[0].options.len(), 2);
 
+        // Same ID and same key can take part in both elections
+        let user = generate_user("12345", 12341u32).await;
+        register_user(&server_impl, &user).await;
+
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : "other".to_string(),
+            id : user.0.clone(),
+            blinded_credential : vec!()
+        });
+        let validate_res = server_impl.validate_id(validate_req).await;
+        assert!(validate_res.is_ok(), "Did throw error while validating same ID in a different election");
+
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : "missing".to_string(),
+            id : user.0.clone(),
+            blinded_credential : vec!()
+        });
+        let validate_res = server_impl.validate_id(validate_req).await;
+        assert!(validate_res.is_err(), "Did not throw error while passing unknown election");
+
+        // Ballot of the test election has more options than the other one
+        set_phase(&server_impl, "other", ElectionPhase::VotingOpen);
+        let (ballot, ballot_proof) = encrypt_ballot(&server_impl, 2);
+        let vote_res = server_impl.vote(vote_req("other", &user.2, &ballot, Some(&ballot_proof))).await;
+        assert!(vote_res.is_err(), "Did not throw error while passing vote outside of election options");
+
+        set_phase(&server_impl, "other", ElectionPhase::Closed);
+        let results = server_impl.get_results(Request::new(GetResultsReq { election_id : "other".to_string() })).await;
+        assert_eq!(results.unwrap().into_inner().votes, vec![0, 0]);
+    }
+
+    async fn validate_vote<S : VoteStore>(server_impl : &AnonVoteImpl<S>, user : &TestUserData, auth_session_id : String, k : SecretScalar, c : BigUint) -> Result<Response<ValidateVoteRes>, Status> {
+        let solution = user.1.solve(&k, &c);
         let validate_req = Request::new(ValidateVoteReq {
-            auth_session_id : "dsadasadas".to_string(),
+            election_id : TEST_ELECTION.to_string(),
+            auth_session_id,
             solution : solution.to_bytes_be(),
-            vote : 1
         });
+        server_impl.validate_vote(validate_req).await
+    }
 
-        let validate_res = server_impl.validate_vote(validate_req).await;
-        assert!(validate_res.is_err(), "Did not throw error while not giving valid auth session id.");
-        
+    async fn get_results<S : VoteStore>(server_impl : &AnonVoteImpl<S>) -> Result<Vec<u32>, Status> {
+        let res = server_impl.get_results(Request::new(GetResultsReq { election_id : TEST_ELECTION.to_string() })).await;
+        res.map(|res| res.into_inner().votes)
     }
-}
\ No newline at end of file
+
+    async fn test_lifecycle<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+        let user1 = generate_user("12345", 12341u32).await;
+        let user2 = generate_user("54321", 423211u32).await;
+        let user3 = generate_user("11111", 8765432u32).await;
+
+        // Nothing is allowed before registration opens
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::Draft);
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id : user1.0.clone(),
+            blinded_credential : vec!()
+        });
+        let validate_res = server_impl.validate_id(validate_req).await;
+        assert_eq!(validate_res.unwrap_err().code(), Code::FailedPrecondition, "Did not reject ID validation in draft");
+
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::RegistrationOpen);
+        register_user(&server_impl, &user1).await;
+        register_user(&server_impl, &user2).await;
+
+        let (ballot, ballot_proof) = encrypt_ballot(&server_impl, 1);
+        let vote_res = server_impl.vote(vote_req(TEST_ELECTION, &user1.2, &ballot, Some(&ballot_proof))).await;
+        assert_eq!(vote_res.unwrap_err().code(), Code::FailedPrecondition, "Did not reject vote while registration is open");
+
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+
+        // Registration is closed once voting opens
+        let validate_req = Request::new(ValidateIdReq {
+            election_id : TEST_ELECTION.to_string(),
+            id : user3.0.clone(),
+            blinded_credential : vec!()
+        });
+        let validate_res = server_impl.validate_id(validate_req).await;
+        assert_eq!(validate_res.unwrap_err().code(), Code::FailedPrecondition, "Did not reject ID validation while voting is open");
+
+        let (auth_session_id, k, c) = vote(&server_impl, &user1, 1).await;
+        let validate_res = validate_vote(&server_impl, &user1, auth_session_id, k, c).await;
+        assert!(validate_res.is_ok(), "Did throw error while voting correctly. Error: {:?}", validate_res);
+
+        let res = get_results(&server_impl).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Results were not withheld while voting is open");
+
+        // Session started before the election closed can't be finished after it
+        let (auth_session_id, k, c) = vote(&server_impl, &user2, 2).await;
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::Closed);
+        let validate_res = validate_vote(&server_impl, &user2, auth_session_id, k, c).await;
+        assert_eq!(validate_res.unwrap_err().code(), Code::FailedPrecondition, "Did not reject vote validation after the election closed");
+
+        let res = get_results(&server_impl).await;
+        assert_eq!(res.unwrap(), vec![0, 1, 0]);
+
+        let election = server_impl.db.get_election(TEST_ELECTION).unwrap();
+        assert_eq!(election.current_phase(), ElectionPhase::Tallied);
+        assert!(!server_impl.db.set_schedule(TEST_ELECTION, 0, 0, u64::MAX).unwrap(), "Tallied election was reopened");
+
+        let res = get_results(&server_impl).await;
+        assert_eq!(res.unwrap(), vec![0, 1, 0]);
+    }
+
+    async fn test_session_expiry<S : TestBackend>() {
+        let (_dir, mut server_impl) = create_server::<S>();
+        let user = generate_user("12345", 12341u32).await;
+        register_user(&server_impl, &user).await;
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+
+        // Sessions expire right away
+        server_impl.session_ttl = 0;
+        let (expired_session_id, k, c) = vote(&server_impl, &user, 1).await;
+        let validate_res = validate_vote(&server_impl, &user, expired_session_id.clone(), k, c).await;
+        assert_eq!(validate_res.unwrap_err().code(), Code::FailedPrecondition, "Accepted solution for expired session");
+
+        // Expired pending vote doesn't lock the user out
+        server_impl.session_ttl = SESSION_TTL_SEC;
+        let (auth_session_id, k, c) = vote(&server_impl, &user, 2).await;
+        assert!(server_impl.db.get_challenge(TEST_ELECTION, &expired_session_id).is_some());
+        assert_eq!(server_impl.db.remove_expired(election::now()).unwrap(), 1, "Sweeper should only remove the expired challenge");
+
+        let validate_res = validate_vote(&server_impl, &user, auth_session_id, k, c).await;
+        assert!(validate_res.is_ok(), "Did throw error while voting correctly. Error: {:?}", validate_res);
+        let ballots = server_impl.db.get_ballots(TEST_ELECTION).unwrap();
+        assert_eq!(ballot::decrypt_tally(&ballots, 3, &server_impl.decryption_key), Some(vec![0, 0, 1]));
+    }
+
+    fn cast_vote_req(user : &TestUserData, proof : &Proof, ballot : &[Ciphertext], ballot_proof : &BallotProof) -> Request<CastVoteReq> {
+        Request::new(CastVoteReq {
+            election_id : TEST_ELECTION.to_string(),
+            a : user.2.a().to_bytes_be(),
+            b : user.2.b().to_bytes_be(),
+            ka : proof.ka().to_bytes_be(),
+            kb : proof.kb().to_bytes_be(),
+            solution : proof.solution().to_bytes_be(),
+            ballot : ballot.iter().map(ciphertext_msg).collect(),
+            option_proofs : bit_proof_msgs(ballot_proof),
+            sum_proof : Some(sum_proof_msg(ballot_proof))
+        })
+    }
+
+    // Ballot of the test election with the proofs of its validity
+    fn encrypt_ballot<S : VoteStore>(server_impl : &AnonVoteImpl<S>, vote : u32) -> (Vec<Ciphertext>, BallotProof) {
+        let context = zkp_util::validity_context(TEST_ELECTION);
+        let election = server_impl.db.get_election(TEST_ELECTION).unwrap();
+        election_key(&election, server_impl.decryption_key.encryption_key()).encrypt_vote_with_proof(vote, 3, &context)
+    }
+
+    // Ballot with the proofs of its validity and the proof of the user's key
+    fn encrypt_vote<S : VoteStore>(server_impl : &AnonVoteImpl<S>, user : &TestUserData, vote : u32) -> (Vec<Ciphertext>, BallotProof, Proof) {
+        let (ballot, ballot_proof) = encrypt_ballot(server_impl, vote);
+        let proof = user.1.prove(&user.2, &zkp_util::ballot_context(TEST_ELECTION, &ballot));
+        (ballot, ballot_proof, proof)
+    }
+
+    async fn test_cast_vote<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+        let user1 = generate_user("12345", 12341u32).await;
+        register_user(&server_impl, &user1).await;
+        let user2 = generate_user("54321", 423211u32).await;
+        register_user(&server_impl, &user2).await;
+
+        let (ballot, ballot_proof, proof) = encrypt_vote(&server_impl, &user1, 1);
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &proof, &ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Accepted vote while registration is open");
+
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+
+        // Proof for a different ballot or election
+        let (other_ballot, other_ballot_proof, _) = encrypt_vote(&server_impl, &user1, 1);
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &proof, &other_ballot, &other_ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted proof made for a different ballot");
+        let other_proof = user1.1.prove(&user1.2, &zkp_util::ballot_context("other", &ballot));
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &other_proof, &ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted proof made for a different election");
+
+        // Proof made without the secret key
+        let forged_proof = user2.1.prove(&user1.2, &zkp_util::ballot_context(TEST_ELECTION, &ballot));
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &forged_proof, &ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted proof made without the secret key");
+
+        // Ballot without a ciphertext for every option, or with a ciphertext outside of the group
+        let short_ballot = &ballot[..2];
+        let short_proof = user1.1.prove(&user1.2, &zkp_util::ballot_context(TEST_ELECTION, short_ballot));
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &short_proof, short_ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted ballot with missing option");
+        let mut invalid_ballot = ballot.clone();
+        invalid_ballot[0] = Ciphertext::new(BigUint::ZERO, invalid_ballot[0].c2().clone());
+        let invalid_proof = user1.1.prove(&user1.2, &zkp_util::ballot_context(TEST_ELECTION, &invalid_ballot));
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &invalid_proof, &invalid_ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted ballot with invalid ciphertext");
+
+        // Ballot that doesn't encrypt a single vote, or a copy of the ballot of another user
+        let encryption_key = server_impl.decryption_key.encryption_key();
+        let double_ballot : Vec<Ciphertext> = [1, 1, 0].iter().map(|vote| encryption_key.encrypt(*vote)).collect();
+        let double_proof = user1.1.prove(&user1.2, &zkp_util::ballot_context(TEST_ELECTION, &double_ballot));
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &double_proof, &double_ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted ballot with two votes");
+        let mut req = cast_vote_req(&user1, &proof, &ballot, &ballot_proof);
+        req.get_mut().sum_proof = None;
+        let res = server_impl.cast_vote(req).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted ballot without the proof");
+
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &proof, &ballot, &ballot_proof)).await;
+        assert!(res.is_ok(), "Did throw error while voting correctly. Error: {:?}", res);
+        let res = server_impl.cast_vote(cast_vote_req(&user1, &proof, &ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::AlreadyExists, "User voted twice");
+
+        // Ballot and its proofs are on the board, another user can't cast a copy of them
+        let copy_proof = user2.1.prove(&user2.2, &zkp_util::ballot_context(TEST_ELECTION, &ballot));
+        let res = server_impl.cast_vote(cast_vote_req(&user2, &copy_proof, &ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted copy of another user's ballot");
+
+        // Server only stores the ballot it received with its proofs, it never sees the vote
+        assert_eq!(server_impl.db.get_ballots(TEST_ELECTION), Some(vec![EncryptedBallot { options : ballot.clone(), proof : Some(ballot_proof.clone()) }]));
+
+        // Interactive vote is rejected as well once the user voted
+        let (ballot, ballot_proof) = encrypt_ballot(&server_impl, 1);
+        let res = server_impl.vote(vote_req(TEST_ELECTION, &user1.2, &ballot, Some(&ballot_proof))).await;
+        assert_eq!(res.unwrap_err().code(), Code::AlreadyExists, "User voted twice");
+
+        let unregistered = generate_user("11111", 8765432u32).await;
+        let (ballot, ballot_proof, proof) = encrypt_vote(&server_impl, &unregistered, 0);
+        let res = server_impl.cast_vote(cast_vote_req(&unregistered, &proof, &ballot, &ballot_proof)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Unregistered user voted");
+
+        let (ballot, ballot_proof, proof) = encrypt_vote(&server_impl, &user2, 2);
+        let res = server_impl.cast_vote(cast_vote_req(&user2, &proof, &ballot, &ballot_proof)).await;
+        assert!(res.is_ok(), "Did throw error while voting correctly. Error: {:?}", res);
+
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::Closed);
+        assert_eq!(get_results(&server_impl).await.unwrap(), vec![0, 1, 1]);
+    }
+
+    // Decryptions of the tally by the trustee with the given key share, sent as the given trustee
+    fn partial_decryption_req(trustee : u32, key_share : &KeyShare, tally : &[Ciphertext]) -> Request<SubmitPartialDecryptionReq> {
+        let context = zkp_util::election_context(TEST_ELECTION);
+        Request::new(SubmitPartialDecryptionReq {
+            election_id : TEST_ELECTION.to_string(),
+            trustee,
+            decryptions : tally.iter().map(|sum| {
+                let partial = key_share.partial_decrypt(sum, &context).unwrap();
+                PartialDecryptionMsg {
+                    d : partial.d().to_bytes_be(),
+                    ka : partial.proof().ka().to_bytes_be(),
+                    kb : partial.proof().kb().to_bytes_be(),
+                    solution : partial.proof().solution().to_bytes_be(),
+                }
+            }).collect()
+        })
+    }
+
+    async fn test_trustee_tally<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+        let tally_req = || Request::new(GetEncryptedTallyReq { election_id : TEST_ELECTION.to_string() });
+        let res = server_impl.get_encrypted_tally(tally_req()).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Got encrypted tally of an election without trustees");
+
+        let (trustees, key_shares) = crate::model::trustees::test::test_trustees(&GroupParams::default());
+        assert!(server_impl.db.set_trustees(TEST_ELECTION, trustees.clone(), election::now()).unwrap());
+        let elections = server_impl.get_elections(Request::new(GetElectionsReq {})).await.unwrap().into_inner().elections;
+        assert_eq!(elections[0].encryption_key, trustees.encryption_key.h().to_bytes_be(), "Election is not encrypted with the trustee key");
+        assert_eq!(elections[0].trustee_threshold, 2);
+
+        let user1 = generate_user("12345", 12341u32).await;
+        register_user(&server_impl, &user1).await;
+        let user2 = generate_user("54321", 423211u32).await;
+        register_user(&server_impl, &user2).await;
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+        for (user, vote) in [(&user1, 1), (&user2, 2)] {
+            let (ballot, ballot_proof, proof) = encrypt_vote(&server_impl, user, vote);
+            server_impl.cast_vote(cast_vote_req(user, &proof, &ballot, &ballot_proof)).await.unwrap();
+        }
+        let res = server_impl.get_encrypted_tally(tally_req()).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Got encrypted tally while voting is open");
+
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::Closed);
+        assert_eq!(get_results(&server_impl).await.unwrap_err().code(), Code::FailedPrecondition, "Tallied without the trustees");
+
+        let res = server_impl.get_encrypted_tally(tally_req()).await.unwrap().into_inner();
+        assert_eq!(res.threshold, 2);
+        assert_eq!(res.verification_keys, trustees.verification_keys.iter().map(|key| key.to_bytes_be()).collect::<Vec<_>>());
+        let tally : Vec<Ciphertext> = res.tally.iter().map(|sum| Ciphertext::from_bytes_be(&sum.c1, &sum.c2)).collect();
+
+        let res = server_impl.submit_partial_decryption(partial_decryption_req(2, &key_shares[0], &tally)).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted decryption of a different trustee");
+        let res = server_impl.submit_partial_decryption(partial_decryption_req(1, &key_shares[0], &tally[..2])).await;
+        assert_eq!(res.unwrap_err().code(), Code::InvalidArgument, "Accepted decryption with a missing option");
+
+        let res = server_impl.submit_partial_decryption(partial_decryption_req(1, &key_shares[0], &tally)).await;
+        assert_eq!(res.unwrap().into_inner().remaining, 1);
+        let res = server_impl.submit_partial_decryption(partial_decryption_req(1, &key_shares[0], &tally)).await;
+        assert_eq!(res.unwrap_err().code(), Code::AlreadyExists, "Same trustee decrypted the tally twice");
+        assert_eq!(get_results(&server_impl).await.unwrap_err().code(), Code::FailedPrecondition, "Tallied with less than threshold trustees");
+
+        let res = server_impl.submit_partial_decryption(partial_decryption_req(3, &key_shares[2], &tally)).await;
+        assert_eq!(res.unwrap().into_inner().remaining, 0);
+        assert_eq!(server_impl.db.get_election(TEST_ELECTION).unwrap().tally, Some(vec![0, 1, 1]), "Election was not tallied once enough trustees decrypted it");
+        assert_eq!(get_results(&server_impl).await.unwrap(), vec![0, 1, 1]);
+
+        let res = server_impl.submit_partial_decryption(partial_decryption_req(2, &key_shares[1], &tally)).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Accepted decryption after the election was tallied");
+    }
+
+    async fn test_mixed_ballots<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+        let mixed_req = || Request::new(GetMixedBallotsReq { election_id : TEST_ELECTION.to_string() });
+        let users = [generate_user("12345", 12341u32).await, generate_user("54321", 423211u32).await, generate_user("11111", 8765432u32).await];
+        for user in &users {
+            register_user(&server_impl, user).await;
+        }
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+        for (user, vote) in users.iter().zip([2, 0, 2]) {
+            let (ballot, ballot_proof, proof) = encrypt_vote(&server_impl, user, vote);
+            server_impl.cast_vote(cast_vote_req(user, &proof, &ballot, &ballot_proof)).await.unwrap();
+        }
+        let res = server_impl.get_mixed_ballots(mixed_req()).await;
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition, "Mixed the ballots while voting is open");
+
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::Closed);
+        let res = server_impl.get_mixed_ballots(mixed_req()).await.unwrap().into_inner();
+        let ballots = |ballots : &[EncryptedBallotMsg]| -> Vec<Vec<Ciphertext>> {
+            ballots.iter().map(|ballot| ballot.options.iter().map(|ciphertext| Ciphertext::from_bytes_be(&ciphertext.c1, &ciphertext.c2)).collect()).collect()
+        };
+        let (input, output) = (ballots(&res.ballots), ballots(&res.mixed_ballots));
+        assert_eq!(input.len(), 3);
+        assert!(input.iter().all(|ballot| !output.contains(ballot)), "Mixed ballots were not re-encrypted");
+
+        let key = EncryptionKey::from_bytes_be(server_impl.decryption_key.group(), &res.encryption_key);
+        let mix = server_impl.db.get_mix(TEST_ELECTION).unwrap();
+        assert_eq!(shuffle_proof_msg(&mix), res.proof.unwrap());
+        assert!(mixnet::verify_shuffle(&key, &input, &output, &mix.proof, &zkp_util::election_context(TEST_ELECTION)), "Shuffle proof was not valid");
+
+        // Ballots are mixed only once and the tally comes from the mix
+        let res = server_impl.get_mixed_ballots(mixed_req()).await.unwrap().into_inner();
+        assert_eq!(ballots(&res.mixed_ballots), output, "Ballots were mixed again");
+        assert_eq!(ballot::decrypt_tally(&mix.ballots, 3, &server_impl.decryption_key), Some(vec![1, 0, 2]));
+        assert_eq!(get_results(&server_impl).await.unwrap(), vec![1, 0, 2]);
+
+        assert!(!server_impl.db.add_vote(TEST_ELECTION, VoterId([1; 32]), EncryptedBallot::encrypt(&key, 0, 3)).unwrap(), "Vote was added after mixing");
+    }
+
+    // Entries of the board from the given index, verified against the checkpoint at the end of the stream
+    async fn bulletin_board<S : VoteStore>(server_impl : &AnonVoteImpl<S>, from_index : u64, previous : &Hash) -> Vec<(BoardRecord, Hash)> {
+        let req = Request::new(GetBulletinBoardReq { election_id : TEST_ELECTION.to_string(), from_index });
+        let items : Vec<_> = server_impl.get_bulletin_board(req).await.unwrap().into_inner().collect().await;
+        let mut items : Vec<bulletin_board_item::Item> = items.into_iter().map(|item| item.unwrap().item.unwrap()).collect();
+        let Some(bulletin_board_item::Item::Checkpoint(checkpoint)) = items.pop() else {
+            panic!("Board did not end with a checkpoint");
+        };
+
+        let entries : Vec<(BoardRecord, Hash)> = items.into_iter().enumerate().map(|(i, item)| {
+            let bulletin_board_item::Item::Entry(entry) = item else {
+                panic!("Checkpoint in the middle of the board");
+            };
+            assert_eq!(entry.index, from_index + i as u64);
+            let record = match entry.record.unwrap() {
+                board_entry::Record::Schedule(schedule) => BoardRecord::Schedule { registration_opens_at : schedule.registration_opens_at, opens_at : schedule.opens_at, closes_at : schedule.closes_at },
+                board_entry::Record::Registration(voter) => BoardRecord::Registration { voter : PublicKey::from_bytes_be(&GroupParams::default(), &voter.a, &voter.b, &voter.alpha, &voter.beta) },
+                board_entry::Record::Ballot(ballot) => BoardRecord::Ballot {
+                    ballot : ballot.options.iter().map(|c| Ciphertext::from_bytes_be(&c.c1, &c.c2)).collect(),
+                    proof : ballot.sum_proof.as_ref().map(|sum| BallotProof::new(ballot.option_proofs.iter().map(bit_proof).collect(), sum_proof(sum))),
+                },
+                board_entry::Record::Tally(tally) => BoardRecord::Tally { votes : tally.votes },
+            };
+            (record, entry.hash.try_into().unwrap())
+        }).collect();
+
+        let head = board::verify_entries(TEST_ELECTION, from_index, previous, &entries).expect("Hash chain is broken");
+        let signature = Proof::from_bytes_be(&checkpoint.ka, &checkpoint.kb, &checkpoint.solution);
+        assert_eq!((checkpoint.size, checkpoint.head), (from_index + entries.len() as u64, head.to_vec()));
+        assert_eq!(BigUint::from_bytes_be(&checkpoint.public_key), *server_impl.board_key.public_key());
+        assert!(board::verify_checkpoint(server_impl.board_key.group(), server_impl.board_key.public_key(), TEST_ELECTION, checkpoint.size, &head, &signature), "Invalid checkpoint signature");
+        entries
+    }
+
+    async fn test_bulletin_board<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+        let entries = bulletin_board(&server_impl, 0, &EMPTY_HASH).await;
+        assert!(matches!(entries[..], [(BoardRecord::Schedule { opens_at, .. }, _)] if opens_at == u64::MAX - 1), "Election creation is not on the board");
+
+        let user = generate_user("12345", 12341u32).await;
+        register_user(&server_impl, &user).await;
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+        let (ballot, ballot_proof, proof) = encrypt_vote(&server_impl, &user, 1);
+        server_impl.cast_vote(cast_vote_req(&user, &proof, &ballot, &ballot_proof)).await.unwrap();
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::Closed);
+        get_results(&server_impl).await.unwrap();
+
+        // Observer that replicated the first entry only fetches the rest
+        let entries = bulletin_board(&server_impl, 1, &entries[0].1).await;
+        let [(BoardRecord::Registration { voter }, _), (BoardRecord::Schedule { .. }, _), (BoardRecord::Ballot { ballot : posted, .. }, _), (BoardRecord::Schedule { .. }, _), (BoardRecord::Tally { votes }, _)] = &entries[..] else {
+            panic!("Unexpected board entries");
+        };
+        assert_eq!(voter.a(), user.2.a());
+        assert_eq!(*posted, ballot);
+        assert_eq!(*votes, vec![0, 1, 0]);
+
+        let req = Request::new(GetBulletinBoardReq { election_id : "missing".to_string(), from_index : 0 });
+        assert_eq!(server_impl.get_bulletin_board(req).await.unwrap_err().code(), Code::NotFound);
+    }
+
+    // Receipt signed with the board key, for the ballot the user sent
+    fn verify_receipt<S : VoteStore>(server_impl : &AnonVoteImpl<S>, receipt : &BallotReceipt, ballot : &[Ciphertext]) -> Hash {
+        let root = Hash::try_from(receipt.root.as_slice()).unwrap();
+        let signature = Proof::from_bytes_be(&receipt.ka, &receipt.kb, &receipt.solution);
+        assert_eq!(receipt.ballot_hash, merkle::ballot_hash(TEST_ELECTION, ballot).to_vec(), "Receipt is for a different ballot");
+        assert_eq!(BigUint::from_bytes_be(&receipt.public_key), *server_impl.board_key.public_key());
+        assert!(merkle::verify_root(server_impl.board_key.group(), server_impl.board_key.public_key(), TEST_ELECTION, receipt.tree_size, &root, &signature), "Invalid root signature");
+        root
+    }
+
+    async fn test_ballot_receipts<S : TestBackend>() {
+        let (_dir, server_impl) = create_server::<S>();
+        let users = [generate_user("12345", 12341u32).await, generate_user("54321", 423211u32).await];
+        for user in &users {
+            register_user(&server_impl, user).await;
+        }
+        set_phase(&server_impl, TEST_ELECTION, ElectionPhase::VotingOpen);
+
+        let mut ballots = Vec::new();
+        for (index, user) in users.iter().enumerate() {
+            let (ballot, ballot_proof, proof) = encrypt_vote(&server_impl, user, index as u32);
+            let res = server_impl.cast_vote(cast_vote_req(user, &proof, &ballot, &ballot_proof)).await.unwrap().into_inner();
+            let receipt = res.receipt.unwrap();
+            verify_receipt(&server_impl, &receipt, &ballot);
+            assert_eq!((receipt.leaf_index, receipt.tree_size), (index as u64, index as u64 + 1));
+            ballots.push(ballot);
+        }
+
+        // First ballot is still in the tree after the second one was added
+        let ballot_hash = merkle::ballot_hash(TEST_ELECTION, &ballots[0]);
+        let req = Request::new(GetInclusionProofReq { election_id : TEST_ELECTION.to_string(), ballot_hash : ballot_hash.to_vec() });
+        let res = server_impl.get_inclusion_proof(req).await.unwrap().into_inner();
+        let receipt = res.receipt.unwrap();
+        let root = verify_receipt(&server_impl, &receipt, &ballots[0]);
+        let path : Vec<Hash> = res.path.iter().map(|hash| Hash::try_from(hash.as_slice()).unwrap()).collect();
+        assert_eq!((receipt.leaf_index, receipt.tree_size), (0, 2));
+        assert!(merkle::verify_inclusion(&ballot_hash, receipt.leaf_index, receipt.tree_size, &path, &root), "Invalid inclusion proof");
+
+        let req = Request::new(GetInclusionProofReq { election_id : TEST_ELECTION.to_string(), ballot_hash : vec![0; 32] });
+        assert_eq!(server_impl.get_inclusion_proof(req).await.unwrap_err().code(), Code::NotFound, "Found a ballot that was never cast");
+        let req = Request::new(GetInclusionProofReq { election_id : TEST_ELECTION.to_string(), ballot_hash : vec![0; 5] });
+        assert_eq!(server_impl.get_inclusion_proof(req).await.unwrap_err().code(), Code::InvalidArgument);
+        let req = Request::new(GetInclusionProofReq { election_id : "missing".to_string(), ballot_hash : ballot_hash.to_vec() });
+        assert_eq!(server_impl.get_inclusion_proof(req).await.unwrap_err().code(), Code::NotFound);
+    }
+}
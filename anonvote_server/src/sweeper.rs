@@ -0,0 +1,18 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db::VoteStore;
+use crate::model::election;
+
+// Periodically removes expired challenges and pending votes, so abandoned sessions don't pile up
+pub async fn run<S : VoteStore>(db : Arc<S>, interval : Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match db.remove_expired(election::now()) {
+            Ok(0) => {}
+            Ok(removed) => println!("Removed {} expired challenges and pending votes", removed),
+            Err(e) => eprintln!("Could not remove expired sessions: {}", e),
+        }
+    }
+}
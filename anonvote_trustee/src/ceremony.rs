@@ -0,0 +1,268 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use zkp_protocol::group::GroupParams;
+use zkp_protocol::threshold::{self, Dealing, KeyShare};
+
+// Files of the key ceremony, all kept in one directory that the trustees exchange offline:
+// commitments_<i>.json - public commitments of trustee i
+// share_<i>_to_<j>.json - share that trustee i dealt to trustee j, only trustee j may see it
+// key_share_<j>.json - decryption key share of trustee j, never leaves the trustee
+// election_key.json - public key of the election and the verification keys of all trustees
+// Keys are in the group of the election (GetParameters), every trustee has to deal in the same group.
+// Files from before the group was stored are in the default group.
+
+#[derive(Serialize, Deserialize)]
+struct GroupFile {
+    name : String,
+    p : String,
+    q : String,
+    alpha : String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CommitmentsFile {
+    #[serde(default)]
+    group : GroupFile,
+    index : u32,
+    threshold : u32,
+    trustee_count : u32,
+    commitments : Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ShareFile {
+    from : u32,
+    to : u32,
+    share : String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyShareFile {
+    #[serde(default)]
+    group : GroupFile,
+    index : u32,
+    x : String,
+}
+
+// Same for every trustee, so they can compare it before it is published
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ElectionKeyFile {
+    pub threshold : u32,
+    pub encryption_key : String,
+    pub verification_keys : Vec<String>, // Key of trustee i is at i - 1
+}
+
+fn commitments_path(dir : &Path, index : u32) -> PathBuf {
+    dir.join(format!("commitments_{}.json", index))
+}
+
+fn share_path(dir : &Path, from : u32, to : u32) -> PathBuf {
+    dir.join(format!("share_{}_to_{}.json", from, to))
+}
+
+fn key_share_path(dir : &Path, index : u32) -> PathBuf {
+    dir.join(format!("key_share_{}.json", index))
+}
+
+fn election_key_path(dir : &Path) -> PathBuf {
+    dir.join("election_key.json")
+}
+
+fn invalid_data(message : String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn to_hex(num : &BigUint) -> String {
+    hex::encode(num.to_bytes_be())
+}
+
+fn hex_bytes(hex_str : &str) -> io::Result<Vec<u8>> {
+    hex::decode(hex_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn from_hex(hex_str : &str) -> io::Result<BigUint> {
+    Ok(BigUint::from_bytes_be(&hex_bytes(hex_str)?))
+}
+
+impl GroupFile {
+    fn new(group : &GroupParams) -> GroupFile {
+        GroupFile {
+            name : group.name().to_string(),
+            p : to_hex(group.p()),
+            q : to_hex(group.q()),
+            alpha : to_hex(group.alpha()),
+        }
+    }
+
+    fn parse(&self) -> io::Result<GroupParams> {
+        GroupParams::from_numbers(&self.name, &from_hex(&self.p)?, &from_hex(&self.q)?, &from_hex(&self.alpha)?).map_err(|e| invalid_data(e.to_string()))
+    }
+}
+
+impl Default for GroupFile {
+    fn default() -> GroupFile {
+        GroupFile::new(&GroupParams::default())
+    }
+}
+
+fn read_json<T : DeserializeOwned>(path : &Path) -> io::Result<T> {
+    let content = fs::read_to_string(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    serde_json::from_str(&content).map_err(|e| invalid_data(format!("{}: {}", path.display(), e)))
+}
+
+fn write_json<T : Serialize>(path : &Path, value : &T) -> io::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(value).map_err(io::Error::from)?)
+}
+
+// Deals a random polynomial, writes the commitments and a share for every trustee (including this one)
+pub fn deal(dir : &Path, group : &GroupParams, index : u32, threshold : u32, trustee_count : u32) -> io::Result<()> {
+    if index == 0 || index > trustee_count {
+        return Err(invalid_data(format!("Trustee index must be between 1 and {}", trustee_count)));
+    }
+    let dealing = Dealing::generate(group, threshold, trustee_count).ok_or(invalid_data("Threshold must be between 1 and the number of trustees".to_string()))?;
+
+    fs::create_dir_all(dir)?;
+    write_json(&commitments_path(dir, index), &CommitmentsFile {
+        group : GroupFile::new(group),
+        index,
+        threshold,
+        trustee_count,
+        commitments : dealing.commitments().iter().map(to_hex).collect(),
+    })?;
+    for (to, share) in (1..=trustee_count).zip(dealing.shares()) {
+        write_json(&share_path(dir, index, to), &ShareFile { from : index, to, share : to_hex(share) })?;
+    }
+    Ok(())
+}
+
+// Checks the shares dealt to this trustee and writes its key share and the election key.
+// Fails if some trustee dealt a share that doesn't match its commitments.
+pub fn combine(dir : &Path, index : u32) -> io::Result<ElectionKeyFile> {
+    let own : CommitmentsFile = read_json(&commitments_path(dir, index))?;
+    let (threshold, trustee_count) = (own.threshold, own.trustee_count);
+    let group = own.group.parse()?;
+
+    let mut commitments = Vec::new();
+    let mut shares = Vec::new();
+    for dealer in 1..=trustee_count {
+        let file : CommitmentsFile = read_json(&commitments_path(dir, dealer))?;
+        if file.index != dealer || file.threshold != threshold || file.trustee_count != trustee_count || file.commitments.len() != threshold as usize || file.group.parse()? != group {
+            return Err(invalid_data(format!("Trustee {} used different ceremony parameters", dealer)));
+        }
+        let dealer_commitments = file.commitments.iter().map(|commitment| from_hex(commitment)).collect::<io::Result<Vec<_>>>()?;
+
+        let share : ShareFile = read_json(&share_path(dir, dealer, index))?;
+        let share_value = from_hex(&share.share)?;
+        if share.from != dealer || share.to != index || !threshold::verify_share(&group, &dealer_commitments, index, &share_value) {
+            return Err(invalid_data(format!("Share from trustee {} doesn't match its commitments", dealer)));
+        }
+        commitments.push(dealer_commitments);
+        shares.push(share_value);
+    }
+
+    let key_share = KeyShare::combine(&group, index, &shares);
+    write_json(&key_share_path(dir, index), &KeyShareFile { group : GroupFile::new(&group), index, x : hex::encode(key_share.secret().to_bytes_be()) })?;
+
+    // Commitments were checked with the shares, so they are all in the group
+    let invalid_key = || invalid_data("Commitments are not in the group".to_string());
+    let encryption_key = threshold::election_key(&group, &commitments).ok_or_else(invalid_key)?;
+    let verification_keys = (1..=trustee_count).map(|trustee| threshold::verification_key(&group, &commitments, trustee).ok_or_else(invalid_key)).collect::<io::Result<Vec<_>>>()?;
+    let election_key = ElectionKeyFile {
+        threshold,
+        encryption_key : to_hex(encryption_key.h()),
+        verification_keys : verification_keys.iter().map(to_hex).collect(),
+    };
+    write_json(&election_key_path(dir), &election_key)?;
+    Ok(election_key)
+}
+
+pub fn load_key_share(dir : &Path, index : u32) -> io::Result<KeyShare> {
+    let file : KeyShareFile = read_json(&key_share_path(dir, index))?;
+    if file.index != index {
+        return Err(invalid_data(format!("Key share belongs to trustee {}", file.index)));
+    }
+    Ok(KeyShare::from_bytes_be(&file.group.parse()?, index, &hex_bytes(&file.x)?))
+}
+
+pub fn load_election_key(dir : &Path) -> io::Result<ElectionKeyFile> {
+    read_json(&election_key_path(dir))
+}
+
+// Custom group of the server, the same file the server was started with (--group-file)
+pub fn load_group_file(path : &Path) -> io::Result<GroupParams> {
+    read_json::<GroupFile>(path)?.parse()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use zkp_protocol::zkp_util;
+    use zkp_protocol::elgamal::EncryptionKey;
+
+    #[test]
+    fn test_group_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("group.json");
+        fs::write(&path, "{\"name\":\"custom\",\"p\":\"17\",\"q\":\"0b\",\"alpha\":\"04\"}").unwrap();
+        let group = load_group_file(&path).unwrap();
+        assert_eq!((group.name(), group.q()), ("custom", &BigUint::from(11u32)));
+        fs::write(&path, "{\"name\":\"ristretto255\",\"p\":\"17\",\"q\":\"0b\",\"alpha\":\"04\"}").unwrap();
+        assert!(load_group_file(&path).is_err(), "Loaded a built-in group with other numbers");
+    }
+
+    #[test]
+    fn test_ceremony() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let group = GroupParams::ristretto255();
+        for index in 1..=3 {
+            deal(dir, &group, index, 2, 3).unwrap();
+        }
+        let election_keys : Vec<ElectionKeyFile> = (1..=3).map(|index| combine(dir, index).unwrap()).collect();
+        assert!(election_keys.iter().all(|key| *key == election_keys[0]), "Trustees computed different election keys");
+        assert_eq!(load_election_key(dir).unwrap(), election_keys[0]);
+
+        // Any two trustees decrypt what was encrypted with the election key
+        let encryption_key = EncryptionKey::new(&group, from_hex(&election_keys[0].encryption_key).unwrap());
+        assert!(encryption_key.valid(), "Election key is not in the group of the ceremony");
+        let ciphertext = encryption_key.encrypt(3);
+        let context = zkp_util::election_context("election");
+        let partials : Vec<_> = [1, 3].iter().map(|index| load_key_share(dir, *index).unwrap().partial_decrypt(&ciphertext, &context).unwrap()).collect();
+        for partial in &partials {
+            let verification_key = from_hex(&election_keys[0].verification_keys[partial.index() as usize - 1]).unwrap();
+            assert!(partial.verify(&group, &verification_key, &ciphertext, &context));
+        }
+        assert_eq!(threshold::combine(&group, &ciphertext, &partials, 3), Some(3));
+
+        assert!(deal(dir, &group, 4, 2, 3).is_err(), "Dealt with an index bigger than the trustee count");
+        assert!(deal(dir, &group, 1, 4, 3).is_err(), "Dealt with a threshold bigger than the trustee count");
+    }
+
+    #[test]
+    fn test_invalid_share() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let group = GroupParams::default();
+        for index in 1..=3 {
+            deal(dir, &group, index, 2, 3).unwrap();
+        }
+
+        // Trustee 2 gives trustee 1 a share that doesn't match its commitments
+        let mut share : ShareFile = read_json(&share_path(dir, 2, 1)).unwrap();
+        share.share = to_hex(&(from_hex(&share.share).unwrap() + 1u32));
+        write_json(&share_path(dir, 2, 1), &share).unwrap();
+        assert_eq!(combine(dir, 1).unwrap_err().kind(), io::ErrorKind::InvalidData, "Accepted an invalid share");
+        assert!(combine(dir, 3).is_ok());
+
+        // Trustee dealt with different parameters or in another group
+        deal(dir, &group, 2, 3, 3).unwrap();
+        assert!(combine(dir, 3).is_err(), "Combined shares from different ceremonies");
+        deal(dir, &GroupParams::ristretto255(), 2, 2, 3).unwrap();
+        assert!(combine(dir, 3).is_err(), "Combined shares from different groups");
+    }
+}
@@ -0,0 +1,274 @@
+mod ceremony;
+
+use anonvote_proto::proto::anonvote::{anon_vote_client::AnonVoteClient, anon_vote_admin_client::AnonVoteAdminClient, GetEncryptedTallyReq, SubmitPartialDecryptionReq, PartialDecryption, SetTrusteesReq, GetMixedBallotsReq, GetBulletinBoardReq, GetParametersReq};
+use anonvote_proto::proto::anonvote::{Ciphertext as CiphertextMsg, EncryptedBallot as EncryptedBallotMsg, ShuffleProof as ShuffleProofMsg, board_entry, bulletin_board_item};
+use zkp_protocol::{zkp_util, Proof, PublicKey};
+use zkp_protocol::ballot_proof::{BallotProof, BitProof, SumProof};
+use zkp_protocol::board::{self, BoardRecord, Hash, EMPTY_HASH};
+use zkp_protocol::elgamal::{self, Ciphertext, EncryptionKey};
+use zkp_protocol::mixnet::{self, ShuffleProof};
+use zkp_protocol::group::GroupParams;
+
+use std::path::{Path, PathBuf};
+use std::process;
+use num_bigint::BigUint;
+use tonic::Request;
+use tonic::transport::Channel;
+
+const SERVER_URL : &str = "http://127.0.0.1:50051";
+const CEREMONY_DIR : &str = "ceremony";
+const ADMIN_TOKEN_ENV : &str = "ANONVOTE_ADMIN_TOKEN";
+
+const USAGE : &str = "Usage:
+  anonvote_trustee deal --index <i> --threshold <t> --trustees <n>   deal shares to all trustees in the group of the server (offline)
+  anonvote_trustee combine --index <i>                               check the shares dealt to trustee i and make its key share (offline)
+  anonvote_trustee publish --election <id>                           set the election key of the election, needs the admin token
+  anonvote_trustee verify-mix --election <id>                        check the shuffle proof of the mixed ballots
+  anonvote_trustee verify-board --election <id> [--board-key <hex>]  check the hash chain and the signed checkpoint of the bulletin board
+  anonvote_trustee decrypt --election <id> --index <i>               verify the mix and send trustee i's partial decryption of the tally
+Options: --dir <path> (default ceremony), --group <name> or --group-file <path> (group of the server, default rfc5114-1024-160), --server <url> (default http://127.0.0.1:50051), --admin-token <token>";
+
+fn arg_value(name : &str) -> Option<String> {
+    let mut args = std::env::args();
+    args.position(|arg| arg == name)?;
+    args.next()
+}
+
+fn exit_with(message : impl std::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn required_arg(name : &str) -> String {
+    arg_value(name).unwrap_or_else(|| exit_with(format!("Missing {}\n{}", name, USAGE)))
+}
+
+fn number_arg(name : &str) -> u32 {
+    required_arg(name).parse().unwrap_or_else(|_| exit_with(format!("{} must be a number", name)))
+}
+
+fn handle_response<T>(res: Result<T, tonic::Status>) -> T {
+    res.unwrap_or_else(|e| exit_with(format!("{} : {}", e.code(), e.message())))
+}
+
+#[tokio::main]
+async fn main() {
+    let dir = PathBuf::from(arg_value("--dir").unwrap_or(CEREMONY_DIR.to_string()));
+    let server = arg_value("--server").unwrap_or(SERVER_URL.to_string());
+
+    match std::env::args().nth(1).as_deref() {
+        Some("deal") => {
+            let index = number_arg("--index");
+            let group = match (arg_value("--group"), arg_value("--group-file")) {
+                (Some(_), Some(_)) => exit_with("Pass either --group or --group-file"),
+                (Some(name), None) => GroupParams::by_name(&name).unwrap_or_else(|e| exit_with(e)),
+                (None, Some(path)) => ceremony::load_group_file(Path::new(&path)).unwrap_or_else(|e| exit_with(e)),
+                (None, None) => GroupParams::default(),
+            };
+            ceremony::deal(&dir, &group, index, number_arg("--threshold"), number_arg("--trustees")).unwrap_or_else(|e| exit_with(e));
+            println!("Dealt shares into {}, send share_{}_to_<j>.json only to trustee j and publish commitments_{}.json", dir.display(), index, index);
+        }
+        Some("combine") => {
+            let index = number_arg("--index");
+            let election_key = ceremony::combine(&dir, index).unwrap_or_else(|e| exit_with(e));
+            println!("All shares are valid, key share of trustee {} is in {}", index, dir.display());
+            println!("Election key: {}", election_key.encryption_key);
+        }
+        Some("publish") => publish(&dir, &server, required_arg("--election")).await,
+        Some("verify-mix") => {
+            let mut client = connect(&server).await;
+            let election_id = required_arg("--election");
+            let group = election_group(&mut client, &election_id).await;
+            let mixed_ballots = verify_mix(&mut client, election_id, &group).await;
+            println!("Shuffle proof is valid, {} ballots were mixed", mixed_ballots.len());
+        }
+        Some("verify-board") => {
+            let mut client = connect(&server).await;
+            verify_board(&mut client, required_arg("--election"), arg_value("--board-key")).await;
+        }
+        Some("decrypt") => decrypt(&dir, &server, required_arg("--election"), number_arg("--index")).await,
+        _ => exit_with(USAGE),
+    }
+}
+
+async fn publish(dir : &Path, server : &str, election_id : String) {
+    let election_key = ceremony::load_election_key(dir).unwrap_or_else(|e| exit_with(e));
+    let admin_token = arg_value("--admin-token").or(std::env::var(ADMIN_TOKEN_ENV).ok()).unwrap_or_else(|| exit_with("Missing admin token"));
+
+    let hex_bytes = |hex_str : &String| ceremony::from_hex(hex_str).unwrap_or_else(|e| exit_with(e)).to_bytes_be();
+    let mut req = Request::new(SetTrusteesReq {
+        election_id,
+        threshold : election_key.threshold,
+        encryption_key : hex_bytes(&election_key.encryption_key),
+        verification_keys : election_key.verification_keys.iter().map(hex_bytes).collect(),
+    });
+    let authorization = format!("Bearer {}", admin_token).parse().unwrap_or_else(|_| exit_with("Invalid admin token"));
+    req.metadata_mut().insert("authorization", authorization);
+
+    let mut client = AnonVoteAdminClient::connect(server.to_string()).await.unwrap_or_else(|e| exit_with(format!("Could not connect to the server: {}", e)));
+    let election = handle_response(client.set_trustees(req).await).into_inner().election.unwrap_or_default();
+    println!("Election {} is now encrypted with the key of the trustees, {} of them are needed to decrypt the tally", election.id, election.trustee_threshold);
+}
+
+async fn connect(server : &str) -> AnonVoteClient<Channel> {
+    AnonVoteClient::connect(server.to_string()).await.unwrap_or_else(|e| exit_with(format!("Could not connect to the server: {}", e)))
+}
+
+// Ballots, the election key and the board signature are in the group of the election. A built-in group has to come with its own numbers.
+async fn election_group(client : &mut AnonVoteClient<Channel>, election_id : &str) -> GroupParams {
+    let res = handle_response(client.get_parameters(GetParametersReq { election_id : election_id.to_string() }).await).into_inner();
+    let [p, q, alpha] = [&res.p, &res.q, &res.alpha].map(|bytes| BigUint::from_bytes_be(bytes));
+    GroupParams::from_numbers(&res.group, &p, &q, &alpha).unwrap_or_else(|e| exit_with(e))
+}
+
+fn ciphertext(msg : &CiphertextMsg) -> Ciphertext {
+    Ciphertext::from_bytes_be(&msg.c1, &msg.c2)
+}
+
+fn ballots(msgs : &[EncryptedBallotMsg]) -> Vec<Vec<Ciphertext>> {
+    msgs.iter().map(|ballot| ballot.options.iter().map(ciphertext).collect()).collect()
+}
+
+fn shuffle_proof(msg : &ShuffleProofMsg) -> Option<ShuffleProof> {
+    let numbers = |values : &[Vec<u8>]| values.iter().map(|bytes| BigUint::from_bytes_be(bytes)).collect::<Vec<_>>();
+    Some(ShuffleProof::new(
+        numbers(&msg.commitments),
+        numbers(&msg.chain),
+        numbers(&msg.t).try_into().ok()?,
+        msg.t4.iter().map(ciphertext).collect(),
+        numbers(&msg.t_hat),
+        numbers(&msg.s).try_into().ok()?,
+        numbers(&msg.s4),
+        numbers(&msg.s_hat),
+        numbers(&msg.s_prime),
+    ))
+}
+
+// Checks that the mixed ballots hold the same votes as the cast ballots, returns the mixed ballots
+async fn verify_mix(client : &mut AnonVoteClient<Channel>, election_id : String, group : &GroupParams) -> Vec<Vec<Ciphertext>> {
+    let context = zkp_util::election_context(&election_id);
+    let res = handle_response(client.get_mixed_ballots(GetMixedBallotsReq { election_id }).await).into_inner();
+    let key = EncryptionKey::from_bytes_be(group, &res.encryption_key);
+    let (input, output) = (ballots(&res.ballots), ballots(&res.mixed_ballots));
+    let proof = res.proof.as_ref().and_then(shuffle_proof).unwrap_or_else(|| exit_with("Missing shuffle proof"));
+    if !mixnet::verify_shuffle(&key, &input, &output, &proof, &context) {
+        exit_with("Invalid shuffle proof, the mixed ballots don't match the cast ballots");
+    }
+    output
+}
+
+// Cast ballots come with their proofs of validity, the board hash covers them too
+fn ballot_proof(ballot : &EncryptedBallotMsg) -> Option<BallotProof> {
+    let sum = ballot.sum_proof.as_ref()?;
+    let options = ballot.option_proofs.iter().map(|proof| {
+        let [a0, b0, a1, b1, e0, e1, z0, z1] = [&proof.a0, &proof.b0, &proof.a1, &proof.b1, &proof.e0, &proof.e1, &proof.z0, &proof.z1].map(|bytes| BigUint::from_bytes_be(bytes));
+        BitProof::new(a0, b0, a1, b1, e0, e1, z0, z1)
+    }).collect();
+    Some(BallotProof::new(options, SumProof::new(BigUint::from_bytes_be(&sum.a), BigUint::from_bytes_be(&sum.b), BigUint::from_bytes_be(&sum.z))))
+}
+
+fn board_record(record : board_entry::Record, group : &GroupParams) -> BoardRecord {
+    match record {
+        board_entry::Record::Schedule(schedule) => BoardRecord::Schedule {
+            registration_opens_at : schedule.registration_opens_at,
+            opens_at : schedule.opens_at,
+            closes_at : schedule.closes_at,
+        },
+        board_entry::Record::Registration(voter) => BoardRecord::Registration { voter : PublicKey::from_bytes_be(group, &voter.a, &voter.b, &voter.alpha, &voter.beta) },
+        board_entry::Record::Ballot(ballot) => BoardRecord::Ballot {
+            proof : ballot_proof(&ballot),
+            ballot : ballot.options.iter().map(ciphertext).collect(),
+        },
+        board_entry::Record::Tally(tally) => BoardRecord::Tally { votes : tally.votes },
+    }
+}
+
+// Replays the whole bulletin board and checks it against the signed checkpoint.
+// The board key should be compared with the one other observers see, or pinned with --board-key.
+async fn verify_board(client : &mut AnonVoteClient<Channel>, election_id : String, pinned_key : Option<String>) {
+    let req = GetBulletinBoardReq { election_id : election_id.clone(), from_index : 0 };
+    let group = election_group(client, &election_id).await;
+    let mut stream = handle_response(client.get_bulletin_board(req).await).into_inner();
+
+    let mut entries : Vec<(BoardRecord, Hash)> = Vec::new();
+    let (mut ballots, mut registrations) = (0, 0);
+    let checkpoint = loop {
+        let item = handle_response(stream.message().await).and_then(|item| item.item);
+        match item {
+            Some(bulletin_board_item::Item::Entry(entry)) => {
+                let record = board_record(entry.record.unwrap_or_else(|| exit_with(format!("Entry {} has no record", entry.index))), &group);
+                let hash : Hash = entry.hash.try_into().unwrap_or_else(|_| exit_with(format!("Entry {} has an invalid hash", entry.index)));
+                match record {
+                    BoardRecord::Ballot { .. } => ballots += 1,
+                    BoardRecord::Registration { .. } => registrations += 1,
+                    _ => {}
+                }
+                entries.push((record, hash));
+            }
+            Some(bulletin_board_item::Item::Checkpoint(checkpoint)) => break checkpoint,
+            None => exit_with("Board ended without a checkpoint"),
+        }
+    };
+
+    let head = board::verify_entries(&election_id, 0, &EMPTY_HASH, &entries).unwrap_or_else(|| exit_with("Hash chain of the board is broken"));
+    let public_key = BigUint::from_bytes_be(&checkpoint.public_key);
+    let signature = Proof::from_bytes_be(&checkpoint.ka, &checkpoint.kb, &checkpoint.solution);
+    if checkpoint.size != entries.len() as u64 || checkpoint.head != head || !board::verify_checkpoint(&group, &public_key, &election_id, checkpoint.size, &head, &signature) {
+        exit_with("Checkpoint doesn't match the board");
+    }
+    let public_key_hex = hex::encode(&checkpoint.public_key);
+    if pinned_key.is_some_and(|key| key != public_key_hex) {
+        exit_with("Checkpoint was signed with a different board key");
+    }
+    println!("Board of {} is valid: {} entries, {} registrations, {} ballots", election_id, entries.len(), registrations, ballots);
+    println!("Head: {}", hex::encode(head));
+    println!("Board key: {}", public_key_hex);
+}
+
+async fn decrypt(dir : &Path, server : &str, election_id : String, index : u32) {
+    let key_share = ceremony::load_key_share(dir, index).unwrap_or_else(|e| exit_with(e));
+    let mut client = connect(server).await;
+    let group = election_group(&mut client, &election_id).await;
+    if key_share.group() != &group {
+        exit_with(format!("Key share of trustee {} is in group {}, election {} is in group {}", index, key_share.group().name(), election_id, group.name()));
+    }
+
+    let res = client.get_encrypted_tally(GetEncryptedTallyReq { election_id : election_id.clone() }).await;
+    let res = handle_response(res).into_inner();
+    let verification_key = index.checked_sub(1).and_then(|i| res.verification_keys.get(i as usize)).map(|key| BigUint::from_bytes_be(key));
+    if verification_key != Some(key_share.verification_key()) {
+        exit_with(format!("Key share of trustee {} doesn't belong to election {}", index, election_id));
+    }
+
+    // Tally must be the sum of the verified mix, otherwise the trustee could be decrypting anything
+    let mixed_ballots = verify_mix(&mut client, election_id.clone(), &group).await;
+    let sums_match = res.tally.iter().enumerate().all(|(option, sum)| {
+        mixed_ballots.iter().all(|ballot| ballot.len() == res.tally.len()) &&
+            elgamal::sum(key_share.group(), mixed_ballots.iter().map(|ballot| &ballot[option])) == Some(ciphertext(sum))
+    });
+    if !sums_match {
+        exit_with("Encrypted tally is not the sum of the mixed ballots");
+    }
+
+    // Only the sums are decrypted, each with a proof that the key share behind the verification key was used
+    let context = zkp_util::election_context(&election_id);
+    let decryptions = res.tally.iter().map(|sum| {
+        let partial = key_share.partial_decrypt(&ciphertext(sum), &context).unwrap_or_else(|| exit_with("Encrypted tally is not in the group of the key share"));
+        PartialDecryption {
+            d : partial.d().to_bytes_be(),
+            ka : partial.proof().ka().to_bytes_be(),
+            kb : partial.proof().kb().to_bytes_be(),
+            solution : partial.proof().solution().to_bytes_be(),
+        }
+    }).collect();
+
+    let res = client.submit_partial_decryption(SubmitPartialDecryptionReq {
+        election_id,
+        trustee : index,
+        decryptions
+    }).await;
+    match handle_response(res).into_inner().remaining {
+        0 => println!("Tally is decrypted, results are available"),
+        remaining => println!("Partial decryption accepted, {} more trustees are needed", remaining),
+    }
+}
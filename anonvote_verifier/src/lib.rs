@@ -0,0 +1,4 @@
+// Independent verifier of exported election records, only depends on the protocol crate
+// so it doesn't have to trust any of the server code.
+pub mod record;
+pub mod verify;
@@ -0,0 +1,25 @@
+use std::process;
+
+use anonvote_verifier::record::ElectionRecord;
+use anonvote_verifier::verify;
+
+// anonvote_verifier <record>, JSON or binary
+// Prints the report as JSON, exits with 1 if the election doesn't verify and 2 if the record can't be read
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("Usage: anonvote_verifier <record>");
+        process::exit(2);
+    };
+    let record = std::fs::read(&path).map_err(|e| e.to_string())
+        .and_then(|bytes| ElectionRecord::read(&bytes));
+    let record = record.unwrap_or_else(|e| {
+        eprintln!("Could not read the record {}: {}", path, e);
+        process::exit(2);
+    });
+
+    let report = verify::verify(&record);
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    if !report.valid {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,379 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{self, SeqAccess, Visitor};
+use zkp_protocol::{Proof, PublicKey};
+use zkp_protocol::ballot_proof::{BallotProof, BitProof, SumProof};
+use zkp_protocol::board::{BoardRecord, Hash};
+use zkp_protocol::elgamal::Ciphertext;
+use zkp_protocol::group::GroupParams;
+use zkp_protocol::mixnet::ShuffleProof;
+use zkp_protocol::threshold::PartialDecryption;
+
+// Election record, everything needed to check an election after it was tallied without trusting the server:
+// the group, the options, the bulletin board (schedule, registered keys, ballots and tally in order) with a signed
+// checkpoint, the mix of the ballots and the decryption of the tally. The format is described under "Election records" in the README.
+// JSON records have numbers and hashes as big-endian hex strings, binary records are MessagePack after
+// BINARY_MAGIC with the same fields as raw bytes.
+// Version 2 added the validity proofs of the ballots on the board, version 3 the name of the group.
+pub const RECORD_VERSION : u32 = 3;
+pub const BINARY_MAGIC : &[u8] = b"AVREC";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ElectionRecord {
+    pub version : u32,
+    pub election_id : String,
+    pub title : String,
+    pub group : GroupRecord,
+    pub options : Vec<OptionRecord>,
+    pub encryption_key : HexBytes,
+    pub trustees : Option<TrusteesRecord>, // None if the ballots were encrypted with the server key
+    pub board : BulletinBoardRecord,
+    pub mix : MixRecord,
+    pub decryptions : Vec<DecryptionRecord>,
+    pub tally : Vec<u32>,
+}
+
+// Bytes written as hex in JSON and as they are in binary records
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HexBytes(pub Vec<u8>);
+
+// Built-in groups are checked against their name, any other name is a custom group that is checked when it is parsed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GroupRecord {
+    pub name : String,
+    pub p : HexBytes,
+    pub q : HexBytes,
+    pub alpha : HexBytes,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OptionRecord {
+    pub id : String,
+    pub name : String,
+    pub description : String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TrusteesRecord {
+    pub threshold : u32,
+    pub verification_keys : Vec<HexBytes>, // Key of trustee i is at i - 1
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BulletinBoardRecord {
+    pub public_key : HexBytes,
+    pub entries : Vec<EntryRecord>,
+    pub checkpoint : CheckpointRecord,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EntryRecord {
+    pub hash : HexBytes,
+    pub content : EntryContent,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryContent {
+    Schedule { registration_opens_at : u64, opens_at : u64, closes_at : u64 },
+    Registration { voter : [HexBytes; 4] }, // a, b, alpha, beta
+    Ballot { ballot : Vec<[HexBytes; 2]>, #[serde(default)] proof : Option<BallotProofRecord> },
+    Tally { votes : Vec<u32> },
+}
+
+// Proof that the ballot encrypts exactly one vote (see zkp_protocol::ballot_proof)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BallotProofRecord {
+    pub options : Vec<[HexBytes; 8]>, // a0, b0, a1, b1, e0, e1, z0, z1
+    pub sum : [HexBytes; 3], // a, b, z
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CheckpointRecord {
+    pub size : u64,
+    pub head : HexBytes,
+    pub signature : [HexBytes; 3], // ka, kb, solution
+}
+
+// Ballots in the order they were mixed and the mixed ballots that were decrypted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MixRecord {
+    pub ballots : Vec<Vec<[HexBytes; 2]>>,
+    pub mixed_ballots : Vec<Vec<[HexBytes; 2]>>,
+    pub proof : ShuffleProofRecord,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ShuffleProofRecord {
+    pub commitments : Vec<HexBytes>,
+    pub chain : Vec<HexBytes>,
+    pub t : Vec<HexBytes>,
+    pub t4 : Vec<[HexBytes; 2]>,
+    pub t_hat : Vec<HexBytes>,
+    pub s : Vec<HexBytes>,
+    pub s4 : Vec<HexBytes>,
+    pub s_hat : Vec<HexBytes>,
+    pub s_prime : Vec<HexBytes>,
+}
+
+// Decryption of every option's sum by one trustee, the server is trustee 1 of 1 if the election has no trustees
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DecryptionRecord {
+    pub trustee : u32,
+    pub decryptions : Vec<[HexBytes; 4]>, // d, ka, kb, solution
+}
+
+impl Serialize for HexBytes {
+    fn serialize<S : Serializer>(&self, serializer : S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(&self.0))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+struct HexBytesVisitor;
+
+impl<'de> Visitor<'de> for HexBytesVisitor {
+    type Value = HexBytes;
+
+    fn expecting(&self, formatter : &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("hex string or bytes")
+    }
+
+    fn visit_str<E : de::Error>(self, value : &str) -> Result<HexBytes, E> {
+        hex::decode(value).map(HexBytes).map_err(E::custom)
+    }
+
+    fn visit_bytes<E : de::Error>(self, value : &[u8]) -> Result<HexBytes, E> {
+        Ok(HexBytes(value.to_vec()))
+    }
+
+    fn visit_seq<A : SeqAccess<'de>>(self, mut seq : A) -> Result<HexBytes, A::Error> {
+        let mut bytes = Vec::new();
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(HexBytes(bytes))
+    }
+}
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D : Deserializer<'de>>(deserializer : D) -> Result<HexBytes, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(HexBytesVisitor)
+        } else {
+            deserializer.deserialize_bytes(HexBytesVisitor)
+        }
+    }
+}
+
+impl From<&BigUint> for HexBytes {
+    fn from(value : &BigUint) -> Self {
+        HexBytes(value.to_bytes_be())
+    }
+}
+
+impl From<&Hash> for HexBytes {
+    fn from(hash : &Hash) -> Self {
+        HexBytes(hash.to_vec())
+    }
+}
+
+impl HexBytes {
+    pub fn number(&self) -> BigUint {
+        BigUint::from_bytes_be(&self.0)
+    }
+
+    pub fn hash(&self) -> Result<Hash, String> {
+        Hash::try_from(self.0.as_slice()).map_err(|_| "Expected a 32 byte hash".to_string())
+    }
+}
+
+fn numbers(values : &[HexBytes]) -> Vec<BigUint> {
+    values.iter().map(HexBytes::number).collect()
+}
+
+fn triple(values : &[HexBytes]) -> Result<[BigUint; 3], String> {
+    <[BigUint; 3]>::try_from(numbers(values)).map_err(|_| "Expected 3 values".to_string())
+}
+
+pub fn ciphertexts_to_record(ciphertexts : &[Ciphertext]) -> Vec<[HexBytes; 2]> {
+    ciphertexts.iter().map(|ciphertext| [ciphertext.c1().into(), ciphertext.c2().into()]).collect()
+}
+
+pub fn ciphertexts_from_record(ciphertexts : &[[HexBytes; 2]]) -> Vec<Ciphertext> {
+    ciphertexts.iter().map(|[c1, c2]| Ciphertext::new(c1.number(), c2.number())).collect()
+}
+
+pub fn proof_to_record(proof : &Proof) -> [HexBytes; 3] {
+    [proof.ka().into(), proof.kb().into(), proof.solution().into()]
+}
+
+pub fn proof_from_record([ka, kb, solution] : &[HexBytes; 3]) -> Proof {
+    Proof::new(ka.number(), kb.number(), solution.number())
+}
+
+impl ElectionRecord {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut output = BINARY_MAGIC.to_vec();
+        output.extend(rmp_serde::to_vec(self).unwrap());
+        output
+    }
+
+    // Reads a binary or a JSON record
+    pub fn read(bytes : &[u8]) -> Result<ElectionRecord, String> {
+        match bytes.strip_prefix(BINARY_MAGIC) {
+            Some(binary) => rmp_serde::from_slice(binary).map_err(|e| e.to_string()),
+            None => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl GroupRecord {
+    pub fn new(group : &GroupParams) -> GroupRecord {
+        GroupRecord {
+            name : group.name().to_string(),
+            p : group.p().into(),
+            q : group.q().into(),
+            alpha : group.alpha().into(),
+        }
+    }
+
+    // Group everything in the record is computed in
+    pub fn parse(&self) -> Result<GroupParams, String> {
+        GroupParams::from_numbers(&self.name, &self.p.number(), &self.q.number(), &self.alpha.number()).map_err(|e| e.to_string())
+    }
+}
+
+impl EntryContent {
+    pub fn new(record : &BoardRecord) -> EntryContent {
+        match record {
+            BoardRecord::Schedule { registration_opens_at, opens_at, closes_at } => EntryContent::Schedule {
+                registration_opens_at : *registration_opens_at,
+                opens_at : *opens_at,
+                closes_at : *closes_at,
+            },
+            BoardRecord::Registration { voter } => EntryContent::Registration {
+                voter : [voter.a().into(), voter.b().into(), voter.alpha().into(), voter.beta().into()],
+            },
+            BoardRecord::Ballot { ballot, proof } => EntryContent::Ballot {
+                ballot : ciphertexts_to_record(ballot),
+                proof : proof.as_ref().map(BallotProofRecord::new),
+            },
+            BoardRecord::Tally { votes } => EntryContent::Tally {
+                votes : votes.clone(),
+            },
+        }
+    }
+
+    pub fn parse(&self, group : &GroupParams) -> BoardRecord {
+        match self {
+            EntryContent::Schedule { registration_opens_at, opens_at, closes_at } => BoardRecord::Schedule {
+                registration_opens_at : *registration_opens_at,
+                opens_at : *opens_at,
+                closes_at : *closes_at,
+            },
+            EntryContent::Registration { voter : [a, b, alpha, beta] } => BoardRecord::Registration {
+                voter : PublicKey::new(group, a.number(), b.number(), alpha.number(), beta.number()),
+            },
+            EntryContent::Ballot { ballot, proof } => BoardRecord::Ballot {
+                ballot : ciphertexts_from_record(ballot),
+                proof : proof.as_ref().map(BallotProofRecord::parse),
+            },
+            EntryContent::Tally { votes } => BoardRecord::Tally {
+                votes : votes.clone(),
+            },
+        }
+    }
+}
+
+impl EntryRecord {
+    pub fn new(record : &BoardRecord, hash : &Hash) -> EntryRecord {
+        EntryRecord {
+            hash : hash.into(),
+            content : EntryContent::new(record),
+        }
+    }
+
+    pub fn parse(&self, group : &GroupParams) -> Result<(BoardRecord, Hash), String> {
+        Ok((self.content.parse(group), self.hash.hash()?))
+    }
+}
+
+impl BallotProofRecord {
+    pub fn new(proof : &BallotProof) -> BallotProofRecord {
+        BallotProofRecord {
+            options : proof.options().iter().map(|option| {
+                [option.a0(), option.b0(), option.a1(), option.b1(), option.e0(), option.e1(), option.z0(), option.z1()].map(HexBytes::from)
+            }).collect(),
+            sum : [proof.sum().a(), proof.sum().b(), proof.sum().z()].map(HexBytes::from),
+        }
+    }
+
+    pub fn parse(&self) -> BallotProof {
+        let options = self.options.iter().map(|[a0, b0, a1, b1, e0, e1, z0, z1]| {
+            BitProof::new(a0.number(), b0.number(), a1.number(), b1.number(), e0.number(), e1.number(), z0.number(), z1.number())
+        }).collect();
+        let [a, b, z] = &self.sum;
+        BallotProof::new(options, SumProof::new(a.number(), b.number(), z.number()))
+    }
+}
+
+impl CheckpointRecord {
+    pub fn new(size : u64, head : &Hash, signature : &Proof) -> CheckpointRecord {
+        CheckpointRecord {
+            size,
+            head : head.into(),
+            signature : proof_to_record(signature),
+        }
+    }
+}
+
+impl ShuffleProofRecord {
+    pub fn new(proof : &ShuffleProof) -> ShuffleProofRecord {
+        let record = |values : &[BigUint]| values.iter().map(HexBytes::from).collect();
+        ShuffleProofRecord {
+            commitments : record(proof.commitments()),
+            chain : record(proof.chain()),
+            t : record(proof.t()),
+            t4 : ciphertexts_to_record(proof.t4()),
+            t_hat : record(proof.t_hat()),
+            s : record(proof.s()),
+            s4 : record(proof.s4()),
+            s_hat : record(proof.s_hat()),
+            s_prime : record(proof.s_prime()),
+        }
+    }
+
+    pub fn parse(&self) -> Result<ShuffleProof, String> {
+        Ok(ShuffleProof::new(numbers(&self.commitments), numbers(&self.chain), triple(&self.t)?, ciphertexts_from_record(&self.t4),
+            numbers(&self.t_hat), triple(&self.s)?, numbers(&self.s4), numbers(&self.s_hat), numbers(&self.s_prime)))
+    }
+}
+
+impl DecryptionRecord {
+    pub fn new(trustee : u32, decryptions : &[PartialDecryption]) -> DecryptionRecord {
+        DecryptionRecord {
+            trustee,
+            decryptions : decryptions.iter().map(|partial| {
+                let [ka, kb, solution] = proof_to_record(partial.proof());
+                [partial.d().into(), ka, kb, solution]
+            }).collect(),
+        }
+    }
+
+    pub fn parse(&self) -> Vec<PartialDecryption> {
+        self.decryptions.iter().map(|[d, ka, kb, solution]| {
+            let proof = proof_from_record(&[ka.clone(), kb.clone(), solution.clone()]);
+            PartialDecryption::new(self.trustee, d.number(), proof)
+        }).collect()
+    }
+}
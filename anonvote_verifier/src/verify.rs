@@ -0,0 +1,501 @@
+use std::collections::HashSet;
+
+use num_bigint::BigUint;
+use serde::Serialize;
+use zkp_protocol::{zkp_util, PublicKey};
+use zkp_protocol::ballot_proof::BallotProof;
+use zkp_protocol::board::{self, BoardRecord, Hash, EMPTY_HASH};
+use zkp_protocol::elgamal::{self, Ciphertext, EncryptionKey};
+use zkp_protocol::group::GroupParams;
+use zkp_protocol::mixnet;
+use zkp_protocol::params::KeyParams;
+use zkp_protocol::threshold::{self, PartialDecryption};
+
+use crate::record::{self, ElectionRecord, HexBytes, RECORD_VERSION};
+
+// Result of every check, valid only if all of them passed
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub election_id : String,
+    pub valid : bool,
+    pub checks : Vec<Check>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Check {
+    pub name : &'static str,
+    pub passed : bool,
+    pub detail : String,
+}
+
+impl Report {
+    fn add(&mut self, name : &'static str, result : Result<String, String>) {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        self.valid &= passed;
+        self.checks.push(Check { name, passed, detail });
+    }
+
+    pub fn check(&self, name : &str) -> Option<&Check> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+}
+
+// Parts of the record every later check needs, parsed once
+struct Parsed {
+    group : GroupParams,
+    entries : Vec<(BoardRecord, Hash)>,
+    encryption_key : EncryptionKey,
+    ballots : Vec<Vec<Ciphertext>>,
+    mixed_ballots : Vec<Vec<Ciphertext>>,
+}
+
+fn parse(record : &ElectionRecord) -> Result<Parsed, String> {
+    let ballots = |ballots : &[Vec<[HexBytes; 2]>]| ballots.iter().map(|ballot| record::ciphertexts_from_record(ballot)).collect();
+    let group = record.group.parse()?;
+    Ok(Parsed {
+        entries : record.board.entries.iter().map(|entry| entry.parse(&group)).collect::<Result<_, String>>()?,
+        encryption_key : EncryptionKey::new(&group, record.encryption_key.number()),
+        ballots : ballots(&record.mix.ballots),
+        mixed_ballots : ballots(&record.mix.mixed_ballots),
+        group,
+    })
+}
+
+fn board_ballots(entries : &[(BoardRecord, Hash)]) -> Vec<(&Vec<Ciphertext>, Option<&BallotProof>)> {
+    entries.iter().filter_map(|(record, _)| match record {
+        BoardRecord::Ballot { ballot, proof } => Some((ballot, proof.as_ref())),
+        _ => None,
+    }).collect()
+}
+
+fn voters(entries : &[(BoardRecord, Hash)]) -> Vec<&PublicKey> {
+    entries.iter().filter_map(|(record, _)| match record {
+        BoardRecord::Registration { voter } => Some(voter),
+        _ => None,
+    }).collect()
+}
+
+fn check_version(record : &ElectionRecord) -> Result<String, String> {
+    if record.version != RECORD_VERSION {
+        return Err(format!("Unsupported record version {}, expected {}", record.version, RECORD_VERSION));
+    }
+    Ok(format!("Version {}", record.version))
+}
+
+// Built-in group with its own numbers, or a custom group that passes the checks of GroupParams::new
+fn check_group(record : &ElectionRecord) -> Result<String, String> {
+    let group = record.group.parse()?;
+    Ok(format!("{} ({} bit)", group.name(), group.p().bits()))
+}
+
+// Hash chain from the first entry and the checkpoint signed over its size and head
+fn check_board(record : &ElectionRecord, parsed : &Parsed) -> Result<String, String> {
+    let head = board::verify_entries(&record.election_id, 0, &EMPTY_HASH, &parsed.entries);
+    let head = head.ok_or("Hash chain of the board is broken")?;
+    let checkpoint = &record.board.checkpoint;
+    if checkpoint.size != parsed.entries.len() as u64 || checkpoint.head.hash()? != head {
+        return Err("Checkpoint is not for the board in the record".to_string());
+    }
+    let public_key = record.board.public_key.number();
+    let signature = record::proof_from_record(&checkpoint.signature);
+    if !board::verify_checkpoint(&parsed.group, &public_key, &record.election_id, checkpoint.size, &head, &signature) {
+        return Err("Invalid checkpoint signature".to_string());
+    }
+    Ok(format!("{} entries, head {}", checkpoint.size, hex::encode(head)))
+}
+
+// Same rules the server applies when a key is registered, in the group of the election
+fn check_voter_keys(record : &ElectionRecord, parsed : &Parsed) -> Result<String, String> {
+    let voters = voters(&parsed.entries);
+    let params = KeyParams::for_election(&parsed.group, &record.election_id);
+    let mut seen = HashSet::new();
+    for (index, voter) in voters.iter().enumerate() {
+        if !params.valid_key(voter) {
+            return Err(format!("Registered key {} is not valid", index));
+        }
+        if !seen.insert([voter.a(), voter.b(), voter.alpha(), voter.beta()]) {
+            return Err(format!("Key {} is registered twice", index));
+        }
+    }
+    Ok(format!("{} registered keys in {}", voters.len(), params.group().name()))
+}
+
+// Ballots on the board are well formed with valid proofs, not more than registered keys, and exactly the ones that were mixed
+fn check_ballots(record : &ElectionRecord, parsed : &Parsed) -> Result<String, String> {
+    let ballots = board_ballots(&parsed.entries);
+    let group = parsed.encryption_key.group();
+    if let Some(index) = ballots.iter().position(|(ballot, _)| ballot.len() != record.options.len() || !ballot.iter().all(|ciphertext| ciphertext.valid(group))) {
+        return Err(format!("Ballot {} is not a valid ballot for {} options", index, record.options.len()));
+    }
+    if ballots.len() > voters(&parsed.entries).len() {
+        return Err("More ballots than registered keys".to_string());
+    }
+
+    // Same proofs the server checked when the ballot was cast, so every ballot holds exactly one vote
+    let context = zkp_util::validity_context(&record.election_id);
+    for (index, (ballot, proof)) in ballots.iter().enumerate() {
+        let proof = proof.ok_or(format!("Ballot {} has no validity proof", index))?;
+        if !parsed.encryption_key.verify_ballot(ballot, proof, &context) {
+            return Err(format!("Ballot {} has an invalid validity proof", index));
+        }
+    }
+
+    // Mix input is in storage order, compared as sorted lists
+    let encode = |ballot : &Vec<Ciphertext>| BoardRecord::Ballot { ballot : ballot.clone(), proof : None }.encode();
+    let mut posted : Vec<Vec<u8>> = ballots.iter().map(|(ballot, _)| encode(ballot)).collect();
+    let mut mixed : Vec<Vec<u8>> = parsed.ballots.iter().map(encode).collect();
+    posted.sort_unstable();
+    mixed.sort_unstable();
+    if posted.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err("A ballot is on the board twice".to_string());
+    }
+    if posted != mixed {
+        return Err("Mixed ballots are not the ballots on the board".to_string());
+    }
+    Ok(format!("{} ballots", ballots.len()))
+}
+
+fn check_mix(record : &ElectionRecord, parsed : &Parsed) -> Result<String, String> {
+    if !parsed.encryption_key.valid() {
+        return Err("Invalid encryption key".to_string());
+    }
+    let proof = record.mix.proof.parse()?;
+    let context = zkp_util::election_context(&record.election_id);
+    if !mixnet::verify_shuffle(&parsed.encryption_key, &parsed.ballots, &parsed.mixed_ballots, &proof, &context) {
+        return Err("Invalid shuffle proof".to_string());
+    }
+    Ok(format!("{} ballots shuffled", parsed.mixed_ballots.len()))
+}
+
+// Sum of the mixed ballots for every option, what the trustees decrypt
+fn encrypted_tally(record : &ElectionRecord, parsed : &Parsed) -> Result<Vec<Ciphertext>, String> {
+    let group = parsed.encryption_key.group();
+    let tally : Option<Vec<Ciphertext>> = (0..record.options.len()).map(|option| elgamal::sum(group, parsed.mixed_ballots.iter().filter_map(|ballot| ballot.get(option)))).collect();
+    tally.ok_or("Mixed ballots are not in the group of the encryption key".to_string())
+}
+
+// Every partial decryption is proven against the verification key of its trustee, returns the verified ones
+fn check_decryptions(record : &ElectionRecord, parsed : &Parsed) -> Result<Vec<Vec<PartialDecryption>>, String> {
+    // Server decrypts as the single trustee of elections without trustees
+    let (threshold, verification_keys) = match &record.trustees {
+        Some(trustees) => (trustees.threshold, trustees.verification_keys.iter().map(HexBytes::number).collect::<Vec<BigUint>>()),
+        None => (1, vec![parsed.encryption_key.h().clone()]),
+    };
+    let tally = encrypted_tally(record, parsed)?;
+    let context = zkp_util::election_context(&record.election_id);
+
+    let mut trustees = HashSet::new();
+    let mut verified = Vec::new();
+    for decryption in &record.decryptions {
+        let verification_key = (decryption.trustee as usize).checked_sub(1).and_then(|index| verification_keys.get(index));
+        let verification_key = verification_key.ok_or(format!("Unknown trustee {}", decryption.trustee))?;
+        let partials = decryption.parse();
+        let valid = partials.len() == tally.len() && partials.iter().zip(&tally).all(|(partial, ciphertext)| partial.verify(parsed.encryption_key.group(), verification_key, ciphertext, &context));
+        if !valid {
+            return Err(format!("Invalid decryption of trustee {}", decryption.trustee));
+        }
+        if !trustees.insert(decryption.trustee) {
+            return Err(format!("Trustee {} decrypted twice", decryption.trustee));
+        }
+        verified.push(partials);
+    }
+    if (trustees.len() as u32) < threshold {
+        return Err(format!("{} decryptions, {} needed", trustees.len(), threshold));
+    }
+    Ok(verified)
+}
+
+// Counts decrypted from the proven decryptions match the published tally and the tally on the board
+fn check_tally(record : &ElectionRecord, parsed : &Parsed, decryptions : &[Vec<PartialDecryption>]) -> Result<String, String> {
+    let max = u32::try_from(parsed.mixed_ballots.len()).unwrap_or(u32::MAX);
+    let votes = encrypted_tally(record, parsed)?.iter().enumerate().map(|(option, ciphertext)| {
+        let partials : Vec<PartialDecryption> = decryptions.iter().map(|partials| partials[option].clone()).collect();
+        threshold::combine(parsed.encryption_key.group(), ciphertext, &partials, max)
+    }).collect::<Option<Vec<u32>>>();
+    let votes = votes.ok_or("Could not decrypt the tally")?;
+
+    if votes != record.tally {
+        return Err(format!("Decrypted tally {:?} doesn't match the published tally {:?}", votes, record.tally));
+    }
+    let posted = parsed.entries.iter().rev().find_map(|(record, _)| match record {
+        BoardRecord::Tally { votes } => Some(votes),
+        _ => None,
+    });
+    if posted != Some(&votes) {
+        return Err("Tally on the board doesn't match the published tally".to_string());
+    }
+    if votes.iter().map(|count| *count as usize).sum::<usize>() != parsed.mixed_ballots.len() {
+        return Err("Tally doesn't count every ballot once".to_string());
+    }
+    Ok(format!("{:?}", votes))
+}
+
+pub fn verify(record : &ElectionRecord) -> Report {
+    let mut report = Report {
+        election_id : record.election_id.clone(),
+        valid : true,
+        checks : Vec::new(),
+    };
+    report.add("version", check_version(record));
+    report.add("group", check_group(record));
+
+    let parsed = match parse(record) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            report.add("record", Err(format!("Could not parse the record: {}", e)));
+            return report;
+        }
+    };
+    report.add("board", check_board(record, &parsed));
+    report.add("voter_keys", check_voter_keys(record, &parsed));
+    report.add("ballots", check_ballots(record, &parsed));
+    report.add("mix", check_mix(record, &parsed));
+    match check_decryptions(record, &parsed) {
+        Ok(decryptions) => {
+            report.add("decryptions", Ok(format!("{} trustees", decryptions.len())));
+            report.add("tally", check_tally(record, &parsed, &decryptions));
+        }
+        Err(e) => {
+            report.add("decryptions", Err(e));
+            report.add("tally", Err("Not checked without valid decryptions".to_string()));
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use zkp_protocol::SecretKey;
+    use zkp_protocol::board::BoardKey;
+    use zkp_protocol::group::GroupParams;
+    use zkp_protocol::elgamal::DecryptionKey;
+    use zkp_protocol::threshold::{Dealing, KeyShare};
+    use crate::record::{BulletinBoardRecord, CheckpointRecord, DecryptionRecord, EntryRecord, GroupRecord, MixRecord, OptionRecord, ShuffleProofRecord, TrusteesRecord};
+
+    // Board with the given records, hashed and signed
+    fn board_record(group : &GroupParams, election_id : &str, records : &[BoardRecord]) -> BulletinBoardRecord {
+        let key = BoardKey::generate(group);
+        let mut head = EMPTY_HASH;
+        let entries = records.iter().enumerate().map(|(index, record)| {
+            head = board::entry_hash(election_id, index as u64, &head, record);
+            EntryRecord::new(record, &head)
+        }).collect();
+        let signature = key.sign_checkpoint(election_id, records.len() as u64, &head);
+        BulletinBoardRecord {
+            public_key : key.public_key().into(),
+            entries,
+            checkpoint : CheckpointRecord::new(records.len() as u64, &head, &signature),
+        }
+    }
+
+    // Record of a tallied election with 3 options, decrypted by the given key shares (a single share of index 1 is the server key)
+    fn test_record(votes : &[u32], encryption_key : &EncryptionKey, key_shares : &[KeyShare]) -> ElectionRecord {
+        let context = zkp_util::validity_context("test");
+        let ballots = votes.iter().map(|vote| encryption_key.encrypt_vote_with_proof(*vote, 3, &context)).collect();
+        let counts : Vec<u32> = (0..3).map(|option| votes.iter().filter(|vote| **vote == option).count() as u32).collect();
+        ballot_record(ballots, &counts, encryption_key, key_shares)
+    }
+
+    // Record with the given ballots on the board and the published counts
+    fn ballot_record(cast : Vec<(Vec<Ciphertext>, BallotProof)>, counts : &[u32], encryption_key : &EncryptionKey, key_shares : &[KeyShare]) -> ElectionRecord {
+        let election_id = "test";
+        let group = encryption_key.group();
+        let voters : Vec<PublicKey> = cast.iter().map(|_| SecretKey::generate(group).public_key(&KeyParams::for_election(group, election_id))).collect();
+        let ballots : Vec<Vec<Ciphertext>> = cast.iter().map(|(ballot, _)| ballot.clone()).collect();
+        let context = zkp_util::election_context(election_id);
+        let (mixed_ballots, proof) = mixnet::shuffle(encryption_key, &ballots, &context).unwrap();
+
+        let tally : Vec<Ciphertext> = (0..3).map(|option| elgamal::sum(encryption_key.group(), mixed_ballots.iter().map(|ballot| &ballot[option])).unwrap()).collect();
+        let decryptions = key_shares.iter().map(|key_share| {
+            let partials : Vec<PartialDecryption> = tally.iter().map(|ciphertext| key_share.partial_decrypt(ciphertext, &context).unwrap()).collect();
+            DecryptionRecord::new(key_share.index(), &partials)
+        }).collect();
+
+        let mut records = vec![BoardRecord::Schedule { registration_opens_at : 0, opens_at : 10, closes_at : 20 }];
+        records.extend(voters.into_iter().map(|voter| BoardRecord::Registration { voter }));
+        records.extend(cast.into_iter().map(|(ballot, proof)| BoardRecord::Ballot { ballot, proof : Some(proof) }));
+        records.push(BoardRecord::Tally { votes : counts.to_vec() });
+
+        ElectionRecord {
+            version : RECORD_VERSION,
+            election_id : election_id.to_string(),
+            title : "Test election".to_string(),
+            group : GroupRecord::new(group),
+            options : ["Alice", "Bob", "Carol"].iter().enumerate().map(|(index, name)| OptionRecord {
+                id : index.to_string(),
+                name : name.to_string(),
+                description : String::new(),
+            }).collect(),
+            encryption_key : encryption_key.h().into(),
+            trustees : None,
+            board : board_record(group, election_id, &records),
+            mix : MixRecord {
+                ballots : ballots.iter().map(|ballot| record::ciphertexts_to_record(ballot)).collect(),
+                mixed_ballots : mixed_ballots.iter().map(|ballot| record::ciphertexts_to_record(ballot)).collect(),
+                proof : ShuffleProofRecord::new(&proof),
+            },
+            decryptions,
+            tally : counts.to_vec(),
+        }
+    }
+
+    fn server_record(group : &GroupParams, votes : &[u32]) -> ElectionRecord {
+        let key = DecryptionKey::generate(group);
+        test_record(votes, key.encryption_key(), &[KeyShare::new(group, 1, key.secret().clone())])
+    }
+
+    fn failed(report : &Report) -> Vec<&'static str> {
+        report.checks.iter().filter(|check| !check.passed).map(|check| check.name).collect()
+    }
+
+    #[test]
+    fn test_verify() {
+        let record = server_record(&GroupParams::default(), &[0, 2, 2, 1]);
+        let report = verify(&record);
+        assert!(report.valid, "Valid record failed: {:?}", report);
+        assert_eq!(report.check("tally").unwrap().detail, "[1, 1, 2]");
+
+        assert_eq!(ElectionRecord::read(record.to_json().as_bytes()).unwrap(), record, "Record changed after JSON serialization");
+        let binary = record.to_binary();
+        assert!(binary.len() < record.to_json().len() / 2, "Binary record is not compact");
+        assert_eq!(ElectionRecord::read(&binary).unwrap(), record, "Record changed after binary serialization");
+        assert!(ElectionRecord::read(&binary[..binary.len() - 1]).is_err(), "Read a truncated record");
+
+        let report = verify(&server_record(&GroupParams::default(), &[]));
+        assert!(report.valid, "Election without ballots failed: {:?}", report);
+    }
+
+    #[test]
+    fn test_trustees() {
+        let group = &GroupParams::default();
+        let dealings : Vec<Dealing> = (0..3).map(|_| Dealing::generate(group, 2, 3).unwrap()).collect();
+        let commitments : Vec<Vec<BigUint>> = dealings.iter().map(|dealing| dealing.commitments().to_vec()).collect();
+        let key_shares : Vec<KeyShare> = (1..=3).map(|index| {
+            let shares : Vec<BigUint> = dealings.iter().map(|dealing| dealing.shares()[index as usize - 1].clone()).collect();
+            KeyShare::combine(group, index, &shares)
+        }).collect();
+
+        let mut record = test_record(&[1, 1, 0], &threshold::election_key(group, &commitments).unwrap(), &key_shares[1..]);
+        record.trustees = Some(TrusteesRecord {
+            threshold : 2,
+            verification_keys : (1..=3).map(|index| (&threshold::verification_key(group, &commitments, index).unwrap()).into()).collect(),
+        });
+        assert!(verify(&record).valid, "Valid record of trustees failed");
+
+        record.trustees.as_mut().unwrap().threshold = 3;
+        assert_eq!(failed(&verify(&record)), vec!["decryptions", "tally"], "Verified with fewer decryptions than the threshold");
+    }
+
+    // Everything in the record is checked in the group the record names
+    #[test]
+    fn test_groups() {
+        let group = GroupParams::ristretto255();
+        let record = server_record(&group, &[0, 1]);
+        let report = verify(&record);
+        assert!(report.valid, "Valid record failed: {:?}", report);
+        assert_eq!(report.check("voter_keys").unwrap().detail, "2 registered keys in ristretto255");
+        assert_eq!(ElectionRecord::read(record.to_json().as_bytes()).unwrap(), record, "Record changed after JSON serialization");
+
+        // Voter keys of another group than the one of the election
+        let mut other_keys = record.clone();
+        let params = KeyParams::for_election(&GroupParams::default(), "test");
+        let mut records : Vec<BoardRecord> = record.board.entries.iter().map(|entry| entry.content.parse(&group)).collect();
+        for board_record in records.iter_mut() {
+            if let BoardRecord::Registration { voter } = board_record {
+                *voter = SecretKey::generate(params.group()).public_key(&params);
+            }
+        }
+        other_keys.board = board_record(&group, "test", &records);
+        assert_eq!(failed(&verify(&other_keys)), vec!["voter_keys"], "Verified keys of another group");
+
+        // Built-in name with the numbers of another group, and a custom group that is not a group
+        let mut renamed = record.clone();
+        renamed.group.name = "ffdhe2048".to_string();
+        assert_eq!(failed(&verify(&renamed)), vec!["group", "record"]);
+        let mut custom = server_record(&GroupParams::default(), &[1]);
+        custom.group.name = "custom".to_string();
+        assert!(verify(&custom).valid, "Record in a custom group failed");
+        custom.group.alpha = HexBytes(vec![1]);
+        assert_eq!(failed(&verify(&custom)), vec!["group", "record"], "Verified a custom group with a trivial generator");
+    }
+
+    #[test]
+    fn test_tampered() {
+        let record = server_record(&GroupParams::default(), &[0, 2, 1]);
+
+        let mut changed = record.clone();
+        changed.tally = vec![0, 2, 1];
+        assert_eq!(failed(&verify(&changed)), vec!["tally"], "Verified a changed tally");
+
+        // Dropping a ballot after the board was signed breaks the chain, the ballot list and the mix
+        let mut dropped = record.clone();
+        dropped.board.entries.remove(4);
+        assert_eq!(failed(&verify(&dropped)), vec!["board", "ballots"]);
+        let mut dropped = record.clone();
+        dropped.mix.mixed_ballots.pop();
+        assert!(failed(&verify(&dropped)).contains(&"mix"), "Verified a mix with a dropped ballot");
+
+        // Board re-signed by a server that replaced a ballot with its own
+        let mut replaced = record.clone();
+        let group = GroupParams::default();
+        let entries : Vec<(BoardRecord, Hash)> = record.board.entries.iter().map(|entry| entry.parse(&group).unwrap()).collect();
+        let mut records : Vec<BoardRecord> = entries.into_iter().map(|(record, _)| record).collect();
+        let encryption_key = EncryptionKey::new(&group, record.encryption_key.number());
+        let (ballot, proof) = encryption_key.encrypt_vote_with_proof(1, 3, &zkp_util::validity_context("test"));
+        records[4] = BoardRecord::Ballot { ballot, proof : Some(proof) };
+        replaced.board = board_record(&group, "test", &records);
+        assert_eq!(failed(&verify(&replaced)), vec!["ballots"], "Verified a board with a replaced ballot");
+
+        let mut invalid_key = record.clone();
+        records[1] = BoardRecord::Registration { voter : PublicKey::new(&group, BigUint::from(2u32), BigUint::from(2u32), BigUint::from(3u32), BigUint::from(3u32)) };
+        invalid_key.board = board_record(&group, "test", &records);
+        assert!(failed(&verify(&invalid_key)).contains(&"voter_keys"), "Verified an invalid voter key");
+
+        let mut version = record.clone();
+        version.version = RECORD_VERSION + 1;
+        assert_eq!(failed(&verify(&version)), vec!["version"]);
+    }
+
+    // Ballots have to come with valid proofs even if the board and the mix are consistent
+    #[test]
+    fn test_ballot_proofs() {
+        let group = GroupParams::default();
+        let key = DecryptionKey::generate(&group);
+        let encryption_key = key.encryption_key();
+        let key_shares = [KeyShare::new(&group, 1, key.secret().clone())];
+        let context = zkp_util::validity_context("test");
+        let ballots = || [0, 1].map(|vote| encryption_key.encrypt_vote_with_proof(vote, 3, &context)).to_vec();
+
+        // Ballot with 5 votes for the first option, posted with the proof of another ballot
+        let mut stuffed = ballots();
+        let (_, proof) = encryption_key.encrypt_vote_with_proof(0, 3, &context);
+        stuffed.push(([5, 0, 0].map(|vote| encryption_key.encrypt(vote)).to_vec(), proof));
+        let report = verify(&ballot_record(stuffed, &[6, 1, 0], encryption_key, &key_shares));
+        assert!(failed(&report).contains(&"ballots"), "Verified a ballot that encrypts 5 votes");
+        assert_eq!(report.check("ballots").unwrap().detail, "Ballot 2 has an invalid validity proof");
+
+        // Valid proof, but made for another election
+        let mut other = ballots();
+        other.push(encryption_key.encrypt_vote_with_proof(2, 3, &zkp_util::validity_context("other")));
+        assert_eq!(failed(&verify(&ballot_record(other, &[1, 1, 1], encryption_key, &key_shares))), vec!["ballots"], "Verified a proof of another election");
+
+        // Ballot without a proof, and the same ballot cast twice
+        let record = ballot_record(ballots(), &[1, 1, 0], encryption_key, &key_shares);
+        let mut records : Vec<BoardRecord> = record.board.entries.iter().map(|entry| entry.content.parse(&group)).collect();
+        if let BoardRecord::Ballot { proof, .. } = &mut records[3] {
+            *proof = None;
+        }
+        let mut missing = record.clone();
+        missing.board = board_record(&group, "test", &records);
+        assert_eq!(failed(&verify(&missing)), vec!["ballots"], "Verified a ballot without a proof");
+
+        let mut twice = ballots();
+        twice.push(twice[0].clone());
+        let report = verify(&ballot_record(twice, &[2, 1, 0], encryption_key, &key_shares));
+        assert_eq!(report.check("ballots").unwrap().detail, "A ballot is on the board twice");
+    }
+}
@@ -1,12 +1,26 @@
 use wasm_bindgen::prelude::*;
 use zkp_protocol::*;
+use zkp_protocol::blind_signature::{self, SignerPublicKey};
+use zkp_protocol::elgamal::{Ciphertext, EncryptionKey};
+use zkp_protocol::ballot_proof::{BallotProof, BitProof, SumProof};
+use zkp_protocol::board::Hash;
+use zkp_protocol::merkle;
+use zkp_protocol::group::{GroupError, GroupParams};
+use zkp_protocol::scalar::SecretScalar;
 use num_bigint::BigUint;
+use zeroize::Zeroize;
+use std::sync::Mutex;
 
 // wasm-pack build --target web
 
+// Custom groups from GetParameters, see register_group
+static GROUPS : Mutex<Vec<GroupParams>> = Mutex::new(Vec::new());
+
+// Keys and ballots name their group, a built-in one or one passed to register_group before. An unknown name is an error.
 #[wasm_bindgen]
 pub struct SecretKeyWasm {
     secret : Vec<u8>,
+    group : GroupParams,
 }
 
 #[wasm_bindgen]
@@ -15,6 +29,7 @@ pub struct PublicKeyWasm {
     b : Vec<u8>,
     alpha : Vec<u8>,
     beta : Vec<u8>,
+    group : GroupParams,
 }
 
 #[wasm_bindgen]
@@ -24,50 +39,163 @@ pub struct ChallengeRequestWasm {
     kb : Vec<u8>
 }
 
+// The secret and the nonce are zeroed when JS frees the object
+impl Drop for SecretKeyWasm {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl Drop for ChallengeRequestWasm {
+    fn drop(&mut self) {
+        self.k.zeroize();
+    }
+}
+
+#[wasm_bindgen]
+pub struct ProofWasm {
+    ka : Vec<u8>,
+    kb : Vec<u8>,
+    solution : Vec<u8>
+}
+
+// Ballot encrypted with the election key, one ciphertext per option, with the proofs that it is valid
+#[wasm_bindgen]
+pub struct BallotWasm {
+    options : Vec<Ciphertext>,
+    proof : BallotProof
+}
+
+#[wasm_bindgen]
+pub struct BitProofWasm {
+    proof : BitProof
+}
+
+#[wasm_bindgen]
+pub struct SumProofWasm {
+    proof : SumProof
+}
+
+// Group of the election as GetParameters publishes it, name and numbers. Has to be called before a custom group is used,
+// a built-in group is only accepted with its own numbers.
+#[wasm_bindgen]
+pub fn register_group(name : String, p : Vec<u8>, q : Vec<u8>, alpha : Vec<u8>) -> Result<(), JsError> {
+    let [p, q, alpha] = [&p, &q, &alpha].map(|bytes| BigUint::from_bytes_be(bytes));
+    let group = GroupParams::from_numbers(&name, &p, &q, &alpha)?;
+    let mut groups = GROUPS.lock().unwrap_or_else(|e| e.into_inner());
+    groups.retain(|known| known.name() != group.name());
+    groups.push(group);
+    Ok(())
+}
+
+// Proofs of validity are bound to the election, they are published on the board with the ballot.
+// Ballots are encrypted in the group of the election from GetParameters.
+// Undefined if the encryption key is not an element of that group.
+#[wasm_bindgen]
+pub fn encrypt_ballot(encryption_key : Vec<u8>, vote : u32, option_count : usize, election_id : String, group : String) -> Result<Option<BallotWasm>, JsError> {
+    let encryption_key = EncryptionKey::from_bytes_be(&group_params(&group)?, &encryption_key);
+    if !encryption_key.valid() {
+        return Ok(None);
+    }
+    let context = zkp_util::validity_context(&election_id);
+    let (options, proof) = encryption_key.encrypt_vote_with_proof(vote, option_count, &context);
+    Ok(Some(BallotWasm {
+        options,
+        proof
+    }))
+}
+
+// Receipt from ValidateVote, CastVote or GetInclusionProof
+#[wasm_bindgen]
+pub struct ReceiptWasm {
+    ballot_hash : Vec<u8>,
+    leaf_index : u64,
+    tree_size : u64,
+    root : Vec<u8>,
+    public_key : Vec<u8>,
+    signature : ProofWasm
+}
+
+#[wasm_bindgen]
+pub struct SignerPublicKeyWasm {
+    n : Vec<u8>,
+    e : Vec<u8>
+}
+
+#[wasm_bindgen]
+pub struct BlindedCredentialWasm {
+    blinded : Vec<u8>,
+    r : Vec<u8>
+}
+
 #[wasm_bindgen]
 impl SecretKeyWasm {
-    pub fn new(secret : Vec<u8>) -> SecretKeyWasm {
-        SecretKeyWasm {
-            secret
-        }
+    pub fn new(secret : Vec<u8>, group : String) -> Result<SecretKeyWasm, JsError> {
+        Ok(SecretKeyWasm {
+            secret,
+            group : group_params(&group)?
+        })
     }
 
-    pub fn generate() -> SecretKeyWasm {
-        let secret = SecretKey::generate();
-        SecretKeyWasm {
-            secret : secret.secret().to_bytes_be()
-        }
+    pub fn generate(group : String) -> Result<SecretKeyWasm, JsError> {
+        let group = group_params(&group)?;
+        let secret = SecretKey::generate(&group);
+        Ok(SecretKeyWasm {
+            secret : secret.secret().to_bytes_be(),
+            group
+        })
     }
 
-    pub fn generate_public_key(&self) -> PublicKeyWasm {
-        let secret = self.parse();
-        let public = secret.generate_public_key();
-        PublicKeyWasm {
+    // Key with the alpha and beta from GetParameters, undefined if they are not valid parameters of the group of this key
+    pub fn generate_public_key(&self, alpha : Vec<u8>, beta : Vec<u8>) -> Option<PublicKeyWasm> {
+        let params = params::KeyParams::from_bytes_be(&self.group, &alpha, &beta);
+        if !params.valid() {
+            return None;
+        }
+        let public = self.parse().public_key(&params);
+        Some(PublicKeyWasm {
             a : public.a().to_bytes_be(),
             b : public.b().to_bytes_be(),
             alpha : public.alpha().to_bytes_be(),
             beta : public.beta().to_bytes_be(),
-        }
+            group : self.group.clone(),
+        })
     }
 
-    pub fn solve(&self, k : Vec<u8>, challenge : Vec<u8>) -> Vec<u8> {
-        let k = BigUint::from_bytes_be(&k);
-        let challenge = BigUint::from_bytes_be(&challenge);
+    pub fn solve(&self, mut k : Vec<u8>, challenge : Vec<u8>) -> Vec<u8> {
         let secret = self.parse();
-        secret.solve(&k, &challenge).to_bytes_be()
+        let nonce = SecretScalar::from_bytes_be(&k, secret.group().q());
+        k.zeroize();
+        secret.solve(&nonce, &BigUint::from_bytes_be(&challenge)).to_bytes_be()
+    }
+
+    // Proof for the CastVote request, bound to the election and the ballot
+    pub fn prove(&self, public_key : &PublicKeyWasm, election_id : String, ballot : &BallotWasm) -> ProofWasm {
+        let context = zkp_util::ballot_context(&election_id, &ballot.options);
+        let proof = self.parse().prove(&public_key.parse(), &context);
+        ProofWasm {
+            ka : proof.ka().to_bytes_be(),
+            kb : proof.kb().to_bytes_be(),
+            solution : proof.solution().to_bytes_be()
+        }
     }
 
     pub fn secret(&self) -> Vec<u8> {
         self.secret.clone()
     }
+
+    pub fn group(&self) -> String {
+        self.group.name().to_string()
+    }
 }
 
 #[wasm_bindgen]
 impl PublicKeyWasm {
-    pub fn new(a : Vec<u8>, b : Vec<u8>, alpha : Vec<u8>, beta : Vec<u8>) -> PublicKeyWasm {
-        PublicKeyWasm {
-            a,b,alpha,beta
-        }
+    pub fn new(a : Vec<u8>, b : Vec<u8>, alpha : Vec<u8>, beta : Vec<u8>, group : String) -> Result<PublicKeyWasm, JsError> {
+        Ok(PublicKeyWasm {
+            a,b,alpha,beta,
+            group : group_params(&group)?
+        })
     }
 
     pub fn generate_challenge_request(&self) -> ChallengeRequestWasm {
@@ -95,6 +223,10 @@ impl PublicKeyWasm {
     pub fn beta(&self) -> Vec<u8> {
         self.beta.clone()
     }
+
+    pub fn group(&self) -> String {
+        self.group.name().to_string()
+    }
 }
 
 #[wasm_bindgen]
@@ -112,37 +244,319 @@ impl ChallengeRequestWasm {
     }
 }
 
+#[wasm_bindgen]
+impl ProofWasm {
+    pub fn ka(&self) -> Vec<u8> {
+        self.ka.clone()
+    }
+
+    pub fn kb(&self) -> Vec<u8> {
+        self.kb.clone()
+    }
+
+    pub fn solution(&self) -> Vec<u8> {
+        self.solution.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl BallotWasm {
+    pub fn option_count(&self) -> usize {
+        self.options.len()
+    }
+
+    pub fn c1(&self, option : usize) -> Vec<u8> {
+        self.options[option].c1().to_bytes_be()
+    }
+
+    pub fn c2(&self, option : usize) -> Vec<u8> {
+        self.options[option].c2().to_bytes_be()
+    }
+
+    pub fn option_proof(&self, option : usize) -> BitProofWasm {
+        BitProofWasm {
+            proof : self.proof.options()[option].clone()
+        }
+    }
+
+    pub fn sum_proof(&self) -> SumProofWasm {
+        SumProofWasm {
+            proof : self.proof.sum().clone()
+        }
+    }
+
+    // Hash the receipt of the ballot should have
+    pub fn hash(&self, election_id : String) -> Vec<u8> {
+        merkle::ballot_hash(&election_id, &self.options).to_vec()
+    }
+}
+
+#[wasm_bindgen]
+impl BitProofWasm {
+    pub fn a0(&self) -> Vec<u8> {
+        self.proof.a0().to_bytes_be()
+    }
+
+    pub fn b0(&self) -> Vec<u8> {
+        self.proof.b0().to_bytes_be()
+    }
+
+    pub fn a1(&self) -> Vec<u8> {
+        self.proof.a1().to_bytes_be()
+    }
+
+    pub fn b1(&self) -> Vec<u8> {
+        self.proof.b1().to_bytes_be()
+    }
+
+    pub fn e0(&self) -> Vec<u8> {
+        self.proof.e0().to_bytes_be()
+    }
+
+    pub fn e1(&self) -> Vec<u8> {
+        self.proof.e1().to_bytes_be()
+    }
+
+    pub fn z0(&self) -> Vec<u8> {
+        self.proof.z0().to_bytes_be()
+    }
+
+    pub fn z1(&self) -> Vec<u8> {
+        self.proof.z1().to_bytes_be()
+    }
+}
+
+#[wasm_bindgen]
+impl SumProofWasm {
+    pub fn a(&self) -> Vec<u8> {
+        self.proof.a().to_bytes_be()
+    }
+
+    pub fn b(&self) -> Vec<u8> {
+        self.proof.b().to_bytes_be()
+    }
+
+    pub fn z(&self) -> Vec<u8> {
+        self.proof.z().to_bytes_be()
+    }
+}
+
+#[wasm_bindgen]
+impl SignerPublicKeyWasm {
+    pub fn new(n : Vec<u8>, e : Vec<u8>) -> SignerPublicKeyWasm {
+        SignerPublicKeyWasm {
+            n, e
+        }
+    }
+
+    // Blinded credential goes to ValidateID, keep the blinding factor to unblind the signature
+    pub fn blind_credential(&self, public_key : &PublicKeyWasm, election_id : String) -> BlindedCredentialWasm {
+        let credential = blind_signature::credential(&election_id, &public_key.parse());
+        let (blinded, r) = self.parse().blind(&credential);
+        BlindedCredentialWasm {
+            blinded : blinded.to_bytes_be(),
+            r : r.to_bytes_be()
+        }
+    }
+
+    // Returns empty signature if it can't be unblinded
+    pub fn unblind(&self, blind_signature : Vec<u8>, r : Vec<u8>) -> Vec<u8> {
+        let blind_signature = BigUint::from_bytes_be(&blind_signature);
+        let r = BigUint::from_bytes_be(&r);
+        self.parse().unblind(&blind_signature, &r).map(|signature| signature.to_bytes_be()).unwrap_or_default()
+    }
+}
+
+#[wasm_bindgen]
+impl BlindedCredentialWasm {
+    pub fn blinded(&self) -> Vec<u8> {
+        self.blinded.clone()
+    }
+
+    pub fn r(&self) -> Vec<u8> {
+        self.r.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl ReceiptWasm {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(ballot_hash : Vec<u8>, leaf_index : u64, tree_size : u64, root : Vec<u8>, public_key : Vec<u8>, ka : Vec<u8>, kb : Vec<u8>, solution : Vec<u8>) -> ReceiptWasm {
+        ReceiptWasm {
+            ballot_hash, leaf_index, tree_size, root, public_key,
+            signature : ProofWasm { ka, kb, solution }
+        }
+    }
+
+    // Root has to be signed with the board key, pass the key the client trusts and the group of the election
+    pub fn verify_signature(&self, election_id : String, public_key : Vec<u8>, group : String) -> Result<bool, JsError> {
+        let group = group_params(&group)?;
+        let Some(root) = hash(&self.root) else {
+            return Ok(false);
+        };
+        let signature = Proof::from_bytes_be(&self.signature.ka, &self.signature.kb, &self.signature.solution);
+        Ok(public_key == self.public_key && merkle::verify_root(&group, &BigUint::from_bytes_be(&public_key), &election_id, self.tree_size, &root, &signature))
+    }
+
+    // Path from GetInclusionProof as concatenated 32 byte hashes
+    pub fn verify_inclusion(&self, path : Vec<u8>) -> bool {
+        let path : Option<Vec<Hash>> = path.chunks(32).map(hash).collect();
+        match (hash(&self.ballot_hash), hash(&self.root), path) {
+            (Some(ballot_hash), Some(root), Some(path)) => merkle::verify_inclusion(&ballot_hash, self.leaf_index, self.tree_size, &path, &root),
+            _ => false,
+        }
+    }
+
+    pub fn ballot_hash(&self) -> Vec<u8> {
+        self.ballot_hash.clone()
+    }
+
+    pub fn leaf_index(&self) -> u64 {
+        self.leaf_index
+    }
+
+    pub fn tree_size(&self) -> u64 {
+        self.tree_size
+    }
+
+    pub fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+}
+
+// Keys saved before groups were added have no group name, they are in the default group
+fn group_params(name : &str) -> Result<GroupParams, GroupError> {
+    if name.is_empty() {
+        return Ok(GroupParams::default());
+    }
+    GroupParams::by_name(name).or_else(|e| {
+        let groups = GROUPS.lock().unwrap_or_else(|e| e.into_inner());
+        groups.iter().find(|group| group.name() == name).cloned().ok_or(e)
+    })
+}
+
+fn hash(bytes : &[u8]) -> Option<Hash> {
+    Hash::try_from(bytes).ok()
+}
+
 impl SecretKeyWasm {
     pub fn parse(&self) -> SecretKey {
-        SecretKey::from_bytes_be(&self.secret)
+        SecretKey::from_bytes_be(&self.group, &self.secret)
     }
 }
 
 impl PublicKeyWasm {
     pub fn parse(&self) -> PublicKey {
-        PublicKey::from_bytes_be(&self.a, &self.b, &self.alpha, &self.beta)
+        PublicKey::from_bytes_be(&self.group, &self.a, &self.b, &self.alpha, &self.beta)
+    }
+}
+
+impl SignerPublicKeyWasm {
+    pub fn parse(&self) -> SignerPublicKey {
+        SignerPublicKey::from_bytes_be(&self.n, &self.e)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use zkp_protocol::group::DEFAULT_GROUP;
+
+    fn generate_public_key(secret_wasm : &SecretKeyWasm) -> PublicKeyWasm {
+        let params = params::KeyParams::for_election(&secret_wasm.group, "election");
+        secret_wasm.generate_public_key(params.alpha().to_bytes_be(), params.beta().to_bytes_be()).unwrap()
+    }
 
     #[test]
     fn test_random() {
-        for _ in 0..10 {
-            let secret_wasm = SecretKeyWasm::generate();
-            let public_wasm = secret_wasm.generate_public_key();
+        for group in [DEFAULT_GROUP, DEFAULT_GROUP, "ristretto255", "ristretto255"] {
+            let secret_wasm = SecretKeyWasm::generate(group.to_string()).unwrap();
+            let public_wasm = generate_public_key(&secret_wasm);
             let challenge_req_wasm = public_wasm.generate_challenge_request();
-            let challenge = zkp_util::generate_challenge();
+            let challenge = public_wasm.parse().group().random_exponent();
             let challenge_wasm = challenge.to_bytes_be();
             let solution_wasm = secret_wasm.solve(challenge_req_wasm.k(), challenge_wasm);
             let ka = BigUint::from_bytes_be(&challenge_req_wasm.ka());
             let kb = BigUint::from_bytes_be(&challenge_req_wasm.kb());
             let solution = BigUint::from_bytes_be(&solution_wasm);
             let result = public_wasm.parse().verify(&ka, &kb, &challenge, &solution);
-            assert!(result);
+            assert!(result, "Solution in {} was not verified", group);
+            assert_eq!(public_wasm.group(), group);
             
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_prove() {
+        let secret_wasm = SecretKeyWasm::generate(DEFAULT_GROUP.to_string()).unwrap();
+        let public_wasm = generate_public_key(&secret_wasm);
+        assert!(secret_wasm.generate_public_key(vec!(1), vec!(1)).is_none(), "Key was made with invalid parameters");
+
+        let decryption_key = zkp_protocol::elgamal::DecryptionKey::generate(&GroupParams::ristretto255());
+        let ballot_wasm = encrypt_ballot(decryption_key.encryption_key().h().to_bytes_be(), 2, 3, "election".to_string(), "ristretto255".to_string()).unwrap().unwrap();
+        assert!(encrypt_ballot(vec!(1), 2, 3, "election".to_string(), "ristretto255".to_string()).unwrap().is_none(), "Ballot was encrypted with an invalid key");
+        assert_eq!(ballot_wasm.option_count(), 3);
+        let ciphertext = Ciphertext::from_bytes_be(&ballot_wasm.c1(2), &ballot_wasm.c2(2));
+        assert_eq!(decryption_key.decrypt(&ciphertext, 1), Some(1));
+
+        let option_proofs = (0..3).map(|option| {
+            let proof = ballot_wasm.option_proof(option);
+            let [a0, b0, a1, b1, e0, e1, z0, z1] = [proof.a0(), proof.b0(), proof.a1(), proof.b1(), proof.e0(), proof.e1(), proof.z0(), proof.z1()].map(|bytes| BigUint::from_bytes_be(&bytes));
+            BitProof::new(a0, b0, a1, b1, e0, e1, z0, z1)
+        }).collect();
+        let sum_wasm = ballot_wasm.sum_proof();
+        let sum_proof = SumProof::new(BigUint::from_bytes_be(&sum_wasm.a()), BigUint::from_bytes_be(&sum_wasm.b()), BigUint::from_bytes_be(&sum_wasm.z()));
+        let context = zkp_util::validity_context("election");
+        assert!(decryption_key.encryption_key().verify_ballot(&ballot_wasm.options, &BallotProof::new(option_proofs, sum_proof), &context));
+        let proof_wasm = secret_wasm.prove(&public_wasm, "election".to_string(), &ballot_wasm);
+        let proof = Proof::from_bytes_be(&proof_wasm.ka(), &proof_wasm.kb(), &proof_wasm.solution());
+        assert!(public_wasm.parse().verify_proof(&proof, &zkp_util::ballot_context("election", &ballot_wasm.options)));
+        assert!(!public_wasm.parse().verify_proof(&proof, &zkp_util::ballot_context("other", &ballot_wasm.options)));
+    }
+    #[test]
+    fn test_blind_credential() {
+        let signer = blind_signature::SignerSecretKey::generate(512);
+        let signer_wasm = SignerPublicKeyWasm::new(signer.public_key().n().to_bytes_be(), signer.public_key().e().to_bytes_be());
+        let public_wasm = generate_public_key(&SecretKeyWasm::generate(DEFAULT_GROUP.to_string()).unwrap());
+
+        let blinded_wasm = signer_wasm.blind_credential(&public_wasm, "election".to_string());
+        let blind_signature = signer.sign_blinded(&BigUint::from_bytes_be(&blinded_wasm.blinded()));
+        let signature_wasm = signer_wasm.unblind(blind_signature.to_bytes_be(), blinded_wasm.r());
+        let credential = blind_signature::credential("election", &public_wasm.parse());
+        assert!(signer.public_key().verify(&credential, &BigUint::from_bytes_be(&signature_wasm)));
+    }
+
+    // Errors become JsError only on wasm, the lookup is tested on its own
+    #[test]
+    fn test_register_group() {
+        assert_eq!(group_params("wasm-custom").unwrap_err(), GroupError::UnknownGroup("wasm-custom".to_string()), "Unknown group was used");
+        assert_eq!(group_params("").unwrap(), GroupParams::default(), "Key without a group is not in the default group");
+
+        assert!(register_group("wasm-custom".to_string(), vec!(23), vec!(11), vec!(4)).is_ok());
+        let secret_wasm = SecretKeyWasm::generate("wasm-custom".to_string()).unwrap();
+        let public_wasm = generate_public_key(&secret_wasm);
+        assert_eq!((public_wasm.group(), public_wasm.parse().group().p()), ("wasm-custom".to_string(), &BigUint::from(23u32)));
+    }
+
+    #[test]
+    fn test_receipt() {
+        let group = GroupParams::default();
+        let decryption_key = zkp_protocol::elgamal::DecryptionKey::generate(&group);
+        let ballots : Vec<BallotWasm> = (0..3).map(|vote| encrypt_ballot(decryption_key.encryption_key().h().to_bytes_be(), vote, 3, "election".to_string(), DEFAULT_GROUP.to_string()).unwrap().unwrap()).collect();
+        let ballot_hashes : Vec<Hash> = ballots.iter().map(|ballot| hash(&ballot.hash("election".to_string())).unwrap()).collect();
+
+        let board_key = zkp_protocol::board::BoardKey::generate(&group);
+        let root = merkle::root(&ballot_hashes);
+        let signature = merkle::sign_root(&board_key, "election", 3, &root);
+        let public_key = board_key.public_key().to_bytes_be();
+        let receipt = ReceiptWasm::new(ballot_hashes[0].to_vec(), 0, 3, root.to_vec(), public_key.clone(),
+            signature.ka().to_bytes_be(), signature.kb().to_bytes_be(), signature.solution().to_bytes_be());
+        assert!(receipt.verify_signature("election".to_string(), public_key.clone(), DEFAULT_GROUP.to_string()).unwrap());
+        assert!(!receipt.verify_signature("election".to_string(), zkp_protocol::board::BoardKey::generate(&group).public_key().to_bytes_be(), DEFAULT_GROUP.to_string()).unwrap(), "Verified with a key the client doesn't trust");
+        assert!(!receipt.verify_signature("election".to_string(), public_key.clone(), "ristretto255".to_string()).unwrap(), "Verified in another group");
+
+        let path = merkle::inclusion_proof(&ballot_hashes, 0).unwrap().concat();
+        assert!(receipt.verify_inclusion(path.clone()));
+        assert!(!receipt.verify_inclusion(path[..32].to_vec()), "Verified with a missing sibling");
+    }
+}
@@ -0,0 +1,344 @@
+// Proofs that an encrypted ballot is valid, without revealing the vote.
+// Every option gets a disjunctive Chaum-Pedersen proof (Cramer-Damgard-Schoenmakers) that it encrypts 0 or 1:
+// the voter proves the real case and simulates the other one, the verifier can't tell which one is real.
+// The product of all options encrypts the number of chosen options, a plain Chaum-Pedersen proof shows that it is 1.
+// All challenges are derived with Fiat-Shamir, the context binds the proofs to the election.
+
+use num_bigint::BigUint;
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::elgamal::{self, Ciphertext, EncryptionKey};
+use crate::group::GroupParams;
+use crate::scalar::SecretScalar;
+
+// Proof that the ciphertext encrypts 0 or 1.
+// Case j proves log_alpha(c1) == log_h(c2 / alpha^j), commitments (a, b), challenge e and response z.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitProof {
+    a0 : BigUint,
+    b0 : BigUint,
+    a1 : BigUint,
+    b1 : BigUint,
+    e0 : BigUint,
+    e1 : BigUint,
+    z0 : BigUint,
+    z1 : BigUint,
+}
+
+// Proof that the ciphertext encrypts 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct SumProof {
+    a : BigUint,
+    b : BigUint,
+    z : BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BallotProof {
+    options : Vec<BitProof>,
+    sum : SumProof,
+}
+
+// c2 / alpha^message, encryption of 0 if the ciphertext encrypts the message
+fn shifted(group : &GroupParams, ciphertext : &Ciphertext, message : u32) -> Option<BigUint> {
+    group.div(ciphertext.c2(), &group.exp(group.alpha(), &BigUint::from(message))?)
+}
+
+// Swaps two group elements if choice is set. Both are padded to the same length, their lengths are public anyway.
+fn conditional_swap(x : &mut BigUint, y : &mut BigUint, choice : Choice) {
+    let len = (x.bits().max(y.bits()) as usize).div_ceil(8).max(1);
+    let padded = |value : &BigUint| {
+        let bytes = value.to_bytes_be();
+        let mut padded = vec![0u8; len - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        padded
+    };
+    let (mut x_bytes, mut y_bytes) = (padded(x), padded(y));
+    for (x, y) in x_bytes.iter_mut().zip(y_bytes.iter_mut()) {
+        u8::conditional_swap(x, y, choice);
+    }
+    *x = BigUint::from_bytes_be(&x_bytes);
+    *y = BigUint::from_bytes_be(&y_bytes);
+}
+
+// Checks alpha^z == a * c1^e and h^z == b * d^e
+fn check(key : &EncryptionKey, c1 : &BigUint, d : &BigUint, a : &BigUint, b : &BigUint, e : &BigUint, z : &BigUint) -> bool {
+    let group = key.group();
+    let side = |base : &BigUint, commitment : &BigUint, value : &BigUint| {
+        let left = group.exp(base, z)?;
+        Some(left == group.mul(commitment, &group.exp(value, e)?)?)
+    };
+    side(group.alpha(), a, c1) == Some(true) && side(key.h(), b, d) == Some(true)
+}
+
+// Commitments must be elements of the group and the other values below q, otherwise the same proof has many encodings
+fn in_range(group : &GroupParams, elements : &[&BigUint], scalars : &[&BigUint]) -> bool {
+    elements.iter().all(|value| group.is_element(value)) && scalars.iter().all(|value| *value < group.q())
+}
+
+impl BitProof {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(a0 : BigUint, b0 : BigUint, a1 : BigUint, b1 : BigUint, e0 : BigUint, e1 : BigUint, z0 : BigUint, z1 : BigUint) -> BitProof {
+        BitProof {
+            a0, b0, a1, b1, e0, e1, z0, z1
+        }
+    }
+
+    // Message must be 0 or 1, r the randomness the ciphertext was encrypted with and the key valid.
+    // The proof is made for a vote of 0, for a vote of 1 the two cases are swapped. Swaps are constant time
+    // and all secrets are SecretScalar, so neither the time nor the memory that is read depend on the vote.
+    pub fn prove(key : &EncryptionKey, ciphertext : &Ciphertext, message : u32, r : &SecretScalar, context : &[u8]) -> BitProof {
+        assert!(message <= 1, "Message must be 0 or 1");
+        let group = key.group();
+        let q = group.q();
+        let alpha = group.alpha();
+        let swap = Choice::from(message as u8);
+        let element = |value : Option<BigUint>| value.expect("Ciphertext is not in the group");
+
+        // Simulated case picks the challenge and the response first and computes the commitments from them.
+        // Both shifted values are public, the simulated one is picked with a swap.
+        let e_sim = SecretScalar::random(q);
+        let z_sim = SecretScalar::random(q);
+        let (mut d_sim, mut d_real) = (element(shifted(group, ciphertext, 1)), element(shifted(group, ciphertext, 0)));
+        conditional_swap(&mut d_sim, &mut d_real, swap);
+        let minus_e_sim = SecretScalar::from_biguint(&BigUint::ZERO, q).sub(&e_sim);
+        let a_sim = element(group.mul(&element(group.exp_secret(alpha, &z_sim)), &element(group.exp_secret(ciphertext.c1(), &minus_e_sim))));
+        let b_sim = element(group.mul(&element(group.exp_secret(key.h(), &z_sim)), &element(group.exp_secret(&d_sim, &minus_e_sim))));
+
+        let w = SecretScalar::random(q);
+        let (mut a0, mut b0) = (element(group.exp_secret(alpha, &w)), element(group.exp_secret(key.h(), &w)));
+        let (mut a1, mut b1) = (a_sim, b_sim);
+        conditional_swap(&mut a0, &mut a1, swap);
+        conditional_swap(&mut b0, &mut b1, swap);
+
+        let e = SecretScalar::from_biguint(&Self::challenge(key, ciphertext, &a0, &b0, &a1, &b1, context), q);
+        let mut e0 = e.sub(&e_sim);
+        let mut z0 = w.add(&e0.mul(r));
+        let (mut e1, mut z1) = (e_sim, z_sim);
+        e0.conditional_swap(&mut e1, swap);
+        z0.conditional_swap(&mut z1, swap);
+        BitProof::new(a0, b0, a1, b1, e0.to_biguint(), e1.to_biguint(), z0.to_biguint(), z1.to_biguint())
+    }
+
+    fn challenge(key : &EncryptionKey, ciphertext : &Ciphertext, a0 : &BigUint, b0 : &BigUint, a1 : &BigUint, b1 : &BigUint, context : &[u8]) -> BigUint {
+        key.group().hash_challenge(&[key.h(), ciphertext.c1(), ciphertext.c2(), a0, b0, a1, b1], context)
+    }
+
+    pub fn verify(&self, key : &EncryptionKey, ciphertext : &Ciphertext, context : &[u8]) -> bool {
+        let group = key.group();
+        if !in_range(group, &[&self.a0, &self.b0, &self.a1, &self.b1], &[&self.e0, &self.e1, &self.z0, &self.z1]) || !ciphertext.valid(group) {
+            return false;
+        }
+
+        // Voter can choose only one of the challenges, the other one is fixed by the hash
+        let e = Self::challenge(key, ciphertext, &self.a0, &self.b0, &self.a1, &self.b1, context);
+        if (&self.e0 + &self.e1) % group.q() != e {
+            return false;
+        }
+        let (Some(d0), Some(d1)) = (shifted(group, ciphertext, 0), shifted(group, ciphertext, 1)) else {
+            return false;
+        };
+        check(key, ciphertext.c1(), &d0, &self.a0, &self.b0, &self.e0, &self.z0) &&
+        check(key, ciphertext.c1(), &d1, &self.a1, &self.b1, &self.e1, &self.z1)
+    }
+
+    pub fn a0(&self) -> &BigUint {
+        &self.a0
+    }
+
+    pub fn b0(&self) -> &BigUint {
+        &self.b0
+    }
+
+    pub fn a1(&self) -> &BigUint {
+        &self.a1
+    }
+
+    pub fn b1(&self) -> &BigUint {
+        &self.b1
+    }
+
+    pub fn e0(&self) -> &BigUint {
+        &self.e0
+    }
+
+    pub fn e1(&self) -> &BigUint {
+        &self.e1
+    }
+
+    pub fn z0(&self) -> &BigUint {
+        &self.z0
+    }
+
+    pub fn z1(&self) -> &BigUint {
+        &self.z1
+    }
+}
+
+impl SumProof {
+    pub fn new(a : BigUint, b : BigUint, z : BigUint) -> SumProof {
+        SumProof {
+            a, b, z
+        }
+    }
+
+    // r is the randomness of the ciphertext, for a sum of ciphertexts it is the sum of their randomness
+    pub fn prove(key : &EncryptionKey, ciphertext : &Ciphertext, r : &SecretScalar, context : &[u8]) -> SumProof {
+        let group = key.group();
+        let w = SecretScalar::random(group.q());
+        let a = group.exp_secret(group.alpha(), &w).expect("Alpha is not in the group");
+        let b = group.exp_secret(key.h(), &w).expect("Key is not in the group");
+        let e = SecretScalar::from_biguint(&Self::challenge(key, ciphertext, &a, &b, context), group.q());
+        let z = w.add(&e.mul(r)).to_biguint();
+        SumProof::new(a, b, z)
+    }
+
+    fn challenge(key : &EncryptionKey, ciphertext : &Ciphertext, a : &BigUint, b : &BigUint, context : &[u8]) -> BigUint {
+        key.group().hash_challenge(&[key.h(), ciphertext.c1(), ciphertext.c2(), a, b], context)
+    }
+
+    pub fn verify(&self, key : &EncryptionKey, ciphertext : &Ciphertext, context : &[u8]) -> bool {
+        let group = key.group();
+        if !in_range(group, &[&self.a, &self.b], &[&self.z]) || !ciphertext.valid(group) {
+            return false;
+        }
+        let e = Self::challenge(key, ciphertext, &self.a, &self.b, context);
+        shifted(group, ciphertext, 1).is_some_and(|d| check(key, ciphertext.c1(), &d, &self.a, &self.b, &e, &self.z))
+    }
+
+    pub fn a(&self) -> &BigUint {
+        &self.a
+    }
+
+    pub fn b(&self) -> &BigUint {
+        &self.b
+    }
+
+    pub fn z(&self) -> &BigUint {
+        &self.z
+    }
+}
+
+impl BallotProof {
+    pub fn new(options : Vec<BitProof>, sum : SumProof) -> BallotProof {
+        BallotProof {
+            options, sum
+        }
+    }
+
+    pub fn options(&self) -> &[BitProof] {
+        &self.options
+    }
+
+    pub fn sum(&self) -> &SumProof {
+        &self.sum
+    }
+}
+
+impl EncryptionKey {
+    // Encrypted ballot (see encrypt_vote) with the proof that it is valid
+    pub fn encrypt_vote_with_proof(&self, vote : u32, option_count : usize, context : &[u8]) -> (Vec<Ciphertext>, BallotProof) {
+        let q = self.group().q();
+        let mut ballot = Vec::with_capacity(option_count);
+        let mut option_proofs = Vec::with_capacity(option_count);
+        let mut r_sum = SecretScalar::from_biguint(&BigUint::ZERO, q);
+        for option in 0..option_count {
+            let message = (option == vote as usize) as u32;
+            let r = SecretScalar::random(q);
+            let ciphertext = self.encrypt_with(message, &r);
+            option_proofs.push(BitProof::prove(self, &ciphertext, message, &r, context));
+            ballot.push(ciphertext);
+            r_sum = r_sum.add(&r);
+        }
+        let sum = SumProof::prove(self, &elgamal::sum(self.group(), &ballot).expect("Ciphertext is not in the group"), &r_sum, context);
+        (ballot, BallotProof::new(option_proofs, sum))
+    }
+
+    // Every option encrypts 0 or 1 and exactly one option encrypts 1
+    pub fn verify_ballot(&self, ballot : &[Ciphertext], proof : &BallotProof, context : &[u8]) -> bool {
+        if ballot.is_empty() || ballot.len() != proof.options.len() {
+            return false;
+        }
+        let options_valid = ballot.iter().zip(&proof.options).all(|(ciphertext, option_proof)| option_proof.verify(self, ciphertext, context));
+        options_valid && elgamal::sum(self.group(), ballot).is_some_and(|sum| proof.sum.verify(self, &sum, context))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::elgamal::DecryptionKey;
+
+    #[test]
+    fn test_ballot_proof() {
+        let key = DecryptionKey::generate(&GroupParams::default());
+        let encryption_key = key.encryption_key();
+        let context = b"election";
+
+        for vote in 0..3 {
+            let (ballot, proof) = encryption_key.encrypt_vote_with_proof(vote, 3, context);
+            assert!(encryption_key.verify_ballot(&ballot, &proof, context), "Valid ballot for option {} was not verified", vote);
+            assert_eq!(key.decrypt(&ballot[vote as usize], 1), Some(1));
+        }
+
+        // Same proofs in another group
+        let ristretto = DecryptionKey::generate(&GroupParams::ristretto255());
+        let (ristretto_ballot, ristretto_proof) = ristretto.encryption_key().encrypt_vote_with_proof(2, 3, context);
+        assert!(ristretto.encryption_key().verify_ballot(&ristretto_ballot, &ristretto_proof, context));
+        assert_eq!(ristretto.decrypt(&ristretto_ballot[2], 1), Some(1));
+
+        let (ballot, proof) = encryption_key.encrypt_vote_with_proof(1, 3, context);
+        assert!(!encryption_key.verify_ballot(&ballot, &proof, b"other"), "Proof verified for a different context");
+        let other_key = DecryptionKey::generate(&GroupParams::default());
+        assert!(!other_key.encryption_key().verify_ballot(&ballot, &proof, context), "Proof verified for a different key");
+
+        // Proofs of another ballot, or options in a different order
+        let (other_ballot, _) = encryption_key.encrypt_vote_with_proof(1, 3, context);
+        assert!(!encryption_key.verify_ballot(&other_ballot, &proof, context), "Proof verified for a different ballot");
+        let swapped = vec![ballot[1].clone(), ballot[0].clone(), ballot[2].clone()];
+        assert!(!encryption_key.verify_ballot(&swapped, &proof, context), "Proof verified for swapped options");
+        assert!(!encryption_key.verify_ballot(&ballot[..2], &proof, context), "Proof verified for a ballot with missing option");
+        assert!(!encryption_key.verify_ballot(&[], &BallotProof::new(vec![], proof.sum().clone()), context), "Empty ballot was verified");
+        assert!(!ristretto.encryption_key().verify_ballot(&ballot, &proof, context), "Proof verified in a different group");
+    }
+
+    #[test]
+    fn test_invalid_ballot() {
+        let group = GroupParams::default();
+        let encryption_key = DecryptionKey::generate(&group).encryption_key().clone();
+        let context = b"election";
+        let q = group.q();
+
+        // Option that encrypts 2 can't get a valid bit proof, even when the prover knows the randomness
+        let r = SecretScalar::random(q);
+        let ciphertext = encryption_key.encrypt_with(2, &r);
+        for claimed in 0..2 {
+            let proof = BitProof::prove(&encryption_key, &ciphertext, claimed, &r, context);
+            assert!(!proof.verify(&encryption_key, &ciphertext, context), "Bit proof verified for 2");
+        }
+
+        // Every option is 0 or 1, but two options are chosen
+        let randomness : Vec<SecretScalar> = (0..3).map(|_| SecretScalar::random(q)).collect();
+        let ballot : Vec<Ciphertext> = [1, 1, 0].iter().zip(&randomness).map(|(message, r)| encryption_key.encrypt_with(*message, r)).collect();
+        let option_proofs : Vec<BitProof> = [1, 1, 0].iter().zip(&ballot).zip(&randomness).map(|((message, ciphertext), r)| BitProof::prove(&encryption_key, ciphertext, *message, r, context)).collect();
+        assert!(option_proofs.iter().zip(&ballot).all(|(proof, ciphertext)| proof.verify(&encryption_key, ciphertext, context)));
+        let r_sum = randomness.iter().fold(SecretScalar::from_biguint(&BigUint::ZERO, q), |sum, r| sum.add(r));
+        let sum = SumProof::prove(&encryption_key, &elgamal::sum(&group, &ballot).unwrap(), &r_sum, context);
+        assert!(!encryption_key.verify_ballot(&ballot, &BallotProof::new(option_proofs.clone(), sum), context), "Ballot with two votes was verified");
+
+        // No option is chosen
+        let zero_ballot : Vec<Ciphertext> = randomness.iter().map(|r| encryption_key.encrypt_with(0, r)).collect();
+        let zero_proofs : Vec<BitProof> = zero_ballot.iter().zip(&randomness).map(|(ciphertext, r)| BitProof::prove(&encryption_key, ciphertext, 0, r, context)).collect();
+        let sum = SumProof::prove(&encryption_key, &elgamal::sum(&group, &zero_ballot).unwrap(), &r_sum, context);
+        assert!(!encryption_key.verify_ballot(&zero_ballot, &BallotProof::new(zero_proofs, sum), context), "Empty vote was verified");
+
+        // Tampered proof
+        let (ballot, proof) = encryption_key.encrypt_vote_with_proof(0, 2, context);
+        let mut tampered = proof.options()[0].clone();
+        tampered.z0 = (&tampered.z0 + 1u32) % q;
+        assert!(!tampered.verify(&encryption_key, &ballot[0], context), "Tampered proof was verified");
+        let mut out_of_range = proof.options()[0].clone();
+        out_of_range.e0 = &out_of_range.e0 + q;
+        assert!(!out_of_range.verify(&encryption_key, &ballot[0], context), "Proof with challenge above q was verified");
+    }
+}
@@ -0,0 +1,290 @@
+// RSA blind signatures (full domain hash variant).
+// The voter blinds the message with a random factor, the signer signs the blinded message without
+// seeing it and the voter removes the blinding factor, ending with a valid signature the signer never saw.
+// The signer can't see what it signs either, so the election id in the message doesn't stop a voter from asking
+// for a credential of another election. Every election is signed with its own public exponent instead (see for_election).
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use sha2::{Digest, Sha256};
+
+use crate::PublicKey;
+
+const PUBLIC_EXPONENT : u32 = 65537;
+const MILLER_RABIN_ROUNDS : usize = 40;
+const SMALL_PRIMES : [u32; 24] = [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97];
+
+pub(crate) fn is_probable_prime(n : &BigUint) -> bool {
+    let one = BigUint::from(1u32);
+    let two = BigUint::from(2u32);
+    if *n < two {
+        return false;
+    }
+    if n.is_even() {
+        return *n == two;
+    }
+    for p in SMALL_PRIMES {
+        if *n == BigUint::from(p) {
+            return true;
+        }
+        if (n % p) == BigUint::ZERO {
+            return false;
+        }
+    }
+
+    // n - 1 = d * 2^s
+    let n_minus_one = n - &one;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+
+    let mut rng = rand::thread_rng();
+    'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+fn generate_prime(bits : u64) -> BigUint {
+    let mut rng = rand::thread_rng();
+    loop {
+        // Top two bits set so the product of two primes has exactly 2 * bits bits, bottom bit set so it's odd
+        let mut candidate = rng.gen_biguint(bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(bits - 2, true);
+        candidate.set_bit(0, true);
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
+
+// Hash of the message expanded to the size of the modulus
+fn full_domain_hash(message : &[u8], n : &BigUint) -> BigUint {
+    let len = n.bits().div_ceil(8) as usize;
+    let mut output = Vec::with_capacity(len + 32);
+    let mut counter : u32 = 0;
+    while output.len() < len {
+        let mut hasher = Sha256::new();
+        hasher.update(b"anonvote-blind-signature");
+        hasher.update(counter.to_be_bytes());
+        hasher.update(message);
+        output.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    BigUint::from_bytes_be(&output[..len]) % n
+}
+
+// Message the authority signs for a voter, ties the voter's key to the election
+pub fn credential(election_id : &str, public_key : &PublicKey) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(&(election_id.len() as u32).to_be_bytes());
+    message.extend_from_slice(election_id.as_bytes());
+    for value in [public_key.a(), public_key.b(), public_key.alpha(), public_key.beta()] {
+        let bytes = value.to_bytes_be();
+        message.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        message.extend_from_slice(&bytes);
+    }
+    message
+}
+
+const ELECTION_EXPONENT_BITS : u64 = 128;
+
+// Prime public exponent of the election, derived from the id so voters can check that everyone gets the same key.
+// With the same modulus, signatures for one prime exponent don't give signatures for another (strong RSA assumption).
+pub fn election_exponent(election_id : &str) -> BigUint {
+    let mut counter : u32 = 0;
+    loop {
+        let mut hasher = Sha256::new();
+        hasher.update(b"anonvote-election-exponent");
+        hasher.update(counter.to_be_bytes());
+        hasher.update(election_id.as_bytes());
+        let mut candidate = BigUint::from_bytes_be(&hasher.finalize()[..(ELECTION_EXPONENT_BITS / 8) as usize]);
+        candidate.set_bit(ELECTION_EXPONENT_BITS - 1, true);
+        candidate.set_bit(0, true);
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+#[derive(Clone)]
+pub struct SignerPublicKey {
+    n : BigUint,
+    e : BigUint,
+}
+
+#[derive(Clone)]
+pub struct SignerSecretKey {
+    public_key : SignerPublicKey,
+    d : BigUint,
+}
+
+impl SignerPublicKey {
+    pub fn new(n : BigUint, e : BigUint) -> SignerPublicKey {
+        SignerPublicKey {
+            n, e
+        }
+    }
+
+    pub fn from_bytes_be(n : &[u8], e : &[u8]) -> SignerPublicKey {
+        SignerPublicKey {
+            n : BigUint::from_bytes_be(n),
+            e : BigUint::from_bytes_be(e),
+        }
+    }
+
+    // Returns the blinded message for the signer and the blinding factor needed to unblind the signature
+    pub fn blind(&self, message : &[u8]) -> (BigUint, BigUint) {
+        let mut rng = rand::thread_rng();
+        let one = BigUint::from(1u32);
+        let r = loop {
+            let r = rng.gen_biguint_range(&BigUint::from(2u32), &self.n);
+            if r.gcd(&self.n) == one {
+                break r;
+            }
+        };
+        let blinded = (full_domain_hash(message, &self.n) * r.modpow(&self.e, &self.n)) % &self.n;
+        (blinded, r)
+    }
+
+    pub fn unblind(&self, blind_signature : &BigUint, r : &BigUint) -> Option<BigUint> {
+        let r_inv = r.modinv(&self.n)?;
+        Some((blind_signature * r_inv) % &self.n)
+    }
+
+    pub fn verify(&self, message : &[u8], signature : &BigUint) -> bool {
+        *signature < self.n && signature.modpow(&self.e, &self.n) == full_domain_hash(message, &self.n)
+    }
+
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    pub fn e(&self) -> &BigUint {
+        &self.e
+    }
+
+    pub fn for_election(&self, election_id : &str) -> SignerPublicKey {
+        SignerPublicKey::new(self.n.clone(), election_exponent(election_id))
+    }
+}
+
+impl SignerSecretKey {
+    pub fn new(n : BigUint, e : BigUint, d : BigUint) -> SignerSecretKey {
+        SignerSecretKey {
+            public_key : SignerPublicKey::new(n, e),
+            d
+        }
+    }
+
+    pub fn generate(bits : u64) -> SignerSecretKey {
+        let e = BigUint::from(PUBLIC_EXPONENT);
+        let one = BigUint::from(1u32);
+        loop {
+            let p = generate_prime(bits / 2);
+            let q = generate_prime(bits - bits / 2);
+            if p == q {
+                continue;
+            }
+            let phi = (&p - &one) * (&q - &one);
+            if let Some(d) = e.modinv(&phi) {
+                return SignerSecretKey::new(p * q, e, d);
+            }
+        }
+    }
+
+    // e * d - 1 is a multiple of the order of the group mod n, so an inverse of the election exponent mod it works as d.
+    // A 128 bit prime only divides it with negligible chance.
+    pub fn for_election(&self, election_id : &str) -> SignerSecretKey {
+        let order_multiple = &self.public_key.e * &self.d - 1u32;
+        let e = election_exponent(election_id);
+        let d = e.modinv(&order_multiple).expect("Election exponent divides the order of the signer key");
+        SignerSecretKey::new(self.public_key.n.clone(), e, d)
+    }
+
+    // Signer never learns the message, only the blinded one
+    pub fn sign_blinded(&self, blinded : &BigUint) -> BigUint {
+        blinded.modpow(&self.d, &self.public_key.n)
+    }
+
+    pub fn public_key(&self) -> &SignerPublicKey {
+        &self.public_key
+    }
+
+    pub fn d(&self) -> &BigUint {
+        &self.d
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SecretKey;
+    use crate::group::default_group;
+
+    #[test]
+    fn test_primes() {
+        for p in [2u32, 3, 97, 7919, 65537] {
+            assert!(is_probable_prime(&BigUint::from(p)), "{} is prime", p);
+        }
+        // 561 is a Carmichael number
+        for n in [0u32, 1, 9, 561, 7917, 65535] {
+            assert!(!is_probable_prime(&BigUint::from(n)), "{} is not prime", n);
+        }
+        assert_eq!(generate_prime(64).bits(), 64);
+    }
+
+    #[test]
+    fn test_blind_signature() {
+        let signer = SignerSecretKey::generate(512);
+        let signer_public = signer.public_key();
+
+        let voter_key = SecretKey::generate(default_group()).public_key(&crate::params::KeyParams::for_election(default_group(), "election"));
+        let message = credential("election", &voter_key);
+
+        let (blinded, r) = signer_public.blind(&message);
+        assert_ne!(blinded, full_domain_hash(&message, signer_public.n()), "Message was not blinded");
+        let blind_signature = signer.sign_blinded(&blinded);
+        let signature = signer_public.unblind(&blind_signature, &r).unwrap();
+
+        assert!(signer_public.verify(&message, &signature));
+        assert!(!signer_public.verify(&credential("other", &voter_key), &signature), "Signature verified for a different election");
+        let other_key = SecretKey::generate(default_group()).public_key(&crate::params::KeyParams::for_election(default_group(), "election"));
+        assert!(!signer_public.verify(&credential("election", &other_key), &signature), "Signature verified for a different key");
+        assert!(!signer_public.verify(&message, &blind_signature), "Blind signature verified without unblinding");
+
+        let other_signer = SignerSecretKey::generate(512);
+        assert!(!other_signer.public_key().verify(&message, &signature), "Signature verified with a different signer");
+    }
+
+    #[test]
+    fn test_election_keys() {
+        let signer = SignerSecretKey::generate(512);
+        let (first, second) = (signer.for_election("first"), signer.for_election("second"));
+        assert_eq!(first.public_key().e(), signer.public_key().for_election("first").e(), "Voters derive a different key");
+        assert_eq!(first.public_key().e(), &election_exponent("first"));
+        assert_ne!(first.public_key().e(), second.public_key().e());
+        assert_eq!(election_exponent("first").bits(), ELECTION_EXPONENT_BITS);
+
+        // Voter of the first election blinds a credential of the second one
+        let voter_key = SecretKey::generate(default_group()).public_key(&crate::params::KeyParams::for_election(default_group(), "second"));
+        let message = credential("second", &voter_key);
+        let (blinded, r) = first.public_key().blind(&message);
+        let signature = first.public_key().unblind(&first.sign_blinded(&blinded), &r).unwrap();
+        assert!(first.public_key().verify(&message, &signature));
+        assert!(!second.public_key().verify(&message, &signature), "Signature of one election verified in another");
+        assert!(!signer.public_key().verify(&message, &signature));
+    }
+}
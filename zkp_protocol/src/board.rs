@@ -0,0 +1,245 @@
+// Bulletin board, a public append-only log of what the server accepted for an election.
+// Every entry is hashed together with the hash of the previous one, so changing or dropping an entry
+// changes every hash after it. The server signs checkpoints (size and head hash of the chain),
+// an observer that keeps an older checkpoint can prove if the server rewrote its history.
+// Checkpoints are signed with a Schnorr signature in the same group: the proof of knowledge of the
+// board key made non-interactive with the checkpoint as the context.
+
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::{zkp_util, PublicKey, SecretKey, Proof};
+use crate::ballot_proof::BallotProof;
+use crate::elgamal::Ciphertext;
+use crate::group::GroupParams;
+use crate::scalar::SecretScalar;
+
+pub type Hash = [u8; 32];
+
+// Hash before the first entry
+pub const EMPTY_HASH : Hash = [0; 32];
+
+#[derive(Clone)]
+pub enum BoardRecord {
+    Schedule { registration_opens_at : u64, opens_at : u64, closes_at : u64 }, // Election created or rescheduled
+    Registration { voter : PublicKey },
+    Ballot { ballot : Vec<Ciphertext>, proof : Option<BallotProof> }, // Ciphertexts with their validity proof, not the voter. Older boards have no proofs.
+    Tally { votes : Vec<u32> },
+}
+
+// Key that signs the checkpoints, public part is alpha^x in the group of the server
+#[derive(Clone)]
+pub struct BoardKey {
+    group : GroupParams,
+    x : SecretScalar,
+    public_key : BigUint,
+}
+
+fn push_bytes(output : &mut Vec<u8>, bytes : &[u8]) {
+    output.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    output.extend_from_slice(bytes);
+}
+
+pub(crate) fn push_number(output : &mut Vec<u8>, value : &BigUint) {
+    push_bytes(output, &value.to_bytes_be());
+}
+
+impl BoardRecord {
+    // Tag followed by the length prefixed fields, what the entry hash covers
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        match self {
+            BoardRecord::Schedule { registration_opens_at, opens_at, closes_at } => {
+                output.push(0);
+                for time in [registration_opens_at, opens_at, closes_at] {
+                    output.extend_from_slice(&time.to_be_bytes());
+                }
+            }
+            BoardRecord::Registration { voter } => {
+                output.push(1);
+                for value in [voter.a(), voter.b(), voter.alpha(), voter.beta()] {
+                    push_number(&mut output, value);
+                }
+            }
+            BoardRecord::Ballot { ballot, proof } => {
+                output.push(2);
+                output.extend_from_slice(&(ballot.len() as u32).to_be_bytes());
+                for ciphertext in ballot {
+                    push_number(&mut output, ciphertext.c1());
+                    push_number(&mut output, ciphertext.c2());
+                }
+                // Nothing is added without a proof, so boards from before the proofs keep their hashes
+                if let Some(proof) = proof {
+                    output.extend_from_slice(&(proof.options().len() as u32).to_be_bytes());
+                    for option in proof.options() {
+                        for value in [option.a0(), option.b0(), option.a1(), option.b1(), option.e0(), option.e1(), option.z0(), option.z1()] {
+                            push_number(&mut output, value);
+                        }
+                    }
+                    for value in [proof.sum().a(), proof.sum().b(), proof.sum().z()] {
+                        push_number(&mut output, value);
+                    }
+                }
+            }
+            BoardRecord::Tally { votes } => {
+                output.push(3);
+                output.extend_from_slice(&(votes.len() as u32).to_be_bytes());
+                for vote in votes {
+                    output.extend_from_slice(&vote.to_be_bytes());
+                }
+            }
+        }
+        output
+    }
+}
+
+// Hash of the entry at the given index, chained to the hash of the entry before it
+pub fn entry_hash(election_id : &str, index : u64, previous : &Hash, record : &BoardRecord) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(b"anonvote-board-entry");
+    hasher.update(zkp_util::election_context(election_id));
+    hasher.update(index.to_be_bytes());
+    hasher.update(previous);
+    hasher.update(record.encode());
+    hasher.finalize().into()
+}
+
+// Recomputes the chain from the hash before the first given entry, returns the new head
+// or None if some entry doesn't have the hash the server claimed
+pub fn verify_entries(election_id : &str, first_index : u64, previous : &Hash, entries : &[(BoardRecord, Hash)]) -> Option<Hash> {
+    let mut head = *previous;
+    for (index, (record, hash)) in (first_index..).zip(entries) {
+        head = entry_hash(election_id, index, &head, record);
+        if head != *hash {
+            return None;
+        }
+    }
+    Some(head)
+}
+
+// What a checkpoint signature covers
+pub fn checkpoint_message(election_id : &str, size : u64, head : &Hash) -> Vec<u8> {
+    let mut message = b"anonvote-board-checkpoint".to_vec();
+    message.extend_from_slice(&zkp_util::election_context(election_id));
+    message.extend_from_slice(&size.to_be_bytes());
+    message.extend_from_slice(head);
+    message
+}
+
+// Schnorr statement, both bases are alpha
+fn statement(group : &GroupParams, public_key : &BigUint) -> PublicKey {
+    let alpha = group.alpha();
+    PublicKey::new(group, public_key.clone(), public_key.clone(), alpha.clone(), alpha.clone())
+}
+
+pub fn verify_signature(group : &GroupParams, public_key : &BigUint, message : &[u8], signature : &Proof) -> bool {
+    group.contains(public_key) && statement(group, public_key).verify_proof(signature, message)
+}
+
+pub fn verify_checkpoint(group : &GroupParams, public_key : &BigUint, election_id : &str, size : u64, head : &Hash, signature : &Proof) -> bool {
+    verify_signature(group, public_key, &checkpoint_message(election_id, size, head), signature)
+}
+
+impl BoardKey {
+    pub fn new(group : &GroupParams, x : SecretScalar) -> BoardKey {
+        assert!(x.is_mod(group.q()), "Secret is not mod the order of the group");
+        let public_key = group.exp_secret(group.alpha(), &x).expect("Alpha is not in the group");
+        BoardKey {
+            group : group.clone(), x, public_key
+        }
+    }
+
+    pub fn generate(group : &GroupParams) -> BoardKey {
+        BoardKey::new(group, SecretScalar::random(group.q()))
+    }
+
+    pub fn from_bytes_be(group : &GroupParams, x : &[u8]) -> BoardKey {
+        BoardKey::new(group, SecretScalar::from_bytes_be(x, group.q()))
+    }
+
+    pub fn sign(&self, message : &[u8]) -> Proof {
+        SecretKey::new(&self.group, self.x.clone()).prove(&statement(&self.group, &self.public_key), message)
+    }
+
+    pub fn sign_checkpoint(&self, election_id : &str, size : u64, head : &Hash) -> Proof {
+        self.sign(&checkpoint_message(election_id, size, head))
+    }
+
+    pub fn group(&self) -> &GroupParams {
+        &self.group
+    }
+
+    pub fn secret(&self) -> &SecretScalar {
+        &self.x
+    }
+
+    pub fn public_key(&self) -> &BigUint {
+        &self.public_key
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::elgamal::DecryptionKey;
+
+    fn test_records() -> Vec<BoardRecord> {
+        let group = GroupParams::default();
+        let voter = SecretKey::generate(&group).public_key(&crate::params::KeyParams::for_election(&group, "election"));
+        let key = DecryptionKey::generate(&group);
+        let (ballot, proof) = key.encryption_key().encrypt_vote_with_proof(1, 2, &zkp_util::validity_context("election"));
+        vec![
+            BoardRecord::Schedule { registration_opens_at : 0, opens_at : 10, closes_at : 20 },
+            BoardRecord::Registration { voter },
+            BoardRecord::Ballot { ballot, proof : Some(proof) },
+            BoardRecord::Tally { votes : vec![0, 1] },
+        ]
+    }
+
+    fn chain(election_id : &str, records : Vec<BoardRecord>) -> Vec<(BoardRecord, Hash)> {
+        let mut head = EMPTY_HASH;
+        records.into_iter().enumerate().map(|(index, record)| {
+            head = entry_hash(election_id, index as u64, &head, &record);
+            (record, head)
+        }).collect()
+    }
+
+    #[test]
+    fn test_chain() {
+        let entries = chain("election", test_records());
+        let head = verify_entries("election", 0, &EMPTY_HASH, &entries);
+        assert_eq!(head, Some(entries[3].1));
+        assert_eq!(verify_entries("election", 2, &entries[1].1, &entries[2..]), head, "Could not continue from a replicated prefix");
+        assert_eq!(verify_entries("other", 0, &EMPTY_HASH, &entries), None, "Chain verified for a different election");
+
+        // Dropping or changing an entry breaks every hash after it
+        let mut dropped = entries.clone();
+        dropped.remove(1);
+        assert_eq!(verify_entries("election", 0, &EMPTY_HASH, &dropped), None, "Verified chain with a dropped entry");
+        let mut changed = entries.clone();
+        changed[3].0 = BoardRecord::Tally { votes : vec![1, 0] };
+        assert_eq!(verify_entries("election", 0, &EMPTY_HASH, &changed), None, "Verified chain with a changed entry");
+        let mut without_proof = entries.clone();
+        if let BoardRecord::Ballot { proof, .. } = &mut without_proof[2].0 {
+            *proof = None;
+        }
+        assert_eq!(verify_entries("election", 0, &EMPTY_HASH, &without_proof), None, "Verified chain with a dropped ballot proof");
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        for group in [GroupParams::default(), GroupParams::ristretto255()] {
+            let key = BoardKey::generate(&group);
+            let head = chain("election", test_records())[3].1;
+            let signature = key.sign_checkpoint("election", 4, &head);
+            assert!(verify_checkpoint(&group, key.public_key(), "election", 4, &head, &signature), "Checkpoint in {} was not verified", group.name());
+            assert!(!verify_checkpoint(&group, key.public_key(), "election", 3, &head, &signature), "Verified checkpoint of a different size");
+            assert!(!verify_checkpoint(&group, key.public_key(), "other", 4, &head, &signature), "Verified checkpoint of a different election");
+            assert!(!verify_checkpoint(&group, key.public_key(), "election", 4, &EMPTY_HASH, &signature), "Verified checkpoint of a different head");
+            assert!(!verify_checkpoint(&group, BoardKey::generate(&group).public_key(), "election", 4, &head, &signature), "Verified with a different key");
+        }
+        let key = BoardKey::generate(&GroupParams::default());
+        let signature = key.sign_checkpoint("election", 4, &EMPTY_HASH);
+        assert!(!verify_checkpoint(&GroupParams::ristretto255(), key.public_key(), "election", 4, &EMPTY_HASH, &signature), "Verified in a different group");
+    }
+}
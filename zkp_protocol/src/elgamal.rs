@@ -0,0 +1,216 @@
+// Exponential ElGamal in a group of prime order (see group::GroupParams), usually the one of the election.
+// The message is encrypted in the exponent (alpha^m), so multiplying two ciphertexts gives the encryption of the sum.
+// Decryption only recovers alpha^m, m is found by trying every value up to a bound, which is fine for vote counts.
+
+use num_bigint::BigUint;
+
+use crate::group::GroupParams;
+use crate::scalar::SecretScalar;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ciphertext {
+    c1 : BigUint, // alpha^r
+    c2 : BigUint, // alpha^m * h^r
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptionKey {
+    h : BigUint, // alpha^x
+    group : GroupParams,
+}
+
+#[derive(Clone)]
+pub struct DecryptionKey {
+    x : SecretScalar,
+    encryption_key : EncryptionKey,
+}
+
+impl Ciphertext {
+    pub fn new(c1 : BigUint, c2 : BigUint) -> Ciphertext {
+        Ciphertext {
+            c1, c2
+        }
+    }
+
+    pub fn from_bytes_be(c1 : &[u8], c2 : &[u8]) -> Ciphertext {
+        Ciphertext {
+            c1 : BigUint::from_bytes_be(c1),
+            c2 : BigUint::from_bytes_be(c2),
+        }
+    }
+
+    // Encryption of 0 without randomness, adding it changes nothing
+    pub fn zero(group : &GroupParams) -> Ciphertext {
+        Ciphertext::new(group.identity(), group.identity())
+    }
+
+    // Encryption of the sum of both messages, None if a part is not an element of the group
+    pub fn add(&self, group : &GroupParams, other : &Ciphertext) -> Option<Ciphertext> {
+        Some(Ciphertext {
+            c1 : group.mul(&self.c1, &other.c1)?,
+            c2 : group.mul(&self.c2, &other.c2)?,
+        })
+    }
+
+    // Both parts must be elements of the group, otherwise they could leak or break the tally
+    pub fn valid(&self, group : &GroupParams) -> bool {
+        group.is_element(&self.c1) && group.is_element(&self.c2)
+    }
+
+    pub fn c1(&self) -> &BigUint {
+        &self.c1
+    }
+
+    pub fn c2(&self) -> &BigUint {
+        &self.c2
+    }
+}
+
+// Finds m from alpha^m by trying every value up to max
+pub(crate) fn discrete_log(group : &GroupParams, target : &BigUint, max : u32) -> Option<u32> {
+    let mut power = group.identity();
+    for message in 0..=max {
+        if power == *target {
+            return Some(message);
+        }
+        power = group.mul(&power, group.alpha())?;
+    }
+    None
+}
+
+// None if a ciphertext is not made of group elements
+pub fn sum<'a>(group : &GroupParams, ciphertexts : impl IntoIterator<Item = &'a Ciphertext>) -> Option<Ciphertext> {
+    ciphertexts.into_iter().try_fold(Ciphertext::zero(group), |sum, ciphertext| sum.add(group, ciphertext))
+}
+
+impl EncryptionKey {
+    pub fn new(group : &GroupParams, h : BigUint) -> EncryptionKey {
+        EncryptionKey {
+            h,
+            group : group.clone(),
+        }
+    }
+
+    pub fn from_bytes_be(group : &GroupParams, h : &[u8]) -> EncryptionKey {
+        EncryptionKey::new(group, BigUint::from_bytes_be(h))
+    }
+
+    pub fn encrypt(&self, message : u32) -> Ciphertext {
+        self.encrypt_with(message, &SecretScalar::random(self.group.q()))
+    }
+
+    // The key has to be valid (see valid). The randomness and the message are secret exponents.
+    pub fn encrypt_with(&self, message : u32, r : &SecretScalar) -> Ciphertext {
+        let group = &self.group;
+        let message = SecretScalar::from_biguint(&BigUint::from(message), group.q());
+        let shared = group.exp_secret(&self.h, r).expect("Key is not in the group");
+        Ciphertext {
+            c1 : group.exp_secret(group.alpha(), r).expect("Alpha is not in the group"),
+            c2 : group.mul(&group.exp_secret(group.alpha(), &message).expect("Alpha is not in the group"), &shared).expect("Key is not in the group"),
+        }
+    }
+
+    // One ciphertext per option, the chosen option encrypts 1 and all others encrypt 0
+    pub fn encrypt_vote(&self, vote : u32, option_count : usize) -> Vec<Ciphertext> {
+        (0..option_count).map(|option| self.encrypt((option == vote as usize) as u32)).collect()
+    }
+
+    pub fn valid(&self) -> bool {
+        self.group.contains(&self.h)
+    }
+
+    pub fn h(&self) -> &BigUint {
+        &self.h
+    }
+
+    pub fn group(&self) -> &GroupParams {
+        &self.group
+    }
+}
+
+impl DecryptionKey {
+    pub fn new(group : &GroupParams, x : SecretScalar) -> DecryptionKey {
+        assert!(x.is_mod(group.q()), "Secret is not mod the order of the group");
+        let h = group.exp_secret(group.alpha(), &x).expect("Alpha is not in the group");
+        DecryptionKey {
+            x,
+            encryption_key : EncryptionKey::new(group, h)
+        }
+    }
+
+    pub fn from_bytes_be(group : &GroupParams, x : &[u8]) -> DecryptionKey {
+        DecryptionKey::new(group, SecretScalar::from_bytes_be(x, group.q()))
+    }
+
+    pub fn generate(group : &GroupParams) -> DecryptionKey {
+        loop {
+            let x = SecretScalar::random(group.q());
+            if !bool::from(x.is_zero()) {
+                return DecryptionKey::new(group, x);
+            }
+        }
+    }
+
+    // Returns None if the message is bigger than max
+    pub fn decrypt(&self, ciphertext : &Ciphertext, max : u32) -> Option<u32> {
+        let group = self.group();
+        let shared = group.exp_secret(&ciphertext.c1, &self.x)?;
+        discrete_log(group, &group.div(&ciphertext.c2, &shared)?, max)
+    }
+
+    pub fn encryption_key(&self) -> &EncryptionKey {
+        &self.encryption_key
+    }
+
+    // For the key file, the bytes are as long as q
+    pub fn secret(&self) -> &SecretScalar {
+        &self.x
+    }
+
+    pub fn group(&self) -> &GroupParams {
+        &self.encryption_key.group
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_homomorphic_tally() {
+        for group in [GroupParams::default(), GroupParams::ristretto255()] {
+            let key = DecryptionKey::generate(&group);
+            let encryption_key = key.encryption_key();
+            assert!(encryption_key.valid());
+
+            let votes = [2u32, 0, 2, 1, 2];
+            let ballots : Vec<Vec<Ciphertext>> = votes.iter().map(|vote| encryption_key.encrypt_vote(*vote, 3)).collect();
+            assert!(ballots.iter().flatten().all(|ciphertext| ciphertext.valid(&group)));
+
+            let tally : Vec<Option<u32>> = (0..3).map(|option| key.decrypt(&sum(&group, ballots.iter().map(|ballot| &ballot[option])).unwrap(), votes.len() as u32)).collect();
+            assert_eq!(tally, vec![Some(1), Some(1), Some(3)], "Wrong tally in {}", group.name());
+
+            assert_eq!(key.decrypt(&sum(&group, []).unwrap(), 0), Some(0), "Empty sum should decrypt to 0");
+            assert_eq!(key.decrypt(&encryption_key.encrypt(5), 4), None, "Decrypted message bigger than max");
+
+            let other_key = DecryptionKey::generate(&group);
+            assert_ne!(other_key.decrypt(&encryption_key.encrypt(1), 1), Some(1), "Decrypted with a different key");
+        }
+    }
+
+    #[test]
+    fn test_valid() {
+        let group = GroupParams::default();
+        let encryption_key = DecryptionKey::generate(&group).encryption_key().clone();
+        let ciphertext = encryption_key.encrypt(1);
+        assert!(ciphertext.valid(&group));
+        assert!(!Ciphertext::new(BigUint::ZERO, ciphertext.c2().clone()).valid(&group), "Zero is not in the group");
+        assert!(!Ciphertext::new(ciphertext.c1().clone(), group.p().clone()).valid(&group), "Value not below p");
+        assert!(!EncryptionKey::new(&group, group.identity()).valid(), "Trivial key");
+
+        // Ciphertext of one group is not valid in another
+        let ristretto = GroupParams::ristretto255();
+        assert!(!ciphertext.valid(&ristretto), "Ciphertext of another group is valid");
+        assert_eq!(ciphertext.add(&ristretto, &ciphertext), None);
+    }
+}
@@ -0,0 +1,471 @@
+// Groups the protocol is computed in, a group of prime order q with a generator alpha.
+// The built-in groups are the 1024 bit DSA group of RFC 5114 (the default), the 2048 and 3072 bit MODP groups of RFC 3526,
+// the 2048 and 3072 bit ffdhe groups of RFC 7919 and the Ristretto255 curve. Tests also use a tiny group that can't be selected.
+// Custom MODP groups are checked when they are loaded. Keys carry their group, so keys of different groups can be used side by side.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use sha2::{Digest, Sha256};
+
+use crate::blind_signature::is_probable_prime;
+use crate::ristretto::Ristretto255;
+use crate::scalar::{self, SecretScalar};
+
+pub const DEFAULT_GROUP : &str = "rfc5114-1024-160";
+
+const RFC5114_1024_P : &str = "B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371";
+const RFC5114_1024_Q : &str = "F518AA8781A8DF278ABA4E7D64B7CB9D49462353";
+const RFC5114_1024_ALPHA : &str = "A4D1CBD5C3FD34126765A442EFB99905F8104DD258AC507FD6406CFF14266D31266FEA1E5C41564B777E690F5504F213160217B4B01B886A5E91547F9E2749F4D7FBD7D3B9A92EE1909D0D2263F80A76A6A24C087A091F531DBF0A0169B6A28AD662A4D18E73AFA32D779D5918D08BC8858F4DCEF97C2A24855E6EEB22B3B2E5";
+
+// Safe primes, q = (p - 1) / 2 and the generator is 2
+const RFC3526_2048_P : &str = "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF";
+const RFC3526_3072_P : &str = "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E208E24FA074E5AB3143DB5BFCE0FD108E4B82D120A93AD2CAFFFFFFFFFFFFFFFF";
+const FFDHE2048_P : &str = "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617AD3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797ABC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F619172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005C58EF1837D1683B2C6F34A26C1B2EFFA886B423861285C97FFFFFFFFFFFFFFFF";
+const FFDHE3072_P : &str = "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617AD3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797ABC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F619172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005C58EF1837D1683B2C6F34A26C1B2EFFA886B4238611FCFDCDE355B3B6519035BBC34F4DEF99C023861B46FC9D6E6C9077AD91D2691F7F7EE598CB0FAC186D91CAEFE130985139270B4130C93BC437944F4FD4452E2D74DD364F2E21E71F54BFF5CAE82AB9C9DF69EE86D2BC522363A0DABC521979B0DEADA1DBF9A42D5C4484E0ABCD06BFA53DDEF3C1B20EE3FD59D7C25E41D2B66C62E37FFFFFFFFFFFFFFFF";
+
+pub const BUILTIN_GROUPS : [&str; 6] = [DEFAULT_GROUP, "rfc3526-2048", "rfc3526-3072", "ffdhe2048", "ffdhe3072", "ristretto255"];
+
+// Operations the Chaum-Pedersen protocol needs from a group of prime order. Elements are passed around encoded as numbers,
+// so keys, proofs and messages look the same for every backend. Operations on a number that is not an element give None.
+pub trait Group {
+    fn name(&self) -> &str;
+
+    // Prime of the field the group is defined over
+    fn modulus(&self) -> &BigUint;
+
+    // Exponents are taken mod the order
+    fn order(&self) -> &BigUint;
+
+    fn generator(&self) -> &BigUint;
+
+    // base^exponent, a scalar multiplication on a curve
+    fn exp(&self, base : &BigUint, exponent : &BigUint) -> Option<BigUint>;
+
+    // base^exponent for a secret exponent mod the order, the time doesn't depend on its value
+    fn exp_secret(&self, base : &BigUint, exponent : &SecretScalar) -> Option<BigUint>;
+
+    // x * y, a point addition on a curve
+    fn mul(&self, x : &BigUint, y : &BigUint) -> Option<BigUint>;
+
+    // Neutral element, 1 mod p or the point at infinity
+    fn identity(&self) -> BigUint;
+
+    // x^-1, the negated point on a curve
+    fn inv(&self, x : &BigUint) -> Option<BigUint>;
+
+    // Element of the group that is not the identity
+    fn contains(&self, value : &BigUint) -> bool;
+
+    // Any element of the group, the identity included. Ciphertexts and commitments can be the identity.
+    fn is_element(&self, value : &BigUint) -> bool {
+        self.contains(value) || *value == self.identity()
+    }
+
+    // Element nobody knows the logarithm of, neither the identity nor the generator
+    fn hash_to_group(&self, domain : &[u8], data : &[u8]) -> BigUint;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GroupError {
+    UnknownGroup(String),
+    InvalidHex(&'static str),
+    NotPrime(&'static str),
+    InvalidOrder,
+    InvalidGenerator,
+    Mismatch(String),
+}
+
+impl fmt::Display for GroupError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupError::UnknownGroup(name) => write!(f, "Unknown group: {}", name),
+            GroupError::InvalidHex(value) => write!(f, "{} of the group is not a hex number", value),
+            GroupError::NotPrime(value) => write!(f, "{} of the group is not a prime", value),
+            GroupError::InvalidOrder => write!(f, "q does not divide p - 1"),
+            GroupError::InvalidGenerator => write!(f, "alpha is not a generator of order q"),
+            GroupError::Mismatch(name) => write!(f, "Numbers don't match the built-in group {}", name),
+        }
+    }
+}
+
+impl std::error::Error for GroupError {}
+
+// Subgroup of order q of the integers mod p
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct ModpGroup {
+    name : String,
+    p : BigUint,
+    q : BigUint,
+    alpha : BigUint,
+}
+
+impl Group for ModpGroup {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn modulus(&self) -> &BigUint {
+        &self.p
+    }
+
+    fn order(&self) -> &BigUint {
+        &self.q
+    }
+
+    fn generator(&self) -> &BigUint {
+        &self.alpha
+    }
+
+    fn exp(&self, base : &BigUint, exponent : &BigUint) -> Option<BigUint> {
+        Some(base.modpow(exponent, &self.p))
+    }
+
+    // modpow of num-bigint skips work for zero bits and short numbers, see scalar::exp_mod
+    fn exp_secret(&self, base : &BigUint, exponent : &SecretScalar) -> Option<BigUint> {
+        Some(scalar::exp_mod(base, exponent, &self.p))
+    }
+
+    fn mul(&self, x : &BigUint, y : &BigUint) -> Option<BigUint> {
+        Some((x * y) % &self.p)
+    }
+
+    fn identity(&self) -> BigUint {
+        BigUint::from(1u32)
+    }
+
+    fn inv(&self, x : &BigUint) -> Option<BigUint> {
+        x.modinv(&self.p)
+    }
+
+    // Non-trivial element of the subgroup of order q
+    fn contains(&self, value : &BigUint) -> bool {
+        *value > BigUint::from(1u32) && *value < self.p && value.modpow(&self.q, &self.p) == BigUint::from(1u32)
+    }
+
+    // The hash, expanded to 16 bytes more than p so the reduction mod p is close to uniform, raised to (p - 1) / q.
+    // A counter is added until it is neither 1 nor alpha, which only happens in the small test group.
+    fn hash_to_group(&self, domain : &[u8], data : &[u8]) -> BigUint {
+        let (p, one) = (&self.p, BigUint::from(1u32));
+        let cofactor = (p - &one) / &self.q;
+        let len = (p.bits() as usize).div_ceil(8) + 16;
+        for counter in 0u32.. {
+            let mut bytes = Vec::with_capacity(len + 32);
+            for block in 0u32.. {
+                if bytes.len() >= len {
+                    break;
+                }
+                let mut hasher = Sha256::new();
+                hasher.update(b"anonvote-hash-to-group");
+                for part in [domain, data] {
+                    hasher.update((part.len() as u32).to_be_bytes());
+                    hasher.update(part);
+                }
+                hasher.update(counter.to_be_bytes());
+                hasher.update(block.to_be_bytes());
+                bytes.extend_from_slice(&hasher.finalize());
+            }
+            let element = (BigUint::from_bytes_be(&bytes[..len]) % p).modpow(&cofactor, p);
+            if element > one && element != self.alpha {
+                return element;
+            }
+        }
+        unreachable!("Ran out of counters")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Backend {
+    Modp(ModpGroup),
+    Ristretto(Ristretto255),
+}
+
+// Cheap to clone, every key holds one
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GroupParams {
+    group : Arc<Backend>,
+}
+
+fn from_hex(value : &str, name : &'static str) -> Result<BigUint, GroupError> {
+    hex::decode(value).map(|bytes| BigUint::from_bytes_be(&bytes)).map_err(|_| GroupError::InvalidHex(name))
+}
+
+// Group that is used when no other group is configured
+pub fn default_group() -> &'static GroupParams {
+    static DEFAULT : OnceLock<GroupParams> = OnceLock::new();
+    DEFAULT.get_or_init(GroupParams::rfc5114_1024_160)
+}
+
+impl Default for GroupParams {
+    fn default() -> GroupParams {
+        default_group().clone()
+    }
+}
+
+impl GroupParams {
+    // Custom MODP group, checked to be a group of prime order q with alpha as its generator
+    pub fn new(name : &str, p : BigUint, q : BigUint, alpha : BigUint) -> Result<GroupParams, GroupError> {
+        let one = BigUint::from(1u32);
+        if !is_probable_prime(&p) {
+            return Err(GroupError::NotPrime("p"));
+        }
+        if !is_probable_prime(&q) {
+            return Err(GroupError::NotPrime("q"));
+        }
+        if q >= p || !(&p - &one).is_multiple_of(&q) {
+            return Err(GroupError::InvalidOrder);
+        }
+        if alpha <= one || alpha >= p || alpha.modpow(&q, &p) != one {
+            return Err(GroupError::InvalidGenerator);
+        }
+        Ok(GroupParams::modp(name, p, q, alpha))
+    }
+
+    pub fn from_hex(name : &str, p : &str, q : &str, alpha : &str) -> Result<GroupParams, GroupError> {
+        GroupParams::new(name, from_hex(p, "p")?, from_hex(q, "q")?, from_hex(alpha, "alpha")?)
+    }
+
+    // Group as it is published (GetParameters, records, stored elections), its name and numbers.
+    // A built-in name must come with the numbers of that group, any other name is a custom group.
+    // Checking the primes is slow, so custom groups that passed the check once are remembered.
+    pub fn from_numbers(name : &str, p : &BigUint, q : &BigUint, alpha : &BigUint) -> Result<GroupParams, GroupError> {
+        static CHECKED : Mutex<Vec<GroupParams>> = Mutex::new(Vec::new());
+
+        let same_numbers = |group : &GroupParams| (group.p(), group.q(), group.alpha()) == (p, q, alpha);
+        if let Ok(group) = GroupParams::by_name(name) {
+            return if same_numbers(&group) { Ok(group) } else { Err(GroupError::Mismatch(name.to_string())) };
+        }
+        let mut checked = CHECKED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(group) = checked.iter().find(|group| group.name() == name && same_numbers(group)) {
+            return Ok(group.clone());
+        }
+        let group = GroupParams::new(name, p.clone(), q.clone(), alpha.clone())?;
+        checked.push(group.clone());
+        Ok(group)
+    }
+
+    pub fn by_name(name : &str) -> Result<GroupParams, GroupError> {
+        match name {
+            DEFAULT_GROUP => Ok(GroupParams::rfc5114_1024_160()),
+            "rfc3526-2048" => Ok(GroupParams::safe_prime(name, RFC3526_2048_P)),
+            "rfc3526-3072" => Ok(GroupParams::safe_prime(name, RFC3526_3072_P)),
+            "ffdhe2048" => Ok(GroupParams::safe_prime(name, FFDHE2048_P)),
+            "ffdhe3072" => Ok(GroupParams::safe_prime(name, FFDHE3072_P)),
+            "ristretto255" => Ok(GroupParams::ristretto255()),
+            _ => Err(GroupError::UnknownGroup(name.to_string())),
+        }
+    }
+
+    pub fn rfc5114_1024_160() -> GroupParams {
+        let value = |hex : &str| BigUint::from_bytes_be(&hex::decode(hex).unwrap());
+        GroupParams::modp(DEFAULT_GROUP, value(RFC5114_1024_P), value(RFC5114_1024_Q), value(RFC5114_1024_ALPHA))
+    }
+
+    // Only for tests, there are 10 non-trivial elements. It is not built in, so no key can end up in it.
+    #[cfg(test)]
+    pub fn small() -> GroupParams {
+        GroupParams::modp("small-23-11", BigUint::from(23u32), BigUint::from(11u32), BigUint::from(4u32))
+    }
+
+    pub fn ristretto255() -> GroupParams {
+        GroupParams {
+            group : Arc::new(Backend::Ristretto(Ristretto255::new()))
+        }
+    }
+
+    fn safe_prime(name : &str, p : &str) -> GroupParams {
+        let p = BigUint::from_bytes_be(&hex::decode(p).unwrap());
+        let q = &p >> 1;
+        GroupParams::modp(name, p, q, BigUint::from(2u32))
+    }
+
+    fn modp(name : &str, p : BigUint, q : BigUint, alpha : BigUint) -> GroupParams {
+        GroupParams {
+            group : Arc::new(Backend::Modp(ModpGroup {
+                name : name.to_string(), p, q, alpha
+            }))
+        }
+    }
+
+    fn backend(&self) -> &dyn Group {
+        match self.group.as_ref() {
+            Backend::Modp(group) => group,
+            Backend::Ristretto(group) => group,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.backend().name()
+    }
+
+    pub fn p(&self) -> &BigUint {
+        self.backend().modulus()
+    }
+
+    pub fn q(&self) -> &BigUint {
+        self.backend().order()
+    }
+
+    pub fn alpha(&self) -> &BigUint {
+        self.backend().generator()
+    }
+
+    pub fn exp(&self, base : &BigUint, exponent : &BigUint) -> Option<BigUint> {
+        self.backend().exp(base, exponent)
+    }
+
+    // For secrets and nonces, exp is only for public exponents
+    pub fn exp_secret(&self, base : &BigUint, exponent : &SecretScalar) -> Option<BigUint> {
+        self.backend().exp_secret(base, exponent)
+    }
+
+    pub fn mul(&self, x : &BigUint, y : &BigUint) -> Option<BigUint> {
+        self.backend().mul(x, y)
+    }
+
+    pub fn identity(&self) -> BigUint {
+        self.backend().identity()
+    }
+
+    pub fn inv(&self, x : &BigUint) -> Option<BigUint> {
+        self.backend().inv(x)
+    }
+
+    // x / y
+    pub fn div(&self, x : &BigUint, y : &BigUint) -> Option<BigUint> {
+        self.mul(x, &self.inv(y)?)
+    }
+
+    pub fn contains(&self, value : &BigUint) -> bool {
+        self.backend().contains(value)
+    }
+
+    pub fn is_element(&self, value : &BigUint) -> bool {
+        self.backend().is_element(value)
+    }
+
+    // Random exponent below q
+    pub fn random_exponent(&self) -> BigUint {
+        crate::zkp_util::generate_random_below(self.q())
+    }
+
+    pub fn hash_to_group(&self, domain : &[u8], data : &[u8]) -> BigUint {
+        self.backend().hash_to_group(domain, data)
+    }
+
+    // Fiat-Shamir challenge, hash of the group, all given numbers and the context reduced mod q.
+    // Every value is prefixed with its length so different inputs can't produce the same hash input.
+    pub fn hash_challenge(&self, values : &[&BigUint], context : &[u8]) -> BigUint {
+        let mut hasher = Sha256::new();
+        hasher.update(b"anonvote-chaum-pedersen");
+        let group = [self.p(), self.q(), self.alpha()];
+        for value in group.iter().chain(values) {
+            let bytes = value.to_bytes_be();
+            hasher.update((bytes.len() as u32).to_be_bytes());
+            hasher.update(&bytes);
+        }
+        hasher.update((context.len() as u32).to_be_bytes());
+        hasher.update(context);
+        BigUint::from_bytes_be(&hasher.finalize()) % self.q()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::params::KeyParams;
+    use crate::SecretKey;
+
+    #[test]
+    fn test_builtin() {
+        for name in BUILTIN_GROUPS {
+            let group = GroupParams::by_name(name).unwrap();
+            assert_eq!(group.name(), name);
+            assert!(group.contains(group.alpha()), "{} has no generator of order q", name);
+            assert_eq!(group.exp(group.alpha(), group.q()), group.exp(group.alpha(), &BigUint::ZERO), "{} has the wrong order", name);
+        }
+        assert_eq!(*default_group(), GroupParams::by_name(DEFAULT_GROUP).unwrap());
+        assert_eq!(GroupParams::by_name("other"), Err(GroupError::UnknownGroup("other".to_string())));
+        assert!(GroupParams::by_name("small-23-11").is_err(), "Test group can be selected");
+
+        // Full check of the smaller groups, the primes of the big ones take too long without optimizations
+        let group = GroupParams::rfc5114_1024_160();
+        assert_eq!(GroupParams::new("custom", group.p().clone(), group.q().clone(), group.alpha().clone()).unwrap().p(), group.p());
+        let group = GroupParams::by_name("ffdhe2048").unwrap();
+        assert!(GroupParams::new("custom", group.p().clone(), group.q().clone(), group.alpha().clone()).is_ok());
+    }
+
+    #[test]
+    fn test_custom() {
+        assert!(GroupParams::from_hex("custom", "17", "0b", "04").is_ok());
+        assert_eq!(GroupParams::from_hex("custom", "1x", "0b", "04"), Err(GroupError::InvalidHex("p")));
+        assert_eq!(GroupParams::from_hex("custom", "19", "0b", "04"), Err(GroupError::NotPrime("p")));
+        assert_eq!(GroupParams::from_hex("custom", "17", "0c", "04"), Err(GroupError::NotPrime("q")));
+        assert_eq!(GroupParams::from_hex("custom", "17", "05", "04"), Err(GroupError::InvalidOrder));
+        assert_eq!(GroupParams::from_hex("custom", "17", "0b", "05"), Err(GroupError::InvalidGenerator), "5 has order 22");
+        assert_eq!(GroupParams::from_hex("custom", "17", "0b", "01"), Err(GroupError::InvalidGenerator));
+    }
+
+    #[test]
+    fn test_from_numbers() {
+        for name in BUILTIN_GROUPS {
+            let group = GroupParams::by_name(name).unwrap();
+            assert_eq!(GroupParams::from_numbers(name, group.p(), group.q(), group.alpha()), Ok(group));
+        }
+        let group = GroupParams::rfc5114_1024_160();
+        let other = GroupParams::by_name("ffdhe2048").unwrap();
+        assert_eq!(GroupParams::from_numbers(DEFAULT_GROUP, other.p(), other.q(), other.alpha()), Err(GroupError::Mismatch(DEFAULT_GROUP.to_string())), "Built-in name with other numbers");
+
+        let custom = GroupParams::from_numbers("custom", group.p(), group.q(), group.alpha()).unwrap();
+        assert_eq!((custom.name(), custom.p()), ("custom", group.p()));
+        assert_eq!(GroupParams::from_numbers("custom", group.p(), group.q(), group.alpha()), Ok(custom));
+        let small = GroupParams::small();
+        assert_eq!(GroupParams::from_numbers("custom", small.p(), small.q(), &BigUint::from(5u32)), Err(GroupError::InvalidGenerator));
+    }
+
+    // Keys of different groups in the same binary, each proves and verifies in its own group
+    #[test]
+    fn test_keys_in_groups() {
+        for group in [GroupParams::default(), GroupParams::by_name("ffdhe2048").unwrap(), GroupParams::small(), GroupParams::ristretto255()] {
+            let params = KeyParams::for_election(&group, "election");
+            assert!(params.valid());
+            let secret_key = SecretKey::from_bytes_be(&group, &12345u32.to_be_bytes());
+            let public_key = secret_key.public_key(&params);
+            assert_eq!(public_key.group(), &group);
+            assert!(params.valid_key(&public_key), "Key in {} is not valid", group.name());
+
+            let proof = secret_key.prove(&public_key, b"context");
+            assert!(public_key.verify_proof(&proof, b"context"), "Proof in {} is not valid", group.name());
+        }
+
+        let key = SecretKey::from_bytes_be(&GroupParams::default(), &12345u32.to_be_bytes()).public_key(&KeyParams::for_election(&GroupParams::default(), "election"));
+        assert!(!KeyParams::for_election(&GroupParams::small(), "election").valid_key(&key), "Key of another group was accepted");
+    }
+
+    #[test]
+    fn test_elements() {
+        for group in [GroupParams::default(), GroupParams::small(), GroupParams::ristretto255()] {
+            let x = group.exp(group.alpha(), &BigUint::from(5u32)).unwrap();
+            assert!(group.is_element(&group.identity()) && !group.contains(&group.identity()));
+            assert_eq!(group.mul(&x, &group.inv(&x).unwrap()), Some(group.identity()), "Wrong inverse in {}", group.name());
+            assert_eq!(group.div(&group.mul(&x, group.alpha()).unwrap(), &x).as_ref(), Some(group.alpha()));
+            assert_eq!(group.mul(&x, &group.identity()), Some(x));
+        }
+        assert!(!GroupParams::small().is_element(&BigUint::from(5u32)), "Element of order 22 is in the group");
+    }
+
+    #[test]
+    fn test_hash_to_group() {
+        let group = GroupParams::default();
+        let element = group.hash_to_group(b"test", b"data");
+        assert!(group.contains(&element));
+        assert_ne!(element, group.hash_to_group(b"test", b"other"));
+        assert_ne!(element, group.hash_to_group(b"other", b"data"), "Domain is not part of the hash");
+
+        let small = GroupParams::small();
+        assert!(small.contains(&small.hash_to_group(b"test", b"data")));
+        assert_ne!(small.hash_to_group(b"test", b"data"), *small.alpha());
+    }
+}
@@ -1,74 +1,69 @@
 use num_bigint::BigUint;
 
-pub mod zkp_constants {
-    use num_bigint::BigUint;
-    use lazy_static::lazy_static;
+use group::GroupParams;
+use scalar::SecretScalar;
 
-    #[cfg(not(feature = "small_number_mode"))]
-    lazy_static! {
-        pub static ref P : BigUint = {
-            BigUint::from_bytes_be(&hex::decode("B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371").unwrap())
-        };
-        pub static ref Q : BigUint = {
-            BigUint::from_bytes_be(&hex::decode("F518AA8781A8DF278ABA4E7D64B7CB9D49462353").unwrap())
-        };
-        pub static ref ALPHA : BigUint = {
-            BigUint::from_bytes_be(&hex::decode("A4D1CBD5C3FD34126765A442EFB99905F8104DD258AC507FD6406CFF14266D31266FEA1E5C41564B777E690F5504F213160217B4B01B886A5E91547F9E2749F4D7FBD7D3B9A92EE1909D0D2263F80A76A6A24C087A091F531DBF0A0169B6A28AD662A4D18E73AFA32D779D5918D08BC8858F4DCEF97C2A24855E6EEB22B3B2E5").unwrap())
-        };
-        pub static ref ONE : BigUint = {
-            BigUint::from(1u32)
-        };
-    }
-
-    #[cfg(feature = "small_number_mode")]
-    lazy_static! {
-        pub static ref P : BigUint = {
-            BigUint::from(23u32)
-        };
-        pub static ref Q : BigUint = {
-            BigUint::from(11u32)
-        };
-        pub static ref ALPHA : BigUint = {
-            BigUint::from(4u32)
-        };
-        pub static ref ONE : BigUint = {
-            BigUint::from(1u32)
-        };
-    }
+pub mod blind_signature;
+pub mod elgamal;
+pub mod ballot_proof;
+pub mod threshold;
+pub mod mixnet;
+pub mod board;
+pub mod merkle;
+pub mod params;
+pub mod group;
+pub mod ristretto;
+pub mod scalar;
 
-    pub fn p() -> &'static BigUint {
-        &P
-    }
+pub mod zkp_util {
+    use num_bigint::{ BigUint, RandBigInt};
+    use crate::elgamal::Ciphertext;
 
-    pub fn q() -> &'static BigUint {
-        &Q
+    pub fn generate_random_below(bound: &BigUint) -> BigUint {
+        let mut rng = rand::thread_rng();
+        rng.gen_biguint_below(bound)
     }
 
-    pub fn alpha() -> &'static BigUint {
-        &ALPHA
+    // Context that binds a proof to the election and the vote
+    pub fn vote_context(election_id : &str, vote : u32) -> Vec<u8> {
+        let mut context = Vec::new();
+        context.extend_from_slice(&(election_id.len() as u32).to_be_bytes());
+        context.extend_from_slice(election_id.as_bytes());
+        context.extend_from_slice(&vote.to_be_bytes());
+        context
     }
 
-    pub fn one() -> &'static BigUint {
-        &ONE
+    // Context that binds a proof to the election only
+    pub fn election_context(election_id : &str) -> Vec<u8> {
+        let mut context = Vec::new();
+        context.extend_from_slice(&(election_id.len() as u32).to_be_bytes());
+        context.extend_from_slice(election_id.as_bytes());
+        context
     }
-}
-
-pub mod zkp_util {
-    use num_bigint::{ BigUint, RandBigInt};
-    use crate::zkp_constants;
 
-    pub fn generate_random_below(bound: &BigUint) -> BigUint {
-        let mut rng = rand::thread_rng();
-        rng.gen_biguint_below(bound)
+    // Context of the proofs that a ballot is valid. They are published with the ballot, so they are bound to the election
+    // but not to the voter. A copy of a ballot is refused as a duplicate, a re-encrypted copy would need new proofs.
+    pub fn validity_context(election_id : &str) -> Vec<u8> {
+        election_context(election_id)
     }
 
-    pub fn generate_challenge() -> BigUint {
-        generate_random_below(&zkp_constants::q())
+    // Context that binds a proof to the election and the encrypted ballot
+    pub fn ballot_context(election_id : &str, ballot : &[Ciphertext]) -> Vec<u8> {
+        let mut context = Vec::new();
+        context.extend_from_slice(&(election_id.len() as u32).to_be_bytes());
+        context.extend_from_slice(election_id.as_bytes());
+        for value in ballot.iter().flat_map(|ciphertext| [ciphertext.c1(), ciphertext.c2()]) {
+            let bytes = value.to_bytes_be();
+            context.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+            context.extend_from_slice(&bytes);
+        }
+        context
     }
 }
 
 pub struct SecretKey {
-    secret : BigUint,
+    secret : SecretScalar,
+    group : GroupParams,
 }
 
 #[derive(Hash, Clone)]
@@ -77,37 +72,109 @@ pub struct PublicKey {
     b : BigUint,
     alpha : BigUint,
     beta : BigUint,
+    group : GroupParams,
+}
+
+// Non-interactive proof of knowledge of the secret key, the challenge is derived from the commitments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    ka : BigUint,
+    kb : BigUint,
+    solution : BigUint,
+}
+
+impl Proof {
+    pub fn new(ka : BigUint, kb : BigUint, solution : BigUint) -> Proof {
+        Proof {
+            ka, kb, solution
+        }
+    }
+
+    pub fn from_bytes_be(ka : &[u8], kb : &[u8], solution : &[u8]) -> Proof {
+        Proof {
+            ka : BigUint::from_bytes_be(ka),
+            kb : BigUint::from_bytes_be(kb),
+            solution : BigUint::from_bytes_be(solution),
+        }
+    }
+
+    pub fn ka(&self) -> &BigUint {
+        &self.ka
+    }
+
+    pub fn kb(&self) -> &BigUint {
+        &self.kb
+    }
+
+    pub fn solution(&self) -> &BigUint {
+        &self.solution
+    }
 }
 
 impl PublicKey {
-    pub fn new(a : BigUint, b : BigUint, alpha : BigUint, beta : BigUint) -> PublicKey {
+    pub fn new(group : &GroupParams, a : BigUint, b : BigUint, alpha : BigUint, beta : BigUint) -> PublicKey {
         PublicKey {
-            a,b,alpha,beta
+            a,b,alpha,beta,
+            group : group.clone(),
         }
     }
 
-    pub fn from_bytes_be(a : &Vec<u8>, b : &Vec<u8>, alpha : &Vec<u8>, beta : &Vec<u8>) -> PublicKey {
+    pub fn from_bytes_be(group : &GroupParams, a : &[u8], b : &[u8], alpha : &[u8], beta : &[u8]) -> PublicKey {
         PublicKey {
-            a : BigUint::from_bytes_be(&a),
-            b : BigUint::from_bytes_be(&b),
-            alpha : BigUint::from_bytes_be(&alpha),
-            beta : BigUint::from_bytes_be(&beta),
+            a : BigUint::from_bytes_be(a),
+            b : BigUint::from_bytes_be(b),
+            alpha : BigUint::from_bytes_be(alpha),
+            beta : BigUint::from_bytes_be(beta),
+            group : group.clone(),
         }
     }
 
-    pub fn generate_challenge_request(&self) -> (BigUint, BigUint, BigUint) {
-        let k = zkp_util::generate_random_below(&zkp_constants::q());
-        let ka = self.alpha.modpow(&k, &zkp_constants::p());
-        let kb = self.beta.modpow(&k, &zkp_constants::p());
+    // Alpha and beta have to be elements of the group (see valid)
+    pub fn generate_challenge_request(&self) -> (SecretScalar, BigUint, BigUint) {
+        let k = SecretScalar::random(self.group.q());
+        let ka = self.group.exp_secret(&self.alpha, &k).expect("Alpha is not in the group");
+        let kb = self.group.exp_secret(&self.beta, &k).expect("Beta is not in the group");
         (k ,ka, kb)
     }
 
     pub fn verify(&self, ka : &BigUint, kb : &BigUint, challenge : &BigUint, solution : &BigUint) -> bool {
-        let cond1 = *ka == (self.alpha.modpow(solution, &zkp_constants::p()) * self.a.modpow(challenge, &zkp_constants::p())).modpow(&BigUint::from(1u32), &zkp_constants::p());
-        let cond2 = *kb == (self.beta.modpow(solution, &zkp_constants::p()) * self.b.modpow(challenge, &zkp_constants::p())).modpow(&BigUint::from(1u32), &zkp_constants::p());
+        let commitment = |base : &BigUint, value : &BigUint| self.group.mul(&self.group.exp(base, solution)?, &self.group.exp(value, challenge)?);
+        let cond1 = commitment(&self.alpha, &self.a).as_ref() == Some(ka);
+        let cond2 = commitment(&self.beta, &self.b).as_ref() == Some(kb);
         cond1 && cond2
     }
 
+    pub fn proof_challenge(&self, ka : &BigUint, kb : &BigUint, context : &[u8]) -> BigUint {
+        self.group.hash_challenge(&[&self.a, &self.b, &self.alpha, &self.beta, ka, kb], context)
+    }
+
+    pub fn verify_proof(&self, proof : &Proof, context : &[u8]) -> bool {
+        let challenge = self.proof_challenge(&proof.ka, &proof.kb, context);
+        self.verify(&proof.ka, &proof.kb, &challenge, &proof.solution)
+    }
+
+    // Canonical encoding of the key, every number big endian without leading zeros and prefixed with its length
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        for value in [&self.a, &self.b, &self.alpha, &self.beta] {
+            let bytes = value.to_bytes_be();
+            output.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+            output.extend_from_slice(&bytes);
+        }
+        output
+    }
+
+    // Key of a voter has to be made of group elements that are not trivial, with alpha and beta as different generators
+    pub fn valid(&self) -> bool {
+        // Alpha and beta must be different
+        if self.alpha == self.beta {
+            return false;
+        }
+
+        // In a group of prime order every element but the identity is a generator of order q
+        [&self.a, &self.b, &self.alpha, &self.beta].iter().all(|value| self.group.contains(value))
+    }
+
     pub fn a(&self) -> &BigUint {
         &self.a
     }
@@ -123,46 +190,63 @@ impl PublicKey {
     pub fn beta(&self) -> &BigUint {
         &self.beta
     }
+
+    pub fn group(&self) -> &GroupParams {
+        &self.group
+    }
 }
 
 impl SecretKey {
-    pub fn new(secret : BigUint) -> SecretKey {
+    pub fn new(group : &GroupParams, secret : SecretScalar) -> SecretKey {
+        assert!(secret.is_mod(group.q()), "Secret is not mod the order of the group");
         SecretKey {
-            secret
+            secret,
+            group : group.clone(),
         }
     }
 
-    pub fn from_bytes_be(bytes : &Vec<u8>) -> SecretKey {
+    pub fn from_bytes_be(group : &GroupParams, bytes : &[u8]) -> SecretKey {
         SecretKey {
-            secret : BigUint::from_bytes_be(&bytes)
+            secret : SecretScalar::from_bytes_be(bytes, group.q()),
+            group : group.clone(),
         }
     }
 
-    pub fn generate() -> SecretKey {
-        let secret = zkp_util::generate_random_below(&zkp_constants::q());
+    pub fn generate(group : &GroupParams) -> SecretKey {
         SecretKey {
-            secret
+            secret : SecretScalar::random(group.q()),
+            group : group.clone(),
         }
     }
 
-    pub fn generate_public_key(&self) -> PublicKey {
-        let beta = zkp_constants::alpha().modpow(&zkp_util::generate_random_below(&zkp_constants::q()), &zkp_constants::p());
-        let alpha = zkp_constants::alpha();
-        let a = alpha.modpow(&self.secret, &zkp_constants::p());
-        let b = beta.modpow(&self.secret, &zkp_constants::p());
-        PublicKey::new(a,b,alpha.clone(),beta)
+    // Key with the parameters the server published for the election, they have to be valid parameters of the group of this key
+    pub fn public_key(&self, params : &params::KeyParams) -> PublicKey {
+        let a = self.group.exp_secret(params.alpha(), &self.secret).expect("Alpha is not in the group");
+        let b = self.group.exp_secret(params.beta(), &self.secret).expect("Beta is not in the group");
+        params.public_key(a, b)
     }
 
-    pub fn solve(&self, k : &BigUint, challenge : &BigUint) -> BigUint {
-        if *k >= challenge * &self.secret {
-            return (k - challenge * &self.secret).modpow(&BigUint::from(1u32), zkp_constants::q());
-        }
-        return zkp_constants::q() - (challenge * &self.secret - k).modpow(&BigUint::from(1u32), zkp_constants::q());
+    // k - challenge * secret mod q, the time doesn't depend on the secret or the nonce (see scalar)
+    pub fn solve(&self, k : &SecretScalar, challenge : &BigUint) -> BigUint {
+        let challenge = SecretScalar::from_biguint(challenge, self.group.q());
+        k.sub(&self.secret.mul(&challenge)).to_biguint()
+    }
+
+    // Same as the interactive protocol, but the challenge is the hash of the public key, commitments and context
+    pub fn prove(&self, public_key : &PublicKey, context : &[u8]) -> Proof {
+        let (k, ka, kb) = public_key.generate_challenge_request();
+        let challenge = public_key.proof_challenge(&ka, &kb, context);
+        let solution = self.solve(&k, &challenge);
+        Proof::new(ka, kb, solution)
     }
 
-    pub fn secret(&self) -> &BigUint {
+    pub fn secret(&self) -> &SecretScalar {
         &self.secret
     }
+
+    pub fn group(&self) -> &GroupParams {
+        &self.group
+    }
 }
 
 #[cfg(test)]
@@ -172,13 +256,14 @@ mod test {
     #[test]
     fn test_random() {
         for _ in 0..10 {
-            let secret = zkp_util::generate_random_below(&zkp_constants::p());
-            let secret_key = SecretKey::new(secret);
-            let public_key = secret_key.generate_public_key();
+            let group = GroupParams::default();
+            let secret = zkp_util::generate_random_below(group.p());
+            let secret_key = SecretKey::from_bytes_be(&group, &secret.to_bytes_be());
+            let public_key = secret_key.public_key(&params::KeyParams::for_election(&group, "election"));
     
             let (k,ka,kb) = public_key.generate_challenge_request();
     
-            let challenge = zkp_util::generate_challenge();
+            let challenge = group.random_exponent();
     
             let solution = secret_key.solve(&k, &challenge);
     
@@ -186,4 +271,42 @@ mod test {
             assert!(result);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_proof() {
+        let group = GroupParams::default();
+        let params = params::KeyParams::for_election(&group, "election");
+        let secret_key = SecretKey::generate(&group);
+        let public_key = secret_key.public_key(&params);
+        let context = zkp_util::vote_context("election", 1);
+
+        let proof = secret_key.prove(&public_key, &context);
+        assert!(public_key.verify_proof(&proof, &context));
+
+        assert!(!public_key.verify_proof(&proof, &zkp_util::vote_context("election", 2)), "Proof verified for a different vote");
+        assert!(!public_key.verify_proof(&proof, &zkp_util::vote_context("other", 1)), "Proof verified for a different election");
+
+        let other_public_key = SecretKey::generate(&group).public_key(&params);
+        assert!(!other_public_key.verify_proof(&proof, &context), "Proof verified for a different key");
+
+        let forged = Proof::new(proof.ka().clone(), proof.kb().clone(), proof.solution() + 1u32);
+        assert!(!public_key.verify_proof(&forged, &context), "Forged proof was verified");
+
+        let other_secret_key = SecretKey::generate(&group);
+        let proof = other_secret_key.prove(&public_key, &context);
+        assert!(!public_key.verify_proof(&proof, &context), "Proof without the secret key was verified");
+    }
+
+    #[test]
+    fn test_valid_key() {
+        let group = GroupParams::default();
+        let public_key = SecretKey::from_bytes_be(&group, &12345u32.to_be_bytes()).public_key(&params::KeyParams::for_election(&group, "election"));
+        assert!(public_key.valid());
+
+        let (a, b, alpha, beta) = (public_key.a().clone(), public_key.b().clone(), public_key.alpha().clone(), public_key.beta().clone());
+        assert!(!PublicKey::new(&group, a.clone(), b.clone(), alpha.clone(), alpha.clone()).valid(), "Key with the same generators is valid");
+        assert!(!PublicKey::new(&group, group.identity(), b.clone(), alpha.clone(), beta.clone()).valid(), "Trivial key is valid");
+        assert!(!PublicKey::new(&group, a.clone(), b.clone(), alpha.clone(), group.p() + 1u32).valid(), "Key outside of the group is valid");
+        assert!(!PublicKey::new(&group, a, b, alpha, group.p() - 1u32).valid(), "Generator of a different order is valid");
+    }
+}
@@ -0,0 +1,177 @@
+// Merkle tree over the ballots the server accepted for an election, in the order they were accepted.
+// A voter gets a receipt with the hash of their ballot, its leaf index and the root signed with the board key,
+// and can ask for an inclusion proof later: the hashes on the path from the leaf to the root.
+// Same shape as the tree of RFC 9162 (Certificate Transparency), leaves and nodes are hashed with different prefixes
+// so a node can't be passed off as a ballot.
+
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::{zkp_util, Proof};
+use crate::board::{self, BoardKey, Hash, EMPTY_HASH};
+use crate::elgamal::Ciphertext;
+use crate::group::GroupParams;
+
+// What the receipt identifies the ballot with, the voter can compute it from their own ballot
+pub fn ballot_hash(election_id : &str, ballot : &[Ciphertext]) -> Hash {
+    let mut encoded = Vec::new();
+    for ciphertext in ballot {
+        board::push_number(&mut encoded, ciphertext.c1());
+        board::push_number(&mut encoded, ciphertext.c2());
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(b"anonvote-ballot");
+    hasher.update(zkp_util::election_context(election_id));
+    hasher.update((ballot.len() as u32).to_be_bytes());
+    hasher.update(encoded);
+    hasher.finalize().into()
+}
+
+fn leaf_hash(ballot_hash : &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0]);
+    hasher.update(ballot_hash);
+    hasher.finalize().into()
+}
+
+fn node_hash(left : &Hash, right : &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([1]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+// Largest power of two smaller than the size, the left subtree is always complete
+fn split(size : usize) -> usize {
+    let mut left = 1;
+    while left * 2 < size {
+        left *= 2;
+    }
+    left
+}
+
+pub fn root(ballot_hashes : &[Hash]) -> Hash {
+    match ballot_hashes.len() {
+        0 => EMPTY_HASH,
+        1 => leaf_hash(&ballot_hashes[0]),
+        size => {
+            let (left, right) = ballot_hashes.split_at(split(size));
+            node_hash(&root(left), &root(right))
+        }
+    }
+}
+
+// Hashes of the siblings from the leaf up to the root, None if the index is outside of the tree
+pub fn inclusion_proof(ballot_hashes : &[Hash], index : usize) -> Option<Vec<Hash>> {
+    if index >= ballot_hashes.len() {
+        return None;
+    }
+
+    let mut path = Vec::new();
+    let (mut hashes, mut index) = (ballot_hashes, index);
+    while hashes.len() > 1 {
+        let (left, right) = hashes.split_at(split(hashes.len()));
+        if index < left.len() {
+            path.push(root(right));
+            hashes = left;
+        } else {
+            path.push(root(left));
+            hashes = right;
+            index -= left.len();
+        }
+    }
+    // Collected from the root down
+    path.reverse();
+    Some(path)
+}
+
+// Walks the path from the leaf, the index tells on which side each sibling is (RFC 9162, 2.1.3.2)
+pub fn verify_inclusion(ballot_hash : &Hash, index : u64, size : u64, path : &[Hash], root : &Hash) -> bool {
+    if index >= size {
+        return false;
+    }
+
+    let (mut position, mut last) = (index, size - 1);
+    let mut hash = leaf_hash(ballot_hash);
+    for sibling in path {
+        if last == 0 {
+            return false;
+        }
+        if position & 1 == 1 || position == last {
+            hash = node_hash(sibling, &hash);
+            // Right edge of an incomplete tree, the node is carried up without a sibling
+            while position & 1 == 0 && position != 0 {
+                position >>= 1;
+                last >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, sibling);
+        }
+        position >>= 1;
+        last >>= 1;
+    }
+    last == 0 && hash == *root
+}
+
+// What the root signature covers
+pub fn root_message(election_id : &str, size : u64, root : &Hash) -> Vec<u8> {
+    let mut message = b"anonvote-ballot-root".to_vec();
+    message.extend_from_slice(&zkp_util::election_context(election_id));
+    message.extend_from_slice(&size.to_be_bytes());
+    message.extend_from_slice(root);
+    message
+}
+
+pub fn sign_root(key : &BoardKey, election_id : &str, size : u64, root : &Hash) -> Proof {
+    key.sign(&root_message(election_id, size, root))
+}
+
+pub fn verify_root(group : &GroupParams, public_key : &BigUint, election_id : &str, size : u64, root : &Hash, signature : &Proof) -> bool {
+    board::verify_signature(group, public_key, &root_message(election_id, size, root), signature)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::elgamal::DecryptionKey;
+
+    #[test]
+    fn test_inclusion() {
+        let key = DecryptionKey::generate(&GroupParams::default());
+        let ballot_hashes : Vec<Hash> = (0..7).map(|vote| ballot_hash("election", &key.encryption_key().encrypt_vote(vote % 2, 2))).collect();
+        assert_eq!(root(&[]), EMPTY_HASH);
+
+        // Every size up to 7 covers complete and incomplete trees
+        for size in 1..=ballot_hashes.len() {
+            let hashes = &ballot_hashes[..size];
+            let root = root(hashes);
+            for index in 0..size {
+                let path = inclusion_proof(hashes, index).unwrap();
+                assert!(verify_inclusion(&hashes[index], index as u64, size as u64, &path, &root), "Could not verify ballot {} of {}", index, size);
+                if let Some((_, shorter)) = path.split_last() {
+                    assert!(!verify_inclusion(&hashes[index], index as u64, size as u64, shorter, &root), "Verified with a missing sibling");
+                }
+                assert!(!verify_inclusion(&ballot_hashes[(index + 1) % 7], index as u64, size as u64, &path, &root), "Verified a different ballot");
+                if size > 1 {
+                    assert!(!verify_inclusion(&hashes[index], ((index + 1) % size) as u64, size as u64, &path, &root), "Verified at a different index");
+                }
+            }
+            assert_eq!(inclusion_proof(hashes, size), None);
+        }
+        assert_ne!(ballot_hash("election", &[]), ballot_hash("other", &[]), "Ballot hash is not bound to the election");
+    }
+
+    #[test]
+    fn test_root_signature() {
+        let group = GroupParams::default();
+        let key = BoardKey::generate(&group);
+        let root = root(&[[1; 32], [2; 32]]);
+        let signature = sign_root(&key, "election", 2, &root);
+        assert!(verify_root(&group, key.public_key(), "election", 2, &root, &signature));
+        // Size is only bound by the signature, a path can fit trees of different sizes
+        assert!(!verify_root(&group, key.public_key(), "election", 3, &root, &signature), "Verified root of a different size");
+        assert!(!verify_root(&group, key.public_key(), "other", 2, &root, &signature), "Verified root of a different election");
+        assert!(!board::verify_checkpoint(&group, key.public_key(), "election", 2, &root, &signature), "Root signature verified as a checkpoint");
+    }
+}
@@ -0,0 +1,361 @@
+// Re-encryption mix-net with the verifiable shuffle of Terelius and Wikström,
+// following the pseudo-code of Haenni et al. "Pseudo-Code Algorithms for Verifiable Re-Encryption Mix-Nets".
+// Every ballot (one ciphertext per option) is re-encrypted and the ballots are permuted, so the output
+// can't be linked to the input, and the proof shows the output holds the same votes without revealing the permutation.
+// The prover commits to the permutation with Pedersen commitments c_i = alpha^r_i * h_j, and proves with
+// a chain of commitments that the committed matrix is a permutation and that the same permutation was used
+// for the ballots. Ballots with several ciphertexts use independent randomness per ciphertext.
+
+use num_bigint::BigUint;
+use rand::seq::SliceRandom;
+
+use crate::elgamal::{Ciphertext, EncryptionKey};
+use crate::group::GroupParams;
+use crate::scalar::SecretScalar;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShuffleProof {
+    commitments : Vec<BigUint>, // Commitment to the permutation
+    chain : Vec<BigUint>, // Commitment chain of the permuted challenges
+    t : [BigUint; 3],
+    t4 : Vec<Ciphertext>, // One for every ciphertext of a ballot
+    t_hat : Vec<BigUint>,
+    s : [BigUint; 3],
+    s4 : Vec<BigUint>,
+    s_hat : Vec<BigUint>,
+    s_prime : Vec<BigUint>,
+}
+
+// x^-e
+fn pow_neg(group : &GroupParams, x : &BigUint, e : &BigUint) -> Option<BigUint> {
+    let q = group.q();
+    group.exp(x, &((q - e % q) % q))
+}
+
+// Generators nobody knows the discrete logarithm of, derived from a hash so the verifier gets the same ones.
+// The first one is the base of the commitment chain, the others commit to the permutation.
+fn generators(group : &GroupParams, count : usize) -> Vec<BigUint> {
+    (0..count as u64).map(|index| group.hash_to_group(b"anonvote-mixnet-generator", &index.to_be_bytes())).collect()
+}
+
+fn ballot_values(ballots : &[Vec<Ciphertext>]) -> impl Iterator<Item = &BigUint> {
+    ballots.iter().flatten().flat_map(|ciphertext| [ciphertext.c1(), ciphertext.c2()])
+}
+
+// Challenges u_i, one for every input ballot, bound to both ballot lists and the permutation commitment
+fn challenges(key : &EncryptionKey, input : &[Vec<Ciphertext>], output : &[Vec<Ciphertext>], commitments : &[BigUint], context : &[u8]) -> Vec<BigUint> {
+    let group = key.group();
+    let values : Vec<&BigUint> = ballot_values(input).chain(ballot_values(output)).chain(commitments).chain([key.h()]).collect();
+    let seed = group.hash_challenge(&values, context);
+    (0..input.len() as u64).map(|i| group.hash_challenge(&[&seed, &BigUint::from(i)], context)).collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn challenge(key : &EncryptionKey, input : &[Vec<Ciphertext>], output : &[Vec<Ciphertext>], commitments : &[BigUint], chain : &[BigUint], t : &[BigUint; 3], t4 : &[Ciphertext], t_hat : &[BigUint], context : &[u8]) -> BigUint {
+    let values : Vec<&BigUint> = ballot_values(input).chain(ballot_values(output)).chain(commitments).chain(chain).chain([key.h()])
+        .chain(t).chain(t4.iter().flat_map(|t4| [t4.c1(), t4.c2()])).chain(t_hat).collect();
+    key.group().hash_challenge(&values, context)
+}
+
+// Encryption of 0 with the given randomness (negative if needed) multiplied into the ciphertext
+fn re_encrypt(key : &EncryptionKey, ciphertext : &Ciphertext, r : &BigUint) -> Option<Ciphertext> {
+    ciphertext.add(key.group(), &key.encrypt_with(0, &SecretScalar::from_biguint(r, key.group().q())))
+}
+
+// The randomness of the prover and the permuted challenges give the permutation away, so they use the constant time exponentiation
+fn exp_secret(group : &GroupParams, base : &BigUint, e : &BigUint) -> Option<BigUint> {
+    group.exp_secret(base, &SecretScalar::from_biguint(e, group.q()))
+}
+
+fn exp_public(group : &GroupParams, base : &BigUint, e : &BigUint) -> Option<BigUint> {
+    group.exp(base, e)
+}
+
+// Product of x_i^e_i for the c1 and c2 parts of the ciphertexts separately
+fn ciphertext_product<'a>(group : &GroupParams, ciphertexts : impl Iterator<Item = &'a Ciphertext>, exponents : &[BigUint], exp : fn(&GroupParams, &BigUint, &BigUint) -> Option<BigUint>) -> Option<Ciphertext> {
+    let (c1, c2) = ciphertexts.zip(exponents).try_fold((group.identity(), group.identity()), |(c1, c2), (ciphertext, e)| {
+        Some((group.mul(&c1, &exp(group, ciphertext.c1(), e)?)?, group.mul(&c2, &exp(group, ciphertext.c2(), e)?)?))
+    })?;
+    Some(Ciphertext::new(c1, c2))
+}
+
+fn product(group : &GroupParams, mut values : impl Iterator<Item = Option<BigUint>>) -> Option<BigUint> {
+    values.try_fold(group.identity(), |product, value| group.mul(&product, &value?))
+}
+
+// All ballots must have the same number of ciphertexts
+fn width(ballots : &[Vec<Ciphertext>]) -> Option<usize> {
+    let width = ballots.first().map(Vec::len).unwrap_or(0);
+    ballots.iter().all(|ballot| ballot.len() == width).then_some(width)
+}
+
+// Re-encrypts and permutes the ballots, returns the shuffled ballots and the proof.
+// None if the ballots have different sizes or something is not in the group of the key.
+pub fn shuffle(key : &EncryptionKey, input : &[Vec<Ciphertext>], context : &[u8]) -> Option<(Vec<Vec<Ciphertext>>, ShuffleProof)> {
+    let group = key.group();
+    let q = group.q();
+    let alpha = group.alpha();
+    let n = input.len();
+    let w = width(input)?;
+    if !key.valid() {
+        return None;
+    }
+
+    // Output ballot i is input ballot psi[i]
+    let mut psi : Vec<usize> = (0..n).collect();
+    psi.shuffle(&mut rand::thread_rng());
+    let r_prime : Vec<Vec<BigUint>> = (0..n).map(|_| (0..w).map(|_| group.random_exponent()).collect()).collect();
+    let output : Vec<Vec<Ciphertext>> = psi.iter().zip(&r_prime).map(|(j, r)| {
+        input[*j].iter().zip(r).map(|(ciphertext, r)| re_encrypt(key, ciphertext, r)).collect()
+    }).collect::<Option<_>>()?;
+
+    let generators = generators(group, n + 1);
+    let (h, hs) = (&generators[0], &generators[1..]);
+
+    let r : Vec<BigUint> = (0..n).map(|_| group.random_exponent()).collect();
+    let mut commitments = vec![BigUint::ZERO; n];
+    for (i, j) in psi.iter().enumerate() {
+        commitments[*j] = group.mul(&exp_secret(group, alpha, &r[*j])?, &hs[i])?;
+    }
+
+    let u = challenges(key, input, &output, &commitments, context);
+    let u_prime : Vec<&BigUint> = psi.iter().map(|j| &u[*j]).collect();
+
+    let r_hat : Vec<BigUint> = (0..n).map(|_| group.random_exponent()).collect();
+    let mut chain : Vec<BigUint> = Vec::with_capacity(n);
+    for i in 0..n {
+        let previous = chain.last().unwrap_or(h);
+        chain.push(group.mul(&exp_secret(group, alpha, &r_hat[i])?, &exp_secret(group, previous, u_prime[i])?)?);
+    }
+
+    let omega : [BigUint; 3] = [group.random_exponent(), group.random_exponent(), group.random_exponent()];
+    let omega4 : Vec<BigUint> = (0..w).map(|_| group.random_exponent()).collect();
+    let omega_hat : Vec<BigUint> = (0..n).map(|_| group.random_exponent()).collect();
+    let omega_prime : Vec<BigUint> = (0..n).map(|_| group.random_exponent()).collect();
+
+    let t = [
+        exp_secret(group, alpha, &omega[0])?,
+        exp_secret(group, alpha, &omega[1])?,
+        group.mul(&exp_secret(group, alpha, &omega[2])?, &product(group, hs.iter().zip(&omega_prime).map(|(h, o)| exp_secret(group, h, o)))?)?,
+    ];
+    let t4 : Vec<Ciphertext> = (0..w).map(|k| {
+        ciphertext_product(group, output.iter().map(|ballot| &ballot[k]), &omega_prime, exp_secret)?.add(group, &key.encrypt_with(0, &SecretScalar::from_biguint(&((q - &omega4[k]) % q), q)))
+    }).collect::<Option<_>>()?;
+    let t_hat : Vec<BigUint> = (0..n).map(|i| {
+        let previous = if i == 0 { h } else { &chain[i - 1] };
+        group.mul(&exp_secret(group, alpha, &omega_hat[i])?, &exp_secret(group, previous, &omega_prime[i])?)
+    }).collect::<Option<_>>()?;
+
+    let c = challenge(key, input, &output, &commitments, &chain, &t, &t4, &t_hat, context);
+
+    // v_i = u'_{i+1} * ... * u'_{n-1}
+    let mut v = vec![BigUint::from(1u32); n];
+    for i in (0..n.saturating_sub(1)).rev() {
+        v[i] = (u_prime[i + 1] * &v[i + 1]) % q;
+    }
+    let r_bar = r.iter().fold(BigUint::ZERO, |sum, r| (sum + r) % q);
+    let r_hat_sum = r_hat.iter().zip(&v).fold(BigUint::ZERO, |sum, (r, v)| (sum + r * v) % q);
+    let r_tilde = r.iter().zip(&u).fold(BigUint::ZERO, |sum, (r, u)| (sum + r * u) % q);
+    let r4 : Vec<BigUint> = (0..w).map(|k| r_prime.iter().zip(&u_prime).fold(BigUint::ZERO, |sum, (r, u)| (sum + &r[k] * *u) % q)).collect();
+
+    let respond = |omega : &BigUint, secret : &BigUint| (omega + &c * secret) % q;
+    let s = [respond(&omega[0], &r_bar), respond(&omega[1], &r_hat_sum), respond(&omega[2], &r_tilde)];
+    let s4 = omega4.iter().zip(&r4).map(|(o, r)| respond(o, r)).collect();
+    let s_hat = omega_hat.iter().zip(&r_hat).map(|(o, r)| respond(o, r)).collect();
+    let s_prime = omega_prime.iter().zip(&u_prime).map(|(o, u)| respond(o, u)).collect();
+
+    Some((output, ShuffleProof::new(commitments, chain, t, t4, t_hat, s, s4, s_hat, s_prime)))
+}
+
+// Checks that the output ballots are a re-encrypted permutation of the input ballots
+pub fn verify_shuffle(key : &EncryptionKey, input : &[Vec<Ciphertext>], output : &[Vec<Ciphertext>], proof : &ShuffleProof, context : &[u8]) -> bool {
+    let group = key.group();
+    let n = input.len();
+    let Some(w) = width(input) else {
+        return false;
+    };
+
+    let lengths_valid = output.len() == n && output.iter().all(|ballot| ballot.len() == w) &&
+        [&proof.commitments, &proof.chain, &proof.t_hat, &proof.s_hat, &proof.s_prime].iter().all(|values| values.len() == n) &&
+        proof.t4.len() == w && proof.s4.len() == w;
+    if !lengths_valid || !key.valid() {
+        return false;
+    }
+    let elements_valid = proof.commitments.iter().chain(&proof.chain).chain(&proof.t).chain(&proof.t_hat).all(|value| group.is_element(value)) &&
+        proof.t4.iter().chain(input.iter().flatten()).chain(output.iter().flatten()).all(|ciphertext| ciphertext.valid(group));
+    let scalars_valid = proof.s.iter().chain(&proof.s4).chain(&proof.s_hat).chain(&proof.s_prime).all(|s| s < group.q());
+    if !elements_valid || !scalars_valid {
+        return false;
+    }
+    check_shuffle(key, input, output, proof, w, context) == Some(true)
+}
+
+// Recomputes the commitments of the proof, None if some value is not in the group
+fn check_shuffle(key : &EncryptionKey, input : &[Vec<Ciphertext>], output : &[Vec<Ciphertext>], proof : &ShuffleProof, w : usize, context : &[u8]) -> Option<bool> {
+    let group = key.group();
+    let q = group.q();
+    let alpha = group.alpha();
+    let n = input.len();
+
+    let generators = generators(group, n + 1);
+    let (h, hs) = (&generators[0], &generators[1..]);
+    let u = challenges(key, input, output, &proof.commitments, context);
+    let c = challenge(key, input, output, &proof.commitments, &proof.chain, &proof.t, &proof.t4, &proof.t_hat, context);
+
+    let c_bar = group.div(&product(group, proof.commitments.iter().cloned().map(Some))?, &product(group, hs.iter().cloned().map(Some))?)?;
+    let u_product = u.iter().fold(BigUint::from(1u32), |product, u| (product * u) % q);
+    let c_hat = group.mul(proof.chain.last().unwrap_or(h), &pow_neg(group, h, &u_product)?)?;
+    let c_tilde = product(group, proof.commitments.iter().zip(&u).map(|(c, u)| group.exp(c, u)))?;
+
+    let t1 = group.mul(&pow_neg(group, &c_bar, &c)?, &group.exp(alpha, &proof.s[0])?)?;
+    let t2 = group.mul(&pow_neg(group, &c_hat, &c)?, &group.exp(alpha, &proof.s[1])?)?;
+    let h_product = product(group, hs.iter().zip(&proof.s_prime).map(|(h, s)| group.exp(h, s)))?;
+    let t3 = group.mul(&group.mul(&pow_neg(group, &c_tilde, &c)?, &group.exp(alpha, &proof.s[2])?)?, &h_product)?;
+    if [t1, t2, t3] != proof.t {
+        return Some(false);
+    }
+
+    for k in 0..w {
+        let e_tilde = ciphertext_product(group, input.iter().map(|ballot| &ballot[k]), &u, exp_public)?;
+        let e_tilde_neg = Ciphertext::new(pow_neg(group, e_tilde.c1(), &c)?, pow_neg(group, e_tilde.c2(), &c)?);
+        let t4 = ciphertext_product(group, output.iter().map(|ballot| &ballot[k]), &proof.s_prime, exp_public)?
+            .add(group, &e_tilde_neg)?
+            .add(group, &key.encrypt_with(0, &SecretScalar::from_biguint(&((q - &proof.s4[k]) % q), q)))?;
+        if t4 != proof.t4[k] {
+            return Some(false);
+        }
+    }
+
+    for i in 0..n {
+        let previous = if i == 0 { h } else { &proof.chain[i - 1] };
+        let t_hat = group.mul(&group.mul(&pow_neg(group, &proof.chain[i], &c)?, &group.exp(alpha, &proof.s_hat[i])?)?, &group.exp(previous, &proof.s_prime[i])?)?;
+        if t_hat != proof.t_hat[i] {
+            return Some(false);
+        }
+    }
+    Some(true)
+}
+
+impl ShuffleProof {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(commitments : Vec<BigUint>, chain : Vec<BigUint>, t : [BigUint; 3], t4 : Vec<Ciphertext>, t_hat : Vec<BigUint>, s : [BigUint; 3], s4 : Vec<BigUint>, s_hat : Vec<BigUint>, s_prime : Vec<BigUint>) -> ShuffleProof {
+        ShuffleProof {
+            commitments, chain, t, t4, t_hat, s, s4, s_hat, s_prime
+        }
+    }
+
+    pub fn commitments(&self) -> &[BigUint] {
+        &self.commitments
+    }
+
+    pub fn chain(&self) -> &[BigUint] {
+        &self.chain
+    }
+
+    pub fn t(&self) -> &[BigUint; 3] {
+        &self.t
+    }
+
+    pub fn t4(&self) -> &[Ciphertext] {
+        &self.t4
+    }
+
+    pub fn t_hat(&self) -> &[BigUint] {
+        &self.t_hat
+    }
+
+    pub fn s(&self) -> &[BigUint; 3] {
+        &self.s
+    }
+
+    pub fn s4(&self) -> &[BigUint] {
+        &self.s4
+    }
+
+    pub fn s_hat(&self) -> &[BigUint] {
+        &self.s_hat
+    }
+
+    pub fn s_prime(&self) -> &[BigUint] {
+        &self.s_prime
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::elgamal::DecryptionKey;
+    use crate::zkp_util;
+
+    fn decrypt_all(key : &DecryptionKey, ballots : &[Vec<Ciphertext>]) -> Vec<Vec<u32>> {
+        let mut votes : Vec<Vec<u32>> = ballots.iter().map(|ballot| ballot.iter().map(|ciphertext| key.decrypt(ciphertext, 1).unwrap()).collect()).collect();
+        votes.sort();
+        votes
+    }
+
+    #[test]
+    fn test_shuffle() {
+        for group in [GroupParams::default(), GroupParams::ristretto255()] {
+            let key = DecryptionKey::generate(&group);
+            let encryption_key = key.encryption_key();
+            let context = zkp_util::election_context("election");
+
+            let input : Vec<Vec<Ciphertext>> = [0, 2, 1, 2, 0].iter().map(|vote| encryption_key.encrypt_vote(*vote, 3)).collect();
+            let (output, proof) = shuffle(encryption_key, &input, &context).unwrap();
+            assert!(verify_shuffle(encryption_key, &input, &output, &proof, &context), "Shuffle in {} was not verified", group.name());
+            assert_eq!(decrypt_all(&key, &input), decrypt_all(&key, &output), "Shuffle changed the votes");
+
+            let (empty, empty_proof) = shuffle(encryption_key, &[], &context).unwrap();
+            assert!(empty.is_empty());
+            assert!(verify_shuffle(encryption_key, &[], &empty, &empty_proof, &context));
+
+            let mut uneven = input.clone();
+            uneven[0].pop();
+            assert!(shuffle(encryption_key, &uneven, &context).is_none(), "Shuffled ballots of different sizes");
+        }
+
+        // Ballots of one group can't be shuffled or verified with a key of another
+        let key = DecryptionKey::generate(&GroupParams::default());
+        let ristretto = DecryptionKey::generate(&GroupParams::ristretto255());
+        let context = zkp_util::election_context("election");
+        let input : Vec<Vec<Ciphertext>> = [0, 1].iter().map(|vote| key.encryption_key().encrypt_vote(*vote, 2)).collect();
+        let (output, proof) = shuffle(key.encryption_key(), &input, &context).unwrap();
+        assert!(shuffle(ristretto.encryption_key(), &input, &context).is_none(), "Shuffled ballots of another group");
+        assert!(!verify_shuffle(ristretto.encryption_key(), &input, &output, &proof, &context), "Verified shuffle of another group");
+    }
+
+    #[test]
+    fn test_invalid_shuffle() {
+        let group = GroupParams::default();
+        let key = DecryptionKey::generate(&group);
+        let encryption_key = key.encryption_key();
+        let context = zkp_util::election_context("election");
+        let input : Vec<Vec<Ciphertext>> = [0, 2, 1].iter().map(|vote| encryption_key.encrypt_vote(*vote, 3)).collect();
+        let (output, proof) = shuffle(encryption_key, &input, &context).unwrap();
+
+        assert!(!verify_shuffle(encryption_key, &input, &output, &proof, &zkp_util::election_context("other")), "Verified for a different election");
+
+        // Ballot replaced with a fresh one that votes for something else
+        let mut replaced = output.clone();
+        replaced[0] = encryption_key.encrypt_vote(1, 3);
+        assert!(!verify_shuffle(encryption_key, &input, &replaced, &proof, &context), "Verified with a replaced ballot");
+
+        // Ballot dropped and another one duplicated
+        let mut duplicated = output.clone();
+        duplicated[1] = duplicated[0].clone();
+        assert!(!verify_shuffle(encryption_key, &input, &duplicated, &proof, &context), "Verified with a duplicated ballot");
+
+        let mut swapped = output.clone();
+        swapped.swap(0, 1);
+        assert!(!verify_shuffle(encryption_key, &input, &swapped, &proof, &context), "Verified output in a different order than proven");
+
+        // Valid shuffle of different input
+        let other_input : Vec<Vec<Ciphertext>> = [1, 1, 1].iter().map(|vote| encryption_key.encrypt_vote(*vote, 3)).collect();
+        let (other_output, other_proof) = shuffle(encryption_key, &other_input, &context).unwrap();
+        assert!(!verify_shuffle(encryption_key, &input, &other_output, &other_proof, &context), "Verified shuffle of different ballots");
+
+        let mut forged = proof.clone();
+        forged.s[0] = (&forged.s[0] + 1u32) % group.q();
+        assert!(!verify_shuffle(encryption_key, &input, &output, &forged, &context), "Forged proof was verified");
+    }
+}
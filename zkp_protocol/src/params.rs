@@ -0,0 +1,100 @@
+use num_bigint::BigUint;
+
+use crate::group::{GroupParams, BUILTIN_GROUPS};
+use crate::PublicKey;
+
+// Parameters of the voter keys of an election, published by the server.
+// Alpha is the generator of the group and beta is derived from the election id, so nobody knows log_alpha(beta).
+// Voters only send a = alpha^x and b = beta^x.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyParams {
+    group : GroupParams,
+    alpha : BigUint,
+    beta : BigUint,
+}
+
+impl KeyParams {
+    pub fn new(group : &GroupParams, alpha : BigUint, beta : BigUint) -> KeyParams {
+        KeyParams {
+            group : group.clone(),
+            alpha, beta
+        }
+    }
+
+    pub fn from_bytes_be(group : &GroupParams, alpha : &[u8], beta : &[u8]) -> KeyParams {
+        KeyParams::new(group, BigUint::from_bytes_be(alpha), BigUint::from_bytes_be(beta))
+    }
+
+    pub fn for_election(group : &GroupParams, election_id : &str) -> KeyParams {
+        KeyParams::new(group, group.alpha().clone(), group.hash_to_group(b"anonvote-beta", election_id.as_bytes()))
+    }
+
+    // Parameters of the election in the built-in group a key was made in, records only have the numbers of a key
+    pub fn for_key(election_id : &str, key : &PublicKey) -> Option<KeyParams> {
+        BUILTIN_GROUPS.iter().filter_map(|name| GroupParams::by_name(name).ok())
+            .filter(|group| group.alpha() == key.alpha())
+            .map(|group| KeyParams::for_election(&group, election_id))
+            .find(|params| params.beta() == key.beta())
+    }
+
+    pub fn group(&self) -> &GroupParams {
+        &self.group
+    }
+
+    pub fn alpha(&self) -> &BigUint {
+        &self.alpha
+    }
+
+    pub fn beta(&self) -> &BigUint {
+        &self.beta
+    }
+
+    pub fn public_key(&self, a : BigUint, b : BigUint) -> PublicKey {
+        PublicKey::new(&self.group, a, b, self.alpha.clone(), self.beta.clone())
+    }
+
+    // Parameters a client got from the server, alpha and beta must be different generators of order q
+    pub fn valid(&self) -> bool {
+        self.alpha != self.beta && self.group.contains(&self.alpha) && self.group.contains(&self.beta)
+    }
+
+    // Key of a voter must use these parameters, with a and b in the group as well.
+    // Checked in the group of the parameters, whatever group the key was read with.
+    pub fn valid_key(&self, key : &PublicKey) -> bool {
+        *key.alpha() == self.alpha && *key.beta() == self.beta && self.group.contains(key.a()) && self.group.contains(key.b())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SecretKey;
+
+    #[test]
+    fn test_params() {
+        let group = GroupParams::default();
+        let params = KeyParams::for_election(&group, "election");
+        assert!(params.valid());
+        assert_eq!(params, KeyParams::for_election(&group, "election"), "Parameters are not deterministic");
+        assert_eq!(params.alpha(), group.alpha());
+
+        let key = SecretKey::generate(&group).public_key(&params);
+        assert!(params.valid_key(&key));
+        assert!(key.valid());
+
+        // Key for another election or with numbers outside of the group
+        let other = KeyParams::for_election(&group, "other");
+        assert!(!other.valid_key(&key), "Key with the beta of another election was accepted");
+        assert!(!params.valid_key(&params.public_key(key.a().clone(), BigUint::from(1u32))), "Trivial b was accepted");
+        assert!(!params.valid_key(&params.public_key(key.a().clone(), group.p() - 1u32)), "Element outside of the subgroup was accepted");
+        assert!(!KeyParams::new(&group, params.alpha().clone(), params.alpha().clone()).valid(), "Same alpha and beta were accepted");
+
+        // Group of a key from its numbers only
+        assert_eq!(KeyParams::for_key("election", &key), Some(params));
+        let ristretto = KeyParams::for_election(&GroupParams::ristretto255(), "election");
+        let key = SecretKey::generate(ristretto.group()).public_key(&ristretto);
+        let read = PublicKey::new(&group, key.a().clone(), key.b().clone(), key.alpha().clone(), key.beta().clone());
+        assert_eq!(KeyParams::for_key("election", &read), Some(ristretto), "Key read in the default group");
+        assert_eq!(KeyParams::for_key("other", &key), None);
+    }
+}
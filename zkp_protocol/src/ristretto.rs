@@ -0,0 +1,165 @@
+// Ristretto255, the prime order group made from Curve25519 (RFC 9496).
+// A point is encoded as its 32 byte compressed form read as a big-endian number, so it fits where a number mod p does.
+// Exponents are scalars mod the order of the group.
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use num_bigint::BigUint;
+use sha2::Sha512;
+use zeroize::Zeroize;
+
+use crate::group::Group;
+use crate::scalar::SecretScalar;
+
+const NAME : &str = "ristretto255";
+const POINT_LEN : usize = 32;
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Ristretto255 {
+    modulus : BigUint,
+    order : BigUint,
+    generator : BigUint,
+}
+
+fn encode(point : &RistrettoPoint) -> BigUint {
+    BigUint::from_bytes_be(point.compress().as_bytes())
+}
+
+// Leading zeros are lost in the number, they are added back before decompressing
+fn decode(value : &BigUint) -> Option<RistrettoPoint> {
+    let bytes = value.to_bytes_be();
+    if bytes.len() > POINT_LEN {
+        return None;
+    }
+    let mut compressed = [0u8; POINT_LEN];
+    compressed[POINT_LEN - bytes.len()..].copy_from_slice(&bytes);
+    CompressedRistretto(compressed).decompress()
+}
+
+impl Ristretto255 {
+    pub fn new() -> Ristretto255 {
+        Ristretto255 {
+            modulus : (BigUint::from(1u32) << 255) - 19u32,
+            order : (BigUint::from(1u32) << 252) + BigUint::parse_bytes(b"27742317777372353535851937790883648493", 10).unwrap(),
+            generator : encode(&RISTRETTO_BASEPOINT_POINT),
+        }
+    }
+
+    fn scalar(&self, exponent : &BigUint) -> Scalar {
+        let mut bytes = [0u8; 32];
+        let reduced = (exponent % &self.order).to_bytes_le();
+        bytes[..reduced.len()].copy_from_slice(&reduced);
+        Scalar::from_bytes_mod_order(bytes)
+    }
+}
+
+impl Default for Ristretto255 {
+    fn default() -> Ristretto255 {
+        Ristretto255::new()
+    }
+}
+
+impl Group for Ristretto255 {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    fn order(&self) -> &BigUint {
+        &self.order
+    }
+
+    fn generator(&self) -> &BigUint {
+        &self.generator
+    }
+
+    fn exp(&self, base : &BigUint, exponent : &BigUint) -> Option<BigUint> {
+        Some(encode(&(decode(base)? * self.scalar(exponent))))
+    }
+
+    // Scalar multiplication of curve25519-dalek takes the same time for every scalar
+    fn exp_secret(&self, base : &BigUint, exponent : &SecretScalar) -> Option<BigUint> {
+        let base = decode(base)?;
+        let mut bytes = exponent.to_bytes_be();
+        assert!(bytes.len() <= 32, "Exponent is not mod the order of the group");
+        let mut le_bytes = [0u8; 32];
+        for (le, be) in le_bytes.iter_mut().zip(bytes.iter().rev()) {
+            *le = *be;
+        }
+        let value = encode(&(base * Scalar::from_bytes_mod_order(le_bytes)));
+        bytes.zeroize();
+        le_bytes.zeroize();
+        Some(value)
+    }
+
+    fn mul(&self, x : &BigUint, y : &BigUint) -> Option<BigUint> {
+        Some(encode(&(decode(x)? + decode(y)?)))
+    }
+
+    fn identity(&self) -> BigUint {
+        encode(&RistrettoPoint::identity())
+    }
+
+    fn inv(&self, x : &BigUint) -> Option<BigUint> {
+        Some(encode(&-decode(x)?))
+    }
+
+    // Every point but the identity generates the group, the order is prime
+    fn contains(&self, value : &BigUint) -> bool {
+        decode(value).is_some_and(|point| point != RistrettoPoint::identity())
+    }
+
+    // Same input as in the MODP groups, hashed to a point with the map of RFC 9496
+    fn hash_to_group(&self, domain : &[u8], data : &[u8]) -> BigUint {
+        for counter in 0u32.. {
+            let mut input = b"anonvote-hash-to-group".to_vec();
+            for part in [domain, data] {
+                input.extend_from_slice(&(part.len() as u32).to_be_bytes());
+                input.extend_from_slice(part);
+            }
+            input.extend_from_slice(&counter.to_be_bytes());
+            let point = RistrettoPoint::hash_from_bytes::<Sha512>(&input);
+            if point != RistrettoPoint::identity() && point != RISTRETTO_BASEPOINT_POINT {
+                return encode(&point);
+            }
+        }
+        unreachable!("Ran out of counters")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        let group = Ristretto255::new();
+        assert!(group.contains(group.generator()));
+        assert!(!group.contains(&BigUint::ZERO), "Identity is in the group");
+        assert!(!group.contains(&(BigUint::from(1u32) << 256)), "Number longer than a point is in the group");
+
+        // Points with leading zero bytes survive the trip through a number
+        let point = (1u32..).map(|x| RISTRETTO_BASEPOINT_POINT * Scalar::from(x)).find(|point| point.compress().as_bytes()[0] == 0).unwrap();
+        assert_eq!(decode(&encode(&point)), Some(point));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let group = Ristretto255::new();
+        let g = group.generator();
+        let x = BigUint::from(12345u32);
+        let gx = group.exp(g, &x).unwrap();
+        assert_eq!(group.mul(&gx, g), group.exp(g, &(&x + 1u32)));
+        assert_eq!(group.exp(g, &(group.order() + &x)), Some(gx.clone()), "Exponents are not mod the order");
+        assert_eq!(group.exp(&((BigUint::from(1u32) << 256) - 1u32), &x), None, "Number that is not a point was used");
+
+        let element = group.hash_to_group(b"test", b"data");
+        assert!(group.contains(&element));
+        assert_ne!(element, group.hash_to_group(b"other", b"data"));
+    }
+}
@@ -0,0 +1,482 @@
+// Numbers mod q for secrets and nonces. The arithmetic takes the same time whatever the values are:
+// the number of limbs comes from q, values are always reduced and there are no branches or early exits on the value.
+// The limbs are zeroed when the scalar is dropped. exp_mod raises a number mod p to a secret scalar the same way.
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use rand::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[derive(Clone)]
+pub struct SecretScalar {
+    // Little endian, as many limbs as the modulus
+    limbs : Vec<u64>,
+    modulus : Vec<u64>,
+}
+
+fn adc(a : u64, b : u64, carry : u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+fn sbb(a : u64, b : u64, borrow : u64) -> (u64, u64) {
+    let t = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (t as u64, (t >> 127) as u64)
+}
+
+// a + b mod m for a, b < m
+fn add_mod(a : &[u64], b : &[u64], m : &[u64]) -> Vec<u64> {
+    let mut sum = vec![0u64; m.len()];
+    let mut carry = 0;
+    for i in 0..m.len() {
+        (sum[i], carry) = adc(a[i], b[i], carry);
+    }
+    let mut diff = vec![0u64; m.len()];
+    let mut borrow = 0;
+    for i in 0..m.len() {
+        (diff[i], borrow) = sbb(sum[i], m[i], borrow);
+    }
+    // The sum is below 2m, m is subtracted if the sum overflowed or is not below m
+    let reduce = Choice::from((carry | (borrow ^ 1)) as u8);
+    for i in 0..m.len() {
+        sum[i].conditional_assign(&diff[i], reduce);
+    }
+    diff.zeroize();
+    sum
+}
+
+// a + b * c + carry, the high limb is the carry
+fn mac(a : u64, b : u64, c : u64, carry : u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 * c as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+// a - b mod m for a, b < m
+fn sub_mod(a : &[u64], b : &[u64], m : &[u64]) -> Vec<u64> {
+    let mut diff = vec![0u64; m.len()];
+    let mut borrow = 0;
+    for i in 0..m.len() {
+        (diff[i], borrow) = sbb(a[i], b[i], borrow);
+    }
+    // m is added back if the difference went below zero, the carry out of that cancels the borrow
+    let mask = borrow.wrapping_neg();
+    let mut carry = 0;
+    for i in 0..m.len() {
+        (diff[i], carry) = adc(diff[i], m[i] & mask, carry);
+    }
+    diff
+}
+
+impl SecretScalar {
+    fn with_limbs(limbs : Vec<u64>, modulus : &[u64]) -> SecretScalar {
+        SecretScalar {
+            limbs,
+            modulus : modulus.to_vec(),
+        }
+    }
+
+    fn zero(modulus : &[u64]) -> SecretScalar {
+        SecretScalar::with_limbs(vec![0u64; modulus.len()], modulus)
+    }
+
+    fn one(modulus : &[u64]) -> SecretScalar {
+        let mut one = SecretScalar::zero(modulus);
+        one.limbs[0] = 1;
+        one
+    }
+
+    fn modulus_limbs(modulus : &BigUint) -> Vec<u64> {
+        assert!(*modulus > BigUint::from(1u32), "Modulus has to be bigger than 1");
+        modulus.to_u64_digits()
+    }
+
+    // Reduced in constant time for inputs of the same length, leading zeros are kept
+    pub fn from_bytes_be(bytes : &[u8], modulus : &BigUint) -> SecretScalar {
+        let modulus = SecretScalar::modulus_limbs(modulus);
+        let one = SecretScalar::one(&modulus);
+        let mut result = SecretScalar::zero(&modulus);
+        for byte in bytes {
+            for shift in (0..8).rev() {
+                result = result.add(&result);
+                let plus_one = result.add(&one);
+                result.conditional_assign(&plus_one, Choice::from((byte >> shift) & 1));
+            }
+        }
+        result
+    }
+
+    // BigUint doesn't hide its length, only for public numbers (like the challenge) and secrets that are already a BigUint
+    pub fn from_biguint(value : &BigUint, modulus : &BigUint) -> SecretScalar {
+        let mut bytes = value.to_bytes_be();
+        let scalar = SecretScalar::from_bytes_be(&bytes, modulus);
+        bytes.zeroize();
+        scalar
+    }
+
+    // 64 more bits than the modulus are reduced, so the bias is negligible
+    pub fn random(modulus : &BigUint) -> SecretScalar {
+        let mut bytes = vec![0u8; (modulus.bits() as usize).div_ceil(8) + 8];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let scalar = SecretScalar::from_bytes_be(&bytes, modulus);
+        bytes.zeroize();
+        scalar
+    }
+
+    // Keys check that their secret is mod the order of their group
+    pub fn is_mod(&self, modulus : &BigUint) -> bool {
+        self.modulus == modulus.to_u64_digits()
+    }
+
+    pub fn is_zero(&self) -> Choice {
+        self.limbs.iter().fold(Choice::from(1), |zero, limb| zero & limb.ct_eq(&0))
+    }
+
+    fn check_modulus(&self, other : &SecretScalar) {
+        assert!(self.modulus == other.modulus, "Scalars are mod different numbers");
+    }
+
+    fn conditional_assign(&mut self, other : &SecretScalar, choice : Choice) {
+        self.check_modulus(other);
+        for (limb, other) in self.limbs.iter_mut().zip(other.limbs.iter()) {
+            limb.conditional_assign(other, choice);
+        }
+    }
+
+    // Swaps the values if choice is set, without a branch on it
+    pub fn conditional_swap(&mut self, other : &mut SecretScalar, choice : Choice) {
+        self.check_modulus(other);
+        for (limb, other) in self.limbs.iter_mut().zip(other.limbs.iter_mut()) {
+            u64::conditional_swap(limb, other, choice);
+        }
+    }
+
+    pub fn add(&self, other : &SecretScalar) -> SecretScalar {
+        self.check_modulus(other);
+        SecretScalar::with_limbs(add_mod(&self.limbs, &other.limbs, &self.modulus), &self.modulus)
+    }
+
+    pub fn sub(&self, other : &SecretScalar) -> SecretScalar {
+        self.check_modulus(other);
+        SecretScalar::with_limbs(sub_mod(&self.limbs, &other.limbs, &self.modulus), &self.modulus)
+    }
+
+    // Double and add over every bit of the limbs, the other one is added or not with a select
+    pub fn mul(&self, other : &SecretScalar) -> SecretScalar {
+        self.check_modulus(other);
+        let mut result = SecretScalar::zero(&self.modulus);
+        for i in (0..self.limbs.len() * 64).rev() {
+            result = result.add(&result);
+            let sum = result.add(other);
+            result.conditional_assign(&sum, Choice::from(((self.limbs[i / 64] >> (i % 64)) & 1) as u8));
+        }
+        result
+    }
+
+    // Always as many bytes as the modulus
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let bits = self.modulus.len() * 64 - self.modulus[self.modulus.len() - 1].leading_zeros() as usize;
+        (0..bits.div_ceil(8)).rev().map(|i| (self.limbs[i / 8] >> (8 * (i % 8))) as u8).collect()
+    }
+
+    // For exponentiation and public results, the BigUint is not zeroed
+    pub fn to_biguint(&self) -> BigUint {
+        let mut bytes = self.to_bytes_be();
+        let value = BigUint::from_bytes_be(&bytes);
+        bytes.zeroize();
+        value
+    }
+}
+
+// Multiplication mod an odd p in Montgomery form (x * R mod p with R = 2^(64 * limbs)), the limbs and the steps only depend on p
+struct Montgomery {
+    modulus : BigUint,
+    p : Vec<u64>,
+    p_inv : u64, // -p^-1 mod 2^64
+    r2 : Vec<u64>, // R^2 mod p
+}
+
+impl Montgomery {
+    fn new(p : &BigUint) -> Montgomery {
+        assert!(p.bit(0), "Modulus has to be odd");
+        let limbs = p.to_u64_digits();
+        // Newton's iteration, every step doubles the number of correct low bits
+        let mut inv = 1u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(limbs[0].wrapping_mul(inv)));
+        }
+        let r2 = (BigUint::from(1u32) << (128 * limbs.len())) % p;
+        Montgomery {
+            r2 : Montgomery::pad(r2.to_u64_digits(), limbs.len()),
+            p_inv : inv.wrapping_neg(),
+            p : limbs,
+            modulus : p.clone(),
+        }
+    }
+
+    fn pad(mut limbs : Vec<u64>, len : usize) -> Vec<u64> {
+        limbs.resize(len, 0);
+        limbs
+    }
+
+    // a * b / R mod p for a, b < p, coarsely integrated operand scanning
+    fn mul(&self, a : &[u64], b : &[u64]) -> Vec<u64> {
+        let n = self.p.len();
+        let mut t = vec![0u64; n + 2];
+        for b_i in &b[..n] {
+            let mut carry = 0;
+            for j in 0..n {
+                (t[j], carry) = mac(t[j], a[j], *b_i, carry);
+            }
+            let (sum, overflow) = adc(t[n], carry, 0);
+            (t[n], t[n + 1]) = (sum, overflow);
+
+            // Adding m * p clears the lowest limb, which is shifted out
+            let m = t[0].wrapping_mul(self.p_inv);
+            let (_, mut carry) = mac(t[0], m, self.p[0], 0);
+            for j in 1..n {
+                (t[j - 1], carry) = mac(t[j], m, self.p[j], carry);
+            }
+            let (sum, overflow) = adc(t[n], carry, 0);
+            (t[n - 1], t[n]) = (sum, t[n + 1] + overflow);
+        }
+
+        // The result is below 2p, p is subtracted if it overflowed the limbs or is not below p
+        let mut diff = vec![0u64; n];
+        let mut borrow = 0;
+        for j in 0..n {
+            (diff[j], borrow) = sbb(t[j], self.p[j], borrow);
+        }
+        let reduce = Choice::from((t[n] | (borrow ^ 1)) as u8);
+        t.truncate(n);
+        for j in 0..n {
+            t[j].conditional_assign(&diff[j], reduce);
+        }
+        t
+    }
+
+    fn to_montgomery(&self, x : &BigUint) -> Vec<u64> {
+        let x = Montgomery::pad((x % &self.modulus).to_u64_digits(), self.p.len());
+        self.mul(&x, &self.r2)
+    }
+
+    fn to_biguint(&self, x : &[u64]) -> BigUint {
+        let mut one = vec![0u64; self.p.len()];
+        one[0] = 1;
+        let bytes : Vec<u8> = self.mul(x, &one).iter().flat_map(|limb| limb.to_le_bytes()).collect();
+        BigUint::from_bytes_le(&bytes)
+    }
+}
+
+// base^exponent mod an odd p with a fixed window of 4 bits. Every window of every limb of the exponent is squared in four times
+// and multiplied with the entry of the table it picks, the entry is selected by going over the whole table.
+// So the number of steps and the memory that is read don't depend on the exponent, only on p and the modulus of the exponent.
+pub(crate) fn exp_mod(base : &BigUint, exponent : &SecretScalar, p : &BigUint) -> BigUint {
+    const WINDOW : usize = 4;
+    let montgomery = Montgomery::new(p);
+    let one = montgomery.to_montgomery(&BigUint::from(1u32));
+    let base = montgomery.to_montgomery(base);
+    let mut table = vec![one];
+    for i in 1..1 << WINDOW {
+        table.push(montgomery.mul(&table[i - 1], &base));
+    }
+
+    let mut result = table[0].clone();
+    for limb in exponent.limbs.iter().rev() {
+        for shift in (0..64 / WINDOW).rev() {
+            for _ in 0..WINDOW {
+                result = montgomery.mul(&result, &result);
+            }
+            let window = (limb >> (shift * WINDOW)) & ((1 << WINDOW) - 1);
+            let mut entry = vec![0u64; montgomery.p.len()];
+            for (i, candidate) in table.iter().enumerate() {
+                let choice = (i as u64).ct_eq(&window);
+                for (limb, candidate) in entry.iter_mut().zip(candidate) {
+                    limb.conditional_assign(candidate, choice);
+                }
+            }
+            result = montgomery.mul(&result, &entry);
+            entry.zeroize();
+        }
+    }
+    for entry in table.iter_mut() {
+        entry.zeroize();
+    }
+    montgomery.to_biguint(&result)
+}
+
+impl ConstantTimeEq for SecretScalar {
+    fn ct_eq(&self, other : &SecretScalar) -> Choice {
+        self.limbs.ct_eq(&other.limbs) & Choice::from((self.modulus == other.modulus) as u8)
+    }
+}
+
+impl PartialEq for SecretScalar {
+    fn eq(&self, other : &SecretScalar) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecretScalar {}
+
+// Secrets don't end up in logs
+impl fmt::Debug for SecretScalar {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretScalar(..)")
+    }
+}
+
+impl Drop for SecretScalar {
+    fn drop(&mut self) {
+        self.limbs.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretScalar {}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::group::GroupParams;
+    use crate::params::KeyParams;
+    use crate::{zkp_util, SecretKey};
+
+    #[test]
+    fn test_arithmetic() {
+        for group in [GroupParams::default(), GroupParams::small(), GroupParams::ristretto255(), GroupParams::by_name("rfc3526-2048").unwrap()] {
+            let q = group.q();
+            for _ in 0..10 {
+                let (x, y) = (zkp_util::generate_random_below(q), zkp_util::generate_random_below(q));
+                let (sx, sy) = (SecretScalar::from_biguint(&x, q), SecretScalar::from_biguint(&y, q));
+                assert_eq!(sx.to_biguint(), x);
+                assert_eq!(sx.add(&sy).to_biguint(), (&x + &y) % q, "Wrong sum in {}", group.name());
+                assert_eq!(sx.sub(&sy).to_biguint(), (q + &x - &y) % q, "Wrong difference in {}", group.name());
+                assert_eq!(sx.mul(&sy).to_biguint(), (&x * &y) % q, "Wrong product in {}", group.name());
+            }
+            let max = SecretScalar::from_biguint(&(q - 1u32), q);
+            assert_eq!(max.add(&max).to_biguint(), q - 2u32);
+            assert_eq!(SecretScalar::zero(&max.modulus).sub(&max).to_biguint(), BigUint::from(1u32));
+        }
+    }
+
+    #[test]
+    fn test_bytes() {
+        let q = GroupParams::default().q().clone();
+        let value = BigUint::from(0x1234u32);
+        let scalar = SecretScalar::from_biguint(&value, &q);
+        let bytes = scalar.to_bytes_be();
+        assert_eq!(bytes.len(), 20, "Bytes are not as long as q");
+        assert_eq!(BigUint::from_bytes_be(&bytes), value);
+        assert_eq!(SecretScalar::from_bytes_be(&bytes, &q), scalar);
+
+        // Longer inputs are reduced
+        let big = (&q << 70u32) + 5u32;
+        assert_eq!(SecretScalar::from_biguint(&big, &q).to_biguint(), BigUint::from(5u32));
+        assert!(SecretScalar::random(&q).to_biguint() < q);
+        assert_ne!(SecretScalar::random(&q), SecretScalar::random(&q));
+        assert_eq!(format!("{:?}", scalar), "SecretScalar(..)");
+
+        assert!(scalar.is_mod(&q) && !scalar.is_mod(GroupParams::small().q()));
+        assert!(!bool::from(scalar.is_zero()) && bool::from(SecretScalar::from_biguint(&q, &q).is_zero()));
+
+        let (mut x, mut y) = (scalar.clone(), SecretScalar::from_biguint(&big, &q));
+        x.conditional_swap(&mut y, Choice::from(0));
+        assert_eq!((x.to_biguint(), y.to_biguint()), (value.clone(), BigUint::from(5u32)), "Swapped without the choice");
+        x.conditional_swap(&mut y, Choice::from(1));
+        assert_eq!((x.to_biguint(), y.to_biguint()), (BigUint::from(5u32), value), "Not swapped with the choice");
+    }
+
+    #[test]
+    fn test_exp_mod() {
+        for group in [GroupParams::default(), GroupParams::small(), GroupParams::ristretto255(), GroupParams::by_name("rfc3526-2048").unwrap()] {
+            let q = group.q();
+            let base = group.exp(group.alpha(), &zkp_util::generate_random_below(q)).unwrap();
+            let exponents = [BigUint::ZERO, BigUint::from(1u32), BigUint::from(2u32), q - 1u32, zkp_util::generate_random_below(q)];
+            for e in exponents {
+                let secret = SecretScalar::from_biguint(&e, q);
+                assert_eq!(group.exp_secret(&base, &secret), group.exp(&base, &e), "Exponentiation with {} differs in {}", e, group.name());
+                assert_eq!(group.exp_secret(group.alpha(), &secret), group.exp(group.alpha(), &e), "Exponentiation of alpha with {} differs in {}", e, group.name());
+            }
+        }
+        let ristretto = GroupParams::ristretto255();
+        let zero = SecretScalar::from_biguint(&BigUint::ZERO, ristretto.q());
+        assert_eq!(ristretto.exp_secret(&((BigUint::from(1u32) << 256) - 1u32), &zero), None, "Number that is not a point was used");
+    }
+
+    // dudect's threshold: |t| above 4.5 means the classes can be told apart with high confidence.
+    // 2000 samples per class are enough to find the leak of num-bigint, whose time follows the size of the exponent.
+    // The timing tests measure wall-clock time, so they are ignored by default and run on a quiet machine with
+    // cargo test --release -p zkp_protocol -- --ignored constant_time
+    const T_THRESHOLD : f64 = 4.5;
+
+    // Welch's t statistic of the times of two classes of inputs, like dudect.
+    // The classes are measured in random order and the slowest tenth is dropped, that is mostly noise from the machine.
+    fn timing_t_statistic(samples : usize, mut measure : impl FnMut(bool) -> u128) -> f64 {
+        let mut times = [Vec::new(), Vec::new()];
+        let mut rng = rand::thread_rng();
+        for _ in 0..samples * 2 {
+            let class = rng.next_u32() & 1 == 1;
+            times[class as usize].push(measure(class) as f64);
+        }
+        let [first, second] = times.map(|mut class_times| {
+            class_times.sort_by(f64::total_cmp);
+            class_times.truncate(class_times.len() * 9 / 10);
+            let n = class_times.len() as f64;
+            let mean = class_times.iter().sum::<f64>() / n;
+            let variance = class_times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            (mean, variance, n)
+        });
+        (first.0 - second.0) / (first.1 / first.2 + second.1 / second.2).sqrt()
+    }
+
+    // The old solve branched on k >= challenge * secret and its time followed the size of the secret.
+    // A secret of 1 with k = 0 against random secrets and nonces should not be told apart.
+    #[test]
+    #[ignore = "timing test, run in release mode with --ignored"]
+    fn test_constant_time_solve() {
+        let group = GroupParams::default();
+        let params = KeyParams::for_election(&group, "election");
+        let challenge = zkp_util::generate_random_below(group.q());
+        let keys = [SecretKey::from_bytes_be(&group, &1u32.to_be_bytes()), SecretKey::generate(&group)];
+        let nonces = [SecretScalar::zero(&keys[0].secret().modulus), SecretScalar::random(group.q())];
+
+        // Warm up, so caches and allocations don't count against the first class
+        for (key, k) in keys.iter().zip(nonces.iter()) {
+            assert!(key.public_key(&params).verify(&group.exp(params.alpha(), &k.to_biguint()).unwrap(), &group.exp(params.beta(), &k.to_biguint()).unwrap(), &challenge, &key.solve(k, &challenge)));
+        }
+
+        let t = timing_t_statistic(2000, |class| {
+            let (key, k) = (&keys[class as usize], &nonces[class as usize]);
+            let start = Instant::now();
+            let solution = key.solve(k, &challenge);
+            let elapsed = start.elapsed().as_nanos();
+            std::hint::black_box(solution);
+            elapsed
+        });
+        assert!(t.abs() < T_THRESHOLD, "Time of solve depends on the secret, t = {}", t);
+    }
+
+    // An exponent of 1 against random exponents, num-bigint returns after a single bit for the first
+    #[test]
+    #[ignore = "timing test, run in release mode with --ignored"]
+    fn test_constant_time_exp() {
+        let group = GroupParams::default();
+        let exponents = [SecretScalar::from_biguint(&BigUint::from(1u32), group.q()), SecretScalar::random(group.q())];
+        for e in &exponents {
+            assert!(group.exp_secret(group.alpha(), e).is_some());
+        }
+
+        let t = timing_t_statistic(2000, |class| {
+            let e = &exponents[class as usize];
+            let start = Instant::now();
+            let value = group.exp_secret(group.alpha(), e);
+            let elapsed = start.elapsed().as_nanos();
+            std::hint::black_box(value);
+            elapsed
+        });
+        assert!(t.abs() < T_THRESHOLD, "Time of exp_secret depends on the exponent, t = {}", t);
+    }
+}
@@ -0,0 +1,290 @@
+// Threshold ElGamal, so no single party can decrypt the tally.
+// Key generation is Pedersen's DKG: every trustee deals a random polynomial of degree t - 1 with Feldman VSS,
+// publishing alpha^a_k for every coefficient and sending f(j) privately to trustee j.
+// Trustee j checks the shares against the commitments and keeps the sum of them as its key share x_j.
+// The election key is the product of all alpha^a_0, the matching secret is never known to anyone.
+// Any t trustees can decrypt by publishing c1^x_j, combined with Lagrange interpolation in the exponent.
+
+use num_bigint::BigUint;
+
+use crate::{PublicKey, SecretKey, Proof};
+use crate::elgamal::{self, Ciphertext, EncryptionKey};
+use crate::group::GroupParams;
+use crate::scalar::SecretScalar;
+
+// What one trustee deals, commitments are public and shares[j - 1] goes only to trustee j
+pub struct Dealing {
+    commitments : Vec<BigUint>, // alpha^a_k for every coefficient of the polynomial
+    shares : Vec<BigUint>, // f(1), ..., f(n)
+}
+
+// Share of the decryption key held by one trustee, trustees are numbered from 1
+pub struct KeyShare {
+    index : u32,
+    x : SecretScalar,
+    group : GroupParams,
+}
+
+// c1^x_j with a proof that the same x_j is behind the trustee's verification key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialDecryption {
+    index : u32,
+    d : BigUint,
+    proof : Proof,
+}
+
+// Trustee indices are points of the polynomial, so there can't be more of them than q - 1
+pub fn valid_parameters(group : &GroupParams, threshold : u32, trustee_count : u32) -> bool {
+    threshold >= 1 && threshold <= trustee_count && BigUint::from(trustee_count) < *group.q()
+}
+
+impl Dealing {
+    pub fn new(commitments : Vec<BigUint>, shares : Vec<BigUint>) -> Dealing {
+        Dealing {
+            commitments, shares
+        }
+    }
+
+    pub fn generate(group : &GroupParams, threshold : u32, trustee_count : u32) -> Option<Dealing> {
+        if !valid_parameters(group, threshold, trustee_count) {
+            return None;
+        }
+        let q = group.q();
+        let coefficients : Vec<BigUint> = (0..threshold).map(|_| group.random_exponent()).collect();
+
+        let commitments = coefficients.iter().map(|a| group.exp_secret(group.alpha(), &SecretScalar::from_biguint(a, q))).collect::<Option<_>>()?;
+        let shares = (1..=trustee_count).map(|index| {
+            // Horner's rule, mod q since the shares are exponents
+            let x = BigUint::from(index);
+            coefficients.iter().rev().fold(BigUint::ZERO, |value, a| (value * &x + a) % q)
+        }).collect();
+        Some(Dealing::new(commitments, shares))
+    }
+
+    pub fn commitments(&self) -> &[BigUint] {
+        &self.commitments
+    }
+
+    pub fn shares(&self) -> &[BigUint] {
+        &self.shares
+    }
+}
+
+// alpha^f(index) computed from the public commitments
+fn committed_value(group : &GroupParams, commitments : &[BigUint], index : u32) -> Option<BigUint> {
+    let x = BigUint::from(index);
+    commitments.iter().rev().try_fold(group.identity(), |value, commitment| group.mul(&group.exp(&value, &x)?, commitment))
+}
+
+// Checks the share a trustee received against the commitments the dealer published
+pub fn verify_share(group : &GroupParams, commitments : &[BigUint], index : u32, share : &BigUint) -> bool {
+    if commitments.is_empty() || !commitments.iter().all(|commitment| group.is_element(commitment)) || share >= group.q() {
+        return false;
+    }
+    group.exp_secret(group.alpha(), &SecretScalar::from_biguint(share, group.q())).is_some_and(|value| Some(value) == committed_value(group, commitments, index))
+}
+
+// Election key from the commitments of all trustees, None if a commitment is not in the group
+pub fn election_key(group : &GroupParams, commitments : &[Vec<BigUint>]) -> Option<EncryptionKey> {
+    let h = commitments.iter().try_fold(group.identity(), |h, dealer| group.mul(&h, dealer.first()?))?;
+    Some(EncryptionKey::new(group, h))
+}
+
+// alpha^x_j of trustee j, anyone can compute it from the commitments
+pub fn verification_key(group : &GroupParams, commitments : &[Vec<BigUint>], index : u32) -> Option<BigUint> {
+    commitments.iter().try_fold(group.identity(), |key, dealer| group.mul(&key, &committed_value(group, dealer, index)?))
+}
+
+impl KeyShare {
+    pub fn new(group : &GroupParams, index : u32, x : SecretScalar) -> KeyShare {
+        assert!(x.is_mod(group.q()), "Share is not mod the order of the group");
+        KeyShare {
+            index, x,
+            group : group.clone(),
+        }
+    }
+
+    pub fn from_bytes_be(group : &GroupParams, index : u32, x : &[u8]) -> KeyShare {
+        KeyShare::new(group, index, SecretScalar::from_bytes_be(x, group.q()))
+    }
+
+    // Sum of the shares every trustee dealt to this one
+    pub fn combine(group : &GroupParams, index : u32, shares : &[BigUint]) -> KeyShare {
+        let q = group.q();
+        let x = shares.iter().fold(SecretScalar::from_biguint(&BigUint::ZERO, q), |x, share| x.add(&SecretScalar::from_biguint(share, q)));
+        KeyShare::new(group, index, x)
+    }
+
+    pub fn verification_key(&self) -> BigUint {
+        self.group.exp_secret(self.group.alpha(), &self.x).expect("Alpha is not in the group")
+    }
+
+    // None if the ciphertext is not in the group of the share
+    pub fn partial_decrypt(&self, ciphertext : &Ciphertext, context : &[u8]) -> Option<PartialDecryption> {
+        if !ciphertext.valid(&self.group) {
+            return None;
+        }
+        let d = self.group.exp_secret(ciphertext.c1(), &self.x)?;
+        let statement = PartialDecryption::statement(&self.group, &self.verification_key(), ciphertext, &d);
+        let proof = SecretKey::new(&self.group, self.x.clone()).prove(&statement, context);
+        Some(PartialDecryption::new(self.index, d, proof))
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn secret(&self) -> &SecretScalar {
+        &self.x
+    }
+
+    pub fn group(&self) -> &GroupParams {
+        &self.group
+    }
+}
+
+impl PartialDecryption {
+    pub fn new(index : u32, d : BigUint, proof : Proof) -> PartialDecryption {
+        PartialDecryption {
+            index, d, proof
+        }
+    }
+
+    // Same statement as a voter key: log_alpha(verification key) == log_c1(d)
+    fn statement(group : &GroupParams, verification_key : &BigUint, ciphertext : &Ciphertext, d : &BigUint) -> PublicKey {
+        PublicKey::new(group, verification_key.clone(), d.clone(), group.alpha().clone(), ciphertext.c1().clone())
+    }
+
+    pub fn verify(&self, group : &GroupParams, verification_key : &BigUint, ciphertext : &Ciphertext, context : &[u8]) -> bool {
+        if !group.is_element(&self.d) || !ciphertext.valid(group) {
+            return false;
+        }
+        PartialDecryption::statement(group, verification_key, ciphertext, &self.d).verify_proof(&self.proof, context)
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn d(&self) -> &BigUint {
+        &self.d
+    }
+
+    pub fn proof(&self) -> &Proof {
+        &self.proof
+    }
+}
+
+// Lagrange coefficient at 0 of the given index for the given set of indices, mod q
+fn lagrange_coefficient(group : &GroupParams, index : u32, indices : &[u32]) -> Option<BigUint> {
+    let q = group.q();
+    let mut numerator = BigUint::from(1u32);
+    let mut denominator = BigUint::from(1u32);
+    for other in indices.iter().filter(|other| **other != index) {
+        numerator = (numerator * other) % q;
+        denominator = (denominator * ((BigUint::from(*other) + q - index) % q)) % q;
+    }
+    Some((numerator * denominator.modinv(q)?) % q)
+}
+
+// Decrypts with already verified partial decryptions, at least threshold of them are needed.
+// Returns None for duplicate trustees or if the message is bigger than max.
+pub fn combine(group : &GroupParams, ciphertext : &Ciphertext, partials : &[PartialDecryption], max : u32) -> Option<u32> {
+    let indices : Vec<u32> = partials.iter().map(PartialDecryption::index).collect();
+    if indices.iter().enumerate().any(|(i, index)| *index == 0 || indices[..i].contains(index)) {
+        return None;
+    }
+
+    let mut shared = group.identity();
+    for partial in partials {
+        let coefficient = lagrange_coefficient(group, partial.index, &indices)?;
+        shared = group.mul(&shared, &group.exp(&partial.d, &coefficient)?)?;
+    }
+    elgamal::discrete_log(group, &group.div(ciphertext.c2(), &shared)?, max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::zkp_util;
+    use crate::elgamal::DecryptionKey;
+
+    // Runs the whole ceremony, returns the election key and the key shares
+    fn ceremony(group : &GroupParams, threshold : u32, trustee_count : u32) -> (EncryptionKey, Vec<KeyShare>) {
+        let dealings : Vec<Dealing> = (0..trustee_count).map(|_| Dealing::generate(group, threshold, trustee_count).unwrap()).collect();
+        let commitments : Vec<Vec<BigUint>> = dealings.iter().map(|dealing| dealing.commitments().to_vec()).collect();
+
+        let key_shares : Vec<KeyShare> = (1..=trustee_count).map(|index| {
+            let shares : Vec<BigUint> = dealings.iter().map(|dealing| dealing.shares()[index as usize - 1].clone()).collect();
+            for (dealer, share) in shares.iter().enumerate() {
+                assert!(verify_share(group, &commitments[dealer], index, share));
+            }
+            let key_share = KeyShare::combine(group, index, &shares);
+            assert_eq!(Some(key_share.verification_key()), verification_key(group, &commitments, index));
+            key_share
+        }).collect();
+        (election_key(group, &commitments).unwrap(), key_shares)
+    }
+
+    #[test]
+    fn test_threshold_decryption() {
+        for group in [GroupParams::default(), GroupParams::ristretto255()] {
+            let (key, key_shares) = ceremony(&group, 2, 3);
+            assert!(key.valid());
+            let context = zkp_util::election_context("election");
+
+            let votes = [1u32, 0, 1, 1];
+            let ciphertexts : Vec<Ciphertext> = votes.iter().map(|vote| key.encrypt_vote(*vote, 2).remove(1)).collect();
+            let tally = elgamal::sum(&group, &ciphertexts).unwrap();
+
+            for (first, second) in [(0, 1), (0, 2), (2, 1)] {
+                let partials : Vec<PartialDecryption> = [first, second].iter().map(|i| key_shares[*i].partial_decrypt(&tally, &context).unwrap()).collect();
+                for partial in &partials {
+                    let verification_key = key_shares[partial.index() as usize - 1].verification_key();
+                    assert!(partial.verify(&group, &verification_key, &tally, &context));
+                }
+                assert_eq!(combine(&group, &tally, &partials, votes.len() as u32), Some(3), "Wrong tally in {}", group.name());
+            }
+
+            let all : Vec<PartialDecryption> = key_shares.iter().map(|key_share| key_share.partial_decrypt(&tally, &context).unwrap()).collect();
+            assert_eq!(combine(&group, &tally, &all, votes.len() as u32), Some(3), "More than threshold partials also work");
+
+            let duplicate = [key_shares[0].partial_decrypt(&tally, &context).unwrap(), key_shares[0].partial_decrypt(&tally, &context).unwrap()];
+            assert_eq!(combine(&group, &tally, &duplicate, votes.len() as u32), None, "Same trustee counted twice");
+
+            assert!(Dealing::generate(&group, 4, 3).is_none(), "Threshold bigger than trustee count");
+            assert!(Dealing::generate(&group, 0, 3).is_none(), "Zero threshold");
+        }
+    }
+
+    #[test]
+    fn test_invalid_shares() {
+        let group = GroupParams::default();
+        let dealing = Dealing::generate(&group, 2, 3).unwrap();
+        assert!(verify_share(&group, dealing.commitments(), 2, &dealing.shares()[1]));
+        assert!(!verify_share(&group, dealing.commitments(), 1, &dealing.shares()[1]), "Share verified for a different trustee");
+        assert!(!verify_share(&group, dealing.commitments(), 2, &(&dealing.shares()[1] + 1u32)), "Forged share was verified");
+        assert!(!verify_share(&GroupParams::ristretto255(), dealing.commitments(), 2, &dealing.shares()[1]), "Share verified in a different group");
+
+        let (key, key_shares) = ceremony(&group, 2, 3);
+        let context = zkp_util::election_context("election");
+        let ciphertext = key.encrypt(1);
+        let partial = key_shares[0].partial_decrypt(&ciphertext, &context).unwrap();
+        let verification_key = key_shares[0].verification_key();
+
+        assert!(partial.verify(&group, &verification_key, &ciphertext, &context));
+        assert!(!partial.verify(&group, &key_shares[1].verification_key(), &ciphertext, &context), "Verified for a different trustee");
+        assert!(!partial.verify(&group, &verification_key, &ciphertext, &zkp_util::election_context("other")), "Verified for a different election");
+        assert!(!partial.verify(&group, &verification_key, &key.encrypt(1), &context), "Verified for a different ciphertext");
+
+        // A wrong decryption share can't be passed off with the proof of the correct one
+        let forged = PartialDecryption::new(1, group.mul(partial.d(), group.alpha()).unwrap(), Proof::new(partial.proof().ka().clone(), partial.proof().kb().clone(), partial.proof().solution().clone()));
+        assert!(!forged.verify(&group, &verification_key, &ciphertext, &context), "Forged decryption share was verified");
+
+        let single = [key_shares[0].partial_decrypt(&ciphertext, &context).unwrap()];
+        assert_ne!(combine(&group, &ciphertext, &single, 1), Some(1), "Decrypted with less than threshold trustees");
+
+        let ristretto_ciphertext = DecryptionKey::generate(&GroupParams::ristretto255()).encryption_key().encrypt(1);
+        assert!(key_shares[0].partial_decrypt(&ristretto_ciphertext, &context).is_none(), "Decrypted a ciphertext of another group");
+    }
+}